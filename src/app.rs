@@ -1,56 +1,605 @@
-use crate::api::{QBittorrentClient, ServerState, Torrent};
-use crate::config::Config;
-use crate::utils::log_debug;
+use crate::api::{
+    AddTorrentOptions, ApiError, ApiResult, BuildInfo, ConnectionOptions, LogEntry,
+    QBittorrentClient, ServerState, Torrent,
+};
+use crate::backend::TorrentApi;
+use crate::bindings;
+use crate::cleanup::CleanupView;
+use crate::clipboard::{self, CopyField, CopyTool};
+use crate::columns::{ColumnChooser, ListColumn};
+use crate::completion::{complete_path, expand_tilde};
+use crate::config::{AddDefaults, Config, Profile, UiConfig};
+use crate::credentials;
+use crate::demo::DemoServer;
+use crate::event::AppEvent;
+use crate::file_browser::FileBrowser;
+use crate::files::FilePanel;
+use crate::filter::Query;
+use crate::hooks::{Completion, CompletionHook};
+use crate::keymap::{Action, Keymap};
+use crate::log_view::LogView;
+use crate::metainfo;
+use crate::models::{Peer, Preferences, RssFeed, TorrentFile, Tracker};
+use crate::notification::{self, Notification, Severity};
+use crate::opener;
+use crate::palette::Palette;
+use crate::peers::{self, PeerPanel};
+use crate::pieces::PieceMap;
+use crate::poller::Poller;
+use crate::port_check;
+use crate::rss::{RssFocus, RssView};
+use crate::search::{SearchFocus, SearchView, enabled_plugins};
+use crate::settings::SettingsView;
+use crate::share_limits::ShareLimitsEditor;
+use crate::speed_history::{SpeedHistory, TorrentSpeedHistory};
+use crate::stalled::StallTracker;
+use crate::sync::SyncState;
+use crate::text_input::TextInput;
+use crate::theme::Theme;
+use crate::torrent_state::TorrentState;
+use crate::trackers::{self, TrackerHints, TrackerInput, TrackerPanel, UrlList};
+use crate::ui;
+use crate::utils::INFINITE_SECS;
+use crate::watcher::DirWatcher;
 use anyhow::Result;
-use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use chrono::{DateTime, Utc};
+use crossterm::event::{
+    KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use humansize::{BINARY, format_size};
+use ratatui::layout::{Position, Rect};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
 use url::Url;
 
+/// How often the connecting spinner advances.
+pub const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+/// How often a running plugin search is checked for new results.
+const SEARCH_POLL_INTERVAL: Duration = Duration::from_millis(750);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LoginKind {
+    /// Credentials typed into the form or given on the command line
+    Typed,
+    /// The password from the keyring; failures go back to the form
+    SavedPassword,
+    /// No credentials: only checks whether the server lets us in without
+    /// them, quietly going back where it started when it doesn't
+    Probe,
+}
+
+// A login running in the background
+struct PendingLogin {
+    cancel: CancellationToken,
+    kind: LoginKind,
+    // Where cancelling, or a probe that needs a login, leads
+    return_to: AppState,
+    started: Instant,
+}
+
+/// Longest automatic refresh interval reachable with the `+` key.
+const MAX_REFRESH_INTERVAL_SECS: u64 = 60;
+const MOUSE_SCROLL_ROWS: isize = 3;
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum AppState {
+    ProfilePicker,
+    ProfileEdit,
     UrlConfig,
     Login,
+    /// A login is in flight; Esc abandons it
+    Connecting,
     Main,
     AddTorrent,
+    FileBrowser,
     Search,
     ConfirmDelete,
+    SpeedLimits,
+    Details,
+    /// Editing the share limits of the torrent shown in the details view
+    ShareLimits,
+    Help,
+    Statistics,
+    /// The server's execution log
+    Log,
+    /// Finding new torrents with the server's search plugins
+    OnlineSearch,
+    /// RSS feeds, their articles and the auto-downloading rules
+    Rss,
+    /// The server's preferences, edited in place
+    Settings,
+    /// Every action, searchable by name
+    CommandPalette,
+    /// Picking what of the selected torrent to copy
+    Copy,
+    /// Showing, hiding and reordering the list's columns
+    Columns,
+    /// Picking finished torrents to delete by ratio and seeding time
+    Cleanup,
+    Confirm,
+    Prompt,
     Error(String),
 }
 
+impl AppState {
+    /// Dialogs waiting on the user, during which automatic refreshes are held
+    /// off so the torrents they act on don't shift underneath them.
+    pub fn pauses_refresh(&self) -> bool {
+        matches!(
+            self,
+            AppState::AddTorrent
+                | AppState::FileBrowser
+                | AppState::ConfirmDelete
+                | AppState::SpeedLimits
+                | AppState::ShareLimits
+                | AppState::Confirm
+                | AppState::Prompt
+                | AppState::CommandPalette
+                | AppState::Cleanup
+                | AppState::Error(_)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortColumn {
+    Name,
+    Size,
+    Progress,
+    DlSpeed,
+    UpSpeed,
+    Eta,
+    State,
+    AddedOn,
+    CompletedOn,
+    Ratio,
+}
+
+impl SortColumn {
+    pub fn next(self) -> Self {
+        match self {
+            SortColumn::Name => SortColumn::Size,
+            SortColumn::Size => SortColumn::Progress,
+            SortColumn::Progress => SortColumn::DlSpeed,
+            SortColumn::DlSpeed => SortColumn::UpSpeed,
+            SortColumn::UpSpeed => SortColumn::Eta,
+            SortColumn::Eta => SortColumn::State,
+            SortColumn::State => SortColumn::AddedOn,
+            SortColumn::AddedOn => SortColumn::CompletedOn,
+            SortColumn::CompletedOn => SortColumn::Ratio,
+            SortColumn::Ratio => SortColumn::Name,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortColumn::Name => "Name",
+            SortColumn::Size => "Size",
+            SortColumn::Progress => "Progress",
+            SortColumn::DlSpeed => "Down Speed",
+            SortColumn::UpSpeed => "Up Speed",
+            SortColumn::Eta => "ETA",
+            SortColumn::State => "State",
+            SortColumn::AddedOn => "Added",
+            SortColumn::CompletedOn => "Completed",
+            SortColumn::Ratio => "Ratio",
+        }
+    }
+
+    fn compare(self, a: &Torrent, b: &Torrent) -> Ordering {
+        match self {
+            SortColumn::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortColumn::Size => a.size.cmp(&b.size),
+            SortColumn::Progress => a.progress.total_cmp(&b.progress),
+            SortColumn::DlSpeed => a.dlspeed.cmp(&b.dlspeed),
+            SortColumn::UpSpeed => a.upspeed.cmp(&b.upspeed),
+            SortColumn::Eta => eta_sort_key(a).cmp(&eta_sort_key(b)),
+            SortColumn::State => a.state.display_name().cmp(b.state.display_name()),
+            SortColumn::AddedOn => a.added_on.cmp(&b.added_on),
+            SortColumn::CompletedOn => a.completion_on.cmp(&b.completion_on),
+            SortColumn::Ratio => a.ratio.unwrap_or(0.0).total_cmp(&b.ratio.unwrap_or(0.0)),
+        }
+    }
+}
+
+/// How much of the list each torrent takes.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RowMode {
+    /// One line per torrent
+    #[default]
+    Compact,
+    /// A second line with a progress bar, speeds and the tracker
+    Detailed,
+}
+
+impl RowMode {
+    /// Lines each torrent takes up.
+    pub fn height(self) -> usize {
+        match self {
+            RowMode::Compact => 1,
+            RowMode::Detailed => 2,
+        }
+    }
+}
+
+/// Status buckets shown in the filter sidebar, mirroring the WebUI's filter panel.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusFilter {
+    All,
+    Downloading,
+    Seeding,
+    Completed,
+    Paused,
+    Active,
+    Errored,
+    /// Downloading but idle for longer than `Config::stalled_after_mins`
+    Stalled,
+}
+
+impl StatusFilter {
+    pub const ALL: [StatusFilter; 8] = [
+        StatusFilter::All,
+        StatusFilter::Downloading,
+        StatusFilter::Seeding,
+        StatusFilter::Completed,
+        StatusFilter::Paused,
+        StatusFilter::Active,
+        StatusFilter::Errored,
+        StatusFilter::Stalled,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            StatusFilter::All => "All",
+            StatusFilter::Downloading => "Downloading",
+            StatusFilter::Seeding => "Seeding",
+            StatusFilter::Completed => "Completed",
+            StatusFilter::Paused => "Paused",
+            StatusFilter::Active => "Active",
+            StatusFilter::Errored => "Errored",
+            StatusFilter::Stalled => "Stalled",
+        }
+    }
+
+    /// Whether `torrent` is in this bucket. Stalled depends on how long it
+    /// has been idle, which only the app tracks; see `App::status_matches`.
+    pub fn matches(self, torrent: &Torrent) -> bool {
+        match self {
+            StatusFilter::Stalled => false,
+            StatusFilter::All => true,
+            StatusFilter::Active => torrent.dlspeed > 0 || torrent.upspeed > 0,
+            StatusFilter::Completed => torrent.progress >= 1.0,
+            _ => state_bucket(&torrent.state) == Some(self),
+        }
+    }
+}
+
+/// Maps a torrent state onto the exclusive state bucket it belongs to.
+/// Checking and moving torrents are transitional and only show under All.
+pub fn state_bucket(state: &TorrentState) -> Option<StatusFilter> {
+    if state.is_downloading() {
+        Some(StatusFilter::Downloading)
+    } else if state.is_seeding() {
+        Some(StatusFilter::Seeding)
+    } else if state.is_paused() {
+        Some(StatusFilter::Paused)
+    } else if state.is_errored() {
+        Some(StatusFilter::Errored)
+    } else {
+        None
+    }
+}
+
+/// A category or tag selected in the filter pane.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LabelFilter {
+    Category(String),
+    Uncategorized,
+    Tag(String),
+}
+
+impl LabelFilter {
+    pub fn label(&self) -> String {
+        match self {
+            LabelFilter::Category(name) => name.clone(),
+            LabelFilter::Uncategorized => "(uncategorized)".to_string(),
+            LabelFilter::Tag(tag) => format!("#{tag}"),
+        }
+    }
+
+    pub fn matches(&self, torrent: &Torrent) -> bool {
+        let category = torrent.category.as_deref().unwrap_or("");
+        match self {
+            LabelFilter::Category(name) => category == name,
+            LabelFilter::Uncategorized => category.is_empty(),
+            LabelFilter::Tag(tag) => torrent
+                .tags
+                .as_deref()
+                .unwrap_or("")
+                .split(',')
+                .any(|t| t.trim() == tag),
+        }
+    }
+}
+
+/// Tabs of the detail view, switched with Tab.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum DetailsTab {
+    #[default]
+    General,
+    Trackers,
+    Peers,
+    Files,
+}
+
+impl DetailsTab {
+    pub const ALL: [DetailsTab; 4] = [
+        DetailsTab::General,
+        DetailsTab::Trackers,
+        DetailsTab::Peers,
+        DetailsTab::Files,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DetailsTab::General => "General",
+            DetailsTab::Trackers => "Trackers",
+            DetailsTab::Peers => "Peers",
+            DetailsTab::Files => "Files",
+        }
+    }
+
+    /// The tab `delta` places further along, wrapping around.
+    pub fn cycle(self, delta: isize) -> Self {
+        let index = Self::ALL.iter().position(|tab| *tab == self).unwrap_or(0);
+        Self::ALL[(index as isize + delta).rem_euclid(Self::ALL.len() as isize) as usize]
+    }
+}
+
+/// Input that text edited in `$EDITOR` goes back to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditTarget {
+    TrackerUrls,
+    Peers,
+}
+
+/// Something needing the terminal handed back to the shell, which only the
+/// main loop can do since it owns the terminal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Handoff {
+    /// Ctrl+Z: stop until the shell continues the process
+    Suspend,
+    /// Edit `text` in `$EDITOR`, for `finish_edit`
+    Edit { target: EditTarget, text: String },
+}
+
 #[derive(Debug, Clone, PartialEq)]
+pub enum ConfirmAction {
+    PauseAll,
+    ResumeAll,
+    SaveProfile,
+    /// Turn on automatic management for the torrent in the details view
+    EnableAutoManagement,
+    /// Copy a path on the server that can't be opened from here
+    CopyPath(String),
+    /// Ask `Config::port_check_url` whether this port is reachable
+    CheckPort(i64),
+}
+
+/// A torrent awaiting the delete confirmation, captured when it was asked
+/// so the popup names what will go even if the list changes meanwhile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeleteTarget {
+    pub hash: String,
+    pub name: String,
+    pub size: i64,
+}
+
+/// A yes/no question shown by the generic confirmation popup.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Confirmation {
+    pub title: String,
+    pub message: String,
+    pub action: ConfirmAction,
+    /// Screen to go back to when the question is answered
+    pub return_to: AppState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PromptAction {
+    SetCategory,
+    AddTags,
+    /// Save the highlighted torrent's .torrent file to the entered path
+    ExportTorrent,
+    /// Stop qBittorrent once "yes" is typed
+    ShutdownServer,
+}
+
+/// A single-line text question shown by the generic prompt popup.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prompt {
+    pub title: String,
+    pub input: String,
+    pub action: PromptAction,
+    /// Screen to go back to when the prompt closes
+    pub return_to: AppState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum QueueMove {
+    Up,
+    Down,
+    Top,
+    Bottom,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum InputMode {
+    ProfileName,
     Url,
     Username,
     Password,
     TorrentPath,
+    SavePath,
+    Category,
+    StartPaused,
     Search,
+    DownloadLimit,
+    UploadLimit,
     None,
 }
 
 pub struct App {
-    pub client: QBittorrentClient,
+    pub client: Arc<dyn TorrentApi>,
+    pending_login: Option<PendingLogin>,
+    /// Certificate, proxy auth and header settings every new client is
+    /// built with
+    pub connection: ConnectionOptions,
+    /// Running against `DemoServer`, which stays whatever URL is entered
+    demo: bool,
     pub config: Config,
     pub state: AppState,
     pub input_mode: InputMode,
-    pub url_input: String,
-    pub username_input: String,
-    pub password_input: String,
-    pub torrent_path_input: String,
-    pub search_input: String,
+    pub url_input: TextInput,
+    /// Highlighted entry in the profile picker; one past the last profile is
+    /// "New connection…"
+    pub profile_index: usize,
+    pub profile_name_input: TextInput,
+    /// Name of the profile open in the editor
+    pub editing_profile: Option<String>,
+    pub username_input: TextInput,
+    pub password_input: TextInput,
+    pub torrent_path_input: TextInput,
+    pub add_save_path_input: TextInput,
+    /// Size of the torrent file the add dialog's path names, read again
+    /// only when the path changes
+    add_torrent_size: (String, Option<u64>),
+    /// Index into `categories`, where 0 means no category
+    pub add_category_index: usize,
+    pub add_start_paused: bool,
+    /// Matches offered by the last Tab completion of the torrent path
+    pub path_completions: Vec<String>,
+    pub file_browser: Option<FileBrowser>,
+    pub help_scroll: usize,
+    pub log_view: LogView,
+    pub search_view: SearchView,
+    pub rss_view: RssView,
+    pub settings_view: SettingsView,
+    pub command_palette: Option<Palette<Action>>,
+    pub share_limits: Option<ShareLimitsEditor>,
+    pub details_tab: DetailsTab,
+    pub tracker_panel: TrackerPanel,
+    pub peer_panel: PeerPanel,
+    pub tracker_hints: TrackerHints,
+    pub file_panel: FilePanel,
+    pub piece_map: PieceMap,
+    path_completion_index: Option<usize>,
+    pub search_input: TextInput,
+    pub download_limit_input: String,
+    pub upload_limit_input: String,
     pub torrents: Vec<Torrent>,
-    pub filtered_torrents: Vec<Torrent>,
+    /// Indexes into `torrents` of the listed ones, after the filters and
+    /// search, in display order. Rebuilt rather than cloning torrents, so
+    /// typing a query stays quick with thousands of them.
+    listed: Vec<usize>,
+    /// Last resolved position of `selected_hash`, used to pick the nearest
+    /// torrent when the selected one disappears.
     pub selected_torrent: usize,
+    pub selected_hash: Option<String>,
     pub server_state: Option<ServerState>,
     pub last_update: Instant,
+    /// Wall-clock time of the last successful poll, shown while offline.
+    pub last_update_at: Option<DateTime<Utc>>,
+    pub speed_history: SpeedHistory,
+    /// The highlighted torrent's speeds, for the detail view's graph
+    pub torrent_speed_history: TorrentSpeedHistory,
+    /// When each downloading torrent last moved, for the stalled styling
+    pub stalls: StallTracker,
+    /// Server version and build info, fetched when the statistics popup opens
+    pub server_version: Option<String>,
+    pub build_info: Option<BuildInfo>,
+    /// The server's listening port, from its preferences
+    pub listen_port: Option<i64>,
+    /// The server's preferences, fetched the first time the add dialog needs
+    /// them and kept until the settings screen saves
+    pub preferences: Option<Preferences>,
+    /// Set while the server is unreachable, with the current retry delay.
+    pub offline_retry: Option<Duration>,
+    /// qBittorrent was shut down from here, so losing it isn't an error
+    pub server_shut_down: bool,
+    sync: SyncState,
+    events: Option<mpsc::UnboundedSender<AppEvent>>,
+    poller: Option<Poller>,
+    /// Adds torrent files from `Config::watch_dir` while logged in
+    watcher: Option<DirWatcher>,
+    /// Runs `Config::on_complete_command` for finished torrents
+    completion_hook: Option<CompletionHook>,
     pub should_quit: bool,
-    pub error_message: Option<String>,
+    /// Why the last login failed, shown under the login form
+    pub login_error: Option<String>,
+    /// Where dismissing the error popup goes back to
+    error_return_to: AppState,
     pub show_password: bool,
     pub scroll_offset: usize,
-    pub delete_confirmation_hash: Option<String>,
+    pub delete_confirmation: Vec<DeleteTarget>,
+    pub confirmation: Option<Confirmation>,
+    pub prompt: Option<Prompt>,
+    pub selected_hashes: HashSet<String>,
+    pub sort_column: SortColumn,
+    pub sort_descending: bool,
+    pub status_filter: StatusFilter,
+    pub show_sidebar: bool,
+    pub row_mode: RowMode,
+    /// Columns of the torrent list, in order
+    pub columns: Vec<ListColumn>,
+    pub column_chooser: Option<ColumnChooser>,
+    pub cleanup: Option<CleanupView>,
+    pub label_filter: Option<LabelFilter>,
+    pub categories: Vec<String>,
+    pub tags: Vec<String>,
+    pub show_filter_pane: bool,
+    pub filter_pane_focused: bool,
+    pub filter_pane_index: usize,
+    /// Toasts for recoverable errors and confirmations, oldest first
+    pub notifications: Vec<Notification>,
+    /// State of each torrent at the last refresh, to spot the ones that
+    /// finished since. `None` until the first refresh after logging in.
+    previous_states: Option<HashMap<String, TorrentState>>,
+    /// Bells and escape sequences, such as an OSC 52 copy, to write to the
+    /// terminal after the next draw
+    pub terminal_output: String,
+    /// Tried in turn to copy to the clipboard, before falling back to OSC 52
+    copy_tools: Vec<CopyTool>,
+    /// Taken by the main loop after the events queued so far
+    pub handoff: Option<Handoff>,
     pub max_visible_rows: usize,
     pub terminal_width: u16,
     pub terminal_height: u16,
     pub is_searching: bool,
+    /// The search box parsed into a filter; it keeps the last valid query
+    /// while `search_error` says what is wrong with the current text.
+    pub search_query: Query,
+    pub search_error: Option<String>,
+    pub keymap: Keymap,
+    pub theme: Theme,
+    /// Seconds between automatic refreshes, 0 for manual refresh only
+    pub refresh_interval_secs: u64,
+    /// Torrent rows as last drawn, used to map mouse clicks back to rows
+    pub list_area: Rect,
+    /// Header cells of sortable columns as last drawn
+    pub header_sort_areas: Vec<(Rect, SortColumn)>,
+    last_click: Option<(Instant, usize)>,
 }
 
 impl App {
@@ -61,7 +610,14 @@ impl App {
         password: Option<String>,
     ) -> Result<Self> {
         let config = Config::load();
-        Self::new_with_config(base_url, username, password, config).await
+        Self::new_with_config(
+            base_url,
+            username,
+            password,
+            config,
+            ConnectionOptions::default(),
+        )
+        .await
     }
 
     pub async fn new_with_config(
@@ -69,8 +625,22 @@ impl App {
         username: Option<String>,
         password: Option<String>,
         config: Config,
+        connection: ConnectionOptions,
     ) -> Result<Self> {
-        let client = QBittorrentClient::new(base_url.clone());
+        let client: Arc<dyn TorrentApi> =
+            Arc::new(QBittorrentClient::new(base_url.clone(), &connection));
+        // Invalid entries were already reported by main before startup
+        let (keymap, _) = Keymap::from_config(&config.keybindings);
+        let (theme, _) = Theme::from_config(&config.theme, None);
+        let refresh_interval_secs = config.refresh_interval_secs;
+        let speed_history = SpeedHistory::new(config.speed_history_len);
+        let torrent_speed_history = TorrentSpeedHistory::new(config.speed_history_len);
+        let stalled_after_secs = i64::try_from(config.stalled_after_mins)
+            .ok()
+            .and_then(|mins| mins.checked_mul(60))
+            .unwrap_or(i64::MAX);
+        let stalls = StallTracker::new(stalled_after_secs);
+        let ui = config.ui.clone();
 
         // Use saved config if no CLI args provided
         let (initial_url, initial_username) = if username.is_none() && password.is_none() {
@@ -84,49 +654,365 @@ impl App {
             (base_url.to_string(), String::new())
         };
 
+        // Start on the default profile, or the one used last
+        let profile_index = config
+            .profiles
+            .iter()
+            .position(|profile| profile.default)
+            .or_else(|| {
+                let last_url = config.get_last_url()?;
+                config.profiles.iter().position(|p| p.url == last_url)
+            })
+            .unwrap_or(0);
+
         let mut app = Self {
             client,
-            config,
+            pending_login: None,
+            connection,
+            demo: false,
             state: if username.is_some() && password.is_some() {
                 AppState::Login // Skip URL config if CLI args provided
+            } else if !config.profiles.is_empty() {
+                AppState::ProfilePicker
             } else {
                 AppState::UrlConfig // Start with URL configuration
             },
+            config,
             input_mode: InputMode::Url,
-            url_input: initial_url,
-            username_input: initial_username,
-            password_input: String::new(),
-            torrent_path_input: String::new(),
-            search_input: String::new(),
+            url_input: TextInput::new(initial_url),
+            profile_index,
+            profile_name_input: TextInput::default(),
+            editing_profile: None,
+            username_input: TextInput::new(initial_username),
+            password_input: TextInput::default(),
+            torrent_path_input: TextInput::default(),
+            add_save_path_input: TextInput::default(),
+            add_torrent_size: (String::new(), None),
+            add_category_index: 0,
+            add_start_paused: false,
+            path_completions: Vec::new(),
+            file_browser: None,
+            help_scroll: 0,
+            log_view: LogView::default(),
+            search_view: SearchView::default(),
+            rss_view: RssView::default(),
+            settings_view: SettingsView::default(),
+            command_palette: None,
+            share_limits: None,
+            details_tab: DetailsTab::General,
+            tracker_panel: TrackerPanel::default(),
+            peer_panel: PeerPanel::default(),
+            tracker_hints: TrackerHints::default(),
+            file_panel: FilePanel::default(),
+            piece_map: PieceMap::default(),
+            path_completion_index: None,
+            search_input: TextInput::default(),
+            download_limit_input: String::new(),
+            upload_limit_input: String::new(),
             torrents: Vec::new(),
-            filtered_torrents: Vec::new(),
+            listed: Vec::new(),
             selected_torrent: 0,
+            selected_hash: None,
             server_state: None,
             last_update: Instant::now(),
+            last_update_at: None,
+            speed_history,
+            torrent_speed_history,
+            stalls,
+            server_version: None,
+            build_info: None,
+            listen_port: None,
+            preferences: None,
+            offline_retry: None,
+            server_shut_down: false,
+            sync: SyncState::default(),
+            events: None,
+            poller: None,
+            watcher: None,
+            completion_hook: None,
             should_quit: false,
-            error_message: None,
+            login_error: None,
+            error_return_to: AppState::Main,
             show_password: false,
             scroll_offset: 0,
-            delete_confirmation_hash: None,
+            delete_confirmation: Vec::new(),
+            confirmation: None,
+            prompt: None,
+            selected_hashes: HashSet::new(),
+            sort_column: ui.sort_column,
+            sort_descending: ui.sort_descending,
+            status_filter: ui.status_filter,
+            show_sidebar: ui.show_sidebar,
+            row_mode: ui.row_mode,
+            columns: ui.columns,
+            column_chooser: None,
+            cleanup: None,
+            label_filter: None,
+            categories: Vec::new(),
+            tags: Vec::new(),
+            show_filter_pane: false,
+            filter_pane_focused: false,
+            filter_pane_index: 0,
+            notifications: Vec::new(),
+            previous_states: None,
+            terminal_output: String::new(),
+            copy_tools: clipboard::copy_tools(),
+            handoff: None,
             max_visible_rows: 20,
             terminal_width: 80, // Default values
             terminal_height: 24,
             is_searching: false,
+            search_query: Query::default(),
+            search_error: None,
+            keymap,
+            theme,
+            refresh_interval_secs,
+            list_area: Rect::default(),
+            header_sort_areas: Vec::new(),
+            last_click: None,
         };
 
-        // If credentials were provided, try to login automatically
         if let (Some(user), Some(pass)) = (username, password) {
-            app.username_input = user;
-            app.password_input = pass;
+            app.username_input.set(user);
+            app.password_input.set(pass);
             app.input_mode = InputMode::Username;
-            app.attempt_login().await?;
         }
 
         Ok(app)
     }
 
+    /// Swaps the server for the made-up one of `--demo`, logged in with
+    /// whatever the login screen holds.
+    pub fn use_demo(&mut self, server: DemoServer) {
+        self.client = Arc::new(server);
+        self.demo = true;
+        self.state = AppState::Login;
+        self.url_input.set(self.client.get_base_url().to_string());
+        self.username_input.set("demo");
+        self.password_input.set("demo");
+        self.input_mode = InputMode::Password;
+    }
+
+    /// Points the app at the server at `url`.
+    fn connect(&mut self, url: Url) {
+        if !self.demo {
+            self.client = Arc::new(QBittorrentClient::new(url, &self.connection));
+        }
+    }
+
+    /// Logs in with the credentials given on the command line, or the
+    /// password saved for the last connection, or checks whether the URL on
+    /// the URL screen needs a login at all. Called once the main loop is
+    /// connected so the login can be cancelled.
+    pub async fn login_on_start(&mut self) -> Result<()> {
+        if self.state == AppState::Login
+            && !self.username_input.is_empty()
+            && !self.password_input.is_empty()
+        {
+            return self.attempt_login().await;
+        }
+        if let (Some(url), Some(username)) =
+            (self.config.get_last_url(), self.config.get_last_username())
+            && self.login_with_saved_password(&url, &username).await?
+        {
+            return Ok(());
+        }
+        if self.state == AppState::UrlConfig
+            && let Ok(url) = Url::parse(self.url_input.value())
+        {
+            self.connect(url);
+            self.start_login(LoginKind::Probe).await?;
+        }
+        Ok(())
+    }
+
+    /// Logs in to `url` with the password from the keyring when
+    /// `save_password` is on, returning whether there was one to try. If
+    /// the login fails the login screen is shown with the reason as a toast.
+    async fn login_with_saved_password(&mut self, url: &str, username: &str) -> Result<bool> {
+        if !self.config.save_password {
+            return Ok(false);
+        }
+        let Ok(base_url) = Url::parse(url) else {
+            return Ok(false);
+        };
+        // Passwords are filed under the normalized URL the client reports
+        let Some(password) = credentials::load(base_url.as_str(), username).await else {
+            return Ok(false);
+        };
+
+        self.connect(base_url);
+        self.username_input.set(username);
+        self.password_input.set(password);
+        self.start_login(LoginKind::SavedPassword).await?;
+        Ok(true)
+    }
+
+    /// Connects to a saved profile: the login screen with its username filled
+    /// in, or straight to the list when its password is in the keyring or
+    /// the server needs no login.
+    pub async fn open_profile(&mut self, profile: &Profile) -> Result<()> {
+        let Ok(base_url) = Url::parse(&profile.url) else {
+            self.notify_error(format!(
+                "Profile \"{}\" has an invalid URL: {}",
+                profile.name, profile.url
+            ));
+            return Ok(());
+        };
+
+        self.connect(base_url);
+        self.url_input.set(profile.url.clone());
+        let username = profile.username.clone().unwrap_or_default();
+        self.username_input.set(username.clone());
+        self.password_input.clear();
+        self.state = AppState::Login;
+        self.input_mode = if username.is_empty() {
+            InputMode::Username
+        } else {
+            InputMode::Password
+        };
+
+        if username.is_empty()
+            || !self
+                .login_with_saved_password(&profile.url, &username)
+                .await?
+        {
+            self.start_login(LoginKind::Probe).await?;
+        }
+        Ok(())
+    }
+
+    async fn handle_profile_picker_input(&mut self, key: KeyEvent) -> Result<()> {
+        // The last entry is "New connection…"
+        let last = self.config.profiles.len();
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.profile_index = self.profile_index.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.profile_index = (self.profile_index + 1).min(last);
+            }
+            KeyCode::Enter => match self.config.profiles.get(self.profile_index).cloned() {
+                Some(profile) => self.open_profile(&profile).await?,
+                None => {
+                    self.url_input.clear();
+                    self.state = AppState::UrlConfig;
+                    self.input_mode = InputMode::Url;
+                }
+            },
+            KeyCode::Char('e') => {
+                if let Some(profile) = self.config.profiles.get(self.profile_index).cloned() {
+                    self.profile_name_input.set(profile.name.clone());
+                    self.url_input.set(profile.url);
+                    self.username_input
+                        .set(profile.username.unwrap_or_default());
+                    self.editing_profile = Some(profile.name);
+                    self.state = AppState::ProfileEdit;
+                    self.input_mode = InputMode::ProfileName;
+                }
+            }
+            KeyCode::Char('d') => {
+                if let Some(profile) = self.config.profiles.get(self.profile_index) {
+                    let name = profile.name.clone();
+                    match self.config.remove_profile(&name) {
+                        Ok(()) => self.notify_success(format!("Deleted profile {name}")),
+                        Err(e) => self.notify_error(format!("Failed to save config: {e}")),
+                    }
+                    self.profile_index = self.profile_index.min(self.config.profiles.len());
+                }
+            }
+            KeyCode::Esc => self.should_quit = true,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_profile_edit_input(&mut self, key: KeyEvent) {
+        const FIELDS: [InputMode; 3] =
+            [InputMode::ProfileName, InputMode::Url, InputMode::Username];
+        let field = FIELDS
+            .iter()
+            .position(|mode| *mode == self.input_mode)
+            .unwrap_or(0);
+
+        match key.code {
+            KeyCode::Tab | KeyCode::Down => self.input_mode = FIELDS[(field + 1) % FIELDS.len()],
+            KeyCode::BackTab | KeyCode::Up => {
+                self.input_mode = FIELDS[(field + FIELDS.len() - 1) % FIELDS.len()]
+            }
+            KeyCode::Enter => self.save_edited_profile(),
+            KeyCode::Esc => {
+                self.editing_profile = None;
+                self.state = AppState::ProfilePicker;
+            }
+            _ => {
+                let input = match self.input_mode {
+                    InputMode::Url => &mut self.url_input,
+                    InputMode::Username => &mut self.username_input,
+                    _ => &mut self.profile_name_input,
+                };
+                input.handle_key(key);
+            }
+        }
+    }
+
+    fn save_edited_profile(&mut self) {
+        let name = self.profile_name_input.value().trim().to_string();
+        if name.is_empty() {
+            self.notify_warning("Profile name can't be empty".to_string());
+            return;
+        }
+        let Ok(url) = Url::parse(self.url_input.value().trim()) else {
+            self.notify_warning(
+                "Invalid URL format. Please enter a valid URL (e.g., http://localhost:8080)"
+                    .to_string(),
+            );
+            return;
+        };
+        if self.editing_profile.as_deref() != Some(name.as_str())
+            && self.config.profile(&name).is_some()
+        {
+            self.notify_warning(format!("A profile called {name} already exists"));
+            return;
+        }
+
+        let original = self.editing_profile.take();
+        let username = self.username_input.value().trim();
+        let profile = Profile {
+            name: name.clone(),
+            url: url.to_string(),
+            username: (!username.is_empty()).then(|| username.to_string()),
+            default: original
+                .as_deref()
+                .and_then(|original| self.config.profile(original))
+                .is_some_and(|profile| profile.default),
+        };
+        match self.config.save_profile(profile, original.as_deref()) {
+            Ok(()) => self.notify_success(format!("Saved profile {name}")),
+            Err(e) => self.notify_error(format!("Failed to save config: {e}")),
+        }
+        self.state = AppState::ProfilePicker;
+        self.input_mode = InputMode::None;
+    }
+
+    async fn forget_saved_password(&mut self) {
+        let url = self.client.get_base_url().to_string();
+        match credentials::forget(&url, self.username_input.value()).await {
+            Ok(true) => self.notify_success("Forgot the saved password".to_string()),
+            Ok(false) => self.notify_warning("No password is saved for this user".to_string()),
+            Err(e) => self.notify_error(format!("Failed to forget the saved password: {e}")),
+        }
+    }
+
     pub async fn handle_event(&mut self, event: crossterm::event::Event) -> Result<bool> {
-        if let crossterm::event::Event::Key(key) = event {
+        if let crossterm::event::Event::Mouse(mouse) = event {
+            let in_details = self.state == AppState::Details;
+            self.handle_mouse(mouse);
+            // A double click opened the detail view
+            if !in_details && self.state == AppState::Details {
+                self.fetch_details_tab().await;
+            }
+        } else if let crossterm::event::Event::Key(key) = event {
             // Only handle key press events, ignore key release events
             if key.kind != KeyEventKind::Press {
                 return Ok(false);
@@ -137,465 +1023,4786 @@ impl App {
                 self.should_quit = true;
                 return Ok(self.should_quit);
             }
+            // Raw mode keeps Ctrl+Z from stopping the process by itself
+            if key.code == KeyCode::Char('z') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                self.handoff = Some(Handoff::Suspend);
+                return Ok(false);
+            }
 
             match self.state {
+                AppState::ProfilePicker => self.handle_profile_picker_input(key).await?,
+                AppState::ProfileEdit => self.handle_profile_edit_input(key),
                 AppState::UrlConfig => self.handle_url_config_input(key).await?,
+                AppState::Connecting => self.handle_connecting_input(key),
                 AppState::Login => self.handle_login_input(key).await?,
                 AppState::Main => self.handle_main_input(key).await?,
                 AppState::AddTorrent => self.handle_add_torrent_input(key).await?,
+                AppState::FileBrowser => self.handle_file_browser_input(key),
                 AppState::Search => self.handle_search_input(key).await?,
                 AppState::ConfirmDelete => self.handle_confirm_delete_input(key).await?,
+                AppState::SpeedLimits => self.handle_speed_limits_input(key).await?,
+                AppState::Details => self.handle_details_input(key).await?,
+                AppState::ShareLimits => self.handle_share_limits_input(key).await,
+                AppState::Help => self.handle_help_input(key),
+                AppState::Log => self.handle_log_input(key),
+                AppState::Rss => self.handle_rss_input(key).await,
+                AppState::Settings => self.handle_settings_input(key).await,
+                AppState::CommandPalette => self.handle_command_palette_input(key).await?,
+                AppState::OnlineSearch => self.handle_online_search_input(key).await,
+                AppState::Copy => self.handle_copy_input(key),
+                AppState::Columns => self.handle_column_chooser_input(key),
+                AppState::Cleanup => self.handle_cleanup_input(key),
+                AppState::Statistics => {
+                    if matches!(key.code, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('i')) {
+                        self.state = AppState::Main;
+                    }
+                }
+                AppState::Confirm => self.handle_confirm_input(key).await?,
+                AppState::Prompt => self.handle_prompt_input(key).await?,
                 AppState::Error(_) => {
                     if key.code == KeyCode::Enter || key.code == KeyCode::Esc {
-                        self.state = AppState::Main;
-                        self.error_message = None;
+                        self.state = std::mem::replace(&mut self.error_return_to, AppState::Main);
                     }
                 }
             }
-        } // Auto-refresh torrents every 2 seconds when in main state
-        if self.state == AppState::Main && self.last_update.elapsed() > Duration::from_secs(2) {
-            self.refresh_data().await?;
         }
 
-        Ok(self.should_quit)
-    }
-
-    pub fn handle_resize(&mut self, width: u16, height: u16) {
-        self.terminal_width = width;
-        self.terminal_height = height;
-
-        // Recalculate max visible rows based on new height
-        // Reserve space for header (3), footer (3), and some padding
-        let available_height = height.saturating_sub(6);
-        self.max_visible_rows = available_height.max(1) as usize;
-
-        // Adjust scroll offset if necessary
-        if self.scroll_offset + self.max_visible_rows > self.torrents.len() {
-            self.scroll_offset = self.torrents.len().saturating_sub(self.max_visible_rows);
-        }
-
-        // Reset selected torrent if it's out of bounds
-        if self.selected_torrent >= self.torrents.len() && !self.torrents.is_empty() {
-            self.selected_torrent = self.torrents.len() - 1;
+        if let Some(poller) = &self.poller {
+            poller.set_paused(self.state.pauses_refresh());
         }
+        self.fetch_tracker_hint();
+        Ok(self.should_quit)
     }
 
-    async fn handle_url_config_input(&mut self, key: KeyEvent) -> Result<()> {
-        match key.code {
-            KeyCode::Enter => {
-                if !self.url_input.is_empty() {
-                    match Url::parse(&self.url_input) {
-                        Ok(url) => {
-                            self.client = QBittorrentClient::new(url);
-                            self.state = AppState::Login;
-                            self.input_mode = InputMode::Username;
-                        }
-                        Err(_) => {
-                            self.error_message = Some("Invalid URL format. Please enter a valid URL (e.g., http://localhost:8080)".to_string());
-                            self.state = AppState::Error("Invalid URL".to_string());
-                        }
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        let position = Position::new(mouse.column, mouse.row);
+        match (&self.state, mouse.kind) {
+            (AppState::Main, MouseEventKind::ScrollUp) => self.scroll_list(-MOUSE_SCROLL_ROWS),
+            (AppState::Main, MouseEventKind::ScrollDown) => self.scroll_list(MOUSE_SCROLL_ROWS),
+            (AppState::Main, MouseEventKind::Down(MouseButton::Left)) => {
+                if self.list_area.contains(position) {
+                    self.filter_pane_focused = false;
+                    self.click_row((mouse.row - self.list_area.y) as usize);
+                } else if let Some(&(_, column)) = self
+                    .header_sort_areas
+                    .iter()
+                    .find(|(area, _)| area.contains(position))
+                {
+                    if self.sort_column == column {
+                        self.sort_descending = !self.sort_descending;
+                    } else {
+                        self.sort_column = column;
                     }
+                    self.sort_torrents();
                 }
             }
-            KeyCode::Esc => {
-                self.should_quit = true;
+            (AppState::Help, MouseEventKind::ScrollUp) => {
+                self.help_scroll = self.help_scroll.saturating_sub(MOUSE_SCROLL_ROWS as usize);
             }
-            KeyCode::Char(c) => {
-                self.url_input.push(c);
+            (AppState::Help, MouseEventKind::ScrollDown) => {
+                self.help_scroll += MOUSE_SCROLL_ROWS as usize;
             }
-            KeyCode::Backspace => {
-                self.url_input.pop();
+            (AppState::Log, MouseEventKind::ScrollUp) => {
+                self.log_view.scroll_up(MOUSE_SCROLL_ROWS as usize);
+            }
+            (AppState::Log, MouseEventKind::ScrollDown) => {
+                self.log_view.scroll_down(MOUSE_SCROLL_ROWS as usize);
+            }
+            (AppState::Help, MouseEventKind::Down(MouseButton::Left)) => {
+                self.state = AppState::Main;
             }
+            (AppState::FileBrowser, MouseEventKind::ScrollUp) => {
+                if let Some(browser) = self.file_browser.as_mut() {
+                    browser.move_selection(-MOUSE_SCROLL_ROWS);
+                }
+            }
+            (AppState::FileBrowser, MouseEventKind::ScrollDown) => {
+                if let Some(browser) = self.file_browser.as_mut() {
+                    browser.move_selection(MOUSE_SCROLL_ROWS);
+                }
+            }
+            // Dialogs are keyboard driven; clicks on them are ignored
             _ => {}
         }
-        Ok(())
     }
 
-    async fn handle_login_input(&mut self, key: KeyEvent) -> Result<()> {
-        match key.code {
-            KeyCode::Tab => {
-                self.input_mode = match self.input_mode {
-                    InputMode::Username => InputMode::Password,
-                    InputMode::Password => InputMode::Username,
-                    _ => InputMode::Username,
-                };
-            }
-            KeyCode::Enter => {
-                if !self.username_input.is_empty() && !self.password_input.is_empty() {
-                    self.attempt_login().await?;
+    // Selects the clicked row; a second click on it soon after opens details
+    fn click_row(&mut self, row: usize) {
+        let index = self.scroll_offset + row / self.row_mode.height();
+        if index >= self.get_current_torrent_list_len() {
+            return;
+        }
+
+        let double_click = self
+            .last_click
+            .is_some_and(|(at, last)| last == index && at.elapsed() <= DOUBLE_CLICK_INTERVAL);
+        self.select_index(index);
+        if double_click {
+            self.last_click = None;
+            self.state = AppState::Details;
+        } else {
+            self.last_click = Some((Instant::now(), index));
+        }
+    }
+
+    // Moves the viewport, dragging the selection along when it scrolls out
+    fn scroll_list(&mut self, delta: isize) {
+        let rows = self.get_max_visible_rows();
+        let max_offset = self.get_current_torrent_list_len().saturating_sub(rows);
+        self.scroll_offset = self
+            .scroll_offset
+            .saturating_add_signed(delta)
+            .min(max_offset);
+        let selected = self
+            .selected_index()
+            .clamp(self.scroll_offset, self.scroll_offset + rows - 1);
+        self.select_index(selected);
+    }
+
+    /// Merges a result from the background poller into the app state.
+    pub async fn handle_update(&mut self, event: AppEvent) -> Result<()> {
+        match event {
+            AppEvent::MainDataUpdated(data) => {
+                self.sync.apply(*data);
+                self.apply_torrents(self.sync.torrents());
+                self.announce_completions();
+                self.server_state = self.sync.server_state.clone();
+                if let Some(state) = &self.server_state {
+                    self.speed_history
+                        .push(state.dl_info_speed as u64, state.up_info_speed as u64);
+                }
+                if let Some(torrent) = self.get_current_selected_torrent().cloned() {
+                    self.torrent_speed_history.push(&torrent);
+                }
+                self.update_labels();
+                self.fetch_tracker_hint();
+                // The log, feeds and detail tabs follow the same schedule as
+                // the list
+                match self.state {
+                    AppState::Log => self.fetch_log().await,
+                    AppState::Rss => self.fetch_rss().await,
+                    AppState::Details => self.fetch_details_tab().await,
+                    _ => {}
                 }
+                self.last_update = Instant::now();
+                self.last_update_at = Some(Utc::now());
+                self.offline_retry = None;
+                self.server_shut_down = false;
             }
-            KeyCode::Esc => {
-                self.should_quit = true;
+            // Keep showing the stale list; the header banner explains why
+            AppEvent::ConnectionLost { retry_in } => self.offline_retry = Some(retry_in),
+            // A server on its way down may answer oddly before it's gone
+            AppEvent::ApiError(message) if self.server_shut_down => debug!("{message}"),
+            AppEvent::ApiError(message) => self.notify_error(message),
+            AppEvent::LoginFinished(result) => {
+                // A cancelled login has nothing left to report to
+                if let Some(pending) = self.pending_login.take() {
+                    self.finish_login(result, pending.kind, pending.return_to)
+                        .await?;
+                }
             }
-            KeyCode::Char('h')
-                if key
-                    .modifiers
-                    .contains(crossterm::event::KeyModifiers::CONTROL) =>
-            {
-                self.show_password = !self.show_password;
+            AppEvent::LogFetched(result) => self.finish_log_fetch(result),
+            AppEvent::RssLoaded(result) => self.finish_rss_fetch(result),
+            AppEvent::TrackersLoaded { hash, result } => self.finish_tracker_fetch(&hash, result),
+            AppEvent::PeersLoaded { hash, result } => self.finish_peer_fetch(&hash, result),
+            AppEvent::TrackerHintLoaded { hash, result } => {
+                self.tracker_hints
+                    .finish(&hash, result.as_deref().ok(), Instant::now());
             }
-            _ => match self.input_mode {
-                InputMode::Username => match key.code {
-                    KeyCode::Char(c) => {
-                        self.username_input.push(c);
-                    }
-                    KeyCode::Backspace => {
-                        self.username_input.pop();
-                    }
-                    _ => {}
-                },
-                InputMode::Password => match key.code {
-                    KeyCode::Char(c) => {
-                        self.password_input.push(c);
-                    }
-                    KeyCode::Backspace => {
-                        self.password_input.pop();
-                    }
-                    _ => {}
-                },
-                _ => {}
+            AppEvent::FilesLoaded { hash, result } => self.finish_file_fetch(&hash, result),
+            AppEvent::PiecesLoaded { hash, result } => self.finish_piece_fetch(&hash, result),
+            AppEvent::SearchResults { job, result } => match result {
+                Ok(page) => self.search_view.append(job, page),
+                Err(e) if self.search_view.job == Some(job) => {
+                    self.search_view.running = false;
+                    self.notify_error(format!("Search failed: {e}"));
+                }
+                Err(_) => {}
             },
+            AppEvent::TorrentWatched(name) => {
+                self.notify_success(format!("Added {name} from the watch folder"));
+                self.refresh_data();
+            }
+            AppEvent::Tick | AppEvent::Input(_) => {}
         }
         Ok(())
     }
 
-    async fn handle_main_input(&mut self, key: KeyEvent) -> Result<()> {
-        match key.code {
-            KeyCode::Char('q') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.should_quit = true;
-            }
-            KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.state = AppState::Search;
-                self.input_mode = InputMode::Search;
-                self.search_input.clear();
-                self.is_searching = true;
-                self.filter_torrents();
-            }
-            KeyCode::Char('r') => self.refresh_data().await?,
-            KeyCode::Char('a') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.state = AppState::AddTorrent;
-                self.input_mode = InputMode::TorrentPath;
-                self.torrent_path_input = String::new();
+    async fn open_online_search(&mut self) {
+        self.state = AppState::OnlineSearch;
+        self.search_view.focus = SearchFocus::Query;
+        // Say right away when there is nothing to search with
+        self.search_view.message = self.search_plugins_problem().await;
+    }
+
+    async fn search_plugins_problem(&self) -> Option<String> {
+        match self.client.get_search_plugins().await {
+            Ok(plugins) => enabled_plugins(&plugins).err(),
+            Err(e) => Some(format!("Could not list the search plugins: {e}")),
+        }
+    }
+
+    async fn start_online_search(&mut self) {
+        let pattern = self.search_view.query.value().trim().to_string();
+        if pattern.is_empty() {
+            return;
+        }
+        self.search_view.message = self.search_plugins_problem().await;
+        if self.search_view.message.is_some() {
+            return;
+        }
+
+        self.stop_online_search();
+        let job = match self.client.start_search(&pattern, "enabled").await {
+            Ok(job) => job,
+            Err(e) => {
+                let message = match &e {
+                    ApiError::Conflict(_) => {
+                        "qBittorrent is already running as many searches as it allows".to_string()
+                    }
+                    _ => format!("Failed to start the search: {e}"),
+                };
+                self.notify_error(message);
+                return;
             }
-            KeyCode::Up => {
-                if self.selected_torrent > 0 {
-                    self.selected_torrent -= 1;
-                    self.adjust_scroll();
+        };
+        self.search_view.results.clear();
+        self.search_view.selected = 0;
+        self.search_view.job = Some(job);
+        self.search_view.running = true;
+        self.poll_search(job).await;
+    }
+
+    // Results are fetched in the background as the server finds them, so
+    // typing and scrolling stay responsive while plugins are slow
+    async fn poll_search(&mut self, job: i64) {
+        let client = self.client.clone();
+        let Some(sender) = self.events.clone() else {
+            let result = client.get_search_results(job, 0).await;
+            return match result {
+                Ok(page) => self.search_view.append(job, page),
+                Err(e) => self.notify_error(format!("Search failed: {e}")),
+            };
+        };
+
+        let cancel = CancellationToken::new();
+        self.search_view.cancel = Some(cancel.clone());
+        tokio::spawn(async move {
+            let mut offset = 0;
+            loop {
+                let status = tokio::select! {
+                    _ = cancel.cancelled() => return,
+                    status = client.get_search_status(job) => status,
+                };
+                let status = match status {
+                    Ok(Some(status)) => status,
+                    // The job was deleted on the server
+                    Ok(None) => return,
+                    Err(e) => {
+                        let _ = sender.send(AppEvent::SearchResults {
+                            job,
+                            result: Err(e),
+                        });
+                        return;
+                    }
+                };
+                let running = status.status == "Running";
+
+                // The status is cheap; results are only fetched when there are
+                // new ones, and once more when the job stops
+                if status.total as usize > offset || !running {
+                    let result = tokio::select! {
+                        _ = cancel.cancelled() => return,
+                        result = client.get_search_results(job, offset) => result,
+                    };
+                    let failed = result.is_err();
+                    if let Ok(page) = &result {
+                        offset += page.results.len();
+                    }
+                    if sender
+                        .send(AppEvent::SearchResults { job, result })
+                        .is_err()
+                        || failed
+                    {
+                        return;
+                    }
                 }
-            }
-            KeyCode::Down => {
-                let max_len = self.get_current_torrent_list_len();
-                if self.selected_torrent < max_len.saturating_sub(1) {
-                    self.selected_torrent += 1;
-                    self.adjust_scroll();
+                if !running {
+                    return;
+                }
+                tokio::select! {
+                    _ = cancel.cancelled() => return,
+                    _ = tokio::time::sleep(SEARCH_POLL_INTERVAL) => {}
                 }
             }
-            KeyCode::PageUp => {
-                let page_size = self.get_max_visible_rows().saturating_sub(1).max(1);
-                self.selected_torrent = self.selected_torrent.saturating_sub(page_size);
-                self.adjust_scroll();
-            }
-            KeyCode::PageDown => {
-                let page_size = self.get_max_visible_rows().saturating_sub(1).max(1);
-                let max_len = self.get_current_torrent_list_len();
-                self.selected_torrent =
-                    (self.selected_torrent + page_size).min(max_len.saturating_sub(1));
-                self.adjust_scroll();
-            }
-            KeyCode::Home => {
-                self.selected_torrent = 0;
-                self.adjust_scroll();
+        });
+    }
+
+    // Stops polling and removes the job from the server, which would
+    // otherwise keep its results until restarted
+    fn stop_online_search(&mut self) {
+        let Some(job) = self.search_view.stop() else {
+            return;
+        };
+        let client = self.client.clone();
+        tokio::spawn(async move {
+            let _ = client.stop_search(job).await;
+            let _ = client.delete_search(job).await;
+        });
+    }
+
+    async fn add_search_result(&mut self) {
+        let Some(result) = self.search_view.selected_result().cloned() else {
+            return;
+        };
+        match self
+            .client
+            .add_magnet(&result.file_url, &AddTorrentOptions::default())
+            .await
+        {
+            Ok(()) => {
+                self.notify_success(format!("Added {}", result.file_name));
+                self.refresh_data();
             }
-            KeyCode::End => {
-                let max_len = self.get_current_torrent_list_len();
-                self.selected_torrent = max_len.saturating_sub(1);
-                self.adjust_scroll();
+            Err(e) => self.notify_error(format!("Failed to add {}: {e}", result.file_name)),
+        }
+    }
+
+    async fn handle_online_search_input(&mut self, key: KeyEvent) {
+        let view = &mut self.search_view;
+        match (view.focus, key.code) {
+            (_, KeyCode::Esc) => {
+                self.stop_online_search();
+                self.state = AppState::Main;
             }
-            KeyCode::Char(' ') => {
-                if let Some(torrent) = self.get_current_selected_torrent() {
-                    let hash = torrent.hash.clone();
-                    log_debug(
-                        &format!(
-                            "Torrent state: '{}', name: '{}'",
-                            torrent.state, torrent.name
-                        ),
-                        &self.config.get_timezone(),
-                    );
-                    match torrent.state.as_str() {
-                        "pausedDL" | "pausedUP" | "stoppedDL" | "stoppedUP" => {
-                            log_debug("Attempting to resume torrent", &self.config.get_timezone());
-                            self.client
-                                .resume_torrent(&hash, &self.config.get_timezone())
-                                .await?;
-                        }
-                        _ => {
-                            log_debug("Attempting to pause torrent", &self.config.get_timezone());
-                            self.client
-                                .pause_torrent(&hash, &self.config.get_timezone())
-                                .await?;
-                        }
-                    }
-                    self.refresh_data().await?;
-                }
+            (SearchFocus::Query, KeyCode::Enter) => self.start_online_search().await,
+            (SearchFocus::Query, KeyCode::Down | KeyCode::Tab) if !view.results.is_empty() => {
+                view.focus = SearchFocus::Results;
             }
-            KeyCode::Delete => {
-                if let Some(torrent) = self.get_current_selected_torrent() {
-                    self.delete_confirmation_hash = Some(torrent.hash.clone());
-                    self.state = AppState::ConfirmDelete;
-                }
+            (SearchFocus::Query, _) => {
+                view.query.handle_key(key);
             }
-            KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.state = AppState::Search;
-                self.input_mode = InputMode::Search;
-                self.search_input.clear();
-                self.filtered_torrents.clear();
-                self.is_searching = false;
+            (SearchFocus::Results, KeyCode::Enter) => self.add_search_result().await,
+            (SearchFocus::Results, KeyCode::Tab) => view.focus = SearchFocus::Query,
+            (SearchFocus::Results, KeyCode::Up) if view.selected == 0 => {
+                view.focus = SearchFocus::Query;
             }
+            (SearchFocus::Results, KeyCode::Up) => view.move_selection(-1),
+            (SearchFocus::Results, KeyCode::Down) => view.move_selection(1),
+            (SearchFocus::Results, KeyCode::PageUp) => view.move_selection(-10),
+            (SearchFocus::Results, KeyCode::PageDown) => view.move_selection(10),
+            (SearchFocus::Results, KeyCode::Home) => view.selected = 0,
+            (SearchFocus::Results, KeyCode::End) => view.move_selection(isize::MAX),
             _ => {}
         }
-        Ok(())
     }
 
-    async fn handle_add_torrent_input(&mut self, key: KeyEvent) -> Result<()> {
-        match key.code {
-            KeyCode::Enter => {
-                let path = self.torrent_path_input.clone();
-                if !path.is_empty() {
-                    match std::fs::read(path) {
-                        Ok(data) => {
-                            if let Err(e) = self.client.add_torrent(&data, None).await {
-                                self.error_message = Some(format!("Failed to add torrent: {e}"));
-                                self.state = AppState::Error(format!("Failed to add torrent: {e}"));
-                            } else {
-                                self.state = AppState::Main;
-                                self.input_mode = InputMode::None;
-                                self.refresh_data().await?;
-                            }
-                        }
-                        Err(e) => {
-                            self.error_message = Some(format!("Failed to read file: {e}"));
-                            self.state = AppState::Error(format!("Failed to read file: {e}"));
-                        }
-                    }
-                }
+    async fn open_rss(&mut self) {
+        self.state = AppState::Rss;
+        self.rss_view.new_feed = None;
+        self.rss_view.confirm_remove = false;
+        match self.client.get_rss_rules().await {
+            Ok(rules) => self.rss_view.rules = rules,
+            Err(e) => self.notify_error(format!("Failed to load RSS rules: {e}")),
+        }
+        self.fetch_rss().await;
+    }
+
+    // Reloads every feed with its articles, in the background when the main
+    // loop is listening
+    async fn fetch_rss(&mut self) {
+        if self.rss_view.loading {
+            return;
+        }
+        self.rss_view.loading = true;
+        let client = self.client.clone();
+        let fetch = async move { client.get_rss_items().await };
+
+        match self.events.clone() {
+            Some(sender) => {
+                tokio::spawn(async move {
+                    let _ = sender.send(AppEvent::RssLoaded(fetch.await));
+                });
             }
-            KeyCode::Esc => {
-                self.state = AppState::Main;
-                self.input_mode = InputMode::None;
+            None => {
+                let result = fetch.await;
+                self.finish_rss_fetch(result);
             }
-            _ => match key.code {
-                KeyCode::Char(c) => {
-                    self.torrent_path_input.push(c);
+        }
+    }
+
+    fn finish_rss_fetch(&mut self, result: ApiResult<Vec<RssFeed>>) {
+        self.rss_view.loading = false;
+        match result {
+            Ok(feeds) => self.rss_view.set_feeds(feeds),
+            Err(e) => self.notify_error(format!("Failed to load RSS feeds: {e}")),
+        }
+    }
+
+    async fn handle_rss_input(&mut self, key: KeyEvent) {
+        if let Some(input) = self.rss_view.new_feed.as_mut() {
+            match key.code {
+                KeyCode::Enter => {
+                    let url = input.value().trim().to_string();
+                    self.rss_view.new_feed = None;
+                    self.add_rss_feed(&url).await;
                 }
-                KeyCode::Backspace => {
-                    self.torrent_path_input.pop();
+                KeyCode::Esc => self.rss_view.new_feed = None,
+                _ => {
+                    input.handle_key(key);
                 }
-                _ => {}
-            },
+            }
+            return;
+        }
+        if self.rss_view.confirm_remove {
+            self.rss_view.confirm_remove = false;
+            if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                self.remove_rss_selection().await;
+            }
+            return;
         }
-        Ok(())
-    }
 
-    async fn handle_search_input(&mut self, key: KeyEvent) -> Result<()> {
+        let view = &mut self.rss_view;
         match key.code {
-            KeyCode::Enter | KeyCode::Esc => {
-                self.state = AppState::Main;
-                self.input_mode = InputMode::None;
-                if key.code == KeyCode::Esc {
-                    // Clear search when canceling
-                    self.search_input.clear();
-                    self.is_searching = false;
-                    self.filtered_torrents.clear();
-                }
+            KeyCode::Esc => self.state = AppState::Main,
+            KeyCode::Up => view.move_selection(-1),
+            KeyCode::Down => view.move_selection(1),
+            KeyCode::PageUp => view.move_selection(-10),
+            KeyCode::PageDown => view.move_selection(10),
+            KeyCode::Tab | KeyCode::BackTab => {
+                view.focus = match view.focus {
+                    RssFocus::Feeds => RssFocus::Articles,
+                    RssFocus::Articles | RssFocus::Rules => RssFocus::Feeds,
+                };
             }
-            KeyCode::Char(c) => {
-                self.search_input.push(c);
-                self.filter_torrents();
+            KeyCode::Char('u') => {
+                view.focus = match view.focus {
+                    RssFocus::Rules => RssFocus::Feeds,
+                    _ => RssFocus::Rules,
+                };
             }
-            KeyCode::Backspace => {
-                self.search_input.pop();
-                self.filter_torrents();
+            KeyCode::Enter => match view.focus {
+                RssFocus::Feeds => view.focus = RssFocus::Articles,
+                RssFocus::Articles => self.add_rss_article().await,
+                RssFocus::Rules => {}
+            },
+            KeyCode::Char(' ') if view.focus == RssFocus::Rules => self.toggle_rss_rule().await,
+            KeyCode::Char('a') => view.new_feed = Some(TextInput::default()),
+            KeyCode::Char('d') | KeyCode::Delete => {
+                view.confirm_remove = match view.focus {
+                    RssFocus::Rules => !view.rules.is_empty(),
+                    _ => view.selected_feed().is_some(),
+                };
             }
+            KeyCode::Char('r') => self.refresh_rss_feed().await,
             _ => {}
         }
-        Ok(())
     }
 
-    async fn handle_confirm_delete_input(&mut self, key: KeyEvent) -> Result<()> {
-        match key.code {
-            KeyCode::Char('y') | KeyCode::Char('Y') => {
-                if let Some(hash) = &self.delete_confirmation_hash {
-                    let delete_files = key.modifiers.contains(KeyModifiers::SHIFT);
-                    if let Err(e) = self.client.delete_torrent(hash, delete_files).await {
-                        self.error_message = Some(format!("Failed to delete torrent: {e}"));
-                        self.state = AppState::Error(format!("Failed to delete torrent: {e}"));
-                    } else {
-                        self.state = AppState::Main;
-                        self.delete_confirmation_hash = None;
-                        self.refresh_data().await?;
-                    }
-                }
+    async fn add_rss_feed(&mut self, url: &str) {
+        if url.is_empty() {
+            return;
+        }
+        // Without a path the server names the feed after its URL
+        if let Err(e) = self.client.add_rss_feed(url, "").await {
+            self.notify_error(format!("Failed to add feed: {e}"));
+            return;
+        }
+        self.notify_success(format!("Subscribed to {url}"));
+        self.fetch_rss().await;
+    }
+
+    async fn remove_rss_selection(&mut self) {
+        let view = &self.rss_view;
+        let (result, name) = if view.focus == RssFocus::Rules {
+            let Some((name, _)) = view.rules.get(view.rule_index) else {
+                return;
+            };
+            let name = name.clone();
+            let result = self.client.remove_rss_rule(&name).await;
+            if result.is_ok() {
+                self.rss_view.rules.retain(|(rule, _)| *rule != name);
+                self.rss_view.move_selection(0);
             }
-            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
-                self.state = AppState::Main;
-                self.delete_confirmation_hash = None;
+            (result, name)
+        } else {
+            let Some(feed) = view.selected_feed() else {
+                return;
+            };
+            let name = feed.name().to_string();
+            (self.client.remove_rss_item(&feed.path).await, name)
+        };
+        match result {
+            Ok(()) => {
+                self.notify_success(format!("Removed {name}"));
+                self.fetch_rss().await;
             }
-            _ => {}
+            Err(e) => self.notify_error(format!("Failed to remove {name}: {e}")),
         }
-        Ok(())
     }
 
-    async fn attempt_login(&mut self) -> Result<()> {
-        match self
+    async fn refresh_rss_feed(&mut self) {
+        let Some(feed) = self.rss_view.selected_feed() else {
+            return;
+        };
+        let (path, name) = (feed.path.clone(), feed.name().to_string());
+        match self.client.refresh_rss_item(&path).await {
+            Ok(()) => {
+                self.notify_success(format!("Refreshing {name}"));
+                self.fetch_rss().await;
+            }
+            Err(e) => self.notify_error(format!("Failed to refresh {name}: {e}")),
+        }
+    }
+
+    async fn add_rss_article(&mut self) {
+        let view = &self.rss_view;
+        let (feed_index, article_index) = (view.feed_index, view.article_index);
+        let (Some(feed), Some(article)) = (view.selected_feed(), view.selected_article()) else {
+            return;
+        };
+        let Some(url) = article.torrent_url.clone().or_else(|| article.link.clone()) else {
+            self.notify_warning(format!("{} has no torrent link", article.title));
+            return;
+        };
+        let (path, id, title) = (feed.path.clone(), article.id.clone(), article.title.clone());
+
+        if let Err(e) = self
             .client
-            .login(&self.username_input, &self.password_input)
+            .add_magnet(&url, &AddTorrentOptions::default())
             .await
         {
+            self.notify_error(format!("Failed to add {title}: {e}"));
+            return;
+        }
+        self.notify_success(format!("Added {title}"));
+        self.refresh_data();
+        // Only a nicety; the torrent is already added
+        if self.client.mark_rss_article_read(&path, &id).await.is_ok() {
+            self.rss_view.mark_read(feed_index, article_index);
+        }
+    }
+
+    // Rules are only viewed here, apart from switching them on and off
+    async fn toggle_rss_rule(&mut self) {
+        let Some((name, rule)) = self.rss_view.rules.get(self.rss_view.rule_index) else {
+            return;
+        };
+        let (name, mut rule) = (name.clone(), rule.clone());
+        rule.enabled = !rule.enabled;
+        match self.client.set_rss_rule(&name, &rule).await {
             Ok(()) => {
-                // Save successful connection info to config
-                let current_url = self.client.get_base_url().to_string();
-                if let Err(e) = self
-                    .config
-                    .update_connection_info(&current_url, &self.username_input)
-                {
-                    log_debug(
-                        &format!("Failed to save config: {e}"),
-                        &self.config.get_timezone(),
-                    );
-                } else {
-                    log_debug(
-                        "Successfully saved connection info to config",
-                        &self.config.get_timezone(),
-                    );
+                let enabled = rule.enabled;
+                if let Some((_, stored)) = self.rss_view.rules.get_mut(self.rss_view.rule_index) {
+                    *stored = rule;
                 }
-
-                self.state = AppState::Main;
-                self.input_mode = InputMode::None;
-                self.refresh_data().await?;
+                let state = if enabled { "enabled" } else { "disabled" };
+                self.notify_success(format!("Rule {name} {state}"));
             }
-            Err(e) => {
-                self.error_message = Some(format!("Login failed: {e}"));
-                self.state = AppState::Error(format!("Login failed: {e}"));
+            Err(e) => self.notify_error(format!("Failed to update rule {name}: {e}")),
+        }
+    }
+
+    async fn open_settings(&mut self) {
+        match self.client.get_preferences().await {
+            Ok(preferences) => {
+                self.settings_view = SettingsView::new(&preferences);
+                self.state = AppState::Settings;
             }
+            Err(e) => self.notify_error(format!("Failed to load preferences: {e}")),
         }
-        Ok(())
     }
 
-    async fn refresh_data(&mut self) -> Result<()> {
-        match self.client.get_torrents().await {
-            Ok(torrents) => {
-                self.torrents = torrents;
-                if self.selected_torrent >= self.torrents.len() && !self.torrents.is_empty() {
-                    self.selected_torrent = self.torrents.len() - 1;
+    async fn handle_settings_input(&mut self, key: KeyEvent) {
+        let view = &mut self.settings_view;
+        if let Some(input) = view.editing.as_mut() {
+            match key.code {
+                KeyCode::Enter => view.finish_editing(),
+                KeyCode::Esc => {
+                    view.editing = None;
+                    view.error = None;
+                }
+                _ => {
+                    input.handle_key(key);
                 }
             }
-            Err(e) => {
-                self.error_message = Some(format!("Failed to fetch torrents: {e}"));
-            }
+            return;
         }
 
-        match self.client.get_server_state().await {
-            Ok(state) => {
-                self.server_state = Some(state);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                if !view.changes().is_empty() {
+                    self.notify_warning("Unsaved settings discarded".to_string());
+                }
+                self.state = AppState::Main;
             }
-            Err(e) => {
-                // Don't show error for server state as it's not critical
-                eprintln!("Failed to fetch server state: {e}");
+            KeyCode::Up => view.move_selection(-1),
+            KeyCode::Down => view.move_selection(1),
+            KeyCode::PageUp => view.move_selection(-10),
+            KeyCode::PageDown => view.move_selection(10),
+            KeyCode::Home => view.selected = 0,
+            KeyCode::End => view.move_selection(isize::MAX),
+            KeyCode::Char(' ') => {
+                view.toggle();
             }
+            KeyCode::Enter => view.activate(),
+            KeyCode::Char('s') => self.save_settings().await,
+            _ => {}
         }
+    }
 
-        self.last_update = Instant::now();
-        Ok(())
+    // Sends only the preferences that were changed, so settings this screen
+    // doesn't know about are never overwritten
+    async fn save_settings(&mut self) {
+        let changes = self.settings_view.changes();
+        if changes.is_empty() {
+            self.notify_warning("No settings changed".to_string());
+            return;
+        }
+        let count = changes.len();
+        match self
+            .client
+            .set_preferences(serde_json::Value::Object(changes))
+            .await
+        {
+            Ok(()) => {
+                self.settings_view.mark_saved();
+                // The default save path may be among them
+                self.preferences = None;
+                let noun = if count == 1 { "setting" } else { "settings" };
+                self.notify_success(format!("Saved {count} {noun}"));
+                self.refresh_data();
+            }
+            Err(e) => self.notify_error(format!("Failed to save settings: {e}")),
+        }
     }
 
-    fn adjust_scroll(&mut self) {
-        // Calculate visible rows dynamically - will be set by UI
-        let visible_rows = self.get_max_visible_rows();
-        let torrent_count = self.get_current_torrent_list_len();
+    async fn open_log(&mut self) {
+        self.log_view.follow = true;
+        self.state = AppState::Log;
+        self.fetch_log().await;
+    }
 
-        if torrent_count == 0 {
-            self.scroll_offset = 0;
-            self.selected_torrent = 0;
+    // Asks for messages newer than the last one received, in the background
+    // when the main loop is listening
+    async fn fetch_log(&mut self) {
+        if self.log_view.fetching {
             return;
         }
+        self.log_view.fetching = true;
+        let client = self.client.clone();
+        let last_id = self.log_view.last_id;
+        let fetch = async move { client.get_main_log(true, true, true, true, last_id).await };
 
-        // Ensure selected_torrent is within bounds
-        self.selected_torrent = self.selected_torrent.min(torrent_count.saturating_sub(1));
+        match self.events.clone() {
+            Some(sender) => {
+                tokio::spawn(async move {
+                    let _ = sender.send(AppEvent::LogFetched(fetch.await));
+                });
+            }
+            None => {
+                let result = fetch.await;
+                self.finish_log_fetch(result);
+            }
+        }
+    }
 
-        if self.selected_torrent < self.scroll_offset {
-            self.scroll_offset = self.selected_torrent;
-        } else if self.selected_torrent >= self.scroll_offset + visible_rows {
-            self.scroll_offset = self.selected_torrent - visible_rows + 1;
+    fn finish_log_fetch(&mut self, result: ApiResult<Vec<LogEntry>>) {
+        self.log_view.fetching = false;
+        match result {
+            Ok(entries) => self.log_view.append(entries),
+            Err(e) => self.notify_error(format!("Failed to fetch the server log: {e}")),
         }
     }
 
-    pub fn get_visible_torrents(&self) -> &[Torrent] {
-        let visible_rows = self.get_max_visible_rows();
-        let start = self.scroll_offset;
+    fn handle_log_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('L') | KeyCode::Char('q') => self.state = AppState::Main,
+            KeyCode::Up => self.log_view.scroll_up(1),
+            KeyCode::Down => self.log_view.scroll_down(1),
+            KeyCode::PageUp => self.log_view.scroll_up(10),
+            KeyCode::PageDown => self.log_view.scroll_down(10),
+            KeyCode::Home => self.log_view.scroll_up(usize::MAX),
+            KeyCode::End => self.log_view.follow = true,
+            KeyCode::Char(c @ '1'..='4') => self.log_view.toggle(c as usize - '1' as usize),
+            _ => {}
+        }
+    }
 
-        let torrents = if self.is_searching && !self.filtered_torrents.is_empty() {
-            &self.filtered_torrents
+    /// Gives the app a channel to the main loop and starts polling if already
+    /// logged in.
+    pub fn connect_events(&mut self, sender: mpsc::UnboundedSender<AppEvent>) {
+        if let Some(command) = &self.config.on_complete_command {
+            self.completion_hook = Some(CompletionHook::spawn(
+                command.clone(),
+                self.config.on_complete_dry_run,
+                sender.clone(),
+            ));
+        }
+        self.events = Some(sender);
+        // Logging in at startup may have left a question open on top of the
+        // list, so this checks the session rather than the state
+        if self.client.is_authenticated() {
+            self.start_polling();
+        }
+    }
+
+    fn start_polling(&mut self) {
+        if let Some(sender) = &self.events {
+            self.poller = Some(Poller::spawn(
+                self.client.clone(),
+                self.refresh_interval(),
+                sender.clone(),
+            ));
+            if let Some(dir) = &self.config.watch_dir {
+                self.watcher = Some(DirWatcher::spawn(
+                    self.client.clone(),
+                    expand_tilde(dir),
+                    self.config.watch_after_add,
+                    sender.clone(),
+                ));
+            }
+        }
+    }
+
+    /// Time between automatic refreshes, `None` when refreshing manually.
+    pub fn refresh_interval(&self) -> Option<Duration> {
+        (self.refresh_interval_secs > 0).then(|| Duration::from_secs(self.refresh_interval_secs))
+    }
+
+    // Steps of a second; going below one second switches to manual refresh
+    fn adjust_refresh_interval(&mut self, slower: bool) {
+        self.refresh_interval_secs = if slower {
+            (self.refresh_interval_secs + 1).min(MAX_REFRESH_INTERVAL_SECS)
         } else {
-            &self.torrents
+            self.refresh_interval_secs.saturating_sub(1)
         };
+        if let Some(poller) = &self.poller {
+            poller.set_interval(self.refresh_interval());
+        }
+    }
 
-        let end = (start + visible_rows).min(torrents.len());
-        &torrents[start..end]
+    /// Saves how the list is sorted and filtered for the next start, if
+    /// that changed.
+    pub fn save_ui_state(&mut self) {
+        let ui = UiConfig {
+            sort_column: self.sort_column,
+            sort_descending: self.sort_descending,
+            status_filter: self.status_filter,
+            show_sidebar: self.show_sidebar,
+            row_mode: self.row_mode,
+            columns: self.columns.clone(),
+        };
+        if ui == self.config.ui {
+            return;
+        }
+        self.config.ui = ui;
+        if let Err(e) = self.config.save() {
+            warn!("Failed to save the list layout: {e}");
+        }
     }
 
-    pub fn get_relative_selected_index(&self) -> usize {
-        self.selected_torrent - self.scroll_offset
+    /// Stops polling, the watch folder and the completion command queue.
+    pub async fn stop_background_tasks(&mut self) {
+        if let Some(mut poller) = self.poller.take() {
+            poller.close().await;
+        }
+        if let Some(mut watcher) = self.watcher.take() {
+            watcher.close().await;
+        }
+        if let Some(mut hook) = self.completion_hook.take() {
+            hook.close().await;
+        }
     }
 
-    pub fn get_max_visible_rows(&self) -> usize {
-        // Use the stored value, with a minimum of 1
-        self.max_visible_rows.max(1)
+    pub fn handle_resize(&mut self, width: u16, height: u16) {
+        self.terminal_width = width;
+        self.terminal_height = height;
+
+        self.max_visible_rows = ui::list_rows(
+            height,
+            self.config.show_speed_graphs,
+            self.row_mode.height(),
+        );
+
+        // Re-resolve the selection against the current list and bring it
+        // back into the smaller window
+        self.sync_selection();
     }
 
-    pub fn set_max_visible_rows(&mut self, rows: usize) {
-        // This will be called by the UI to set the actual available rows
-        self.max_visible_rows = rows;
+    async fn handle_url_config_input(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Enter if !self.url_input.is_empty() => {
+                match Url::parse(self.url_input.value()) {
+                    Ok(url) => {
+                        self.connect(url);
+                        self.state = AppState::Login;
+                        self.input_mode = InputMode::Username;
+                        self.start_login(LoginKind::Probe).await?;
+                    }
+                    Err(_) => self.show_error(
+                        "Invalid URL format. Please enter a valid URL (e.g., http://localhost:8080)"
+                            .to_string(),
+                    ),
+                }
+            }
+            // Back to the saved profiles, if there are any to go back to
+            KeyCode::Esc if !self.config.profiles.is_empty() => {
+                self.state = AppState::ProfilePicker;
+            }
+            KeyCode::Esc => {
+                self.should_quit = true;
+            }
+            _ => {
+                self.url_input.handle_key(key);
+            }
+        }
+        Ok(())
     }
 
-    fn filter_torrents(&mut self) {
-        if self.search_input.is_empty() {
-            self.filtered_torrents.clear();
-            self.is_searching = false;
-        } else {
-            let query = self.search_input.to_lowercase();
-            self.filtered_torrents = self
-                .torrents
-                .iter()
-                .filter(|torrent| {
-                    torrent.name.to_lowercase().contains(&query)
-                        || torrent.state.to_lowercase().contains(&query)
-                })
-                .cloned()
-                .collect();
-            self.is_searching = true;
+    /// Opens the error popup over the current screen, which dismissing it
+    /// goes back to.
+    fn show_error(&mut self, message: String) {
+        let previous = std::mem::replace(&mut self.state, AppState::Error(message));
+        if !matches!(previous, AppState::Error(_)) {
+            self.error_return_to = previous;
         }
+    }
 
-        // Reset selection and scroll when filtering
-        self.selected_torrent = 0;
-        self.scroll_offset = 0;
+    fn handle_connecting_input(&mut self, key: KeyEvent) {
+        if key.code == KeyCode::Esc {
+            self.cancel_login();
+        }
     }
 
-    pub fn get_current_torrent_list_len(&self) -> usize {
-        if self.is_searching && !self.filtered_torrents.is_empty() {
-            self.filtered_torrents.len()
-        } else {
-            self.torrents.len()
+    /// Abandons the login in flight and goes back to the login form.
+    fn cancel_login(&mut self) {
+        self.state = AppState::Login;
+        if let Some(pending) = self.pending_login.take() {
+            pending.cancel.cancel();
+            self.state = pending.return_to;
         }
+        self.notify_warning("Login cancelled".to_string());
     }
 
-    pub fn get_current_selected_torrent(&self) -> Option<&Torrent> {
-        let torrents = if self.is_searching && !self.filtered_torrents.is_empty() {
-            &self.filtered_torrents
-        } else {
-            &self.torrents
-        };
+    /// How long the login in flight has been running, for the spinner.
+    pub fn connecting_for(&self) -> Option<Duration> {
+        self.pending_login
+            .as_ref()
+            .map(|pending| pending.started.elapsed())
+    }
+
+    /// Whether the login in flight sends credentials, rather than only
+    /// checking that the server answers.
+    pub fn is_logging_in(&self) -> bool {
+        self.pending_login
+            .as_ref()
+            .is_some_and(|pending| pending.kind != LoginKind::Probe)
+    }
 
-        torrents.get(self.selected_torrent)
+    async fn handle_login_input(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Tab => {
+                self.input_mode = match self.input_mode {
+                    InputMode::Username => InputMode::Password,
+                    InputMode::Password => InputMode::Username,
+                    _ => InputMode::Username,
+                };
+            }
+            KeyCode::Enter => {
+                if !self.username_input.is_empty() && !self.password_input.is_empty() {
+                    self.attempt_login().await?;
+                }
+            }
+            KeyCode::Esc => {
+                self.should_quit = true;
+            }
+            KeyCode::Char('h')
+                if key
+                    .modifiers
+                    .contains(crossterm::event::KeyModifiers::CONTROL) =>
+            {
+                self.show_password = !self.show_password;
+            }
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.forget_saved_password().await;
+            }
+            _ => match self.input_mode {
+                InputMode::Username => {
+                    self.username_input.handle_key(key);
+                }
+                InputMode::Password => {
+                    self.password_input.handle_key(key);
+                }
+                _ => {}
+            },
+        }
+        Ok(())
+    }
+
+    async fn handle_main_input(&mut self, key: KeyEvent) -> Result<()> {
+        if self.filter_pane_focused {
+            return self.handle_filter_pane_input(key).await;
+        }
+
+        let Some(action) = self.keymap.resolve(key) else {
+            return Ok(());
+        };
+        self.run_action(action).await
+    }
+
+    /// Does what `action` is bound to in the main list, on the marked or
+    /// highlighted torrents.
+    async fn run_action(&mut self, action: Action) -> Result<()> {
+        match action {
+            Action::Quit => self.should_quit = true,
+            Action::Search => {
+                // Reopening the search edits the filter that is already applied
+                self.state = AppState::Search;
+                self.input_mode = InputMode::Search;
+            }
+            Action::PauseAll => {
+                self.ask_confirmation(
+                    "Pause All",
+                    "Pause every torrent on the server?",
+                    ConfirmAction::PauseAll,
+                );
+            }
+            Action::ResumeAll => {
+                self.ask_confirmation(
+                    "Resume All",
+                    "Resume every torrent on the server?",
+                    ConfirmAction::ResumeAll,
+                );
+            }
+            Action::Refresh => self.refresh_data(),
+            Action::RefreshSlower => self.adjust_refresh_interval(true),
+            Action::RefreshFaster => self.adjust_refresh_interval(false),
+            Action::Add => self.open_add_torrent().await,
+            Action::QueueUp => self.move_in_queue(QueueMove::Up).await?,
+            Action::QueueDown => self.move_in_queue(QueueMove::Down).await?,
+            Action::QueueTop => self.move_in_queue(QueueMove::Top).await?,
+            Action::QueueBottom => self.move_in_queue(QueueMove::Bottom).await?,
+            Action::Details => {
+                if self.get_current_selected_torrent().is_some() {
+                    self.state = AppState::Details;
+                    self.fetch_details_tab().await;
+                }
+            }
+            Action::MoveUp => {
+                self.select_index(self.selected_index().saturating_sub(1));
+            }
+            Action::MoveDown => {
+                self.select_index(self.selected_index() + 1);
+            }
+            Action::PageUp => {
+                let page_size = self.get_max_visible_rows().saturating_sub(1).max(1);
+                self.select_index(self.selected_index().saturating_sub(page_size));
+            }
+            Action::PageDown => {
+                let page_size = self.get_max_visible_rows().saturating_sub(1).max(1);
+                self.select_index(self.selected_index() + page_size);
+            }
+            Action::Top => {
+                self.select_index(0);
+            }
+            Action::Bottom => {
+                let max_len = self.get_current_torrent_list_len();
+                self.select_index(max_len.saturating_sub(1));
+            }
+            Action::ForceStart => self.toggle_force_start().await?,
+            Action::PauseResume => {
+                let targets = self.action_targets();
+                if !targets.is_empty() {
+                    // Resume only when every target is paused; everything else is
+                    // running, including forcedDL/forcedUP, and gets paused
+                    let all_paused = self
+                        .torrents
+                        .iter()
+                        .filter(|t| targets.contains(&t.hash))
+                        .all(is_paused);
+                    for torrent in self.torrents.iter().filter(|t| targets.contains(&t.hash)) {
+                        debug!(
+                            "Torrent state: '{}', name: '{}'",
+                            torrent.state.as_str(),
+                            torrent.name
+                        );
+                    }
+                    let (result, what, done) = if all_paused {
+                        debug!("Attempting to resume torrents");
+                        let result = self.client.resume_torrents(&targets).await;
+                        (result, "resume", "Resumed")
+                    } else {
+                        debug!("Attempting to pause torrents");
+                        let result = self.client.pause_torrents(&targets).await;
+                        (result, "pause", "Paused")
+                    };
+                    match result {
+                        Ok(()) => {
+                            self.notify_success(format!("{done} {}", torrent_count(targets.len())));
+                            self.refresh_data();
+                        }
+                        Err(e) => self.notify_error(format!("Failed to {what} torrents: {e}")),
+                    }
+                }
+            }
+            Action::Delete => {
+                let targets: Vec<DeleteTarget> = self
+                    .action_targets()
+                    .into_iter()
+                    .filter_map(|hash| {
+                        let torrent = self.torrents.iter().find(|t| t.hash == hash)?;
+                        Some(DeleteTarget {
+                            hash,
+                            name: torrent.name.clone(),
+                            size: torrent.size,
+                        })
+                    })
+                    .collect();
+                if !targets.is_empty() {
+                    self.delete_confirmation = targets;
+                    self.state = AppState::ConfirmDelete;
+                }
+            }
+            Action::SortNext => {
+                self.sort_column = self.sort_column.next();
+                self.sort_torrents();
+            }
+            Action::SortReverse => {
+                self.sort_descending = !self.sort_descending;
+                self.sort_torrents();
+            }
+            Action::ToggleMark => {
+                if let Some(torrent) = self.get_current_selected_torrent() {
+                    let hash = torrent.hash.clone();
+                    if !self.selected_hashes.remove(&hash) {
+                        self.selected_hashes.insert(hash);
+                    }
+                }
+            }
+            Action::ClearMarks => {
+                // Marks go first so Esc never drops a filter the marks came from
+                if !self.selected_hashes.is_empty() {
+                    self.selected_hashes.clear();
+                } else if self.is_searching {
+                    self.clear_search();
+                }
+            }
+            Action::Recheck => {
+                let targets = self.action_targets();
+                if !targets.is_empty() {
+                    if let Err(e) = self.client.recheck_torrents(&targets).await {
+                        self.notify_error(format!("Failed to recheck torrents: {e}"));
+                    } else {
+                        self.notify_success(format!("Rechecking {}", torrent_count(targets.len())));
+                        self.refresh_data();
+                    }
+                }
+            }
+            Action::SetCategory => self.open_prompt("Set Category", PromptAction::SetCategory),
+            Action::AddTags => {
+                self.open_prompt("Add Tags (comma separated)", PromptAction::AddTags)
+            }
+            Action::AltSpeed => {
+                if let Err(e) = self.client.toggle_alternative_speed_limits().await {
+                    self.notify_error(format!("Failed to toggle alternative speed limits: {e}"));
+                } else {
+                    self.notify_success("Toggled alternative speed limits".to_string());
+                    self.refresh_data();
+                }
+            }
+            Action::SpeedLimits => self.open_speed_limits().await,
+            Action::ToggleSidebar => self.show_sidebar = !self.show_sidebar,
+            Action::RowMode => {
+                let lines = self.max_visible_rows * self.row_mode.height();
+                self.row_mode = match self.row_mode {
+                    RowMode::Compact => RowMode::Detailed,
+                    RowMode::Detailed => RowMode::Compact,
+                };
+                // Until the next draw measures it, so the selection stays
+                // in view
+                self.max_visible_rows = lines / self.row_mode.height();
+                self.normalize_view();
+            }
+            Action::Statistics => self.open_statistics().await,
+            Action::TestPort => self.test_port().await,
+            Action::Copy => {
+                if self.get_current_selected_torrent().is_some() {
+                    self.state = AppState::Copy;
+                }
+            }
+            Action::Columns => {
+                self.column_chooser = Some(ColumnChooser::new(&self.columns));
+                self.state = AppState::Columns;
+            }
+            Action::Cleanup => {
+                self.cleanup = Some(CleanupView::default());
+                self.state = AppState::Cleanup;
+            }
+            Action::OpenPath => self.open_torrent_path(false),
+            Action::OpenContent => self.open_torrent_path(true),
+            Action::Log => self.open_log().await,
+            Action::Rss => self.open_rss().await,
+            Action::Settings => self.open_settings().await,
+            Action::ShutdownServer => self.open_shutdown_prompt(),
+            Action::CommandPalette => {
+                self.command_palette = Some(Palette::new(bindings::palette_entries(&self.keymap)));
+                self.state = AppState::CommandPalette;
+            }
+            Action::OnlineSearch => self.open_online_search().await,
+            Action::Help => {
+                self.help_scroll = 0;
+                self.state = AppState::Help;
+            }
+            Action::FilterPane => {
+                self.show_filter_pane = true;
+                self.filter_pane_focused = true;
+            }
+            Action::FocusFilterPane if self.show_filter_pane => self.filter_pane_focused = true,
+            Action::StatusFilter(index) => self.set_status_filter(StatusFilter::ALL[index]),
+            Action::FocusFilterPane => {}
+        }
+        Ok(())
+    }
+
+    async fn handle_command_palette_input(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(palette) = self.command_palette.as_mut() else {
+            self.state = AppState::Main;
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.command_palette = None;
+                self.state = AppState::Main;
+            }
+            KeyCode::Enter => {
+                let action = palette.selected_item();
+                self.command_palette = None;
+                self.state = AppState::Main;
+                if let Some(action) = action {
+                    self.run_action(action).await?;
+                }
+            }
+            _ => palette.handle_key(key),
+        }
+        Ok(())
+    }
+
+    async fn handle_filter_pane_input(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Char('q') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.should_quit = true;
+            }
+            KeyCode::Up => {
+                self.filter_pane_index = self.filter_pane_index.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                let last = self.filter_pane_entries().len().saturating_sub(1);
+                self.filter_pane_index = (self.filter_pane_index + 1).min(last);
+            }
+            KeyCode::Home => self.filter_pane_index = 0,
+            KeyCode::End => {
+                self.filter_pane_index = self.filter_pane_entries().len().saturating_sub(1);
+            }
+            KeyCode::Enter => {
+                if let Some(filter) = self.filter_pane_entries().get(self.filter_pane_index) {
+                    self.set_label_filter(filter.clone());
+                }
+                self.filter_pane_focused = false;
+            }
+            KeyCode::Tab => self.filter_pane_focused = false,
+            // Closing the pane keeps the applied filter
+            KeyCode::Esc | KeyCode::Char('F') => {
+                self.show_filter_pane = false;
+                self.filter_pane_focused = false;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn open_add_torrent(&mut self) {
+        self.state = AppState::AddTorrent;
+        self.input_mode = InputMode::TorrentPath;
+        self.torrent_path_input.clear();
+        self.add_start_paused = false;
+        let saved = self
+            .config
+            .add_defaults(self.client.get_base_url().as_str());
+        // A category deleted since then falls back to none
+        self.add_category_index = saved
+            .category
+            .and_then(|saved| self.categories.iter().position(|name| *name == saved))
+            .map_or(0, |i| i + 1);
+        if self.preferences.is_none() {
+            self.preferences = self.client.get_preferences().await.ok();
+        }
+        let save_path = match saved.save_path {
+            Some(save_path) => save_path,
+            // An empty save path lets the server pick its default
+            None => match &self.preferences {
+                Some(preferences) => preferences.save_path.clone(),
+                None => self
+                    .client
+                    .get_default_save_path()
+                    .await
+                    .unwrap_or_default(),
+            },
+        };
+        self.add_save_path_input = TextInput::new(save_path);
+    }
+
+    fn add_category_path(&self) -> Option<&str> {
+        self.add_category()
+            .and_then(|name| self.sync.categories.get(name))
+            .map(String::as_str)
+            .filter(|path| !path.is_empty())
+    }
+
+    /// Where the add dialog's torrent goes without a save path of its own:
+    /// the picked category's path, else the server default. The flag is set
+    /// when the save path typed differs from it.
+    pub fn add_save_path_hint(&self) -> Option<(String, bool)> {
+        let (hint, path) = match self.add_category_path() {
+            Some(path) => (format!("Category saves to {path}"), path),
+            None => {
+                let path = self.preferences.as_ref()?.save_path.as_str();
+                (format!("Default save path: {path}"), path)
+            }
+        };
+        let trim = |path: &str| path.trim().trim_end_matches(['/', '\\']).to_string();
+        let typed = trim(self.add_save_path_input.value());
+        let custom = !typed.is_empty() && typed != trim(path);
+        Some((hint, custom))
+    }
+
+    /// Bytes free where the server's default save path is, when it says.
+    pub fn free_space(&self) -> Option<u64> {
+        let free = self.server_state.as_ref()?.free_space_on_disk?;
+        u64::try_from(free).ok().filter(|&free| free > 0)
+    }
+
+    /// Free space for the add dialog, and whether the torrent file picked
+    /// needs more than that. Only the default save path's disk is known,
+    /// so it's named when the torrent goes elsewhere.
+    pub fn add_free_space(&self) -> Option<(String, bool)> {
+        let free = self.free_space()?;
+        let free_text = format_size(free, BINARY);
+        let on_default = self.add_category_path().is_none()
+            && !self.add_save_path_hint().is_some_and(|(_, custom)| custom);
+        let size = self
+            .add_torrent_size
+            .1
+            .filter(|_| self.add_torrent_size.0 == self.torrent_path_input.value().trim());
+        if let Some(size) = size.filter(|&size| size > free) {
+            let needs = format_size(size, BINARY);
+            return Some((
+                format!("Needs {needs}, the default save path's disk has {free_text} free"),
+                true,
+            ));
+        }
+        let text = if on_default {
+            format!("{free_text} free")
+        } else {
+            format!("{free_text} free on the default save path's disk")
+        };
+        Some((text, false))
+    }
+
+    // Reads the size of the torrent file being added when its path changed
+    fn update_add_torrent_size(&mut self) {
+        let source = self.torrent_path_input.value().trim();
+        if self.add_torrent_size.0 == source {
+            return;
+        }
+        let size = if is_torrent_url(source) {
+            None
+        } else {
+            // Typing a path can pass over big files that aren't torrents at
+            // all, which aren't worth reading on every keystroke
+            let path = expand_tilde(source);
+            std::fs::metadata(&path)
+                .ok()
+                .filter(|meta| meta.is_file() && meta.len() <= metainfo::MAX_FILE_SIZE)
+                .and_then(|_| std::fs::read(&path).ok())
+                .and_then(|data| metainfo::total_size(&data))
+        };
+        self.add_torrent_size = (source.to_string(), size);
+    }
+
+    /// Moves the add dialog to the next category that has a save path,
+    /// filling in both.
+    fn cycle_save_path_preset(&mut self) {
+        let presets: Vec<(usize, String)> = self
+            .categories
+            .iter()
+            .enumerate()
+            .filter_map(|(i, name)| {
+                let path = self.sync.categories.get(name)?;
+                (!path.is_empty()).then(|| (i + 1, path.clone()))
+            })
+            .collect();
+        if presets.is_empty() {
+            self.notify_warning("No category has a save path".to_string());
+            return;
+        }
+        let current = presets.iter().position(|(index, path)| {
+            *index == self.add_category_index && path == self.add_save_path_input.value()
+        });
+        let (index, path) = presets[current.map_or(0, |c| (c + 1) % presets.len())].clone();
+        self.add_category_index = index;
+        self.add_save_path_input.set(path);
+    }
+
+    async fn handle_add_torrent_input(&mut self, key: KeyEvent) -> Result<()> {
+        const FIELDS: [InputMode; 4] = [
+            InputMode::TorrentPath,
+            InputMode::SavePath,
+            InputMode::Category,
+            InputMode::StartPaused,
+        ];
+        let field = FIELDS
+            .iter()
+            .position(|mode| *mode == self.input_mode)
+            .unwrap_or(0);
+
+        if !(key.code == KeyCode::Tab && self.input_mode == InputMode::TorrentPath) {
+            self.path_completions.clear();
+            self.path_completion_index = None;
+        }
+
+        match key.code {
+            KeyCode::Char('o') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.open_file_browser();
+            }
+            KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.cycle_save_path_preset();
+            }
+            KeyCode::Tab
+                if self.input_mode == InputMode::TorrentPath
+                    && !is_torrent_url(self.torrent_path_input.value()) =>
+            {
+                self.complete_torrent_path();
+            }
+            KeyCode::Enter if field == FIELDS.len() - 1 => self.submit_add_torrent().await,
+            KeyCode::Tab | KeyCode::Down | KeyCode::Enter => {
+                self.input_mode = FIELDS[(field + 1) % FIELDS.len()];
+            }
+            KeyCode::BackTab | KeyCode::Up => {
+                self.input_mode = FIELDS[(field + FIELDS.len() - 1) % FIELDS.len()];
+            }
+            KeyCode::Esc => {
+                self.state = AppState::Main;
+                self.input_mode = InputMode::None;
+            }
+            _ => match self.input_mode {
+                InputMode::TorrentPath => {
+                    self.torrent_path_input.handle_key(key);
+                }
+                InputMode::SavePath => {
+                    self.add_save_path_input.handle_key(key);
+                }
+                InputMode::Category => {
+                    let choices = self.categories.len() + 1;
+                    match key.code {
+                        KeyCode::Left => {
+                            self.add_category_index =
+                                (self.add_category_index + choices - 1) % choices;
+                        }
+                        KeyCode::Right => {
+                            self.add_category_index = (self.add_category_index + 1) % choices;
+                        }
+                        _ => {}
+                    }
+                }
+                InputMode::StartPaused => {
+                    if matches!(
+                        key.code,
+                        KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right
+                    ) {
+                        self.add_start_paused = !self.add_start_paused;
+                    }
+                }
+                _ => {}
+            },
+        }
+        self.update_add_torrent_size();
+        Ok(())
+    }
+
+    // First Tab completes the shared prefix, later ones cycle through matches
+    fn complete_torrent_path(&mut self) {
+        if !self.path_completions.is_empty() {
+            let next = self
+                .path_completion_index
+                .map_or(0, |i| (i + 1) % self.path_completions.len());
+            self.path_completion_index = Some(next);
+            self.torrent_path_input
+                .set(self.path_completions[next].clone());
+            return;
+        }
+
+        let completion = complete_path(self.torrent_path_input.value());
+        self.torrent_path_input.set(completion.completed);
+        if completion.candidates.len() > 1 {
+            self.path_completions = completion.candidates;
+        }
+    }
+
+    fn open_file_browser(&mut self) {
+        // Start in the last browsed directory, else Downloads or home
+        let start = self
+            .config
+            .last_browse_dir
+            .as_ref()
+            .map(PathBuf::from)
+            .or_else(dirs::download_dir)
+            .or_else(dirs::home_dir)
+            .unwrap_or_else(|| PathBuf::from("."));
+        self.file_browser = Some(FileBrowser::open(&start));
+        self.state = AppState::FileBrowser;
+    }
+
+    fn handle_file_browser_input(&mut self, key: KeyEvent) {
+        let Some(browser) = self.file_browser.as_mut() else {
+            self.state = AppState::AddTorrent;
+            return;
+        };
+        let page = self.max_visible_rows.max(1) as isize;
+
+        match key.code {
+            KeyCode::Up => browser.move_selection(-1),
+            KeyCode::Down => browser.move_selection(1),
+            KeyCode::PageUp => browser.move_selection(-page),
+            KeyCode::PageDown => browser.move_selection(page),
+            KeyCode::Home => browser.selected = 0,
+            KeyCode::End => browser.move_selection(isize::MAX),
+            KeyCode::Backspace => browser.go_up(),
+            KeyCode::Enter => {
+                if let Some(path) = browser.enter() {
+                    let dir = browser.dir.to_string_lossy().into_owned();
+                    if let Err(e) = self.config.set_last_browse_dir(&dir) {
+                        warn!("Failed to save config: {e}");
+                    }
+                    self.torrent_path_input.set(path.to_string_lossy());
+                    self.update_add_torrent_size();
+                    self.file_browser = None;
+                    self.state = AppState::AddTorrent;
+                    self.input_mode = InputMode::TorrentPath;
+                }
+            }
+            KeyCode::Esc => {
+                self.file_browser = None;
+                self.state = AppState::AddTorrent;
+                self.input_mode = InputMode::TorrentPath;
+            }
+            _ => {}
+        }
+    }
+
+    /// Category picked in the add dialog, if any.
+    pub fn add_category(&self) -> Option<&str> {
+        self.add_category_index
+            .checked_sub(1)
+            .and_then(|i| self.categories.get(i))
+            .map(String::as_str)
+    }
+
+    async fn submit_add_torrent(&mut self) {
+        let source = self.torrent_path_input.value().trim().to_string();
+        if source.is_empty() {
+            self.input_mode = InputMode::TorrentPath;
+            return;
+        }
+
+        let save_path = self.add_save_path_input.value().trim();
+        let options = AddTorrentOptions {
+            save_path: (!save_path.is_empty()).then(|| save_path.to_string()),
+            category: self.add_category().map(str::to_string),
+            paused: self.add_start_paused,
+            ..AddTorrentOptions::default()
+        };
+
+        let result = if is_torrent_url(&source) {
+            self.client.add_magnet(&source, &options).await
+        } else {
+            match std::fs::read(expand_tilde(&source)) {
+                Ok(data) => self.client.add_torrent(&data, &options).await,
+                // The dialog stays open so the path can be corrected
+                Err(e) => {
+                    self.notify_error(format!("Failed to read file: {e}"));
+                    return;
+                }
+            }
+        };
+
+        if let Err(e) = result {
+            self.notify_error(format!("Failed to add torrent: {e}"));
+        } else {
+            let defaults = AddDefaults {
+                save_path: options.save_path,
+                category: options.category,
+            };
+            let url = self.client.get_base_url().to_string();
+            if let Err(e) = self.config.set_add_defaults(&url, defaults) {
+                warn!("Failed to save config: {e}");
+            }
+            self.notify_success("Torrent added".to_string());
+            self.state = AppState::Main;
+            self.input_mode = InputMode::None;
+            self.refresh_data();
+        }
+    }
+
+    async fn handle_search_input(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Enter | KeyCode::Esc => {
+                self.state = AppState::Main;
+                self.input_mode = InputMode::None;
+                if key.code == KeyCode::Esc {
+                    self.clear_search();
+                }
+            }
+            // The list stays in view, so the highlight can be moved to a match
+            KeyCode::Up => self.select_index(self.selected_index().saturating_sub(1)),
+            KeyCode::Down => self.select_index(self.selected_index() + 1),
+            _ => {
+                if self.search_input.handle_key(key) {
+                    self.filter_torrents();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_details_input(&mut self, key: KeyEvent) -> Result<()> {
+        let handled = match self.details_tab {
+            DetailsTab::General => false,
+            DetailsTab::Trackers => self.handle_tracker_input(key).await,
+            DetailsTab::Peers => self.handle_peer_input(key).await,
+            DetailsTab::Files => self.handle_file_input(key).await,
+        };
+        if handled {
+            return Ok(());
+        }
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter => {
+                self.state = AppState::Main;
+                self.details_tab = DetailsTab::General;
+            }
+            KeyCode::Tab => {
+                self.details_tab = self.details_tab.cycle(1);
+                self.fetch_details_tab().await;
+            }
+            KeyCode::BackTab => {
+                self.details_tab = self.details_tab.cycle(-1);
+                self.fetch_details_tab().await;
+            }
+            KeyCode::Up if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.move_in_queue(QueueMove::Up).await?;
+            }
+            KeyCode::Down if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.move_in_queue(QueueMove::Down).await?;
+            }
+            KeyCode::Home if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.move_in_queue(QueueMove::Top).await?;
+            }
+            KeyCode::End if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.move_in_queue(QueueMove::Bottom).await?;
+            }
+            KeyCode::Char('e') if self.details_tab == DetailsTab::General => {
+                self.open_export_prompt()
+            }
+            KeyCode::Char('a') if self.details_tab == DetailsTab::General => {
+                self.toggle_auto_management().await
+            }
+            KeyCode::Char('s') if self.details_tab == DetailsTab::General => {
+                if let Some(torrent) = self.get_current_selected_torrent() {
+                    self.share_limits = Some(ShareLimitsEditor::new(torrent));
+                    self.state = AppState::ShareLimits;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // Reloads what the open tab of the detail view shows
+    async fn fetch_details_tab(&mut self) {
+        match self.details_tab {
+            DetailsTab::General => self.fetch_pieces().await,
+            DetailsTab::Trackers => self.fetch_trackers().await,
+            DetailsTab::Peers => self.fetch_peers().await,
+            DetailsTab::Files => self.fetch_files().await,
+        }
+    }
+
+    // Loads the piece map of the torrent in the details view, in the
+    // background when the main loop is listening
+    async fn fetch_pieces(&mut self) {
+        let Some(hash) = self.get_current_selected_torrent().map(|t| t.hash.clone()) else {
+            return;
+        };
+        if self.piece_map.hash != hash {
+            self.piece_map = PieceMap::new(hash.clone());
+        }
+        if self.piece_map.loading {
+            return;
+        }
+        self.piece_map.loading = true;
+        let client = self.client.clone();
+        let fetch = {
+            let hash = hash.clone();
+            async move { client.get_piece_states(&hash).await }
+        };
+
+        match self.events.clone() {
+            Some(sender) => {
+                tokio::spawn(async move {
+                    let result = fetch.await;
+                    let _ = sender.send(AppEvent::PiecesLoaded { hash, result });
+                });
+            }
+            None => {
+                let result = fetch.await;
+                self.finish_piece_fetch(&hash, result);
+            }
+        }
+    }
+
+    fn finish_piece_fetch(&mut self, hash: &str, result: ApiResult<Vec<u8>>) {
+        // Another torrent was opened meanwhile
+        if self.piece_map.hash != hash {
+            return;
+        }
+        self.piece_map.loading = false;
+        match result {
+            Ok(states) => self.piece_map.set_states(&states),
+            // The map is a nicety; the rest of the view still works
+            Err(e) => debug!("Failed to load piece states: {e}"),
+        }
+    }
+
+    // Loads the files of the torrent in the details view, in the background
+    // when the main loop is listening
+    async fn fetch_files(&mut self) {
+        let Some(hash) = self.get_current_selected_torrent().map(|t| t.hash.clone()) else {
+            return;
+        };
+        if self.file_panel.hash != hash {
+            self.file_panel = FilePanel {
+                hash: hash.clone(),
+                ..FilePanel::default()
+            };
+        }
+        if self.file_panel.loading {
+            return;
+        }
+        self.file_panel.loading = true;
+        let client = self.client.clone();
+        let fetch = {
+            let hash = hash.clone();
+            async move { client.get_files(&hash).await }
+        };
+
+        match self.events.clone() {
+            Some(sender) => {
+                tokio::spawn(async move {
+                    let result = fetch.await;
+                    let _ = sender.send(AppEvent::FilesLoaded { hash, result });
+                });
+            }
+            None => {
+                let result = fetch.await;
+                self.finish_file_fetch(&hash, result);
+            }
+        }
+    }
+
+    fn finish_file_fetch(&mut self, hash: &str, result: ApiResult<Vec<TorrentFile>>) {
+        // Another torrent was opened meanwhile
+        if self.file_panel.hash != hash {
+            return;
+        }
+        self.file_panel.loading = false;
+        match result {
+            Ok(files) => self.file_panel.set_files(&files),
+            Err(e) => self.notify_error(format!("Failed to load files: {e}")),
+        }
+    }
+
+    // Keys of the Files tab, returning false for the ones the detail view
+    // handles itself
+    async fn handle_file_input(&mut self, key: KeyEvent) -> bool {
+        let panel = &mut self.file_panel;
+        if let Some(input) = panel.rename.as_mut() {
+            match key.code {
+                KeyCode::Enter => {
+                    let new_path = input.value().trim().to_string();
+                    panel.rename = None;
+                    self.rename_selected_entry(&new_path).await;
+                }
+                KeyCode::Esc => panel.rename = None,
+                _ => {
+                    input.handle_key(key);
+                }
+            }
+            return true;
+        }
+
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            return false;
+        }
+        match key.code {
+            KeyCode::Up => panel.move_selection(-1),
+            KeyCode::Down => panel.move_selection(1),
+            KeyCode::PageUp => panel.move_selection(-10),
+            KeyCode::PageDown => panel.move_selection(10),
+            KeyCode::Home => panel.selected = 0,
+            KeyCode::End => panel.move_selection(isize::MAX),
+            KeyCode::F(2) => {
+                if let Some(entry) = panel.selected_entry() {
+                    panel.rename = Some(TextInput::new(entry.path.clone()));
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    async fn rename_selected_entry(&mut self, new_path: &str) {
+        let Some(entry) = self.file_panel.selected_entry().cloned() else {
+            return;
+        };
+        if new_path.is_empty() || new_path == entry.path {
+            return;
+        }
+        let hash = self.file_panel.hash.clone();
+        let result = if entry.is_folder {
+            self.client
+                .rename_folder(&hash, &entry.path, new_path)
+                .await
+        } else {
+            self.client.rename_file(&hash, &entry.path, new_path).await
+        };
+        match result {
+            Ok(()) => {
+                self.notify_success(format!("Renamed to {new_path}"));
+                // Follow the entry to its new place in the tree
+                self.file_panel.entries[self.file_panel.selected].path = new_path.to_string();
+                self.fetch_files().await;
+            }
+            Err(e) => {
+                let message = match &e {
+                    ApiError::Conflict(_) => {
+                        "Not renamed: a file with that name already exists".to_string()
+                    }
+                    _ => format!("Failed to rename {}: {e}", entry.name()),
+                };
+                self.notify_error(message);
+                // Let the name be fixed instead of typed again
+                self.file_panel.rename = Some(TextInput::new(new_path));
+            }
+        }
+    }
+
+    // Loads the trackers of the torrent in the details view, in the
+    // background when the main loop is listening
+    async fn fetch_trackers(&mut self) {
+        let Some(hash) = self.get_current_selected_torrent().map(|t| t.hash.clone()) else {
+            return;
+        };
+        if self.tracker_panel.hash != hash {
+            self.tracker_panel = TrackerPanel {
+                hash: hash.clone(),
+                ..TrackerPanel::default()
+            };
+        }
+        if self.tracker_panel.loading {
+            return;
+        }
+        self.tracker_panel.loading = true;
+        let client = self.client.clone();
+        let fetch = {
+            let hash = hash.clone();
+            async move { client.get_trackers(&hash).await }
+        };
+
+        match self.events.clone() {
+            Some(sender) => {
+                tokio::spawn(async move {
+                    let result = fetch.await;
+                    let _ = sender.send(AppEvent::TrackersLoaded { hash, result });
+                });
+            }
+            None => {
+                let result = fetch.await;
+                self.finish_tracker_fetch(&hash, result);
+            }
+        }
+    }
+
+    /// Why the trackers of `torrent` are failing, when it's in trouble and
+    /// that was fetched.
+    pub fn tracker_hint(&self, torrent: &Torrent) -> Option<&str> {
+        trackers::in_trouble(torrent)
+            .then(|| self.tracker_hints.get(&torrent.hash))
+            .flatten()
+    }
+
+    // Fetches the trackers of the highlighted torrent in the background when
+    // it's in trouble, for the status bar to say why. Only that one, so
+    // scrolling past a list of errored torrents doesn't load them all.
+    fn fetch_tracker_hint(&mut self) {
+        let Some(sender) = self.events.clone() else {
+            return;
+        };
+        let Some(torrent) = self.get_current_selected_torrent() else {
+            return;
+        };
+        if !trackers::in_trouble(torrent) {
+            return;
+        }
+        let hash = torrent.hash.clone();
+        if !self.tracker_hints.start(&hash, Instant::now()) {
+            return;
+        }
+        let client = self.client.clone();
+        tokio::spawn(async move {
+            let result = client.get_trackers(&hash).await;
+            let _ = sender.send(AppEvent::TrackerHintLoaded { hash, result });
+        });
+    }
+
+    fn finish_tracker_fetch(&mut self, hash: &str, result: ApiResult<Vec<Tracker>>) {
+        // Another torrent was opened meanwhile
+        if self.tracker_panel.hash != hash {
+            return;
+        }
+        self.tracker_panel.loading = false;
+        match result {
+            Ok(trackers) => self.tracker_panel.set_trackers(trackers),
+            Err(e) => self.notify_error(format!("Failed to load trackers: {e}")),
+        }
+    }
+
+    // Keys of the Trackers tab, returning false for the ones the detail view
+    // handles itself
+    async fn handle_tracker_input(&mut self, key: KeyEvent) -> bool {
+        let panel = &mut self.tracker_panel;
+        if let Some(TrackerInput::Add(urls)) = panel.input.as_ref()
+            && key.code == KeyCode::Char('e')
+            && key.modifiers.contains(KeyModifiers::CONTROL)
+        {
+            self.handoff = Some(Handoff::Edit {
+                target: EditTarget::TrackerUrls,
+                text: urls.text(),
+            });
+            return true;
+        }
+        if let Some(input) = panel.input.as_mut() {
+            let submit = match input {
+                TrackerInput::Add(_) => {
+                    key.code == KeyCode::Char('s') && key.modifiers.contains(KeyModifiers::CONTROL)
+                }
+                TrackerInput::Edit { .. } => key.code == KeyCode::Enter,
+            };
+            if submit {
+                let input = panel.input.take().expect("tracker input is open");
+                self.submit_tracker_input(input).await;
+                return true;
+            }
+            match (input, key.code) {
+                (_, KeyCode::Esc) => panel.input = None,
+                (TrackerInput::Add(urls), _) => urls.handle_key(key),
+                (TrackerInput::Edit { url, .. }, _) => {
+                    url.handle_key(key);
+                }
+            }
+            return true;
+        }
+
+        if panel.confirm_remove {
+            panel.confirm_remove = false;
+            if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                self.remove_selected_tracker().await;
+            }
+            return true;
+        }
+
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            return false;
+        }
+        match key.code {
+            KeyCode::Up => panel.move_selection(-1),
+            KeyCode::Down => panel.move_selection(1),
+            KeyCode::PageUp => panel.move_selection(-10),
+            KeyCode::PageDown => panel.move_selection(10),
+            KeyCode::Home => panel.selected = 0,
+            KeyCode::End => panel.move_selection(isize::MAX),
+            KeyCode::Char('a') => panel.input = Some(TrackerInput::Add(UrlList::new())),
+            KeyCode::Char('d') | KeyCode::Delete => {
+                panel.confirm_remove = panel.editable_tracker().is_some();
+            }
+            KeyCode::Char('e') => {
+                if let Some(tracker) = panel.editable_tracker() {
+                    panel.input = Some(TrackerInput::Edit {
+                        original: tracker.url.clone(),
+                        url: TextInput::new(tracker.url.clone()),
+                    });
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    async fn submit_tracker_input(&mut self, input: TrackerInput) {
+        let hash = self.tracker_panel.hash.clone();
+        let result = match &input {
+            TrackerInput::Add(urls) => {
+                let urls = urls.urls();
+                if urls.is_empty() {
+                    return;
+                }
+                self.client.add_trackers(&hash, &urls).await
+            }
+            TrackerInput::Edit { original, url } => {
+                let url = url.value().trim();
+                if url.is_empty() || url == original {
+                    return;
+                }
+                self.client.edit_tracker(&hash, original, url).await
+            }
+        };
+        match result {
+            Ok(()) => {
+                let done = match input {
+                    TrackerInput::Add(_) => "Trackers added",
+                    TrackerInput::Edit { .. } => "Tracker updated",
+                };
+                self.notify_success(done.to_string());
+                self.fetch_trackers().await;
+            }
+            Err(e) => {
+                let message = match &e {
+                    ApiError::Conflict(_) => {
+                        "Tracker not changed: the new URL is already in the list".to_string()
+                    }
+                    ApiError::Server(context)
+                        if context.status == Some(reqwest::StatusCode::BAD_REQUEST) =>
+                    {
+                        "That is not a valid tracker URL".to_string()
+                    }
+                    _ => format!("Failed to update trackers: {e}"),
+                };
+                self.notify_error(message);
+                // Let the input be fixed instead of typed again
+                self.tracker_panel.input = Some(input);
+            }
+        }
+    }
+
+    async fn remove_selected_tracker(&mut self) {
+        let Some(url) = self.tracker_panel.editable_tracker().map(|t| t.url.clone()) else {
+            return;
+        };
+        let hash = self.tracker_panel.hash.clone();
+        match self
+            .client
+            .remove_trackers(&hash, std::slice::from_ref(&url))
+            .await
+        {
+            Ok(()) => {
+                self.notify_success(format!("Removed {url}"));
+                self.fetch_trackers().await;
+            }
+            Err(e) => self.notify_error(format!("Failed to remove tracker: {e}")),
+        }
+    }
+
+    // Loads the peers of the torrent in the details view, in the background
+    // when the main loop is listening
+    async fn fetch_peers(&mut self) {
+        let Some(hash) = self.get_current_selected_torrent().map(|t| t.hash.clone()) else {
+            return;
+        };
+        if self.peer_panel.hash != hash {
+            self.peer_panel = PeerPanel {
+                hash: hash.clone(),
+                ..PeerPanel::default()
+            };
+        }
+        if self.peer_panel.loading {
+            return;
+        }
+        self.peer_panel.loading = true;
+        let client = self.client.clone();
+        let fetch = {
+            let hash = hash.clone();
+            async move { client.get_peers(&hash).await }
+        };
+
+        match self.events.clone() {
+            Some(sender) => {
+                tokio::spawn(async move {
+                    let result = fetch.await;
+                    let _ = sender.send(AppEvent::PeersLoaded { hash, result });
+                });
+            }
+            None => {
+                let result = fetch.await;
+                self.finish_peer_fetch(&hash, result);
+            }
+        }
+    }
+
+    fn finish_peer_fetch(&mut self, hash: &str, result: ApiResult<Vec<Peer>>) {
+        // Another torrent was opened meanwhile
+        if self.peer_panel.hash != hash {
+            return;
+        }
+        self.peer_panel.loading = false;
+        match result {
+            Ok(peers) => self.peer_panel.set_peers(peers),
+            Err(e) => self.notify_error(format!("Failed to load peers: {e}")),
+        }
+    }
+
+    // Keys of the Peers tab, returning false for the ones the detail view
+    // handles itself
+    async fn handle_peer_input(&mut self, key: KeyEvent) -> bool {
+        let panel = &mut self.peer_panel;
+        if let Some(input) = panel.new_peers.as_mut() {
+            match key.code {
+                KeyCode::Esc => panel.new_peers = None,
+                KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.handoff = Some(Handoff::Edit {
+                        target: EditTarget::Peers,
+                        text: input
+                            .value()
+                            .split_whitespace()
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    });
+                }
+                KeyCode::Enter => {
+                    let input = panel.new_peers.take().expect("peer input is open");
+                    self.add_peers(input).await;
+                }
+                _ => {
+                    input.handle_key(key);
+                }
+            }
+            return true;
+        }
+
+        if panel.confirm_ban {
+            panel.confirm_ban = false;
+            if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+                self.ban_selected_peer().await;
+            }
+            return true;
+        }
+
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            return false;
+        }
+        match key.code {
+            KeyCode::Up => panel.move_selection(-1),
+            KeyCode::Down => panel.move_selection(1),
+            KeyCode::PageUp => panel.move_selection(-10),
+            KeyCode::PageDown => panel.move_selection(10),
+            KeyCode::Home => panel.selected = 0,
+            KeyCode::End => panel.move_selection(isize::MAX),
+            KeyCode::Char('a') => panel.new_peers = Some(TextInput::default()),
+            KeyCode::Char('b') => panel.confirm_ban = panel.selected_peer().is_some(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Puts what the editor saved back into the input it was opened from,
+    /// if that's still open.
+    pub fn finish_edit(&mut self, target: EditTarget, result: std::io::Result<String>) {
+        let text = match result {
+            Ok(text) => text,
+            Err(e) => {
+                self.notify_error(format!("Editor failed: {e}"));
+                return;
+            }
+        };
+        match target {
+            EditTarget::TrackerUrls => {
+                if let Some(TrackerInput::Add(urls)) = self.tracker_panel.input.as_mut() {
+                    *urls = UrlList::from_text(&text);
+                }
+            }
+            EditTarget::Peers => {
+                if let Some(input) = self.peer_panel.new_peers.as_mut() {
+                    input.set(text.split_whitespace().collect::<Vec<_>>().join(" "));
+                }
+            }
+        }
+    }
+
+    async fn add_peers(&mut self, input: TextInput) {
+        let addresses = match peers::parse_addresses(input.value()) {
+            Ok(addresses) if addresses.is_empty() => return,
+            Ok(addresses) => addresses,
+            Err(e) => {
+                self.notify_error(e);
+                self.peer_panel.new_peers = Some(input);
+                return;
+            }
+        };
+        let hash = self.peer_panel.hash.clone();
+        match self.client.add_peers(&hash, &addresses).await {
+            Ok(()) => {
+                let added = match addresses.as_slice() {
+                    [address] => format!("Connecting to {address}"),
+                    _ => format!("Connecting to {} peers", addresses.len()),
+                };
+                self.notify_success(added);
+                self.fetch_peers().await;
+            }
+            Err(e) => {
+                let message = match &e {
+                    ApiError::Server(context)
+                        if context.status == Some(reqwest::StatusCode::BAD_REQUEST) =>
+                    {
+                        "The server did not accept those peers".to_string()
+                    }
+                    _ => format!("Failed to add peers: {e}"),
+                };
+                self.notify_error(message);
+                // Let the input be fixed instead of typed again
+                self.peer_panel.new_peers = Some(input);
+            }
+        }
+    }
+
+    async fn ban_selected_peer(&mut self) {
+        let Some(address) = self.peer_panel.selected_peer().map(|p| p.address.clone()) else {
+            return;
+        };
+        match self.client.ban_peers(std::slice::from_ref(&address)).await {
+            Ok(()) => {
+                self.notify_success(format!("Banned {address}"));
+                self.fetch_peers().await;
+            }
+            Err(e) => self.notify_error(format!("Failed to ban peer: {e}")),
+        }
+    }
+
+    async fn handle_share_limits_input(&mut self, key: KeyEvent) {
+        let Some(editor) = self.share_limits.as_mut() else {
+            self.state = AppState::Details;
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.share_limits = None;
+                self.state = AppState::Details;
+            }
+            KeyCode::Tab | KeyCode::Down => editor.cycle_focus(1),
+            KeyCode::BackTab | KeyCode::Up => editor.cycle_focus(-1),
+            KeyCode::Enter => {
+                let limits = match editor.limits() {
+                    Ok(limits) => limits,
+                    Err(e) => {
+                        editor.error = Some(e);
+                        return;
+                    }
+                };
+                let hashes = [editor.hash.clone()];
+                let result = self
+                    .client
+                    .set_share_limits(
+                        &hashes,
+                        limits.ratio,
+                        limits.seeding_time,
+                        limits.inactive_seeding_time,
+                    )
+                    .await;
+                match result {
+                    Ok(()) => {
+                        self.share_limits = None;
+                        self.state = AppState::Details;
+                        self.notify_success("Share limits set".to_string());
+                        self.refresh_data();
+                    }
+                    // Shown in the editor, where the limit can be set back
+                    Err(ApiError::Unsupported(context)) => {
+                        if let Some(editor) = self.share_limits.as_mut() {
+                            editor.error =
+                                Some(format!("Inactive seeding time {}", context.detail));
+                        }
+                    }
+                    Err(e) => self.notify_error(format!("Failed to set share limits: {e}")),
+                }
+            }
+            _ => {
+                editor.error = None;
+                editor.focused_input().handle_key(key);
+            }
+        }
+    }
+
+    // The UI clamps the scroll offset to the help text height
+    fn handle_help_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('?') | KeyCode::F(1) => self.state = AppState::Main,
+            KeyCode::Up => self.help_scroll = self.help_scroll.saturating_sub(1),
+            KeyCode::Down => self.help_scroll += 1,
+            KeyCode::PageUp => self.help_scroll = self.help_scroll.saturating_sub(10),
+            KeyCode::PageDown => self.help_scroll += 10,
+            KeyCode::Home => self.help_scroll = 0,
+            _ => {}
+        }
+    }
+
+    fn ask_confirmation(&mut self, title: &str, message: &str, action: ConfirmAction) {
+        self.confirmation = Some(Confirmation {
+            title: title.to_string(),
+            message: message.to_string(),
+            action,
+            return_to: AppState::Main,
+        });
+        self.state = AppState::Confirm;
+    }
+
+    async fn handle_confirm_input(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                if let Some(confirmation) = self.confirmation.take() {
+                    self.state = confirmation.return_to;
+                    self.run_confirmed_action(confirmation.action).await?;
+                } else {
+                    self.state = AppState::Main;
+                }
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.state = self
+                    .confirmation
+                    .take()
+                    .map_or(AppState::Main, |confirmation| confirmation.return_to);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn open_prompt(&mut self, title: &str, action: PromptAction) {
+        if self.action_targets().is_empty() {
+            return;
+        }
+        self.prompt = Some(Prompt {
+            title: title.to_string(),
+            input: String::new(),
+            action,
+            return_to: AppState::Main,
+        });
+        self.state = AppState::Prompt;
+    }
+
+    async fn handle_prompt_input(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(prompt) = self.prompt.as_mut() else {
+            self.state = AppState::Main;
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Enter => {
+                let prompt = self.prompt.take().expect("prompt is open");
+                self.state = prompt.return_to;
+                self.run_prompt_action(prompt.action, prompt.input.trim())
+                    .await?;
+            }
+            KeyCode::Esc => {
+                let prompt = self.prompt.take().expect("prompt is open");
+                self.state = prompt.return_to;
+            }
+            KeyCode::Char(c) => prompt.input.push(c),
+            KeyCode::Backspace => {
+                prompt.input.pop();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn run_prompt_action(&mut self, action: PromptAction, input: &str) -> Result<()> {
+        match action {
+            PromptAction::ExportTorrent => {
+                self.export_torrent(input).await;
+                return Ok(());
+            }
+            PromptAction::ShutdownServer => {
+                self.shutdown_server(input).await;
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        let targets = self.action_targets();
+        let (result, what, done) = match action {
+            PromptAction::SetCategory => (
+                self.client.set_category(&targets, input).await,
+                "set category",
+                "Category set",
+            ),
+            PromptAction::AddTags => (
+                self.client.add_tags(&targets, input).await,
+                "add tags",
+                "Tags added",
+            ),
+            PromptAction::ExportTorrent | PromptAction::ShutdownServer => {
+                unreachable!("handled above")
+            }
+        };
+
+        if let Err(e) = result {
+            self.notify_error(format!("Failed to {what}: {e}"));
+            return Ok(());
+        }
+        self.notify_success(done.to_string());
+        self.refresh_data();
+        Ok(())
+    }
+
+    // Asks where to save the highlighted torrent's .torrent file
+    fn open_export_prompt(&mut self) {
+        let Some(torrent) = self.get_current_selected_torrent() else {
+            return;
+        };
+        // A name can't be allowed to point somewhere else on disk
+        let file_name = torrent.name.replace(['/', '\\'], "_");
+        self.prompt = Some(Prompt {
+            title: "Export .torrent to".to_string(),
+            input: format!("~/Downloads/{file_name}.torrent"),
+            action: PromptAction::ExportTorrent,
+            return_to: self.state.clone(),
+        });
+        self.state = AppState::Prompt;
+    }
+
+    // Typing "yes" is required, since nothing here can start the server again
+    fn open_shutdown_prompt(&mut self) {
+        self.prompt = Some(Prompt {
+            title: "Shut down qBittorrent".to_string(),
+            input: String::new(),
+            action: PromptAction::ShutdownServer,
+            return_to: AppState::Main,
+        });
+        self.state = AppState::Prompt;
+    }
+
+    async fn shutdown_server(&mut self, answer: &str) {
+        if !answer.eq_ignore_ascii_case("yes") {
+            self.notify_warning("Shutdown cancelled; type yes to confirm".to_string());
+            return;
+        }
+        match self.client.shutdown_application().await {
+            Ok(()) => {
+                self.server_shut_down = true;
+                self.notify_warning("qBittorrent is shutting down".to_string());
+                // The next poll finds it gone and switches to the offline banner
+                self.refresh_data();
+            }
+            Err(e) => self.notify_error(format!("Failed to shut down qBittorrent: {e}")),
+        }
+    }
+
+    async fn export_torrent(&mut self, destination: &str) {
+        let Some(hash) = self.get_current_selected_torrent().map(|t| t.hash.clone()) else {
+            return;
+        };
+        if destination.is_empty() {
+            return;
+        }
+        let data = match self.client.export_torrent(&hash).await {
+            Ok(data) => data,
+            Err(e) => {
+                let message = match &e {
+                    ApiError::NotFound(_) => {
+                        "Server does not support export (needs qBittorrent 4.5 or newer)"
+                            .to_string()
+                    }
+                    ApiError::Unsupported(context) => format!("Export {}", context.detail),
+                    ApiError::Conflict(context) => {
+                        format!("Server could not export the torrent: {}", context.detail)
+                    }
+                    _ => format!("Failed to export torrent: {e}"),
+                };
+                self.notify_error(message);
+                return;
+            }
+        };
+        let path = expand_tilde(destination);
+        match std::fs::write(&path, data) {
+            Ok(()) => self.notify_success(format!("Exported to {}", path.display())),
+            Err(e) => self.notify_error(format!("Could not write {}: {e}", path.display())),
+        }
+    }
+
+    /// Hashes an action applies to: the marked torrents if any, otherwise the
+    /// highlighted one.
+    pub fn action_targets(&self) -> Vec<String> {
+        if !self.selected_hashes.is_empty() {
+            let mut hashes: Vec<String> = self.selected_hashes.iter().cloned().collect();
+            hashes.sort();
+            return hashes;
+        }
+
+        self.get_current_selected_torrent()
+            .map(|torrent| vec![torrent.hash.clone()])
+            .unwrap_or_default()
+    }
+
+    async fn run_confirmed_action(&mut self, action: ConfirmAction) -> Result<()> {
+        match action {
+            ConfirmAction::PauseAll => {
+                let count = self.torrents.iter().filter(|t| !is_paused(t)).count();
+                if let Err(e) = self.client.pause_all().await {
+                    self.notify_error(format!("Failed to pause all torrents: {e}"));
+                    return Ok(());
+                }
+                self.notify_success(format!("Paused {}", torrent_count(count)));
+            }
+            ConfirmAction::ResumeAll => {
+                let count = self.torrents.iter().filter(|t| is_paused(t)).count();
+                if let Err(e) = self.client.resume_all().await {
+                    self.notify_error(format!("Failed to resume all torrents: {e}"));
+                    return Ok(());
+                }
+                self.notify_success(format!("Resumed {}", torrent_count(count)));
+            }
+            ConfirmAction::SaveProfile => {
+                let url = self.client.get_base_url().to_string();
+                let username = self.username_input.value();
+                let mut profile =
+                    Profile::for_url(&url, (!username.is_empty()).then(|| username.to_string()));
+                profile.default = self.config.profiles.is_empty();
+                let name = profile.name.clone();
+                match self.config.save_profile(profile, None) {
+                    Ok(()) => self.notify_success(format!("Saved profile {name}")),
+                    Err(e) => self.notify_error(format!("Failed to save config: {e}")),
+                }
+                return Ok(());
+            }
+            ConfirmAction::EnableAutoManagement => {
+                self.set_auto_management(true).await;
+                return Ok(());
+            }
+            ConfirmAction::CopyPath(path) => {
+                self.copy_text(&path);
+                self.notify_success(format!("Copied {path}"));
+                return Ok(());
+            }
+            ConfirmAction::CheckPort(port) => {
+                self.check_port(port).await;
+                return Ok(());
+            }
+        }
+        self.refresh_data();
+        Ok(())
+    }
+
+    // Enabling asks first, since qBittorrent then moves the files to the
+    // category's save path; disabling leaves them where they are
+    async fn toggle_auto_management(&mut self) {
+        let Some(torrent) = self.get_current_selected_torrent() else {
+            return;
+        };
+        if torrent.auto_tmm.unwrap_or(false) {
+            self.set_auto_management(false).await;
+            return;
+        }
+        let category = torrent.category.clone().unwrap_or_default();
+        let destination = if category.is_empty() {
+            "the default save path".to_string()
+        } else {
+            format!("the save path of category {category}")
+        };
+        self.ask_confirmation(
+            "Automatic Management",
+            &format!("This moves the torrent's files to {destination}. Enable it?"),
+            ConfirmAction::EnableAutoManagement,
+        );
+        if let Some(confirmation) = self.confirmation.as_mut() {
+            confirmation.return_to = AppState::Details;
+        }
+    }
+
+    async fn set_auto_management(&mut self, enable: bool) {
+        let Some(hash) = self.get_current_selected_torrent().map(|t| t.hash.clone()) else {
+            return;
+        };
+        match self.client.set_auto_management(&[hash], enable).await {
+            Ok(()) => {
+                let state = if enable { "enabled" } else { "disabled" };
+                self.notify_success(format!("Automatic management {state}"));
+                self.refresh_data();
+            }
+            Err(e) => self.notify_error(format!("Failed to set automatic management: {e}")),
+        }
+    }
+
+    pub fn notify_success(&mut self, message: String) {
+        notification::push(&mut self.notifications, Severity::Success, message);
+    }
+
+    pub fn notify_warning(&mut self, message: String) {
+        notification::push(&mut self.notifications, Severity::Warning, message);
+    }
+
+    /// Reports a failure that doesn't block the workflow; errors that do,
+    /// like a failed login, still use the modal error state.
+    pub fn notify_error(&mut self, message: String) {
+        notification::push(&mut self.notifications, Severity::Error, message);
+    }
+
+    /// Toasts that are still due on screen, oldest first.
+    pub fn active_notifications(&self) -> impl Iterator<Item = &Notification> {
+        self.notifications
+            .iter()
+            .filter(|notification| !notification.is_expired())
+    }
+
+    async fn toggle_force_start(&mut self) -> Result<()> {
+        let targets = self.action_targets();
+        if targets.is_empty() {
+            return Ok(());
+        }
+        // Force start unless every target is already forced
+        let forced = self
+            .torrents
+            .iter()
+            .filter(|t| targets.contains(&t.hash))
+            .all(|t| t.state.is_forced());
+
+        if let Err(e) = self.client.set_force_start(&targets, !forced).await {
+            self.notify_error(format!("Failed to set force start: {e}"));
+        } else {
+            self.refresh_data();
+        }
+        Ok(())
+    }
+
+    async fn move_in_queue(&mut self, direction: QueueMove) -> Result<()> {
+        let Some(torrent) = self.get_current_selected_torrent() else {
+            return Ok(());
+        };
+        let hash = torrent.hash.clone();
+
+        let hashes = [hash.clone()];
+
+        let result = match direction {
+            QueueMove::Up => self.client.increase_priority(&hashes).await,
+            QueueMove::Down => self.client.decrease_priority(&hashes).await,
+            QueueMove::Top => self.client.top_priority(&hashes).await,
+            QueueMove::Bottom => self.client.bottom_priority(&hashes).await,
+        };
+
+        match result {
+            Ok(()) => {
+                self.refresh_data();
+                // Follow the moved torrent to its new position
+                self.select_hash(&hash);
+            }
+            Err(e) => {
+                // qBittorrent answers 409 when torrent queueing is disabled
+                let message = match &e {
+                    ApiError::Conflict(_) => {
+                        "Torrent queueing is disabled. Enable queueing in qBittorrent preferences."
+                            .to_string()
+                    }
+                    _ => format!("Failed to change queue position: {e}"),
+                };
+                self.notify_error(message);
+            }
+        }
+        Ok(())
+    }
+
+    /// Re-sorts the list by the active column while keeping the highlighted
+    /// torrent selected.
+    fn sort_torrents(&mut self) {
+        let column = self.sort_column;
+        let descending = self.sort_descending;
+        let compare = |a: &Torrent, b: &Torrent| {
+            let ordering = column.compare(a, b);
+            let ordering = if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            };
+            // Break ties on the hash so equal rows don't shuffle between refreshes
+            ordering.then_with(|| a.hash.cmp(&b.hash))
+        };
+        self.torrents.sort_by(compare);
+        // The indexes point at the old order
+        self.refilter();
+
+        self.sync_selection();
+    }
+
+    fn select_hash(&mut self, hash: &str) {
+        if self.current_torrents().any(|t| t.hash == hash) {
+            self.selected_hash = Some(hash.to_string());
+            self.sync_selection();
+        }
+    }
+
+    /// Index of the selected torrent in the displayed list. Falls back to the
+    /// nearest position when the selected hash is no longer listed.
+    pub fn selected_index(&self) -> usize {
+        self.selected_hash
+            .as_ref()
+            .and_then(|hash| self.current_torrents().position(|t| &t.hash == hash))
+            .unwrap_or_else(|| {
+                self.selected_torrent
+                    .min(self.get_current_torrent_list_len().saturating_sub(1))
+            })
+    }
+
+    fn select_index(&mut self, index: usize) {
+        let index = index.min(self.get_current_torrent_list_len().saturating_sub(1));
+        self.selected_hash = self.listed_torrent(index).map(|t| t.hash.clone());
+        self.selected_torrent = index;
+        self.normalize_view();
+    }
+
+    fn sync_selection(&mut self) {
+        self.select_index(self.selected_index());
+    }
+
+    fn handle_copy_input(&mut self, key: KeyEvent) {
+        let field = match key.code {
+            KeyCode::Char(c) => CopyField::MENU
+                .iter()
+                .find(|(menu_key, _)| *menu_key == c)
+                .map(|&(_, field)| field),
+            KeyCode::Esc => {
+                self.state = AppState::Main;
+                return;
+            }
+            _ => None,
+        };
+        let Some(field) = field else {
+            return;
+        };
+        self.state = AppState::Main;
+        let Some(torrent) = self.get_current_selected_torrent() else {
+            return;
+        };
+        let name = torrent.name.clone();
+        let Some(text) = field.value(torrent) else {
+            self.notify_warning(format!("No {} known for {name}", field.label()));
+            return;
+        };
+        self.copy_text(&text);
+        self.notify_success(format!("Copied the {} of {name}", field.label()));
+    }
+
+    fn handle_column_chooser_input(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter => {
+                if let Some(chooser) = self.column_chooser.take() {
+                    self.columns = chooser.columns();
+                    self.save_ui_state();
+                }
+                self.state = AppState::Main;
+            }
+            KeyCode::Esc => {
+                self.column_chooser = None;
+                self.state = AppState::Main;
+            }
+            _ => {
+                if let Some(chooser) = self.column_chooser.as_mut() {
+                    chooser.handle_key(key);
+                }
+            }
+        }
+    }
+
+    fn handle_cleanup_input(&mut self, key: KeyEvent) {
+        let Some(view) = self.cleanup.as_mut() else {
+            self.state = AppState::Main;
+            return;
+        };
+        let now = Utc::now().timestamp();
+        match key.code {
+            KeyCode::Esc => {
+                self.cleanup = None;
+                self.state = AppState::Main;
+            }
+            // The same confirmation as deleting from the list, which comes
+            // back here when cancelled
+            KeyCode::Enter => {
+                let targets: Vec<DeleteTarget> = view
+                    .chosen(&self.torrents, now)
+                    .into_iter()
+                    .map(|torrent| DeleteTarget {
+                        hash: torrent.hash.clone(),
+                        name: torrent.name.clone(),
+                        size: torrent.size,
+                    })
+                    .collect();
+                if targets.is_empty() {
+                    view.error = Some("Pick torrents to delete with Space or a".to_string());
+                } else {
+                    self.delete_confirmation = targets;
+                    self.state = AppState::ConfirmDelete;
+                }
+            }
+            _ => view.handle_key(key, &self.torrents, now),
+        }
+    }
+
+    fn copy_text(&mut self, text: &str) {
+        if let Some(escape) = clipboard::copy(text, &self.copy_tools) {
+            self.terminal_output.push_str(&escape);
+        }
+    }
+
+    /// Opens the selected torrent's save path, or with `content` its file or
+    /// top folder, on this machine. Paths that only exist on the server are
+    /// offered for copying instead.
+    fn open_torrent_path(&mut self, content: bool) {
+        let Some(torrent) = self.get_current_selected_torrent() else {
+            return;
+        };
+        let remote = if content {
+            torrent.content_path.clone()
+        } else {
+            torrent.save_path.clone()
+        };
+        let Some(remote) = remote.filter(|path| !path.is_empty()) else {
+            self.notify_warning(format!("No path known for {}", torrent.name));
+            return;
+        };
+        let local = self
+            .config
+            .server_is_local
+            .unwrap_or_else(|| opener::is_local_host(self.client.get_base_url()));
+        let Some(path) = opener::local_path(&remote, &self.config.path_mappings, local) else {
+            self.ask_confirmation(
+                "Path on the server",
+                &format!("{remote} is not on this machine. Copy it?"),
+                ConfirmAction::CopyPath(remote),
+            );
+            return;
+        };
+        if !path.exists() {
+            self.notify_error(format!("{} doesn't exist here", path.display()));
+        } else if let Err(e) = opener::open(&path) {
+            self.notify_error(format!("Failed to open {}: {e}", path.display()));
+        }
+    }
+
+    // Versions don't change while connected, so they are only fetched once
+    async fn open_statistics(&mut self) {
+        if self.server_version.is_none() {
+            self.server_version = self.client.get_app_version().await.ok();
+        }
+        if self.build_info.is_none() {
+            self.build_info = self.client.get_build_info().await.ok();
+        }
+        // The port can be changed in the settings, so it's asked every time
+        if let Ok(preferences) = self.client.get_preferences().await {
+            self.listen_port = Some(preferences.listen_port);
+        }
+        self.state = AppState::Statistics;
+    }
+
+    /// Reports what the server makes of its listening port. qBittorrent has
+    /// no port check of its own, so with `Config::port_check_url` set an
+    /// outside checker is offered too, once confirmed.
+    async fn test_port(&mut self) {
+        let port = match self.client.get_preferences().await {
+            Ok(preferences) => preferences.listen_port,
+            Err(e) => {
+                self.notify_error(format!("Failed to get the listening port: {e}"));
+                return;
+            }
+        };
+        self.listen_port = Some(port);
+        let status = self
+            .server_state
+            .as_ref()
+            .map_or("unknown", |state| state.connection_status.as_str());
+        let message = format!("The server reports port {port} as {status}");
+        let Some(template) = self.config.port_check_url.clone() else {
+            match status {
+                "connected" => self.notify_success(message),
+                "firewalled" => self.notify_warning(message),
+                _ => self.notify_error(message),
+            }
+            return;
+        };
+        match port_check::check_url(&template, port) {
+            Ok(url) => self.ask_confirmation(
+                "Test Port",
+                &format!(
+                    "{message}. Ask {} whether it is reachable from the internet?",
+                    url.host_str().unwrap_or("the port checker")
+                ),
+                ConfirmAction::CheckPort(port),
+            ),
+            Err(e) => self.notify_error(e),
+        }
+    }
+
+    async fn check_port(&mut self, port: i64) {
+        let Some(template) = self.config.port_check_url.clone() else {
+            return;
+        };
+        let answer = match port_check::check_url(&template, port) {
+            Ok(url) => port_check::check(url).await,
+            Err(e) => Err(e),
+        };
+        match answer {
+            Ok(answer) => self.notify_success(format!("Port {port}: {answer}")),
+            Err(e) => self.notify_error(e),
+        }
+    }
+
+    async fn open_speed_limits(&mut self) {
+        let download = self.client.get_global_download_limit().await;
+        let upload = self.client.get_global_upload_limit().await;
+
+        match (download, upload) {
+            (Ok(download), Ok(upload)) => {
+                // Limits are edited in KiB/s, 0 meaning unlimited
+                self.download_limit_input = (download.max(0) / 1024).to_string();
+                self.upload_limit_input = (upload.max(0) / 1024).to_string();
+                self.state = AppState::SpeedLimits;
+                self.input_mode = InputMode::DownloadLimit;
+            }
+            (Err(e), _) | (_, Err(e)) => {
+                self.notify_error(format!("Failed to get speed limits: {e}"));
+            }
+        }
+    }
+
+    async fn handle_speed_limits_input(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Tab | KeyCode::Up | KeyCode::Down => {
+                self.input_mode = match self.input_mode {
+                    InputMode::DownloadLimit => InputMode::UploadLimit,
+                    _ => InputMode::DownloadLimit,
+                };
+            }
+            KeyCode::Enter => {
+                let download = parse_limit_kib(&self.download_limit_input);
+                let upload = parse_limit_kib(&self.upload_limit_input);
+
+                let (Some(download), Some(upload)) = (download, upload) else {
+                    self.notify_warning(
+                        "Speed limits must be whole numbers of KiB/s (0 = unlimited)".to_string(),
+                    );
+                    return Ok(());
+                };
+
+                let result = match self.client.set_global_download_limit(download).await {
+                    Ok(()) => self.client.set_global_upload_limit(upload).await,
+                    Err(e) => Err(e),
+                };
+
+                if let Err(e) = result {
+                    self.notify_error(format!("Failed to set speed limits: {e}"));
+                } else {
+                    self.notify_success("Speed limits updated".to_string());
+                    self.state = AppState::Main;
+                    self.input_mode = InputMode::None;
+                    self.refresh_data();
+                }
+            }
+            KeyCode::Esc => {
+                self.state = AppState::Main;
+                self.input_mode = InputMode::None;
+            }
+            KeyCode::Char(c) if c.is_ascii_digit() => match self.input_mode {
+                InputMode::DownloadLimit => self.download_limit_input.push(c),
+                InputMode::UploadLimit => self.upload_limit_input.push(c),
+                _ => {}
+            },
+            KeyCode::Backspace => match self.input_mode {
+                InputMode::DownloadLimit => {
+                    self.download_limit_input.pop();
+                }
+                InputMode::UploadLimit => {
+                    self.upload_limit_input.pop();
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn handle_confirm_delete_input(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            // Terminals report Shift+y as 'Y', mostly without the SHIFT
+            // modifier, so the case alone decides
+            KeyCode::Char(c @ ('y' | 'Y')) if !self.delete_confirmation.is_empty() => {
+                let delete_files = c == 'Y';
+                let hashes: Vec<String> = std::mem::take(&mut self.delete_confirmation)
+                    .into_iter()
+                    .map(|target| target.hash)
+                    .collect();
+                self.state = AppState::Main;
+                self.cleanup = None;
+                if let Err(e) = self.client.delete_torrents(&hashes, delete_files).await {
+                    self.notify_error(format!("Failed to delete torrent: {e}"));
+                } else {
+                    self.notify_success(format!("Deleted {}", torrent_count(hashes.len())));
+                    for hash in &hashes {
+                        self.selected_hashes.remove(hash);
+                    }
+                    self.refresh_data();
+                }
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.state = if self.cleanup.is_some() {
+                    AppState::Cleanup
+                } else {
+                    AppState::Main
+                };
+                self.delete_confirmation.clear();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn attempt_login(&mut self) -> Result<()> {
+        self.start_login(LoginKind::Typed).await
+    }
+
+    /// Logs in with the entered username and password. Once the main loop is
+    /// connected this runs in the background, reporting back with
+    /// `AppEvent::LoginFinished`, so the screen keeps drawing and Esc can
+    /// cancel it; before that it completes inline.
+    ///
+    /// Servers that bypass authentication for this client are let into
+    /// straight away, whatever was entered.
+    async fn start_login(&mut self, kind: LoginKind) -> Result<()> {
+        let client = self.client.clone();
+        let username = self.username_input.value().to_string();
+        let password = self.password_input.value().to_string();
+        let return_to = match kind {
+            LoginKind::Probe => self.state.clone(),
+            _ => AppState::Login,
+        };
+        let login = async move {
+            let credentials = (kind != LoginKind::Probe).then_some((&*username, &*password));
+            client.login_or_bypass(credentials).await
+        };
+
+        let Some(sender) = self.events.clone() else {
+            let result = login.await;
+            return self.finish_login(result, kind, return_to).await;
+        };
+
+        let cancel = CancellationToken::new();
+        let token = cancel.clone();
+        tokio::spawn(async move {
+            tokio::pin!(login);
+            let mut spinner = tokio::time::interval(SPINNER_INTERVAL);
+            let result = loop {
+                tokio::select! {
+                    // Dropping the future aborts the request
+                    _ = token.cancelled() => return,
+                    result = &mut login => break result,
+                    _ = spinner.tick() => {
+                        let _ = sender.send(AppEvent::Tick);
+                    }
+                }
+            };
+            let _ = sender.send(AppEvent::LoginFinished(result));
+        });
+
+        self.pending_login = Some(PendingLogin {
+            cancel,
+            kind,
+            return_to,
+            started: Instant::now(),
+        });
+        self.state = AppState::Connecting;
+        Ok(())
+    }
+
+    async fn finish_login(
+        &mut self,
+        result: ApiResult<()>,
+        kind: LoginKind,
+        return_to: AppState,
+    ) -> Result<()> {
+        let bypassed = self.client.is_auth_bypassed();
+        // A revealed password goes back to hidden whether the login took
+        // the screen away or failed and left it for someone else to see
+        self.show_password = false;
+        match result {
+            Ok(()) => {
+                self.login_error = None;
+                // A log or search from the previous server would mix into
+                // this one's
+                self.log_view = LogView::default();
+                self.rss_view = RssView::default();
+                self.previous_states = None;
+                self.tracker_hints = TrackerHints::default();
+                self.stop_online_search();
+                self.search_view = SearchView::default();
+                // Save successful connection info to config, except for the
+                // demo's made-up server
+                let current_url = self.client.get_base_url().to_string();
+                let saved = if self.demo {
+                    Ok(())
+                } else if bypassed {
+                    self.config.set_last_url(&current_url)
+                } else {
+                    self.config
+                        .update_connection_info(&current_url, self.username_input.value())
+                };
+                if !bypassed
+                    && !self.demo
+                    && self.config.save_password
+                    && let Err(e) = credentials::store(
+                        &current_url,
+                        self.username_input.value(),
+                        self.password_input.value(),
+                    )
+                    .await
+                {
+                    warn!("Failed to save password to the keyring: {e}");
+                }
+                if let Err(e) = saved {
+                    warn!("Failed to save config: {e}");
+                } else {
+                    debug!("Successfully saved connection info to config");
+                }
+
+                self.state = AppState::Main;
+                self.input_mode = InputMode::None;
+                self.start_polling();
+
+                if !self.demo && !self.config.has_profile_for_url(&current_url) {
+                    self.ask_confirmation(
+                        "Save Profile",
+                        &format!("Save {current_url} as a profile for quick access?"),
+                        ConfirmAction::SaveProfile,
+                    );
+                }
+            }
+            Err(_) if kind == LoginKind::Probe => {
+                // Needs a login after all, or isn't reachable: the login
+                // form explains either soon enough
+                self.state = return_to;
+            }
+            Err(e) => {
+                let message = match &e {
+                    // qBittorrent bans the client IP after too many failed attempts
+                    ApiError::Forbidden(_) => {
+                        "Login failed: this IP has been banned after too many failed attempts"
+                            .to_string()
+                    }
+                    ApiError::Unauthorized(_) => {
+                        "Login failed: invalid username or password".to_string()
+                    }
+                    ApiError::Certificate(context, _) => format!(
+                        "Login failed: the server's HTTPS certificate isn't trusted ({}). \
+                         Restart with --ca-cert <file> to trust it, or --insecure to skip \
+                         verification",
+                        context.detail
+                    ),
+                    _ => format!("Login failed: {e}"),
+                };
+                // Back to the form to type the password (again)
+                self.password_input.clear();
+                self.state = AppState::Login;
+                self.input_mode = InputMode::Password;
+                self.login_error = Some(if kind == LoginKind::SavedPassword {
+                    format!("{message} (using the saved password)")
+                } else {
+                    message
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // Polling happens in the background; this only asks for it to run now
+    fn refresh_data(&self) {
+        if let Some(poller) = &self.poller {
+            poller.refresh_now();
+        }
+    }
+
+    fn update_labels(&mut self) {
+        self.categories = self.sync.categories.keys().cloned().collect();
+        self.categories.sort_by_key(|c| c.to_lowercase());
+        self.tags = self.sync.tags.iter().cloned().collect();
+        self.tags.sort_by_key(|t| t.to_lowercase());
+
+        let last = self.filter_pane_entries().len().saturating_sub(1);
+        self.filter_pane_index = self.filter_pane_index.min(last);
+    }
+
+    /// Entries of the category/tag pane; `None` clears the filter.
+    pub fn filter_pane_entries(&self) -> Vec<Option<LabelFilter>> {
+        let mut entries = vec![None, Some(LabelFilter::Uncategorized)];
+        entries.extend(
+            self.categories
+                .iter()
+                .map(|c| Some(LabelFilter::Category(c.clone()))),
+        );
+        entries.extend(self.tags.iter().map(|t| Some(LabelFilter::Tag(t.clone()))));
+        entries
+    }
+
+    pub fn label_filter_count(&self, filter: &LabelFilter) -> usize {
+        self.torrents.iter().filter(|t| filter.matches(t)).count()
+    }
+
+    /// Replaces the torrent list with fresh data from the server, keeping the
+    /// selection on the same torrent.
+    fn apply_torrents(&mut self, torrents: Vec<Torrent>) {
+        self.torrents = torrents;
+        self.stalls.observe(&self.torrents, Utc::now().timestamp());
+
+        // Forget marks on torrents that no longer exist
+        let torrents = &self.torrents;
+        self.selected_hashes
+            .retain(|hash| torrents.iter().any(|t| &t.hash == hash));
+
+        self.refilter();
+        self.sort_torrents();
+    }
+
+    /// Tells about torrents that went from downloading to complete since
+    /// the last refresh. Torrents that are new or gone aren't compared, so
+    /// neither the first refresh nor a deletion announces anything.
+    fn announce_completions(&mut self) {
+        let states = self
+            .torrents
+            .iter()
+            .map(|t| (t.hash.clone(), t.state.clone()))
+            .collect();
+        let Some(previous) = self.previous_states.replace(states) else {
+            return;
+        };
+        let finished: Vec<Completion> = self
+            .torrents
+            .iter()
+            .filter(|t| {
+                t.state.is_complete()
+                    && previous
+                        .get(&t.hash)
+                        .is_some_and(TorrentState::is_downloading)
+            })
+            .map(Completion::new)
+            .collect();
+        for completion in finished {
+            if self.config.desktop_notifications {
+                notification::send_desktop("Download finished", &completion.name);
+            }
+            self.notify_success(format!("Finished: {}", completion.name));
+            if self.config.completion_bell {
+                self.terminal_output.push('\x07');
+            }
+            if let Some(hook) = &self.completion_hook {
+                hook.push(completion);
+            }
+        }
+    }
+
+    /// Re-establishes `scroll_offset <= selected < scroll_offset + visible`
+    /// after the list, the selection, the scroll position or the number of
+    /// rows changed, without leaving empty rows below a list that shrank.
+    fn normalize_view(&mut self) {
+        let visible_rows = self.get_max_visible_rows();
+        let torrent_count = self.get_current_torrent_list_len();
+
+        if torrent_count == 0 {
+            self.scroll_offset = 0;
+            self.selected_torrent = 0;
+            self.selected_hash = None;
+            return;
+        }
+
+        // Ensure selected_torrent is within bounds
+        self.selected_torrent = self.selected_torrent.min(torrent_count.saturating_sub(1));
+        self.scroll_offset = self
+            .scroll_offset
+            .min(torrent_count.saturating_sub(visible_rows));
+
+        if self.selected_torrent < self.scroll_offset {
+            self.scroll_offset = self.selected_torrent;
+        } else if self.selected_torrent >= self.scroll_offset + visible_rows {
+            self.scroll_offset = self.selected_torrent - visible_rows + 1;
+        }
+    }
+
+    fn current_torrents(&self) -> impl Iterator<Item = &Torrent> {
+        self.listed.iter().map(|&index| &self.torrents[index])
+    }
+
+    fn listed_torrent(&self, position: usize) -> Option<&Torrent> {
+        self.listed
+            .get(position)
+            .map(|&index| &self.torrents[index])
+    }
+
+    pub fn get_visible_torrents(&self) -> Vec<&Torrent> {
+        self.current_torrents()
+            .skip(self.scroll_offset)
+            .take(self.get_max_visible_rows())
+            .collect()
+    }
+
+    /// Row of the selection within the window; `normalize_view` keeps it in
+    /// range, and it saturates rather than underflowing if it ever isn't.
+    pub fn get_relative_selected_index(&self) -> usize {
+        self.selected_index().saturating_sub(self.scroll_offset)
+    }
+
+    pub fn get_max_visible_rows(&self) -> usize {
+        // Use the stored value, with a minimum of 1
+        self.max_visible_rows.max(1)
+    }
+
+    pub fn set_max_visible_rows(&mut self, rows: usize) {
+        // This will be called by the UI to set the actual available rows
+        if rows != self.max_visible_rows {
+            self.max_visible_rows = rows;
+            self.sync_selection();
+        }
+    }
+
+    fn filter_torrents(&mut self) {
+        self.is_searching = !self.search_input.is_empty();
+        match Query::parse(self.search_input.value()) {
+            Ok(query) => {
+                self.search_query = query;
+                self.search_error = None;
+            }
+            Err(e) => self.search_error = Some(e),
+        }
+        self.refilter();
+
+        // Reset selection and scroll when filtering
+        self.scroll_offset = 0;
+        self.select_index(0);
+    }
+
+    // Unlike typing a query, clearing one keeps the selected torrent
+    fn clear_search(&mut self) {
+        self.search_input.clear();
+        self.is_searching = false;
+        self.search_query = Query::default();
+        self.search_error = None;
+        self.refilter();
+        self.sync_selection();
+    }
+
+    /// The search query still narrowing the list, if any.
+    pub fn active_search(&self) -> Option<&str> {
+        self.is_searching.then(|| self.search_input.value())
+    }
+
+    fn set_status_filter(&mut self, filter: StatusFilter) {
+        self.status_filter = filter;
+        self.refilter();
+        self.sort_torrents();
+        self.scroll_offset = 0;
+        self.select_index(0);
+    }
+
+    fn set_label_filter(&mut self, filter: Option<LabelFilter>) {
+        self.label_filter = filter;
+        self.refilter();
+        self.sort_torrents();
+        self.scroll_offset = 0;
+        self.select_index(0);
+    }
+
+    /// Whether a status or label filter narrows the list, independent of search.
+    pub fn has_list_filter(&self) -> bool {
+        self.status_filter != StatusFilter::All || self.label_filter.is_some()
+    }
+
+    // Rebuilds the listed torrents from the status/label filters and search query
+    fn refilter(&mut self) {
+        let mut listed = std::mem::take(&mut self.listed);
+        listed.clear();
+        if !self.has_list_filter() && !self.is_searching {
+            listed.extend(0..self.torrents.len());
+        } else {
+            listed.extend(self.matching_torrents());
+        }
+        self.listed = listed;
+    }
+
+    fn matching_torrents(&self) -> impl Iterator<Item = usize> {
+        // Status filter first, so search only looks inside the chosen bucket
+        self.torrents
+            .iter()
+            .enumerate()
+            .filter(|(_, torrent)| self.status_matches(self.status_filter, torrent))
+            .filter(|(_, torrent)| {
+                self.label_filter
+                    .as_ref()
+                    .is_none_or(|f| f.matches(torrent))
+            })
+            .filter(|(_, torrent)| !self.is_searching || self.search_query.matches(torrent))
+            .map(|(index, _)| index)
+    }
+
+    pub fn status_filter_count(&self, filter: StatusFilter) -> usize {
+        self.torrents
+            .iter()
+            .filter(|t| self.status_matches(filter, t))
+            .count()
+    }
+
+    fn status_matches(&self, filter: StatusFilter, torrent: &Torrent) -> bool {
+        match filter {
+            StatusFilter::Stalled => self.stalled_for(torrent).is_some(),
+            filter => filter.matches(torrent),
+        }
+    }
+
+    /// Seconds `torrent` has been downloading nothing, once that's longer
+    /// than `Config::stalled_after_mins`.
+    pub fn stalled_for(&self, torrent: &Torrent) -> Option<i64> {
+        self.stalls.stalled_for(torrent, Utc::now().timestamp())
+    }
+
+    pub fn get_current_torrent_list_len(&self) -> usize {
+        self.listed.len()
+    }
+
+    pub fn get_current_selected_torrent(&self) -> Option<&Torrent> {
+        self.listed_torrent(self.selected_index())
+    }
+}
+
+// Torrents without a finite ETA sort after every torrent that has one
+fn eta_sort_key(torrent: &Torrent) -> i64 {
+    match torrent.eta {
+        Some(eta) if (0..INFINITE_SECS).contains(&eta) => eta,
+        _ => i64::MAX,
+    }
+}
+
+fn torrent_count(count: usize) -> String {
+    if count == 1 {
+        "1 torrent".to_string()
+    } else {
+        format!("{count} torrents")
+    }
+}
+
+// Magnet links and web URLs are handed to the server instead of read from disk
+pub fn is_torrent_url(source: &str) -> bool {
+    ["magnet:", "http://", "https://", "bc://bt/"]
+        .iter()
+        .any(|prefix| source.starts_with(prefix))
+}
+
+fn is_paused(torrent: &Torrent) -> bool {
+    torrent.state.is_paused()
+}
+
+// Parses a KiB/s limit from user input into bytes/s; empty input means unlimited
+fn parse_limit_kib(input: &str) -> Option<i64> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Some(0);
+    }
+    input
+        .parse::<i64>()
+        .ok()
+        .map(|kib| kib.saturating_mul(1024))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::MockBackend;
+
+    fn torrent(hash: &str, name: &str, size: i64) -> Torrent {
+        serde_json::from_value(serde_json::json!({
+            "hash": hash,
+            "name": name,
+            "size": size,
+            "progress": 0.5,
+            "dlspeed": 0,
+            "upspeed": 0,
+            "state": "downloading",
+        }))
+        .unwrap()
+    }
+
+    async fn test_app(torrents: Vec<Torrent>) -> App {
+        let url = Url::parse("http://localhost:8080").unwrap();
+        let mut app = App::new_with_config(
+            url,
+            None,
+            None,
+            Config::default(),
+            ConnectionOptions::default(),
+        )
+        .await
+        .unwrap();
+        app.state = AppState::Main;
+        // Copies go to terminal_output, never the desktop's clipboard
+        app.copy_tools = Vec::new();
+        app.apply_torrents(torrents);
+        app
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn press(key: KeyEvent) -> crossterm::event::Event {
+        crossterm::event::Event::Key(key)
+    }
+
+    fn selected_hash(app: &App) -> &str {
+        &app.get_current_selected_torrent().unwrap().hash
+    }
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> crossterm::event::Event {
+        crossterm::event::Event::Mouse(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        })
+    }
+
+    #[tokio::test]
+    async fn a_failed_login_hides_the_password_again() {
+        let mut app = test_app(Vec::new()).await;
+        app.state = AppState::Login;
+        app.input_mode = InputMode::Password;
+        let ctrl_h = KeyEvent::new(KeyCode::Char('h'), KeyModifiers::CONTROL);
+        app.handle_event(press(ctrl_h)).await.unwrap();
+        assert!(app.show_password);
+
+        let refused = ApiError::Unauthorized(crate::api::ErrorContext {
+            endpoint: "/api/v2/auth/login".to_string(),
+            target: None,
+            status: None,
+            detail: "Fails.".to_string(),
+        });
+        app.finish_login(Err(refused), LoginKind::Typed, AppState::Login)
+            .await
+            .unwrap();
+        assert!(!app.show_password);
+    }
+
+    #[tokio::test]
+    async fn errors_return_to_the_screen_that_raised_them() {
+        let mut app = test_app(Vec::new()).await;
+        app.state = AppState::Login;
+        app.password_input.set("wrong");
+        let refused = ApiError::Unauthorized(crate::api::ErrorContext {
+            endpoint: "/api/v2/auth/login".to_string(),
+            target: None,
+            status: None,
+            detail: "Fails.".to_string(),
+        });
+        app.finish_login(Err(refused), LoginKind::Typed, AppState::Login)
+            .await
+            .unwrap();
+        assert_eq!(app.state, AppState::Login);
+        assert_eq!(app.input_mode, InputMode::Password);
+        assert!(app.password_input.is_empty());
+        assert_eq!(
+            app.login_error.as_deref(),
+            Some("Login failed: invalid username or password")
+        );
+
+        app.state = AppState::UrlConfig;
+        app.url_input.set("not a url");
+        app.handle_event(press(key(KeyCode::Enter))).await.unwrap();
+        assert!(matches!(app.state, AppState::Error(_)));
+        app.handle_event(press(key(KeyCode::Esc))).await.unwrap();
+        assert_eq!(app.state, AppState::UrlConfig);
+        assert_eq!(app.url_input.value(), "not a url");
+    }
+
+    #[tokio::test]
+    async fn testing_the_port_asks_before_calling_the_checker() {
+        let mut app = test_app(Vec::new()).await;
+        let mock = with_mock(&mut app);
+        mock.preferences.lock().unwrap().listen_port = 6881;
+        app.server_state = Some(ServerState {
+            connection_status: "firewalled".to_string(),
+            ..ServerState::default()
+        });
+
+        app.run_action(Action::TestPort).await.unwrap();
+        assert_eq!(app.state, AppState::Main);
+        assert_eq!(app.listen_port, Some(6881));
+        let toast = app.notifications.last().unwrap();
+        assert_eq!(toast.message, "The server reports port 6881 as firewalled");
+        assert_eq!(toast.severity, Severity::Warning);
+
+        app.config.port_check_url = Some("https://check.example/?port={port}".to_string());
+        app.run_action(Action::TestPort).await.unwrap();
+        assert_eq!(app.state, AppState::Confirm);
+        let confirmation = app.confirmation.as_ref().unwrap();
+        assert_eq!(confirmation.action, ConfirmAction::CheckPort(6881));
+        assert!(confirmation.message.contains("check.example"));
+    }
+
+    #[tokio::test]
+    async fn peers_are_banned_after_a_yes_and_added_from_the_input() {
+        let mut app = test_app(vec![torrent("abc", "linux.iso", 1)]).await;
+        let mock = with_mock(&mut app);
+        *mock.peers.lock().unwrap() = ["10.0.0.2:6881", "10.0.0.3:6881"]
+            .map(|address| Peer {
+                address: address.to_string(),
+                ..Peer::default()
+            })
+            .to_vec();
+        app.state = AppState::Details;
+        app.details_tab = DetailsTab::Trackers;
+        app.handle_details_input(key(KeyCode::Tab)).await.unwrap();
+        assert_eq!(app.details_tab, DetailsTab::Peers);
+        assert_eq!(app.peer_panel.peers.len(), 2);
+
+        // Anything but y keeps the peer
+        app.handle_details_input(key(KeyCode::Char('b')))
+            .await
+            .unwrap();
+        assert!(app.peer_panel.confirm_ban);
+        app.handle_details_input(key(KeyCode::Char('n')))
+            .await
+            .unwrap();
+        app.handle_details_input(key(KeyCode::Char('b')))
+            .await
+            .unwrap();
+        app.handle_details_input(key(KeyCode::Char('y')))
+            .await
+            .unwrap();
+        assert_eq!(
+            app.notifications.last().unwrap().message,
+            "Banned 10.0.0.2:6881"
+        );
+
+        // A bad address keeps the input open to be fixed
+        app.handle_details_input(key(KeyCode::Char('a')))
+            .await
+            .unwrap();
+        app.peer_panel.new_peers.as_mut().unwrap().set("10.0.0.9");
+        app.handle_details_input(key(KeyCode::Enter)).await.unwrap();
+        assert_eq!(app.notifications.last().unwrap().severity, Severity::Error);
+        app.peer_panel
+            .new_peers
+            .as_mut()
+            .unwrap()
+            .set("10.0.0.9:51413");
+        app.handle_details_input(key(KeyCode::Enter)).await.unwrap();
+        assert!(app.peer_panel.new_peers.is_none());
+        assert_eq!(
+            app.notifications.last().unwrap().message,
+            "Connecting to 10.0.0.9:51413"
+        );
+        assert_eq!(app.state, AppState::Details);
+        // The list is fetched again after each change
+        assert_eq!(
+            mock.calls(),
+            [
+                r#"get_peers("abc")"#,
+                r#"ban_peers(["10.0.0.2:6881"])"#,
+                r#"get_peers("abc")"#,
+                r#"add_peers("abc", ["10.0.0.9:51413"])"#,
+                r#"get_peers("abc")"#,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn editing_hands_the_input_to_the_main_loop_and_back() {
+        let mut app = test_app(vec![torrent("abc", "linux.iso", 1)]).await;
+        let ctrl = |c| press(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+        app.handle_event(ctrl('z')).await.unwrap();
+        assert_eq!(app.handoff.take(), Some(Handoff::Suspend));
+
+        app.state = AppState::Details;
+        app.details_tab = DetailsTab::Trackers;
+        app.tracker_panel.input = Some(TrackerInput::Add(UrlList::from_text("udp://a\n")));
+        app.handle_event(ctrl('e')).await.unwrap();
+        assert_eq!(
+            app.handoff.take(),
+            Some(Handoff::Edit {
+                target: EditTarget::TrackerUrls,
+                text: "udp://a\n".to_string()
+            })
+        );
+        app.finish_edit(
+            EditTarget::TrackerUrls,
+            Ok("udp://a\n\nhttps://b/announce\n".to_string()),
+        );
+        match &app.tracker_panel.input {
+            Some(TrackerInput::Add(urls)) => {
+                assert_eq!(urls.urls(), ["udp://a", "https://b/announce"])
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+
+        // A failed editor leaves the input alone
+        app.finish_edit(EditTarget::TrackerUrls, Err(std::io::Error::other("vi")));
+        assert_eq!(
+            app.notifications.last().unwrap().message,
+            "Editor failed: vi"
+        );
+        assert!(app.tracker_panel.input.is_some());
+    }
+
+    #[tokio::test]
+    async fn esc_cancels_a_pending_login() {
+        // Accepts connections but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        tokio::spawn(async move {
+            let mut open = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                open.push(stream);
+            }
+        });
+
+        let mut app = App::new_with_config(
+            url,
+            Some("admin".to_string()),
+            Some("adminadmin".to_string()),
+            Config::default(),
+            ConnectionOptions::default(),
+        )
+        .await
+        .unwrap();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        app.connect_events(sender);
+
+        app.login_on_start().await.unwrap();
+        assert_eq!(app.state, AppState::Connecting);
+        assert!(app.connecting_for().is_some());
+        assert!(app.is_logging_in());
+
+        app.handle_event(press(key(KeyCode::Esc))).await.unwrap();
+        assert_eq!(app.state, AppState::Login);
+        assert!(!app.should_quit);
+        assert_eq!(app.notifications[0].message, "Login cancelled");
+
+        // Nothing but spinner ticks comes back from the abandoned login
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        while let Ok(event) = receiver.try_recv() {
+            assert!(matches!(event, AppEvent::Tick), "{event:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn profile_picker_moves_edits_and_starts_new_connections() {
+        let mut config = Config::default();
+        for (name, url) in [
+            ("home", "http://nas:8080/"),
+            ("seedbox", "https://seed.example/"),
+        ] {
+            config.profiles.push(Profile {
+                name: name.to_string(),
+                url: url.to_string(),
+                username: Some("admin".to_string()),
+                default: name == "seedbox",
+            });
+        }
+        let url = Url::parse("http://localhost:8080").unwrap();
+        let mut app = App::new_with_config(url, None, None, config, ConnectionOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(app.state, AppState::ProfilePicker);
+        assert_eq!(app.profile_index, 1);
+
+        // Renaming onto another profile's name is refused without saving
+        app.handle_event(press(key(KeyCode::Char('e'))))
+            .await
+            .unwrap();
+        assert_eq!(app.state, AppState::ProfileEdit);
+        assert_eq!(app.url_input.value(), "https://seed.example/");
+        app.handle_event(press(KeyEvent::new(
+            KeyCode::Char('u'),
+            KeyModifiers::CONTROL,
+        )))
+        .await
+        .unwrap();
+        for c in "home".chars() {
+            app.handle_event(press(key(KeyCode::Char(c))))
+                .await
+                .unwrap();
+        }
+        app.handle_event(press(key(KeyCode::Enter))).await.unwrap();
+        assert_eq!(app.state, AppState::ProfileEdit);
+        assert_eq!(app.config.profiles[1].name, "seedbox");
+
+        app.handle_event(press(key(KeyCode::Esc))).await.unwrap();
+        app.handle_event(press(key(KeyCode::Down))).await.unwrap();
+        app.handle_event(press(key(KeyCode::Down))).await.unwrap();
+        assert_eq!(app.profile_index, 2);
+        app.handle_event(press(key(KeyCode::Enter))).await.unwrap();
+        assert_eq!(app.state, AppState::UrlConfig);
+        assert!(app.url_input.is_empty());
+
+        // Esc goes back to the picker rather than quitting
+        app.handle_event(press(key(KeyCode::Esc))).await.unwrap();
+        assert_eq!(app.state, AppState::ProfilePicker);
+        assert!(!app.should_quit);
+    }
+
+    #[tokio::test]
+    async fn clicks_select_rows_and_double_click_opens_details() {
+        let mut app = test_app(vec![
+            torrent("aaa", "alpha", 1),
+            torrent("bbb", "beta", 2),
+            torrent("ccc", "gamma", 3),
+        ])
+        .await;
+        app.list_area = Rect::new(10, 5, 60, 10);
+        let click = MouseEventKind::Down(MouseButton::Left);
+
+        app.handle_event(mouse(click, 20, 6)).await.unwrap();
+        assert_eq!(selected_hash(&app), "bbb");
+        assert_eq!(app.state, AppState::Main);
+
+        // Below the last row and outside the list nothing changes
+        app.handle_event(mouse(click, 20, 12)).await.unwrap();
+        app.handle_event(mouse(click, 5, 5)).await.unwrap();
+        assert_eq!(selected_hash(&app), "bbb");
+
+        app.handle_event(mouse(click, 30, 7)).await.unwrap();
+        app.handle_event(mouse(click, 30, 7)).await.unwrap();
+        assert_eq!(selected_hash(&app), "ccc");
+        assert_eq!(app.state, AppState::Details);
+    }
+
+    #[tokio::test]
+    async fn detailed_rows_take_two_lines_each() {
+        let mut app = test_app(vec![
+            torrent("aaa", "alpha", 1),
+            torrent("bbb", "beta", 2),
+            torrent("ccc", "gamma", 3),
+        ])
+        .await;
+        app.list_area = Rect::new(10, 5, 60, 10);
+        app.max_visible_rows = 10;
+        let click = MouseEventKind::Down(MouseButton::Left);
+
+        app.handle_main_input(key(KeyCode::Char('m')))
+            .await
+            .unwrap();
+        assert_eq!(app.row_mode, RowMode::Detailed);
+        assert_eq!(app.max_visible_rows, 5);
+
+        // Either line of a torrent selects it
+        app.handle_event(mouse(click, 20, 8)).await.unwrap();
+        assert_eq!(selected_hash(&app), "bbb");
+        app.handle_event(mouse(click, 20, 9)).await.unwrap();
+        assert_eq!(selected_hash(&app), "ccc");
+
+        app.handle_main_input(key(KeyCode::Char('m')))
+            .await
+            .unwrap();
+        assert_eq!(app.row_mode, RowMode::Compact);
+        assert_eq!(app.max_visible_rows, 10);
+    }
+
+    #[tokio::test]
+    async fn plus_and_minus_adjust_the_refresh_interval() {
+        let mut app = test_app(Vec::new()).await;
+        assert_eq!(app.refresh_interval(), Some(Duration::from_secs(2)));
+
+        app.handle_main_input(key(KeyCode::Char('-')))
+            .await
+            .unwrap();
+        app.handle_main_input(key(KeyCode::Char('-')))
+            .await
+            .unwrap();
+        assert_eq!(app.refresh_interval(), None);
+        app.handle_main_input(key(KeyCode::Char('-')))
+            .await
+            .unwrap();
+        assert_eq!(app.refresh_interval_secs, 0);
+
+        app.refresh_interval_secs = MAX_REFRESH_INTERVAL_SECS;
+        app.handle_main_input(key(KeyCode::Char('+')))
+            .await
+            .unwrap();
+        assert_eq!(app.refresh_interval_secs, MAX_REFRESH_INTERVAL_SECS);
+    }
+
+    #[tokio::test]
+    async fn failed_refreshes_show_a_toast_without_leaving_the_list() {
+        let mut app = test_app(vec![torrent("a", "alpha", 1)]).await;
+        for _ in 0..3 {
+            app.handle_update(AppEvent::ApiError("Failed to fetch torrents".to_string()))
+                .await
+                .unwrap();
+        }
+        assert_eq!(app.state, AppState::Main);
+
+        let toasts: Vec<_> = app.active_notifications().collect();
+        assert_eq!(toasts.len(), 1);
+        assert_eq!(toasts[0].severity, Severity::Error);
+    }
+
+    #[tokio::test]
+    async fn wheel_scrolls_the_list_and_drags_the_selection() {
+        let torrents = (0..20)
+            .map(|i| torrent(&format!("{i:03}"), &format!("t{i:02}"), i))
+            .collect();
+        let mut app = test_app(torrents).await;
+        app.max_visible_rows = 5;
+
+        app.handle_event(mouse(MouseEventKind::ScrollDown, 0, 0))
+            .await
+            .unwrap();
+        assert_eq!(app.scroll_offset, 3);
+        assert_eq!(selected_hash(&app), "003");
+
+        for _ in 0..10 {
+            app.handle_event(mouse(MouseEventKind::ScrollDown, 0, 0))
+                .await
+                .unwrap();
+        }
+        assert_eq!(app.scroll_offset, 15);
+
+        app.handle_event(mouse(MouseEventKind::ScrollUp, 0, 0))
+            .await
+            .unwrap();
+        assert_eq!(app.scroll_offset, 12);
+        assert_eq!(selected_hash(&app), "015");
+    }
+
+    #[tokio::test]
+    async fn the_view_stays_valid_as_the_list_and_window_shrink() {
+        let torrents: Vec<Torrent> = (0..20)
+            .map(|i| torrent(&format!("{i:03}"), &format!("t{i:02}"), i))
+            .collect();
+        let mut app = test_app(torrents.clone()).await;
+        app.max_visible_rows = 5;
+        app.select_index(18);
+        assert_eq!(app.scroll_offset, 14);
+
+        // A refresh drops most of the list, the selected torrent with it
+        app.apply_torrents(torrents[..3].to_vec());
+        assert_eq!(app.scroll_offset, 0);
+        assert_eq!(selected_hash(&app), "002");
+        assert_eq!(app.get_relative_selected_index(), 2);
+
+        // The window shrinks around the selection
+        app.apply_torrents(torrents.clone());
+        app.select_index(12);
+        app.set_max_visible_rows(2);
+        assert_eq!(app.scroll_offset, 11);
+        assert_eq!(app.get_relative_selected_index(), 1);
+
+        // A search matching nothing leaves an empty window at the top
+        app.search_input.set("zzz");
+        app.filter_torrents();
+        assert!(app.get_visible_torrents().is_empty());
+        assert_eq!(app.scroll_offset, 0);
+        assert_eq!(app.get_relative_selected_index(), 0);
+        app.clear_search();
+        assert_eq!(app.get_visible_torrents().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn add_dialog_warns_when_the_torrent_does_not_fit() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("big.torrent");
+        std::fs::write(&file, b"d4:infod6:lengthi4096e4:name3:bigee").unwrap();
+        let mut app = test_app(vec![]).await;
+        app.preferences = Some(Preferences {
+            save_path: "/downloads".to_string(),
+            ..Preferences::default()
+        });
+        app.open_add_torrent().await;
+        // Servers that haven't checked yet report 0
+        app.server_state = Some(ServerState {
+            free_space_on_disk: Some(0),
+            ..ServerState::default()
+        });
+        assert_eq!(app.add_free_space(), None);
+
+        app.server_state.as_mut().unwrap().free_space_on_disk = Some(2048);
+        assert_eq!(
+            app.add_free_space(),
+            Some(("2 KiB free".to_string(), false))
+        );
+        app.add_save_path_input.set("/mnt/other");
+        assert_eq!(
+            app.add_free_space(),
+            Some((
+                "2 KiB free on the default save path's disk".to_string(),
+                false
+            ))
+        );
+
+        app.torrent_path_input.set(file.to_string_lossy());
+        app.handle_event(press(key(KeyCode::End))).await.unwrap();
+        let (warning, too_big) = app.add_free_space().unwrap();
+        assert!(too_big);
+        assert_eq!(
+            warning,
+            "Needs 4 KiB, the default save path's disk has 2 KiB free"
+        );
+    }
+
+    #[tokio::test]
+    async fn a_resize_scrolls_the_way_the_next_frame_will() {
+        let torrents: Vec<Torrent> = (0..60)
+            .map(|i| torrent(&format!("{i:03}"), &format!("t{i:02}"), i))
+            .collect();
+        let mut app = test_app(torrents).await;
+        for (row_mode, speed_graphs) in [
+            (RowMode::Compact, false),
+            (RowMode::Detailed, false),
+            (RowMode::Compact, true),
+        ] {
+            app.row_mode = row_mode;
+            app.config.show_speed_graphs = speed_graphs;
+            app.handle_resize(120, 50);
+            app.select_index(59);
+            // Shrinking with the last torrent selected, one line at a time
+            for height in (10..50).rev() {
+                app.handle_resize(120, height);
+                let rows = app.max_visible_rows;
+                let offset = app.scroll_offset;
+                assert_eq!(offset + rows.max(1), 60, "{row_mode:?} at {height}");
+
+                let backend = ratatui::backend::TestBackend::new(120, height);
+                let mut terminal = ratatui::Terminal::new(backend).unwrap();
+                terminal.draw(|f| ui::draw(f, &mut app)).unwrap();
+                assert_eq!(app.max_visible_rows, rows, "{row_mode:?} at {height}");
+                assert_eq!(app.scroll_offset, offset, "{row_mode:?} at {height}");
+            }
+        }
+    }
+
+    #[test]
+    fn state_strings_map_to_buckets() {
+        let cases = [
+            ("downloading", Some(StatusFilter::Downloading)),
+            ("metaDL", Some(StatusFilter::Downloading)),
+            ("forcedMetaDL", Some(StatusFilter::Downloading)),
+            ("forcedDL", Some(StatusFilter::Downloading)),
+            ("stalledDL", Some(StatusFilter::Downloading)),
+            ("queuedDL", Some(StatusFilter::Downloading)),
+            ("allocating", Some(StatusFilter::Downloading)),
+            ("uploading", Some(StatusFilter::Seeding)),
+            ("forcedUP", Some(StatusFilter::Seeding)),
+            ("stalledUP", Some(StatusFilter::Seeding)),
+            ("queuedUP", Some(StatusFilter::Seeding)),
+            ("pausedDL", Some(StatusFilter::Paused)),
+            ("pausedUP", Some(StatusFilter::Paused)),
+            ("stoppedDL", Some(StatusFilter::Paused)),
+            ("stoppedUP", Some(StatusFilter::Paused)),
+            ("error", Some(StatusFilter::Errored)),
+            ("missingFiles", Some(StatusFilter::Errored)),
+            ("checkingDL", None),
+            ("checkingUP", None),
+            ("checkingResumeData", None),
+            ("moving", None),
+            ("unknown", None),
+        ];
+
+        for (state, bucket) in cases {
+            assert_eq!(state_bucket(&state.into()), bucket, "state {state}");
+        }
+    }
+
+    #[test]
+    fn completed_and_active_use_progress_and_speed() {
+        let mut seeding = torrent("a", "alpha", 1);
+        seeding.state = TorrentState::StalledUp;
+        seeding.progress = 1.0;
+        assert!(StatusFilter::Completed.matches(&seeding));
+        assert!(StatusFilter::Seeding.matches(&seeding));
+        assert!(!StatusFilter::Active.matches(&seeding));
+
+        seeding.upspeed = 1024;
+        assert!(StatusFilter::Active.matches(&seeding));
+        assert!(StatusFilter::All.matches(&seeding));
+        assert!(!StatusFilter::Downloading.matches(&seeding));
+    }
+
+    #[tokio::test]
+    async fn search_stays_applied_until_esc_in_the_main_view() {
+        let mut tagged = torrent("c", "gamma", 1);
+        tagged.tags = Some("keep, linux".to_string());
+        let mut app = test_app(vec![
+            torrent("a", "alpha", 1),
+            torrent("b", "beta", 1),
+            tagged,
+        ])
+        .await;
+        app.state = AppState::Main;
+
+        let ctrl_f = KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL);
+        app.handle_event(press(ctrl_f)).await.unwrap();
+        for c in "zzz".chars() {
+            app.handle_event(press(key(KeyCode::Char(c))))
+                .await
+                .unwrap();
+        }
+        // No matches means an empty list, not every torrent
+        assert_eq!(app.get_current_torrent_list_len(), 0);
+
+        app.search_input.set("LINUX");
+        app.filter_torrents();
+        app.handle_event(press(key(KeyCode::Enter))).await.unwrap();
+        assert_eq!(app.state, AppState::Main);
+        assert_eq!(app.active_search(), Some("LINUX"));
+        assert_eq!(app.get_current_torrent_list_len(), 1);
+        assert_eq!(selected_hash(&app), "c");
+
+        app.handle_event(press(key(KeyCode::Esc))).await.unwrap();
+        assert_eq!(app.active_search(), None);
+        assert_eq!(app.get_current_torrent_list_len(), 3);
+        assert_eq!(selected_hash(&app), "c");
+    }
+
+    #[tokio::test]
+    async fn arrows_move_through_matches_while_typing() {
+        let mut app = test_app(vec![
+            torrent("a", "linux one", 1),
+            torrent("b", "other", 1),
+            torrent("c", "linux two", 1),
+        ])
+        .await;
+        app.state = AppState::Main;
+
+        let ctrl_f = KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL);
+        app.handle_event(press(ctrl_f)).await.unwrap();
+        for c in "linux".chars() {
+            app.handle_event(press(key(KeyCode::Char(c))))
+                .await
+                .unwrap();
+        }
+        app.handle_event(press(key(KeyCode::Down))).await.unwrap();
+        assert_eq!(app.state, AppState::Search);
+        assert_eq!(app.search_input.value(), "linux");
+        assert_eq!(selected_hash(&app), "c");
+
+        app.handle_event(press(key(KeyCode::Enter))).await.unwrap();
+        assert_eq!(app.state, AppState::Main);
+        assert_eq!(selected_hash(&app), "c");
+    }
+
+    fn with_mock(app: &mut App) -> Arc<MockBackend> {
+        let mock = Arc::new(MockBackend::new(app.torrents.clone()));
+        app.client = mock.clone();
+        mock
+    }
+
+    #[tokio::test]
+    async fn space_resumes_only_when_every_target_is_paused() {
+        let mut stopped = torrent("s", "stopped", 1);
+        stopped.state = TorrentState::StoppedDl;
+        let mut paused = torrent("p", "paused", 1);
+        paused.state = TorrentState::PausedUp;
+        let mut app = test_app(vec![stopped, paused, torrent("d", "downloading", 1)]).await;
+        let mock = with_mock(&mut app);
+
+        // Listed by name: downloading, paused, stopped
+        app.select_index(0);
+        app.handle_event(press(key(KeyCode::Char(' '))))
+            .await
+            .unwrap();
+        app.select_index(1);
+        app.handle_event(press(key(KeyCode::Char(' '))))
+            .await
+            .unwrap();
+        app.select_index(2);
+        app.handle_event(press(key(KeyCode::Char(' '))))
+            .await
+            .unwrap();
+        assert_eq!(
+            mock.calls(),
+            [
+                r#"pause_torrents(["d"])"#,
+                r#"resume_torrents(["p"])"#,
+                r#"resume_torrents(["s"])"#,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn only_capital_y_deletes_the_files() {
+        let mut app = test_app(vec![torrent("a", "alpha", 1), torrent("b", "beta", 2)]).await;
+        let mock = with_mock(&mut app);
+
+        app.handle_main_input(key(KeyCode::Delete)).await.unwrap();
+        app.handle_event(press(key(KeyCode::Char('y'))))
+            .await
+            .unwrap();
+        app.handle_main_input(key(KeyCode::Down)).await.unwrap();
+        app.handle_main_input(key(KeyCode::Delete)).await.unwrap();
+        app.handle_event(press(key(KeyCode::Char('Y'))))
+            .await
+            .unwrap();
+        app.handle_main_input(key(KeyCode::Delete)).await.unwrap();
+        app.handle_event(press(key(KeyCode::Char('n'))))
+            .await
+            .unwrap();
+        assert_eq!(
+            mock.calls(),
+            [
+                r#"delete_torrents(["a"], false)"#,
+                r#"delete_torrents(["b"], true)"#,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn a_refresh_with_fewer_torrents_clamps_the_selection() {
+        let mut app = test_app(vec![
+            torrent("a", "alpha", 1),
+            torrent("b", "beta", 1),
+            torrent("c", "gamma", 1),
+        ])
+        .await;
+        let mock = with_mock(&mut app);
+        app.select_index(2);
+        assert_eq!(selected_hash(&app), "c");
+
+        mock.torrents.lock().unwrap().truncate(1);
+        let data = app.client.sync_maindata(0).await.unwrap();
+        app.handle_update(AppEvent::MainDataUpdated(Box::new(data)))
+            .await
+            .unwrap();
+        assert_eq!(app.torrents.len(), 1);
+        assert_eq!(app.selected_torrent, 0);
+        assert_eq!(selected_hash(&app), "a");
+    }
+
+    #[tokio::test]
+    async fn a_successful_login_saves_the_connection() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("config.json");
+        let mut app = test_app(Vec::new()).await;
+        crate::paths::set_test_config_file(file.clone());
+        let mock = with_mock(&mut app);
+        app.state = AppState::Login;
+        app.username_input.set("admin");
+
+        mock.reject_login
+            .store(true, std::sync::atomic::Ordering::SeqCst);
+        app.password_input.set("wrong");
+        app.attempt_login().await.unwrap();
+        assert_eq!(app.state, AppState::Login);
+        assert_eq!(app.config.get_last_url(), None);
+
+        mock.reject_login
+            .store(false, std::sync::atomic::Ordering::SeqCst);
+        app.password_input.set("secret");
+        app.attempt_login().await.unwrap();
+        assert!(app.client.is_authenticated());
+        assert_eq!(
+            app.config.get_last_url().as_deref(),
+            Some("http://mock.invalid:8080/")
+        );
+        assert_eq!(app.config.get_last_username().as_deref(), Some("admin"));
+        let saved = std::fs::read_to_string(file).unwrap();
+        assert!(saved.contains("http://mock.invalid:8080/"));
+        assert_eq!(
+            mock.calls(),
+            [
+                r#"login_or_bypass(Some(("admin", "wrong")))"#,
+                r#"login_or_bypass(Some(("admin", "secret")))"#,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn refreshes_sample_the_highlighted_torrent_speed() {
+        let moving = Torrent {
+            dlspeed: 100,
+            upspeed: 7,
+            ..torrent("a", "alpha", 1)
+        };
+        let mut app = test_app(vec![moving, torrent("b", "beta", 1)]).await;
+        let mock = with_mock(&mut app);
+        for _ in 0..2 {
+            let data = app.client.sync_maindata(0).await.unwrap();
+            app.handle_update(AppEvent::MainDataUpdated(Box::new(data)))
+                .await
+                .unwrap();
+        }
+        let history = app.torrent_speed_history.get("a").unwrap();
+        assert_eq!(history.download(10), [100, 100]);
+        assert_eq!(history.upload(10), [7, 7]);
+
+        // Another torrent starts from nothing
+        app.handle_main_input(key(KeyCode::Down)).await.unwrap();
+        mock.torrents.lock().unwrap()[1].dlspeed = 3;
+        let data = app.client.sync_maindata(0).await.unwrap();
+        app.handle_update(AppEvent::MainDataUpdated(Box::new(data)))
+            .await
+            .unwrap();
+        assert!(app.torrent_speed_history.get("a").is_none());
+        assert_eq!(
+            app.torrent_speed_history.get("b").unwrap().download(10),
+            [3]
+        );
+    }
+
+    #[tokio::test]
+    async fn the_demo_takes_any_login_and_stays_put() {
+        let mut app = test_app(Vec::new()).await;
+        app.use_demo(DemoServer::new());
+        app.password_input.set("not the password");
+        app.login_on_start().await.unwrap();
+        assert_eq!(app.state, AppState::Main);
+
+        let data = app.client.sync_maindata(0).await.unwrap();
+        app.handle_update(AppEvent::MainDataUpdated(Box::new(data)))
+            .await
+            .unwrap();
+        assert_eq!(app.torrents.len(), crate::demo::TORRENT_COUNT);
+
+        // Logging in somewhere else keeps the demo
+        app.connect(Url::parse("http://seedbox.example:8080").unwrap());
+        assert_eq!(app.client.get_base_url().host_str(), Some("demo.invalid"));
+    }
+
+    #[tokio::test]
+    async fn a_huge_stall_threshold_never_stalls() {
+        let config = Config {
+            stalled_after_mins: u64::MAX,
+            ..Config::default()
+        };
+        let url = Url::parse("http://localhost:8080").unwrap();
+        let mut app = App::new_with_config(url, None, None, config, ConnectionOptions::default())
+            .await
+            .unwrap();
+        let idle = torrent("a", "idle", 1);
+        app.stalls.observe(std::slice::from_ref(&idle), 0);
+        app.apply_torrents(vec![idle.clone()]);
+        assert_eq!(app.stalled_for(&idle), None);
+    }
+
+    #[tokio::test]
+    async fn downloads_idle_past_the_threshold_are_stalled() {
+        let idle = torrent("a", "idle", 1);
+        let moving = Torrent {
+            dlspeed: 100,
+            ..torrent("b", "moving", 1)
+        };
+        let fresh = torrent("c", "fresh", 1);
+        let mut app = test_app(Vec::new()).await;
+        // Both were first seen an hour ago, when neither was moving
+        let hour_ago = Utc::now().timestamp() - 3_600;
+        app.stalls
+            .observe(&[idle.clone(), torrent("b", "moving", 1)], hour_ago);
+
+        app.apply_torrents(vec![idle.clone(), moving.clone(), fresh.clone()]);
+        assert!(app.stalled_for(&idle).unwrap() >= 3_600);
+        assert_eq!(app.stalled_for(&moving), None);
+        // Idle too, but there's no history saying for how long
+        assert_eq!(app.stalled_for(&fresh), None);
+
+        app.set_status_filter(StatusFilter::Stalled);
+        assert_eq!(app.status_filter_count(StatusFilter::Stalled), 1);
+        assert_eq!(app.get_current_torrent_list_len(), 1);
+        assert_eq!(selected_hash(&app), "a");
+    }
+
+    #[tokio::test]
+    async fn status_filter_and_search_compose() {
+        let mut paused = torrent("c", "alpha paused", 1);
+        paused.state = TorrentState::PausedDl;
+        let mut app = test_app(vec![
+            torrent("a", "alpha", 1),
+            torrent("b", "beta", 1),
+            paused,
+        ])
+        .await;
+
+        app.set_status_filter(StatusFilter::Downloading);
+        assert_eq!(app.get_current_torrent_list_len(), 2);
+
+        app.search_input.set("alpha");
+        app.filter_torrents();
+        assert_eq!(app.get_current_torrent_list_len(), 1);
+        assert_eq!(selected_hash(&app), "a");
+        assert_eq!(app.status_filter_count(StatusFilter::Paused), 1);
+    }
+
+    #[tokio::test]
+    async fn label_filter_matches_categories_and_tags() {
+        let mut movie = torrent("a", "alpha", 1);
+        movie.category = Some("movies".to_string());
+        movie.tags = Some("hd, new".to_string());
+        let mut empty = torrent("b", "beta", 1);
+        empty.category = Some(String::new());
+        let mut app = test_app(vec![movie, empty, torrent("c", "gamma", 1)]).await;
+
+        assert_eq!(app.label_filter_count(&LabelFilter::Uncategorized), 2);
+        assert_eq!(
+            app.label_filter_count(&LabelFilter::Tag("new".to_string())),
+            1
+        );
+        assert_eq!(
+            app.label_filter_count(&LabelFilter::Tag("h".to_string())),
+            0
+        );
+
+        app.set_label_filter(Some(LabelFilter::Category("movies".to_string())));
+        app.apply_torrents(app.torrents.clone());
+        assert_eq!(app.get_current_torrent_list_len(), 1);
+        assert_eq!(selected_hash(&app), "a");
+    }
+
+    #[tokio::test]
+    async fn add_dialog_starts_from_the_last_add_and_cycles_presets() {
+        let mut app = test_app(vec![]).await;
+        for (name, path) in [("movies", "/data/movies"), ("misc", ""), ("tv", "/data/tv")] {
+            app.sync
+                .categories
+                .insert(name.to_string(), path.to_string());
+        }
+        app.categories = vec!["misc".into(), "movies".into(), "tv".into()];
+        app.config.add_defaults.insert(
+            "http://localhost:8080/".to_string(),
+            AddDefaults {
+                save_path: Some("/downloads/last".to_string()),
+                category: Some("tv".to_string()),
+            },
+        );
+
+        app.open_add_torrent().await;
+        assert_eq!(app.add_save_path_input.value(), "/downloads/last");
+        assert_eq!(app.add_category(), Some("tv"));
+
+        // Categories without a save path are skipped
+        let ctrl_p = KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL);
+        app.handle_event(press(ctrl_p)).await.unwrap();
+        assert_eq!(app.add_save_path_input.value(), "/data/movies");
+        assert_eq!(app.add_category(), Some("movies"));
+        app.handle_event(press(ctrl_p)).await.unwrap();
+        assert_eq!(app.add_save_path_input.value(), "/data/tv");
+        app.handle_event(press(ctrl_p)).await.unwrap();
+        assert_eq!(app.add_category(), Some("movies"));
+    }
+
+    #[tokio::test]
+    async fn add_dialog_says_where_the_torrent_would_go() {
+        let mut app = test_app(vec![]).await;
+        app.sync
+            .categories
+            .insert("movies".to_string(), "/data/movies".to_string());
+        app.sync
+            .categories
+            .insert("misc".to_string(), String::new());
+        app.categories = vec!["misc".into(), "movies".into()];
+        // Kept from an earlier fetch, so the dialog doesn't ask again
+        app.preferences = Some(Preferences {
+            save_path: "/downloads/".to_string(),
+            ..Preferences::default()
+        });
+
+        app.open_add_torrent().await;
+        assert_eq!(app.add_save_path_input.value(), "/downloads/");
+        assert_eq!(
+            app.add_save_path_hint(),
+            Some(("Default save path: /downloads/".to_string(), false))
+        );
+        app.add_save_path_input.set("/downloads");
+        assert!(!app.add_save_path_hint().unwrap().1);
+
+        app.add_category_index = 2;
+        assert_eq!(
+            app.add_save_path_hint(),
+            Some(("Category saves to /data/movies".to_string(), true))
+        );
+        app.add_save_path_input.clear();
+        assert!(!app.add_save_path_hint().unwrap().1);
+        // A category without a path saves to the default
+        app.add_category_index = 1;
+        app.add_save_path_input.set("/mnt/elsewhere");
+        assert_eq!(
+            app.add_save_path_hint(),
+            Some(("Default save path: /downloads/".to_string(), true))
+        );
+    }
+
+    #[tokio::test]
+    async fn only_torrents_seen_downloading_announce_their_completion() {
+        let seeding = |hash, name| {
+            let mut t = torrent(hash, name, 1);
+            t.state = TorrentState::StalledUp;
+            t
+        };
+        let mut app = test_app(vec![
+            torrent("a", "alpha", 1),
+            torrent("b", "beta", 1),
+            seeding("c", "gamma"),
+        ])
+        .await;
+        // What was already seeding at startup is left alone
+        app.announce_completions();
+        assert!(app.notifications.is_empty());
+
+        // beta was deleted elsewhere, delta added complete
+        app.apply_torrents(vec![
+            seeding("a", "alpha"),
+            seeding("c", "gamma"),
+            seeding("d", "delta"),
+        ]);
+        app.announce_completions();
+        let messages: Vec<&str> = app
+            .notifications
+            .iter()
+            .map(|n| n.message.as_str())
+            .collect();
+        assert_eq!(messages, ["Finished: alpha"]);
+        assert_eq!(app.terminal_output, "\x07");
+
+        // Staying complete doesn't announce it again
+        app.notifications.clear();
+        app.announce_completions();
+        assert!(app.notifications.is_empty());
+    }
+
+    #[tokio::test]
+    async fn copy_menu_copies_the_picked_field() {
+        let mut app = test_app(vec![torrent("abc", "alpha", 1)]).await;
+        app.handle_event(press(key(KeyCode::Char('y'))))
+            .await
+            .unwrap();
+        assert_eq!(app.state, AppState::Copy);
+        app.handle_event(press(key(KeyCode::Char('h'))))
+            .await
+            .unwrap();
+        assert_eq!(app.state, AppState::Main);
+        assert_eq!(
+            app.notifications.last().unwrap().message,
+            "Copied the hash of alpha"
+        );
+        assert_eq!(app.terminal_output, clipboard::osc52("abc"));
+
+        // Nothing to copy is said so instead
+        app.handle_event(press(key(KeyCode::Char('y'))))
+            .await
+            .unwrap();
+        app.handle_event(press(key(KeyCode::Char('p'))))
+            .await
+            .unwrap();
+        assert_eq!(
+            app.notifications.last().unwrap().message,
+            "No save path known for alpha"
+        );
+    }
+
+    #[tokio::test]
+    async fn remote_paths_are_mapped_or_offered_for_copying() {
+        let mut seedbox = torrent("a", "alpha", 1);
+        seedbox.save_path = Some("/srv/torrents".to_string());
+        let mut app = test_app(vec![seedbox]).await;
+        app.config.server_is_local = Some(false);
+
+        app.handle_event(press(key(KeyCode::Char('o'))))
+            .await
+            .unwrap();
+        assert_eq!(app.state, AppState::Confirm);
+        assert_eq!(
+            app.confirmation.as_ref().unwrap().action,
+            ConfirmAction::CopyPath("/srv/torrents".to_string())
+        );
+        app.handle_event(press(key(KeyCode::Esc))).await.unwrap();
+
+        app.config
+            .path_mappings
+            .insert("/srv".to_string(), "/nonexistent/seedbox".to_string());
+        app.handle_event(press(key(KeyCode::Char('o'))))
+            .await
+            .unwrap();
+        assert_eq!(app.state, AppState::Main);
+        assert_eq!(
+            app.notifications.last().unwrap().message,
+            "/nonexistent/seedbox/torrents doesn't exist here"
+        );
+    }
+
+    #[tokio::test]
+    async fn selection_follows_torrent_when_refresh_reorders() {
+        let mut app = test_app(vec![
+            torrent("a", "alpha", 1),
+            torrent("b", "beta", 2),
+            torrent("c", "gamma", 3),
+        ])
+        .await;
+        app.sort_column = SortColumn::Size;
+        app.sort_torrents();
+        app.handle_main_input(key(KeyCode::Down)).await.unwrap();
+        assert_eq!(selected_hash(&app), "b");
+
+        // Sizes changed on the server, so the sorted order is now b, c, a
+        app.apply_torrents(vec![
+            torrent("a", "alpha", 3),
+            torrent("b", "beta", 1),
+            torrent("c", "gamma", 2),
+        ]);
+
+        assert_eq!(selected_hash(&app), "b");
+        assert_eq!(app.selected_index(), 0);
+    }
+
+    #[tokio::test]
+    async fn navigation_continues_from_moved_torrent() {
+        let mut app = test_app(vec![
+            torrent("a", "alpha", 1),
+            torrent("b", "beta", 2),
+            torrent("c", "gamma", 3),
+        ])
+        .await;
+        app.sort_column = SortColumn::Size;
+        app.sort_torrents();
+        assert_eq!(selected_hash(&app), "a");
+
+        app.apply_torrents(vec![
+            torrent("a", "alpha", 2),
+            torrent("b", "beta", 1),
+            torrent("c", "gamma", 3),
+        ]);
+        app.handle_main_input(key(KeyCode::Down)).await.unwrap();
+
+        assert_eq!(selected_hash(&app), "c");
+    }
+
+    #[tokio::test]
+    async fn removed_torrent_falls_back_to_nearest_index() {
+        let mut app = test_app(vec![
+            torrent("a", "alpha", 1),
+            torrent("b", "beta", 1),
+            torrent("c", "gamma", 1),
+            torrent("d", "delta", 1),
+        ])
+        .await;
+        // Sorted by name: alpha, beta, delta, gamma
+        app.handle_main_input(key(KeyCode::End)).await.unwrap();
+        assert_eq!(selected_hash(&app), "c");
+
+        app.apply_torrents(vec![
+            torrent("a", "alpha", 1),
+            torrent("b", "beta", 1),
+            torrent("d", "delta", 1),
+        ]);
+
+        assert_eq!(selected_hash(&app), "d");
+    }
+
+    #[tokio::test]
+    async fn delete_targets_the_torrent_selected_before_reorder() {
+        let mut app = test_app(vec![torrent("a", "alpha", 1), torrent("b", "beta", 2)]).await;
+        app.handle_main_input(key(KeyCode::Down)).await.unwrap();
+
+        app.apply_torrents(vec![
+            torrent("c", "aaa", 1),
+            torrent("a", "alpha", 1),
+            torrent("b", "beta", 2),
+        ]);
+        app.handle_main_input(key(KeyCode::Delete)).await.unwrap();
+
+        assert_eq!(app.state, AppState::ConfirmDelete);
+        assert_eq!(
+            app.delete_confirmation,
+            [DeleteTarget {
+                hash: "b".to_string(),
+                name: "beta".to_string(),
+                size: 2,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn cleanup_deletes_only_the_picked_matches_after_confirming() {
+        let mut seeded = torrent("s", "seeded", 5);
+        seeded.state = TorrentState::Uploading;
+        seeded.ratio = Some(2.5);
+        let mut app = test_app(vec![torrent("a", "alpha", 1), seeded]).await;
+
+        let ctrl_x = KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL);
+        app.handle_event(press(ctrl_x)).await.unwrap();
+        app.handle_event(press(key(KeyCode::Char('c'))))
+            .await
+            .unwrap();
+        assert_eq!(app.state, AppState::Cleanup);
+
+        // Nothing is picked yet
+        app.handle_event(press(key(KeyCode::Enter))).await.unwrap();
+        assert_eq!(app.state, AppState::Cleanup);
+        assert!(app.delete_confirmation.is_empty());
+
+        for code in [KeyCode::Tab, KeyCode::Tab, KeyCode::Tab, KeyCode::Char('a')] {
+            app.handle_event(press(key(code))).await.unwrap();
+        }
+        app.handle_event(press(key(KeyCode::Enter))).await.unwrap();
+        assert_eq!(app.state, AppState::ConfirmDelete);
+        assert_eq!(
+            app.delete_confirmation,
+            [DeleteTarget {
+                hash: "s".to_string(),
+                name: "seeded".to_string(),
+                size: 5,
+            }]
+        );
+
+        // Backing out returns to the picks
+        app.handle_event(press(key(KeyCode::Char('n'))))
+            .await
+            .unwrap();
+        assert_eq!(app.state, AppState::Cleanup);
+        assert_eq!(app.cleanup.as_ref().unwrap().selected.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn enabling_auto_management_asks_from_the_details_view() {
+        let mut managed = torrent("b", "beta", 2);
+        managed.auto_tmm = Some(true);
+        let mut app = test_app(vec![torrent("a", "alpha", 1), managed]).await;
+        app.state = AppState::Details;
+
+        app.handle_event(press(key(KeyCode::Char('a'))))
+            .await
+            .unwrap();
+        assert_eq!(app.state, AppState::Confirm);
+        let confirmation = app.confirmation.as_ref().unwrap();
+        assert_eq!(confirmation.action, ConfirmAction::EnableAutoManagement);
+        assert!(confirmation.message.contains("default save path"));
+
+        // Saying no goes back to the torrent instead of the list
+        app.handle_event(press(key(KeyCode::Char('n'))))
+            .await
+            .unwrap();
+        assert_eq!(app.state, AppState::Details);
+        assert!(app.confirmation.is_none());
+    }
+
+    #[tokio::test]
+    async fn searching_ten_thousand_torrents_only_rebuilds_indexes() {
+        let torrents = (0..10_000)
+            .map(|i| torrent(&format!("{i:05}"), &format!("linux-{i}.iso"), i))
+            .collect();
+        let mut app = test_app(torrents).await;
+        assert_eq!(app.get_current_torrent_list_len(), 10_000);
+        app.select_hash("04321");
+
+        let ctrl_f = KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL);
+        app.handle_event(press(ctrl_f)).await.unwrap();
+        for c in "linux-4321".chars() {
+            app.handle_event(press(key(KeyCode::Char(c))))
+                .await
+                .unwrap();
+        }
+        let names: Vec<&str> = app
+            .get_visible_torrents()
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect();
+        assert_eq!(names, ["linux-4321.iso"]);
+        assert_eq!(selected_hash(&app), "04321");
+
+        app.handle_event(press(key(KeyCode::Char('x'))))
+            .await
+            .unwrap();
+        assert_eq!(app.get_current_torrent_list_len(), 0);
+        assert!(app.get_current_selected_torrent().is_none());
+
+        // Clearing the query lists everything again around the same torrent
+        for _ in 0.."linux-4321x".len() {
+            app.handle_event(press(key(KeyCode::Backspace)))
+                .await
+                .unwrap();
+        }
+        assert_eq!(app.get_current_torrent_list_len(), 10_000);
+        app.select_hash("04321");
+        app.handle_event(press(key(KeyCode::Esc))).await.unwrap();
+        assert_eq!(app.get_current_torrent_list_len(), 10_000);
+        assert_eq!(selected_hash(&app), "04321");
+    }
+
+    #[tokio::test]
+    async fn command_palette_runs_the_chosen_action() {
+        let mut app = test_app(vec![torrent("a", "alpha", 1), torrent("b", "beta", 2)]).await;
+        app.handle_event(press(key(KeyCode::Char(':'))))
+            .await
+            .unwrap();
+        assert_eq!(app.state, AppState::CommandPalette);
+        for c in "mark".chars() {
+            app.handle_event(press(key(KeyCode::Char(c))))
+                .await
+                .unwrap();
+        }
+        let palette = app.command_palette.as_ref().unwrap();
+        assert_eq!(palette.selected_item(), Some(Action::ToggleMark));
+        assert_eq!(palette.matches().next().unwrap().keys, "v");
+
+        // Acts on the highlighted torrent, as the key itself would
+        app.handle_event(press(key(KeyCode::Enter))).await.unwrap();
+        assert_eq!(app.state, AppState::Main);
+        assert!(app.command_palette.is_none());
+        assert!(app.selected_hashes.contains(selected_hash(&app)));
+
+        let ctrl_k = KeyEvent::new(KeyCode::Char('k'), KeyModifiers::CONTROL);
+        app.handle_event(press(ctrl_k)).await.unwrap();
+        app.handle_event(press(key(KeyCode::Esc))).await.unwrap();
+        assert_eq!(app.state, AppState::Main);
+        assert_eq!(app.selected_hashes.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn shutting_down_the_server_needs_a_typed_yes() {
+        let mut app = test_app(vec![torrent("a", "alpha", 1)]).await;
+        let ctrl_x = KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL);
+        app.handle_event(press(ctrl_x)).await.unwrap();
+        app.handle_event(press(key(KeyCode::Char('Q'))))
+            .await
+            .unwrap();
+        assert_eq!(app.state, AppState::Prompt);
+        assert_eq!(
+            app.prompt.as_ref().unwrap().action,
+            PromptAction::ShutdownServer
+        );
+
+        // Anything but "yes" leaves the server alone
+        app.handle_event(press(key(KeyCode::Char('y'))))
+            .await
+            .unwrap();
+        app.handle_event(press(key(KeyCode::Enter))).await.unwrap();
+        assert_eq!(app.state, AppState::Main);
+        assert!(!app.server_shut_down);
+        assert!(
+            app.notifications
+                .last()
+                .unwrap()
+                .message
+                .contains("cancelled")
+        );
+
+        // Once it's going away on purpose, failed refreshes stay quiet
+        let shown = app.notifications.len();
+        app.server_shut_down = true;
+        app.handle_update(AppEvent::ApiError("connection refused".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(app.notifications.len(), shown);
     }
 }
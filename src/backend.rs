@@ -0,0 +1,853 @@
+use crate::api::{AddTorrentOptions, ApiResult, BuildInfo, LogEntry, MainData, QBittorrentClient};
+use crate::models::{
+    Peer, Preferences, RssFeed, RssRule, SearchPlugin, SearchResults, SearchStatus, TorrentFile,
+    Tracker,
+};
+use async_trait::async_trait;
+use url::Url;
+
+/// What the app needs from a qBittorrent server. The WebUI client is the
+/// real one; `--demo` swaps in [`crate::demo::DemoServer`].
+#[async_trait]
+pub trait TorrentApi: Send + Sync {
+    fn get_base_url(&self) -> &Url;
+
+    /// Whether a login has succeeded on this client.
+    fn is_authenticated(&self) -> bool;
+
+    /// Whether a re-login after an expired session is in progress.
+    fn is_reconnecting(&self) -> bool;
+
+    /// Whether the server let this client in without a login.
+    fn is_auth_bypassed(&self) -> bool;
+
+    /// Logs in, unless the server lets this client in without credentials
+    /// ("Bypass authentication for clients on localhost" or for whitelisted
+    /// IP subnets). Without `credentials` only the bypass is tried.
+    async fn login_or_bypass(&self, credentials: Option<(&str, &str)>) -> ApiResult<()>;
+
+    /// Fetches changes since `rid`; pass 0 to get a full snapshot.
+    async fn sync_maindata(&self, rid: i64) -> ApiResult<MainData>;
+
+    async fn pause_torrents(&self, hashes: &[String]) -> ApiResult<()>;
+
+    async fn resume_torrents(&self, hashes: &[String]) -> ApiResult<()>;
+
+    async fn pause_all(&self) -> ApiResult<()>;
+
+    async fn resume_all(&self) -> ApiResult<()>;
+
+    async fn set_force_start(&self, hashes: &[String], value: bool) -> ApiResult<()>;
+
+    async fn delete_torrents(&self, hashes: &[String], delete_files: bool) -> ApiResult<()>;
+
+    async fn recheck_torrents(&self, hashes: &[String]) -> ApiResult<()>;
+
+    async fn set_category(&self, hashes: &[String], category: &str) -> ApiResult<()>;
+
+    async fn add_tags(&self, hashes: &[String], tags: &str) -> ApiResult<()>;
+
+    /// Turns automatic torrent management on or off. Turning it on moves the
+    /// torrents' files to their category's save path.
+    async fn set_auto_management(&self, hashes: &[String], enable: bool) -> ApiResult<()>;
+
+    /// Limits how long torrents seed. Each limit is -2 to follow the global
+    /// share limits or -1 for none; times are in minutes. Servers before
+    /// qBittorrent 4.6 only take -2 for the inactive seeding time.
+    async fn set_share_limits(
+        &self,
+        hashes: &[String],
+        ratio_limit: f64,
+        seeding_time_limit: i64,
+        inactive_seeding_time_limit: i64,
+    ) -> ApiResult<()>;
+
+    async fn top_priority(&self, hashes: &[String]) -> ApiResult<()>;
+
+    async fn bottom_priority(&self, hashes: &[String]) -> ApiResult<()>;
+
+    async fn increase_priority(&self, hashes: &[String]) -> ApiResult<()>;
+
+    async fn decrease_priority(&self, hashes: &[String]) -> ApiResult<()>;
+
+    async fn add_torrent(&self, torrent_data: &[u8], options: &AddTorrentOptions) -> ApiResult<()>;
+
+    /// Adds torrents from magnet links or URLs, one per line.
+    async fn add_magnet(&self, urls: &str, options: &AddTorrentOptions) -> ApiResult<()>;
+
+    /// The .torrent file of a torrent on the server. Needs qBittorrent 4.5 or
+    /// newer; older servers answer 404 when their version isn't known.
+    async fn export_torrent(&self, hash: &str) -> ApiResult<Vec<u8>>;
+
+    async fn get_trackers(&self, hash: &str) -> ApiResult<Vec<Tracker>>;
+
+    async fn add_trackers(&self, hash: &str, urls: &[String]) -> ApiResult<()>;
+
+    async fn remove_trackers(&self, hash: &str, urls: &[String]) -> ApiResult<()>;
+
+    async fn edit_tracker(&self, hash: &str, orig_url: &str, new_url: &str) -> ApiResult<()>;
+
+    /// The peers connected for a torrent, fastest first.
+    async fn get_peers(&self, hash: &str) -> ApiResult<Vec<Peer>>;
+
+    /// Bans `peers`, given as `ip:port`, from every torrent.
+    async fn ban_peers(&self, peers: &[String]) -> ApiResult<()>;
+
+    /// Connects the torrent to `peers`, given as `ip:port`.
+    async fn add_peers(&self, hash: &str, peers: &[String]) -> ApiResult<()>;
+
+    /// State of every piece: 0 not downloaded, 1 downloading, 2 done.
+    async fn get_piece_states(&self, hash: &str) -> ApiResult<Vec<u8>>;
+
+    async fn get_files(&self, hash: &str) -> ApiResult<Vec<TorrentFile>>;
+
+    /// Renames or moves a file within the torrent. Paths are relative to the
+    /// save path; a 409 means `new_path` is taken.
+    async fn rename_file(&self, hash: &str, old_path: &str, new_path: &str) -> ApiResult<()>;
+
+    async fn rename_folder(&self, hash: &str, old_path: &str, new_path: &str) -> ApiResult<()>;
+
+    /// Log messages of the chosen severities newer than `last_known_id`; -1
+    /// fetches everything the server still has.
+    async fn get_main_log(
+        &self,
+        normal: bool,
+        info: bool,
+        warning: bool,
+        critical: bool,
+        last_known_id: i64,
+    ) -> ApiResult<Vec<LogEntry>>;
+
+    async fn get_search_plugins(&self) -> ApiResult<Vec<SearchPlugin>>;
+
+    /// Starts a search job on the server with the comma separated `plugins`
+    /// (or "enabled"), returning its id.
+    async fn start_search(&self, pattern: &str, plugins: &str) -> ApiResult<i64>;
+
+    async fn get_search_status(&self, id: i64) -> ApiResult<Option<SearchStatus>>;
+
+    /// Results of job `id` from `offset` on.
+    async fn get_search_results(&self, id: i64, offset: usize) -> ApiResult<SearchResults>;
+
+    async fn stop_search(&self, id: i64) -> ApiResult<()>;
+
+    /// Stops job `id` if needed and frees its results on the server.
+    async fn delete_search(&self, id: i64) -> ApiResult<()>;
+
+    /// Every RSS feed with its articles, folders flattened into the paths.
+    async fn get_rss_items(&self) -> ApiResult<Vec<RssFeed>>;
+
+    /// Subscribes to `url`, at `path` (a name, optionally inside folders).
+    async fn add_rss_feed(&self, url: &str, path: &str) -> ApiResult<()>;
+
+    /// Removes a feed or folder by its path.
+    async fn remove_rss_item(&self, path: &str) -> ApiResult<()>;
+
+    /// Asks the server to fetch a feed now; new articles show up in a later
+    /// `get_rss_items`.
+    async fn refresh_rss_item(&self, path: &str) -> ApiResult<()>;
+
+    async fn mark_rss_article_read(&self, path: &str, article_id: &str) -> ApiResult<()>;
+
+    /// Auto-downloading rules, sorted by name.
+    async fn get_rss_rules(&self) -> ApiResult<Vec<(String, RssRule)>>;
+
+    /// Creates or replaces the rule called `name`.
+    async fn set_rss_rule(&self, name: &str, rule: &RssRule) -> ApiResult<()>;
+
+    async fn remove_rss_rule(&self, name: &str) -> ApiResult<()>;
+
+    async fn get_default_save_path(&self) -> ApiResult<String>;
+
+    /// qBittorrent version of the server, e.g. "v4.6.2".
+    async fn get_app_version(&self) -> ApiResult<String>;
+
+    async fn get_build_info(&self) -> ApiResult<BuildInfo>;
+
+    /// Stops qBittorrent itself. The server goes away right after answering.
+    async fn shutdown_application(&self) -> ApiResult<()>;
+
+    async fn get_preferences(&self) -> ApiResult<Preferences>;
+
+    /// Changes the preferences named in `patch`, a JSON object; the server
+    /// leaves every other preference as it is.
+    async fn set_preferences(&self, patch: serde_json::Value) -> ApiResult<()>;
+
+    async fn get_global_download_limit(&self) -> ApiResult<i64>;
+
+    async fn get_global_upload_limit(&self) -> ApiResult<i64>;
+
+    async fn set_global_download_limit(&self, limit: i64) -> ApiResult<()>;
+
+    async fn set_global_upload_limit(&self, limit: i64) -> ApiResult<()>;
+
+    async fn toggle_alternative_speed_limits(&self) -> ApiResult<()>;
+}
+
+#[async_trait]
+impl TorrentApi for QBittorrentClient {
+    fn get_base_url(&self) -> &Url {
+        QBittorrentClient::get_base_url(self)
+    }
+
+    fn is_authenticated(&self) -> bool {
+        QBittorrentClient::is_authenticated(self)
+    }
+
+    fn is_reconnecting(&self) -> bool {
+        QBittorrentClient::is_reconnecting(self)
+    }
+
+    fn is_auth_bypassed(&self) -> bool {
+        QBittorrentClient::is_auth_bypassed(self)
+    }
+
+    async fn login_or_bypass(&self, credentials: Option<(&str, &str)>) -> ApiResult<()> {
+        QBittorrentClient::login_or_bypass(self, credentials).await
+    }
+
+    async fn sync_maindata(&self, rid: i64) -> ApiResult<MainData> {
+        QBittorrentClient::sync_maindata(self, rid).await
+    }
+
+    async fn pause_torrents(&self, hashes: &[String]) -> ApiResult<()> {
+        QBittorrentClient::pause_torrents(self, hashes).await
+    }
+
+    async fn resume_torrents(&self, hashes: &[String]) -> ApiResult<()> {
+        QBittorrentClient::resume_torrents(self, hashes).await
+    }
+
+    async fn pause_all(&self) -> ApiResult<()> {
+        QBittorrentClient::pause_all(self).await
+    }
+
+    async fn resume_all(&self) -> ApiResult<()> {
+        QBittorrentClient::resume_all(self).await
+    }
+
+    async fn set_force_start(&self, hashes: &[String], value: bool) -> ApiResult<()> {
+        QBittorrentClient::set_force_start(self, hashes, value).await
+    }
+
+    async fn delete_torrents(&self, hashes: &[String], delete_files: bool) -> ApiResult<()> {
+        QBittorrentClient::delete_torrents(self, hashes, delete_files).await
+    }
+
+    async fn recheck_torrents(&self, hashes: &[String]) -> ApiResult<()> {
+        QBittorrentClient::recheck_torrents(self, hashes).await
+    }
+
+    async fn set_category(&self, hashes: &[String], category: &str) -> ApiResult<()> {
+        QBittorrentClient::set_category(self, hashes, category).await
+    }
+
+    async fn add_tags(&self, hashes: &[String], tags: &str) -> ApiResult<()> {
+        QBittorrentClient::add_tags(self, hashes, tags).await
+    }
+
+    async fn set_auto_management(&self, hashes: &[String], enable: bool) -> ApiResult<()> {
+        QBittorrentClient::set_auto_management(self, hashes, enable).await
+    }
+
+    async fn set_share_limits(
+        &self,
+        hashes: &[String],
+        ratio_limit: f64,
+        seeding_time_limit: i64,
+        inactive_seeding_time_limit: i64,
+    ) -> ApiResult<()> {
+        QBittorrentClient::set_share_limits(
+            self,
+            hashes,
+            ratio_limit,
+            seeding_time_limit,
+            inactive_seeding_time_limit,
+        )
+        .await
+    }
+
+    async fn top_priority(&self, hashes: &[String]) -> ApiResult<()> {
+        QBittorrentClient::top_priority(self, hashes).await
+    }
+
+    async fn bottom_priority(&self, hashes: &[String]) -> ApiResult<()> {
+        QBittorrentClient::bottom_priority(self, hashes).await
+    }
+
+    async fn increase_priority(&self, hashes: &[String]) -> ApiResult<()> {
+        QBittorrentClient::increase_priority(self, hashes).await
+    }
+
+    async fn decrease_priority(&self, hashes: &[String]) -> ApiResult<()> {
+        QBittorrentClient::decrease_priority(self, hashes).await
+    }
+
+    async fn add_torrent(&self, torrent_data: &[u8], options: &AddTorrentOptions) -> ApiResult<()> {
+        QBittorrentClient::add_torrent(self, torrent_data, options).await
+    }
+
+    async fn add_magnet(&self, urls: &str, options: &AddTorrentOptions) -> ApiResult<()> {
+        QBittorrentClient::add_magnet(self, urls, options).await
+    }
+
+    async fn export_torrent(&self, hash: &str) -> ApiResult<Vec<u8>> {
+        QBittorrentClient::export_torrent(self, hash).await
+    }
+
+    async fn get_trackers(&self, hash: &str) -> ApiResult<Vec<Tracker>> {
+        QBittorrentClient::get_trackers(self, hash).await
+    }
+
+    async fn add_trackers(&self, hash: &str, urls: &[String]) -> ApiResult<()> {
+        QBittorrentClient::add_trackers(self, hash, urls).await
+    }
+
+    async fn remove_trackers(&self, hash: &str, urls: &[String]) -> ApiResult<()> {
+        QBittorrentClient::remove_trackers(self, hash, urls).await
+    }
+
+    async fn edit_tracker(&self, hash: &str, orig_url: &str, new_url: &str) -> ApiResult<()> {
+        QBittorrentClient::edit_tracker(self, hash, orig_url, new_url).await
+    }
+
+    async fn get_peers(&self, hash: &str) -> ApiResult<Vec<Peer>> {
+        QBittorrentClient::get_peers(self, hash).await
+    }
+
+    async fn ban_peers(&self, peers: &[String]) -> ApiResult<()> {
+        QBittorrentClient::ban_peers(self, peers).await
+    }
+
+    async fn add_peers(&self, hash: &str, peers: &[String]) -> ApiResult<()> {
+        QBittorrentClient::add_peers(self, hash, peers).await
+    }
+
+    async fn get_piece_states(&self, hash: &str) -> ApiResult<Vec<u8>> {
+        QBittorrentClient::get_piece_states(self, hash).await
+    }
+
+    async fn get_files(&self, hash: &str) -> ApiResult<Vec<TorrentFile>> {
+        QBittorrentClient::get_files(self, hash).await
+    }
+
+    async fn rename_file(&self, hash: &str, old_path: &str, new_path: &str) -> ApiResult<()> {
+        QBittorrentClient::rename_file(self, hash, old_path, new_path).await
+    }
+
+    async fn rename_folder(&self, hash: &str, old_path: &str, new_path: &str) -> ApiResult<()> {
+        QBittorrentClient::rename_folder(self, hash, old_path, new_path).await
+    }
+
+    async fn get_main_log(
+        &self,
+        normal: bool,
+        info: bool,
+        warning: bool,
+        critical: bool,
+        last_known_id: i64,
+    ) -> ApiResult<Vec<LogEntry>> {
+        QBittorrentClient::get_main_log(self, normal, info, warning, critical, last_known_id).await
+    }
+
+    async fn get_search_plugins(&self) -> ApiResult<Vec<SearchPlugin>> {
+        QBittorrentClient::get_search_plugins(self).await
+    }
+
+    async fn start_search(&self, pattern: &str, plugins: &str) -> ApiResult<i64> {
+        QBittorrentClient::start_search(self, pattern, plugins).await
+    }
+
+    async fn get_search_status(&self, id: i64) -> ApiResult<Option<SearchStatus>> {
+        QBittorrentClient::get_search_status(self, id).await
+    }
+
+    async fn get_search_results(&self, id: i64, offset: usize) -> ApiResult<SearchResults> {
+        QBittorrentClient::get_search_results(self, id, offset).await
+    }
+
+    async fn stop_search(&self, id: i64) -> ApiResult<()> {
+        QBittorrentClient::stop_search(self, id).await
+    }
+
+    async fn delete_search(&self, id: i64) -> ApiResult<()> {
+        QBittorrentClient::delete_search(self, id).await
+    }
+
+    async fn get_rss_items(&self) -> ApiResult<Vec<RssFeed>> {
+        QBittorrentClient::get_rss_items(self).await
+    }
+
+    async fn add_rss_feed(&self, url: &str, path: &str) -> ApiResult<()> {
+        QBittorrentClient::add_rss_feed(self, url, path).await
+    }
+
+    async fn remove_rss_item(&self, path: &str) -> ApiResult<()> {
+        QBittorrentClient::remove_rss_item(self, path).await
+    }
+
+    async fn refresh_rss_item(&self, path: &str) -> ApiResult<()> {
+        QBittorrentClient::refresh_rss_item(self, path).await
+    }
+
+    async fn mark_rss_article_read(&self, path: &str, article_id: &str) -> ApiResult<()> {
+        QBittorrentClient::mark_rss_article_read(self, path, article_id).await
+    }
+
+    async fn get_rss_rules(&self) -> ApiResult<Vec<(String, RssRule)>> {
+        QBittorrentClient::get_rss_rules(self).await
+    }
+
+    async fn set_rss_rule(&self, name: &str, rule: &RssRule) -> ApiResult<()> {
+        QBittorrentClient::set_rss_rule(self, name, rule).await
+    }
+
+    async fn remove_rss_rule(&self, name: &str) -> ApiResult<()> {
+        QBittorrentClient::remove_rss_rule(self, name).await
+    }
+
+    async fn get_default_save_path(&self) -> ApiResult<String> {
+        QBittorrentClient::get_default_save_path(self).await
+    }
+
+    async fn get_app_version(&self) -> ApiResult<String> {
+        QBittorrentClient::get_app_version(self).await
+    }
+
+    async fn get_build_info(&self) -> ApiResult<BuildInfo> {
+        QBittorrentClient::get_build_info(self).await
+    }
+
+    async fn shutdown_application(&self) -> ApiResult<()> {
+        QBittorrentClient::shutdown_application(self).await
+    }
+
+    async fn get_preferences(&self) -> ApiResult<Preferences> {
+        QBittorrentClient::get_preferences(self).await
+    }
+
+    async fn set_preferences(&self, patch: serde_json::Value) -> ApiResult<()> {
+        QBittorrentClient::set_preferences(self, patch).await
+    }
+
+    async fn get_global_download_limit(&self) -> ApiResult<i64> {
+        QBittorrentClient::get_global_download_limit(self).await
+    }
+
+    async fn get_global_upload_limit(&self) -> ApiResult<i64> {
+        QBittorrentClient::get_global_upload_limit(self).await
+    }
+
+    async fn set_global_download_limit(&self, limit: i64) -> ApiResult<()> {
+        QBittorrentClient::set_global_download_limit(self, limit).await
+    }
+
+    async fn set_global_upload_limit(&self, limit: i64) -> ApiResult<()> {
+        QBittorrentClient::set_global_upload_limit(self, limit).await
+    }
+
+    async fn toggle_alternative_speed_limits(&self) -> ApiResult<()> {
+        QBittorrentClient::toggle_alternative_speed_limits(self).await
+    }
+}
+
+#[cfg(test)]
+pub mod mock {
+    use super::*;
+    use crate::api::{ApiError, ErrorContext, Torrent};
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Stands in for the server in tests. Refreshes return `torrents`, and
+    /// every other call is recorded, with its arguments, and succeeds with
+    /// `preferences`, `peers` or nothing in it.
+    pub struct MockBackend {
+        base_url: Url,
+        pub torrents: Mutex<Vec<Torrent>>,
+        pub preferences: Mutex<Preferences>,
+        pub peers: Mutex<Vec<Peer>>,
+        /// Refuse every login, as with a wrong password
+        pub reject_login: AtomicBool,
+        /// Refuse every torrent added, as with a file that isn't one
+        pub reject_adds: AtomicBool,
+        authenticated: AtomicBool,
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl MockBackend {
+        pub fn new(torrents: Vec<Torrent>) -> Self {
+            Self {
+                base_url: Url::parse("http://mock.invalid:8080/").unwrap(),
+                torrents: Mutex::new(torrents),
+                preferences: Mutex::new(Preferences::default()),
+                peers: Mutex::new(Vec::new()),
+                reject_login: AtomicBool::new(false),
+                reject_adds: AtomicBool::new(false),
+                authenticated: AtomicBool::new(false),
+                calls: Mutex::new(Vec::new()),
+            }
+        }
+
+        /// The calls so far, like `delete_torrents(["abc"], true)`.
+        pub fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+
+        fn record(&self, call: String) {
+            self.calls.lock().unwrap().push(call);
+        }
+    }
+
+    #[async_trait]
+    impl TorrentApi for MockBackend {
+        fn get_base_url(&self) -> &Url {
+            &self.base_url
+        }
+
+        fn is_authenticated(&self) -> bool {
+            self.authenticated.load(Ordering::SeqCst)
+        }
+
+        fn is_reconnecting(&self) -> bool {
+            false
+        }
+
+        fn is_auth_bypassed(&self) -> bool {
+            false
+        }
+
+        async fn login_or_bypass(&self, credentials: Option<(&str, &str)>) -> ApiResult<()> {
+            self.record(format!("login_or_bypass({credentials:?})"));
+            if credentials.is_none() || self.reject_login.load(Ordering::SeqCst) {
+                return Err(ApiError::Unauthorized(ErrorContext {
+                    endpoint: "/api/v2/auth/login".to_string(),
+                    target: None,
+                    status: None,
+                    detail: "Invalid username or password".to_string(),
+                }));
+            }
+            self.authenticated.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn sync_maindata(&self, _rid: i64) -> ApiResult<MainData> {
+            let torrents: serde_json::Map<String, serde_json::Value> = self
+                .torrents
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|t| (t.hash.clone(), serde_json::to_value(t).unwrap()))
+                .collect();
+            Ok(serde_json::from_value(serde_json::json!({
+                "rid": 1,
+                "full_update": true,
+                "torrents": torrents,
+            }))
+            .unwrap())
+        }
+
+        async fn pause_torrents(&self, hashes: &[String]) -> ApiResult<()> {
+            self.record(format!("pause_torrents({hashes:?})"));
+            Ok(())
+        }
+
+        async fn resume_torrents(&self, hashes: &[String]) -> ApiResult<()> {
+            self.record(format!("resume_torrents({hashes:?})"));
+            Ok(())
+        }
+
+        async fn pause_all(&self) -> ApiResult<()> {
+            self.record("pause_all".to_string());
+            Ok(())
+        }
+
+        async fn resume_all(&self) -> ApiResult<()> {
+            self.record("resume_all".to_string());
+            Ok(())
+        }
+
+        async fn set_force_start(&self, hashes: &[String], value: bool) -> ApiResult<()> {
+            self.record(format!("set_force_start({hashes:?}, {value:?})"));
+            Ok(())
+        }
+
+        async fn delete_torrents(&self, hashes: &[String], delete_files: bool) -> ApiResult<()> {
+            self.record(format!("delete_torrents({hashes:?}, {delete_files:?})"));
+            Ok(())
+        }
+
+        async fn recheck_torrents(&self, hashes: &[String]) -> ApiResult<()> {
+            self.record(format!("recheck_torrents({hashes:?})"));
+            Ok(())
+        }
+
+        async fn set_category(&self, hashes: &[String], category: &str) -> ApiResult<()> {
+            self.record(format!("set_category({hashes:?}, {category:?})"));
+            Ok(())
+        }
+
+        async fn add_tags(&self, hashes: &[String], tags: &str) -> ApiResult<()> {
+            self.record(format!("add_tags({hashes:?}, {tags:?})"));
+            Ok(())
+        }
+
+        async fn set_auto_management(&self, hashes: &[String], enable: bool) -> ApiResult<()> {
+            self.record(format!("set_auto_management({hashes:?}, {enable:?})"));
+            Ok(())
+        }
+
+        async fn set_share_limits(
+            &self,
+            hashes: &[String],
+            ratio_limit: f64,
+            seeding_time_limit: i64,
+            inactive_seeding_time_limit: i64,
+        ) -> ApiResult<()> {
+            self.record(format!(
+                "set_share_limits({hashes:?}, {ratio_limit:?}, {seeding_time_limit:?}, {inactive_seeding_time_limit:?})"
+            ));
+            Ok(())
+        }
+
+        async fn top_priority(&self, hashes: &[String]) -> ApiResult<()> {
+            self.record(format!("top_priority({hashes:?})"));
+            Ok(())
+        }
+
+        async fn bottom_priority(&self, hashes: &[String]) -> ApiResult<()> {
+            self.record(format!("bottom_priority({hashes:?})"));
+            Ok(())
+        }
+
+        async fn increase_priority(&self, hashes: &[String]) -> ApiResult<()> {
+            self.record(format!("increase_priority({hashes:?})"));
+            Ok(())
+        }
+
+        async fn decrease_priority(&self, hashes: &[String]) -> ApiResult<()> {
+            self.record(format!("decrease_priority({hashes:?})"));
+            Ok(())
+        }
+
+        async fn add_torrent(
+            &self,
+            torrent_data: &[u8],
+            options: &AddTorrentOptions,
+        ) -> ApiResult<()> {
+            self.record(format!("add_torrent({torrent_data:?}, {options:?})"));
+            if self.reject_adds.load(Ordering::SeqCst) {
+                return Err(ApiError::Server(ErrorContext {
+                    endpoint: "/api/v2/torrents/add".to_string(),
+                    target: None,
+                    status: None,
+                    detail: "Torrent file is not valid".to_string(),
+                }));
+            }
+            Ok(())
+        }
+
+        async fn add_magnet(&self, urls: &str, options: &AddTorrentOptions) -> ApiResult<()> {
+            self.record(format!("add_magnet({urls:?}, {options:?})"));
+            Ok(())
+        }
+
+        async fn export_torrent(&self, hash: &str) -> ApiResult<Vec<u8>> {
+            self.record(format!("export_torrent({hash:?})"));
+            Ok(Default::default())
+        }
+
+        async fn get_trackers(&self, hash: &str) -> ApiResult<Vec<Tracker>> {
+            self.record(format!("get_trackers({hash:?})"));
+            Ok(Default::default())
+        }
+
+        async fn add_trackers(&self, hash: &str, urls: &[String]) -> ApiResult<()> {
+            self.record(format!("add_trackers({hash:?}, {urls:?})"));
+            Ok(())
+        }
+
+        async fn remove_trackers(&self, hash: &str, urls: &[String]) -> ApiResult<()> {
+            self.record(format!("remove_trackers({hash:?}, {urls:?})"));
+            Ok(())
+        }
+
+        async fn edit_tracker(&self, hash: &str, orig_url: &str, new_url: &str) -> ApiResult<()> {
+            self.record(format!("edit_tracker({hash:?}, {orig_url:?}, {new_url:?})"));
+            Ok(())
+        }
+
+        async fn get_peers(&self, hash: &str) -> ApiResult<Vec<Peer>> {
+            self.record(format!("get_peers({hash:?})"));
+            Ok(self.peers.lock().unwrap().clone())
+        }
+
+        async fn ban_peers(&self, peers: &[String]) -> ApiResult<()> {
+            self.record(format!("ban_peers({peers:?})"));
+            Ok(())
+        }
+
+        async fn add_peers(&self, hash: &str, peers: &[String]) -> ApiResult<()> {
+            self.record(format!("add_peers({hash:?}, {peers:?})"));
+            Ok(())
+        }
+
+        async fn get_piece_states(&self, hash: &str) -> ApiResult<Vec<u8>> {
+            self.record(format!("get_piece_states({hash:?})"));
+            Ok(Default::default())
+        }
+
+        async fn get_files(&self, hash: &str) -> ApiResult<Vec<TorrentFile>> {
+            self.record(format!("get_files({hash:?})"));
+            Ok(Default::default())
+        }
+
+        async fn rename_file(&self, hash: &str, old_path: &str, new_path: &str) -> ApiResult<()> {
+            self.record(format!("rename_file({hash:?}, {old_path:?}, {new_path:?})"));
+            Ok(())
+        }
+
+        async fn rename_folder(&self, hash: &str, old_path: &str, new_path: &str) -> ApiResult<()> {
+            self.record(format!(
+                "rename_folder({hash:?}, {old_path:?}, {new_path:?})"
+            ));
+            Ok(())
+        }
+
+        async fn get_main_log(
+            &self,
+            _normal: bool,
+            _info: bool,
+            _warning: bool,
+            _critical: bool,
+            last_known_id: i64,
+        ) -> ApiResult<Vec<LogEntry>> {
+            self.record(format!("get_main_log({last_known_id:?})"));
+            Ok(Vec::new())
+        }
+
+        async fn get_search_plugins(&self) -> ApiResult<Vec<SearchPlugin>> {
+            self.record("get_search_plugins".to_string());
+            Ok(Default::default())
+        }
+
+        async fn start_search(&self, pattern: &str, plugins: &str) -> ApiResult<i64> {
+            self.record(format!("start_search({pattern:?}, {plugins:?})"));
+            Ok(Default::default())
+        }
+
+        async fn get_search_status(&self, id: i64) -> ApiResult<Option<SearchStatus>> {
+            self.record(format!("get_search_status({id:?})"));
+            Ok(Default::default())
+        }
+
+        async fn get_search_results(&self, id: i64, offset: usize) -> ApiResult<SearchResults> {
+            self.record(format!("get_search_results({id:?}, {offset:?})"));
+            Ok(Default::default())
+        }
+
+        async fn stop_search(&self, id: i64) -> ApiResult<()> {
+            self.record(format!("stop_search({id:?})"));
+            Ok(())
+        }
+
+        async fn delete_search(&self, id: i64) -> ApiResult<()> {
+            self.record(format!("delete_search({id:?})"));
+            Ok(())
+        }
+
+        async fn get_rss_items(&self) -> ApiResult<Vec<RssFeed>> {
+            self.record("get_rss_items".to_string());
+            Ok(Default::default())
+        }
+
+        async fn add_rss_feed(&self, url: &str, path: &str) -> ApiResult<()> {
+            self.record(format!("add_rss_feed({url:?}, {path:?})"));
+            Ok(())
+        }
+
+        async fn remove_rss_item(&self, path: &str) -> ApiResult<()> {
+            self.record(format!("remove_rss_item({path:?})"));
+            Ok(())
+        }
+
+        async fn refresh_rss_item(&self, path: &str) -> ApiResult<()> {
+            self.record(format!("refresh_rss_item({path:?})"));
+            Ok(())
+        }
+
+        async fn mark_rss_article_read(&self, path: &str, article_id: &str) -> ApiResult<()> {
+            self.record(format!("mark_rss_article_read({path:?}, {article_id:?})"));
+            Ok(())
+        }
+
+        async fn get_rss_rules(&self) -> ApiResult<Vec<(String, RssRule)>> {
+            self.record("get_rss_rules".to_string());
+            Ok(Default::default())
+        }
+
+        async fn set_rss_rule(&self, name: &str, rule: &RssRule) -> ApiResult<()> {
+            self.record(format!("set_rss_rule({name:?}, {rule:?})"));
+            Ok(())
+        }
+
+        async fn remove_rss_rule(&self, name: &str) -> ApiResult<()> {
+            self.record(format!("remove_rss_rule({name:?})"));
+            Ok(())
+        }
+
+        async fn get_default_save_path(&self) -> ApiResult<String> {
+            self.record("get_default_save_path".to_string());
+            Ok(Default::default())
+        }
+
+        async fn get_app_version(&self) -> ApiResult<String> {
+            self.record("get_app_version".to_string());
+            Ok(Default::default())
+        }
+
+        async fn get_build_info(&self) -> ApiResult<BuildInfo> {
+            self.record("get_build_info".to_string());
+            Ok(Default::default())
+        }
+
+        async fn shutdown_application(&self) -> ApiResult<()> {
+            self.record("shutdown_application".to_string());
+            Ok(())
+        }
+
+        async fn get_preferences(&self) -> ApiResult<Preferences> {
+            self.record("get_preferences".to_string());
+            Ok(self.preferences.lock().unwrap().clone())
+        }
+
+        async fn set_preferences(&self, patch: serde_json::Value) -> ApiResult<()> {
+            self.record(format!("set_preferences({patch:?})"));
+            Ok(())
+        }
+
+        async fn get_global_download_limit(&self) -> ApiResult<i64> {
+            self.record("get_global_download_limit".to_string());
+            Ok(Default::default())
+        }
+
+        async fn get_global_upload_limit(&self) -> ApiResult<i64> {
+            self.record("get_global_upload_limit".to_string());
+            Ok(Default::default())
+        }
+
+        async fn set_global_download_limit(&self, limit: i64) -> ApiResult<()> {
+            self.record(format!("set_global_download_limit({limit:?})"));
+            Ok(())
+        }
+
+        async fn set_global_upload_limit(&self, limit: i64) -> ApiResult<()> {
+            self.record(format!("set_global_upload_limit({limit:?})"));
+            Ok(())
+        }
+
+        async fn toggle_alternative_speed_limits(&self) -> ApiResult<()> {
+            self.record("toggle_alternative_speed_limits".to_string());
+            Ok(())
+        }
+    }
+}
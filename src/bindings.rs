@@ -0,0 +1,246 @@
+use crate::keymap::{Action, Keymap};
+use crate::palette::PaletteEntry;
+use HelpContext::{Details, Dialogs, Main};
+
+/// Where a key binding applies; also the section it is listed under in help.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HelpContext {
+    Main,
+    Details,
+    Dialogs,
+}
+
+impl HelpContext {
+    pub const ALL: [HelpContext; 3] = [
+        HelpContext::Main,
+        HelpContext::Details,
+        HelpContext::Dialogs,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            HelpContext::Main => "Main list",
+            HelpContext::Details => "Detail view",
+            HelpContext::Dialogs => "Dialogs",
+        }
+    }
+}
+
+pub struct Binding {
+    pub context: HelpContext,
+    keys: &'static str,
+    /// Remappable binding; its keys come from the keymap instead of `keys`
+    action: Option<Action>,
+    pub description: &'static str,
+    /// Short label when the binding is also shown in the footer
+    pub footer: Option<&'static str>,
+}
+
+impl Binding {
+    pub fn keys(&self, keymap: &Keymap) -> String {
+        match self.action {
+            Some(action) => keymap.label(action),
+            None => self.keys.to_string(),
+        }
+    }
+}
+
+const fn bind(context: HelpContext, keys: &'static str, description: &'static str) -> Binding {
+    Binding {
+        context,
+        keys,
+        action: None,
+        description,
+        footer: None,
+    }
+}
+
+const fn action(action: Action, description: &'static str) -> Binding {
+    Binding {
+        context: Main,
+        keys: "",
+        action: Some(action),
+        description,
+        footer: None,
+    }
+}
+
+const fn footer(action: Action, description: &'static str, label: &'static str) -> Binding {
+    Binding {
+        context: Main,
+        keys: "",
+        action: Some(action),
+        description,
+        footer: Some(label),
+    }
+}
+
+/// Every key binding, in the order shown by the help popup. The footer is
+/// built from the same table so the two can't drift apart.
+pub const BINDINGS: &[Binding] = &[
+    footer(Action::Help, "Show this help", "Help"),
+    footer(Action::Quit, "Quit", "Quit"),
+    action(Action::CommandPalette, "Find and run any action by name"),
+    action(Action::MoveUp, "Move selection up"),
+    action(Action::MoveDown, "Move selection down"),
+    action(Action::PageUp, "Move up by a page"),
+    action(Action::PageDown, "Move down by a page"),
+    action(Action::Top, "Jump to the first torrent"),
+    action(Action::Bottom, "Jump to the last torrent"),
+    footer(
+        Action::Details,
+        "Show details of the selected torrent",
+        "Details",
+    ),
+    footer(Action::PauseResume, "Pause/resume", "Pause/Resume"),
+    action(Action::ForceStart, "Toggle force start"),
+    action(Action::PauseAll, "Pause all torrents"),
+    action(Action::ResumeAll, "Resume all torrents"),
+    footer(Action::Delete, "Delete", "Delete"),
+    action(Action::Recheck, "Recheck"),
+    action(Action::SetCategory, "Set category"),
+    action(Action::AddTags, "Add tags"),
+    action(Action::ToggleMark, "Mark/unmark for batch actions"),
+    action(Action::ClearMarks, "Clear marks, then the search filter"),
+    action(Action::QueueUp, "Move up in the queue"),
+    action(Action::QueueDown, "Move down in the queue"),
+    action(Action::QueueTop, "Move to the top of the queue"),
+    action(Action::QueueBottom, "Move to the bottom of the queue"),
+    footer(Action::Add, "Add a torrent", "Add"),
+    action(Action::Search, "Search"),
+    action(
+        Action::OnlineSearch,
+        "Find new torrents with qBittorrent's search plugins",
+    ),
+    action(Action::Refresh, "Refresh now"),
+    action(Action::RefreshSlower, "Refresh less often"),
+    action(
+        Action::RefreshFaster,
+        "Refresh more often (below 1s: manual only)",
+    ),
+    action(Action::SortNext, "Cycle sort column"),
+    action(Action::SortReverse, "Reverse sort order"),
+    action(Action::StatusFilter(0), "Show all torrents"),
+    action(Action::StatusFilter(1), "Show downloading"),
+    action(Action::StatusFilter(2), "Show seeding"),
+    action(Action::StatusFilter(3), "Show completed"),
+    action(Action::StatusFilter(4), "Show paused"),
+    action(Action::StatusFilter(5), "Show active"),
+    action(Action::StatusFilter(6), "Show errored"),
+    action(Action::StatusFilter(7), "Show stalled"),
+    action(Action::ToggleSidebar, "Show/hide the status sidebar"),
+    action(Action::FilterPane, "Open the category/tag pane"),
+    action(Action::FocusFilterPane, "Focus the category/tag pane"),
+    action(Action::AltSpeed, "Toggle alternative speed limits"),
+    action(Action::SpeedLimits, "Edit global speed limits"),
+    action(Action::Statistics, "Show transfer statistics"),
+    action(
+        Action::TestPort,
+        "Check whether the listening port is reachable",
+    ),
+    action(
+        Action::Copy,
+        "Copy the magnet link, hash, name or save path",
+    ),
+    action(Action::Columns, "Choose and order the list's columns"),
+    action(
+        Action::RowMode,
+        "Switch between one and two lines per torrent",
+    ),
+    action(Action::OpenPath, "Open the save folder"),
+    action(Action::OpenContent, "Open the downloaded file or folder"),
+    action(Action::Log, "Show the qBittorrent log"),
+    action(Action::Rss, "RSS feeds and auto-download rules"),
+    action(Action::Settings, "Edit qBittorrent preferences"),
+    action(Action::ShutdownServer, "Shut down qBittorrent"),
+    action(
+        Action::Cleanup,
+        "Clean up torrents that seeded enough, by ratio and seeding time",
+    ),
+    bind(Details, "Esc/Enter", "Close"),
+    bind(Details, "Ctrl+↑/↓", "Move up/down in the queue"),
+    bind(Details, "Ctrl+Home/End", "Move to top/bottom of the queue"),
+    bind(Details, "e", "Export the .torrent file"),
+    bind(
+        Details,
+        "Tab",
+        "Switch between the General, Trackers and Files tabs",
+    ),
+    bind(Details, "a", "Toggle automatic torrent management"),
+    bind(Details, "s", "Edit share limits"),
+    bind(Details, "a/e/d (trackers)", "Add, edit or remove trackers"),
+    bind(
+        Details,
+        "F2 (files)",
+        "Rename the highlighted file or folder",
+    ),
+    bind(
+        Dialogs,
+        "1-4 (log)",
+        "Show/hide normal, info, warning, critical",
+    ),
+    bind(Dialogs, "End (log)", "Follow new messages"),
+    bind(Dialogs, "a/d/r (RSS)", "Add, remove or refresh a feed"),
+    bind(Dialogs, "u (RSS)", "Show auto-download rules"),
+    bind(Dialogs, "s (settings)", "Save changed preferences"),
+    bind(Dialogs, "Enter", "Confirm / submit"),
+    bind(Dialogs, "Esc", "Cancel"),
+    bind(Dialogs, "y / n", "Answer a confirmation"),
+    bind(
+        Dialogs,
+        "Y (capital)",
+        "Delete torrents together with their files",
+    ),
+    bind(Dialogs, "Tab / ↑↓", "Switch field in forms"),
+    bind(Dialogs, "←/→ Home/End", "Move the cursor in a text field"),
+    bind(Dialogs, "Ctrl+←/→", "Move the cursor by a word"),
+    bind(Dialogs, "Ctrl+W", "Delete the previous word"),
+    bind(Dialogs, "Ctrl+U", "Clear the text field"),
+    bind(Dialogs, "Tab", "Complete the torrent file path"),
+    bind(Dialogs, "Ctrl+O", "Browse for a .torrent file"),
+    bind(Dialogs, "←/→", "Change category in the add dialog"),
+    bind(Dialogs, "Space", "Toggle start paused in the add dialog"),
+    bind(
+        Dialogs,
+        "Backspace",
+        "Go to the parent folder in the file browser",
+    ),
+    bind(Dialogs, "Ctrl+H", "Show/hide password on the login screen"),
+    bind(
+        Dialogs,
+        "Ctrl+D",
+        "Forget the saved password on the login screen",
+    ),
+    bind(
+        Dialogs,
+        "e / d",
+        "Edit / delete the highlighted server profile",
+    ),
+];
+
+/// Every remappable action for the command palette, described as in help.
+pub fn palette_entries(keymap: &Keymap) -> Vec<PaletteEntry<Action>> {
+    BINDINGS
+        .iter()
+        .filter_map(|binding| {
+            let action = binding.action?;
+            (action != Action::CommandPalette).then(|| PaletteEntry {
+                item: action,
+                label: binding.description.to_string(),
+                keys: binding.keys(keymap),
+            })
+        })
+        .collect()
+}
+
+pub fn footer_text(keymap: &Keymap) -> String {
+    BINDINGS
+        .iter()
+        .filter_map(|binding| {
+            binding
+                .footer
+                .map(|label| format!("{}: {label}", binding.keys(keymap)))
+        })
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
@@ -0,0 +1,347 @@
+use crate::api::Torrent;
+use crate::text_input::TextInput;
+use crossterm::event::{KeyCode, KeyEvent};
+use std::collections::HashSet;
+
+/// Which finished torrents the cleanup popup looks at.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum CleanupStates {
+    #[default]
+    SeedingOrPaused,
+    Seeding,
+    Paused,
+}
+
+impl CleanupStates {
+    pub fn label(self) -> &'static str {
+        match self {
+            CleanupStates::SeedingOrPaused => "Seeding or paused",
+            CleanupStates::Seeding => "Seeding",
+            CleanupStates::Paused => "Paused",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            CleanupStates::SeedingOrPaused => CleanupStates::Seeding,
+            CleanupStates::Seeding => CleanupStates::Paused,
+            CleanupStates::Paused => CleanupStates::SeedingOrPaused,
+        }
+    }
+
+    fn matches(self, torrent: &Torrent) -> bool {
+        let state = &torrent.state;
+        let seeding = state.is_seeding();
+        let paused = state.is_paused() && state.is_complete();
+        match self {
+            CleanupStates::SeedingOrPaused => seeding || paused,
+            CleanupStates::Seeding => seeding,
+            CleanupStates::Paused => paused,
+        }
+    }
+}
+
+/// What a finished torrent has to have reached to be offered for deletion.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CleanupCriteria {
+    pub min_ratio: f64,
+    pub min_seeding_secs: i64,
+    pub states: CleanupStates,
+}
+
+impl CleanupCriteria {
+    pub fn matches(&self, torrent: &Torrent, now: i64) -> bool {
+        self.states.matches(torrent)
+            && torrent.ratio.unwrap_or(0.0) >= self.min_ratio
+            && seeding_secs(torrent, now) >= self.min_seeding_secs
+    }
+}
+
+/// Seconds the torrent has seeded. Servers that don't report it get the
+/// time since it completed, which overcounts time spent paused.
+pub fn seeding_secs(torrent: &Torrent, now: i64) -> i64 {
+    torrent
+        .seeding_time
+        .or_else(|| {
+            torrent
+                .completion_on
+                .filter(|&completed| completed > 0)
+                .map(|completed| now - completed)
+        })
+        .unwrap_or(0)
+        .max(0)
+}
+
+pub fn parse_min_ratio(text: &str) -> Result<f64, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(0.0);
+    }
+    match text.parse::<f64>() {
+        Ok(ratio) if ratio.is_finite() && ratio >= 0.0 => Ok(ratio),
+        _ => Err(format!(
+            "Ratio must be a number of 0 or more, not \"{text}\""
+        )),
+    }
+}
+
+/// Seconds in "90m", "36h", "2w" or a plain number of days.
+pub fn parse_seeding_time(text: &str) -> Result<i64, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(0);
+    }
+    let (number, unit) = match text.char_indices().last() {
+        Some((i, c)) if c.is_ascii_alphabetic() => (&text[..i], c.to_ascii_lowercase()),
+        _ => (text, 'd'),
+    };
+    let unit_secs = match unit {
+        'm' => 60,
+        'h' => 3_600,
+        'd' => 86_400,
+        'w' => 604_800,
+        _ => 0,
+    };
+    match number.trim().parse::<i64>() {
+        Ok(count) if count >= 0 && unit_secs > 0 => Ok(count.saturating_mul(unit_secs)),
+        _ => Err(format!(
+            "Seeding time is a number of days, or ends in m, h, d or w, not \"{text}\""
+        )),
+    }
+}
+
+/// Part of the cleanup popup that has the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CleanupFocus {
+    Ratio,
+    SeedingTime,
+    States,
+    Matches,
+}
+
+/// The cleanup popup: criteria at the top, the torrents meeting them below.
+/// Nothing is selected until picked, so opening it and pressing Enter
+/// deletes nothing.
+#[derive(Debug, Clone)]
+pub struct CleanupView {
+    pub ratio: TextInput,
+    pub seeding_time: TextInput,
+    pub states: CleanupStates,
+    pub focus: CleanupFocus,
+    /// Hashes of the matches picked for deletion
+    pub selected: HashSet<String>,
+    /// Highlighted row of the matches
+    pub cursor: usize,
+    /// Why Enter did nothing
+    pub error: Option<String>,
+}
+
+impl Default for CleanupView {
+    fn default() -> Self {
+        Self {
+            ratio: TextInput::new("2"),
+            seeding_time: TextInput::default(),
+            states: CleanupStates::default(),
+            focus: CleanupFocus::Ratio,
+            selected: HashSet::new(),
+            cursor: 0,
+            error: None,
+        }
+    }
+}
+
+impl CleanupView {
+    pub fn criteria(&self) -> Result<CleanupCriteria, String> {
+        Ok(CleanupCriteria {
+            min_ratio: parse_min_ratio(self.ratio.value())?,
+            min_seeding_secs: parse_seeding_time(self.seeding_time.value())?,
+            states: self.states,
+        })
+    }
+
+    /// The torrents meeting the criteria, largest first; none while they
+    /// don't parse.
+    pub fn matches<'a>(&self, torrents: &'a [Torrent], now: i64) -> Vec<&'a Torrent> {
+        let Ok(criteria) = self.criteria() else {
+            return Vec::new();
+        };
+        let mut matches: Vec<&Torrent> = torrents
+            .iter()
+            .filter(|torrent| criteria.matches(torrent, now))
+            .collect();
+        matches.sort_by_key(|torrent| std::cmp::Reverse(torrent.size));
+        matches
+    }
+
+    /// The picked torrents that still match.
+    pub fn chosen<'a>(&self, torrents: &'a [Torrent], now: i64) -> Vec<&'a Torrent> {
+        self.matches(torrents, now)
+            .into_iter()
+            .filter(|torrent| self.selected.contains(&torrent.hash))
+            .collect()
+    }
+
+    /// Edits the criteria, or moves through and picks from the matches.
+    /// Enter and Esc are left to the caller.
+    pub fn handle_key(&mut self, key: KeyEvent, torrents: &[Torrent], now: i64) {
+        self.error = None;
+        match key.code {
+            KeyCode::Tab => self.cycle_focus(1),
+            KeyCode::BackTab => self.cycle_focus(-1),
+            _ => match self.focus {
+                CleanupFocus::Ratio => {
+                    self.ratio.handle_key(key);
+                }
+                CleanupFocus::SeedingTime => {
+                    self.seeding_time.handle_key(key);
+                }
+                CleanupFocus::States => {
+                    if matches!(
+                        key.code,
+                        KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right
+                    ) {
+                        self.states = self.states.next();
+                    }
+                }
+                CleanupFocus::Matches => self.handle_matches_key(key, torrents, now),
+            },
+        }
+        let count = self.matches(torrents, now).len();
+        self.cursor = self.cursor.min(count.saturating_sub(1));
+    }
+
+    fn handle_matches_key(&mut self, key: KeyEvent, torrents: &[Torrent], now: i64) {
+        let matches = self.matches(torrents, now);
+        match key.code {
+            KeyCode::Up => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Down => {
+                self.cursor = (self.cursor + 1).min(matches.len().saturating_sub(1));
+            }
+            KeyCode::Char(' ') => {
+                if let Some(torrent) = matches.get(self.cursor)
+                    && !self.selected.remove(&torrent.hash)
+                {
+                    self.selected.insert(torrent.hash.clone());
+                }
+            }
+            // Picks every match, or drops them all when they already are
+            KeyCode::Char('a') => {
+                let all = matches.iter().all(|t| self.selected.contains(&t.hash));
+                for torrent in matches {
+                    if all {
+                        self.selected.remove(&torrent.hash);
+                    } else {
+                        self.selected.insert(torrent.hash.clone());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn cycle_focus(&mut self, delta: isize) {
+        const ORDER: [CleanupFocus; 4] = [
+            CleanupFocus::Ratio,
+            CleanupFocus::SeedingTime,
+            CleanupFocus::States,
+            CleanupFocus::Matches,
+        ];
+        let current = ORDER.iter().position(|&f| f == self.focus).unwrap_or(0);
+        let next = (current as isize + delta).rem_euclid(ORDER.len() as isize) as usize;
+        self.focus = ORDER[next];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::torrent_state::TorrentState;
+    use crossterm::event::KeyModifiers;
+
+    const DAY: i64 = 86_400;
+
+    fn finished(hash: &str, state: TorrentState, ratio: f64, seeded_days: i64) -> Torrent {
+        Torrent {
+            hash: hash.to_string(),
+            state,
+            ratio: Some(ratio),
+            seeding_time: Some(seeded_days * DAY),
+            size: ratio as i64,
+            ..Torrent::default()
+        }
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn seeding_times_take_units_and_default_to_days() {
+        assert_eq!(parse_seeding_time(""), Ok(0));
+        assert_eq!(parse_seeding_time("7"), Ok(7 * DAY));
+        assert_eq!(parse_seeding_time("36h"), Ok(36 * 3_600));
+        assert_eq!(parse_seeding_time(" 2W "), Ok(14 * DAY));
+        assert!(parse_seeding_time("3y").is_err());
+        assert!(parse_seeding_time("-1d").is_err());
+        assert_eq!(parse_min_ratio(""), Ok(0.0));
+        assert!(parse_min_ratio("lots").is_err());
+    }
+
+    #[test]
+    fn criteria_need_the_ratio_the_time_and_a_finished_state() {
+        let criteria = CleanupCriteria {
+            min_ratio: 2.0,
+            min_seeding_secs: 7 * DAY,
+            states: CleanupStates::SeedingOrPaused,
+        };
+        let now = 100 * DAY;
+        assert!(criteria.matches(&finished("a", TorrentState::Uploading, 2.0, 7), now));
+        assert!(criteria.matches(&finished("b", TorrentState::StoppedUp, 3.0, 9), now));
+        assert!(!criteria.matches(&finished("c", TorrentState::Uploading, 1.9, 30), now));
+        assert!(!criteria.matches(&finished("d", TorrentState::Uploading, 5.0, 6), now));
+        assert!(!criteria.matches(&finished("e", TorrentState::PausedDl, 5.0, 30), now));
+
+        // Without a seeding time the completion date stands in
+        let older = Torrent {
+            seeding_time: None,
+            completion_on: Some(now - 8 * DAY),
+            ..finished("f", TorrentState::StalledUp, 2.5, 0)
+        };
+        assert!(criteria.matches(&older, now));
+        let paused_only = CleanupCriteria {
+            states: CleanupStates::Paused,
+            ..criteria
+        };
+        assert!(!paused_only.matches(&older, now));
+    }
+
+    #[test]
+    fn nothing_is_chosen_until_picked() {
+        let torrents = [
+            finished("small", TorrentState::Uploading, 2.0, 1),
+            finished("big", TorrentState::Uploading, 4.0, 1),
+            finished("low", TorrentState::Uploading, 1.0, 1),
+        ];
+        let mut view = CleanupView::default();
+        let hashes = |list: Vec<&Torrent>| -> Vec<String> {
+            list.iter().map(|torrent| torrent.hash.clone()).collect()
+        };
+        assert_eq!(hashes(view.matches(&torrents, 0)), ["big", "small"]);
+        assert!(view.chosen(&torrents, 0).is_empty());
+
+        view.focus = CleanupFocus::Matches;
+        view.handle_key(key(KeyCode::Down), &torrents, 0);
+        view.handle_key(key(KeyCode::Char(' ')), &torrents, 0);
+        assert_eq!(hashes(view.chosen(&torrents, 0)), ["small"]);
+        view.handle_key(key(KeyCode::Char('a')), &torrents, 0);
+        assert_eq!(hashes(view.chosen(&torrents, 0)), ["big", "small"]);
+
+        // Raising the ratio leaves the picked torrent that no longer matches out
+        view.handle_key(key(KeyCode::Tab), &torrents, 0);
+        view.ratio.set("3");
+        assert_eq!(hashes(view.chosen(&torrents, 0)), ["big"]);
+        view.ratio.set("x");
+        assert!(view.criteria().is_err());
+        assert!(view.matches(&torrents, 0).is_empty());
+    }
+}
@@ -0,0 +1,271 @@
+use crate::api::{AddTorrentOptions, ApiError, QBittorrentClient, Torrent};
+use crate::app::is_torrent_url;
+use crate::completion::expand_tilde;
+use crate::utils::{fit_to_width, truncate_to_width};
+use anyhow::{Context, Result, bail};
+use clap::Subcommand;
+use humansize::{BINARY, format_size};
+
+/// Environment variable read for the password when `--password` is not given.
+pub const PASSWORD_ENV: &str = "QBITTUI_PASSWORD";
+
+/// One-shot commands that talk to the server and exit without starting the
+/// TUI.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Print the torrent list
+    List {
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Add a torrent from a .torrent file, magnet link or URL
+    Add {
+        /// Path to a .torrent file, or a magnet link or URL
+        source: String,
+        /// Where to save the downloaded files
+        #[arg(long)]
+        save_path: Option<String>,
+        /// Category to put the torrent in
+        #[arg(long)]
+        category: Option<String>,
+        /// Add the torrent paused
+        #[arg(long)]
+        paused: bool,
+    },
+    /// Pause torrents by hash or by part of their name
+    Pause {
+        #[arg(required = true)]
+        torrents: Vec<String>,
+    },
+    /// Resume torrents by hash or by part of their name
+    Resume {
+        #[arg(required = true)]
+        torrents: Vec<String>,
+    },
+    /// Delete torrents by hash or by part of their name
+    Delete {
+        #[arg(required = true)]
+        torrents: Vec<String>,
+        /// Also delete the downloaded files
+        #[arg(long)]
+        with_files: bool,
+        /// Delete every torrent a name matches instead of refusing when there
+        /// is more than one
+        #[arg(long)]
+        all: bool,
+    },
+}
+
+/// Logs in and runs `command`, printing results to stdout.
+pub async fn run(
+    command: Command,
+    client: &QBittorrentClient,
+    username: &str,
+    password: &str,
+) -> Result<()> {
+    if let Err(e) = client.login(username, password).await {
+        if let ApiError::Certificate(context, _) = &e {
+            bail!(
+                "could not log in to {}: the HTTPS certificate isn't trusted ({}); \
+                 pass --ca-cert <file> to trust it or --insecure to skip verification",
+                client.get_base_url(),
+                context.detail
+            );
+        }
+        let url = client.get_base_url();
+        return Err(anyhow::Error::from(e).context(format!("could not log in to {url}")));
+    }
+
+    match command {
+        Command::List { json } => {
+            let mut torrents = client.get_torrents().await?;
+            torrents.sort_by_key(|torrent| torrent.name.to_lowercase());
+            if json {
+                println!("{}", serde_json::to_string_pretty(&torrents)?);
+            } else {
+                print!("{}", format_table(&torrents));
+            }
+        }
+        Command::Add {
+            source,
+            save_path,
+            category,
+            paused,
+        } => {
+            let options = AddTorrentOptions {
+                save_path,
+                category,
+                paused,
+                ..AddTorrentOptions::default()
+            };
+            if is_torrent_url(&source) {
+                client.add_magnet(&source, &options).await?;
+            } else {
+                let path = expand_tilde(&source);
+                let data = std::fs::read(&path)
+                    .with_context(|| format!("could not read {}", path.display()))?;
+                client.add_torrent(&data, &options).await?;
+            }
+            println!("Added {source}");
+        }
+        Command::Pause { torrents } => {
+            let matched = resolve(client, &torrents, true).await?;
+            client.pause_torrents(&hashes(&matched)).await?;
+            report("Paused", &matched);
+        }
+        Command::Resume { torrents } => {
+            let matched = resolve(client, &torrents, true).await?;
+            client.resume_torrents(&hashes(&matched)).await?;
+            report("Resumed", &matched);
+        }
+        Command::Delete {
+            torrents,
+            with_files,
+            all,
+        } => {
+            let matched = resolve(client, &torrents, all).await?;
+            client
+                .delete_torrents(&hashes(&matched), with_files)
+                .await?;
+            report("Deleted", &matched);
+        }
+    }
+    Ok(())
+}
+
+async fn resolve(
+    client: &QBittorrentClient,
+    patterns: &[String],
+    allow_many: bool,
+) -> Result<Vec<Torrent>> {
+    let torrents = client.get_torrents().await?;
+    let mut matched: Vec<Torrent> = Vec::new();
+    for pattern in patterns {
+        let found = find_matches(&torrents, pattern);
+        if found.is_empty() {
+            bail!("no torrent matches \"{pattern}\"");
+        }
+        if found.len() > 1 && !allow_many {
+            let names: Vec<&str> = found.iter().map(|t| t.name.as_str()).collect();
+            bail!(
+                "\"{pattern}\" matches {} torrents ({}); use a hash or --all",
+                found.len(),
+                names.join(", ")
+            );
+        }
+        for torrent in found {
+            if !matched.iter().any(|t| t.hash == torrent.hash) {
+                matched.push(torrent.clone());
+            }
+        }
+    }
+    Ok(matched)
+}
+
+/// Torrents whose hash equals `pattern`, or failing that, whose name
+/// contains it. Both comparisons ignore case.
+fn find_matches<'a>(torrents: &'a [Torrent], pattern: &str) -> Vec<&'a Torrent> {
+    let pattern = pattern.to_lowercase();
+    if let Some(torrent) = torrents.iter().find(|t| t.hash.to_lowercase() == pattern) {
+        return vec![torrent];
+    }
+    torrents
+        .iter()
+        .filter(|t| t.name.to_lowercase().contains(&pattern))
+        .collect()
+}
+
+fn hashes(torrents: &[Torrent]) -> Vec<String> {
+    torrents.iter().map(|t| t.hash.clone()).collect()
+}
+
+fn report(done: &str, torrents: &[Torrent]) {
+    for torrent in torrents {
+        println!("{done} {}", torrent.name);
+    }
+}
+
+const NAME_WIDTH: usize = 40;
+
+fn format_table(torrents: &[Torrent]) -> String {
+    let row = |cells: [&str; 7]| {
+        format!(
+            "{}  {}  {}  {}  {}  {}  {}\n",
+            fit_to_width(cells[0], 8, false),
+            fit_to_width(cells[1], NAME_WIDTH, false),
+            fit_to_width(cells[2], 10, true),
+            fit_to_width(cells[3], 5, true),
+            fit_to_width(cells[4], 12, false),
+            fit_to_width(cells[5], 12, true),
+            fit_to_width(cells[6], 12, true),
+        )
+    };
+    let speed = |bytes: i64| format_size(bytes.max(0) as u64, BINARY) + "/s";
+
+    let mut table = row(["HASH", "NAME", "SIZE", "DONE", "STATE", "DOWN", "UP"]);
+    for torrent in torrents {
+        table.push_str(&row([
+            &torrent.hash.chars().take(8).collect::<String>(),
+            &truncate_to_width(&torrent.name, NAME_WIDTH),
+            &format_size(torrent.size.max(0) as u64, BINARY),
+            &format!("{}%", (torrent.progress * 100.0) as u8),
+            torrent.state.as_str(),
+            &speed(torrent.dlspeed),
+            &speed(torrent.upspeed),
+        ]));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::torrent_state::TorrentState;
+
+    fn torrent(hash: &str, name: &str) -> Torrent {
+        Torrent {
+            hash: hash.to_string(),
+            name: name.to_string(),
+            state: TorrentState::PausedDl,
+            ..Torrent::default()
+        }
+    }
+
+    #[test]
+    fn matches_by_hash_before_name() {
+        let torrents = [
+            torrent("abc123", "Ubuntu 24.04"),
+            torrent("def456", "ubuntu server abc123"),
+            torrent("0918ff", "Debian"),
+        ];
+        let names = |pattern| {
+            find_matches(&torrents, pattern)
+                .iter()
+                .map(|t| t.name.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(names("ABC123"), ["Ubuntu 24.04"]);
+        assert_eq!(names("UBUNTU"), ["Ubuntu 24.04", "ubuntu server abc123"]);
+        assert!(names("arch").is_empty());
+    }
+
+    #[test]
+    fn table_lines_up_columns() {
+        let table = format_table(&[torrent(
+            "0123456789abcdef",
+            "A very long name ".repeat(4).as_str(),
+        )]);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("01234567  A very long name"));
+        // Right-aligned columns end where their header ends
+        assert_eq!(
+            lines[0].find("SIZE").unwrap() + 4,
+            lines[1].find("0 B").unwrap() + 3
+        );
+        assert!(lines[1].contains("..."));
+    }
+}
@@ -0,0 +1,180 @@
+use crate::api::Torrent;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use std::env;
+use std::io::Write;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// How long a copy tool gets before the terminal is asked instead, since the
+/// copy runs between key presses.
+const TOOL_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// A copy command and its arguments, such as `xclip -selection clipboard`.
+pub type CopyTool = &'static [&'static str];
+
+/// What of the selected torrent the copy menu puts on the clipboard.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CopyField {
+    Magnet,
+    Hash,
+    Name,
+    SavePath,
+}
+
+impl CopyField {
+    /// Each choice with the key that picks it, in menu order.
+    pub const MENU: [(char, CopyField); 4] = [
+        ('m', CopyField::Magnet),
+        ('h', CopyField::Hash),
+        ('n', CopyField::Name),
+        ('p', CopyField::SavePath),
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CopyField::Magnet => "magnet link",
+            CopyField::Hash => "hash",
+            CopyField::Name => "name",
+            CopyField::SavePath => "save path",
+        }
+    }
+
+    /// The text copied for `torrent`, `None` when the server didn't say.
+    pub fn value(self, torrent: &Torrent) -> Option<String> {
+        match self {
+            CopyField::Magnet => Some(magnet_link(torrent)),
+            CopyField::Hash => Some(torrent.hash.clone()),
+            CopyField::Name => Some(torrent.name.clone()),
+            CopyField::SavePath => torrent.save_path.clone().filter(|path| !path.is_empty()),
+        }
+    }
+}
+
+/// The magnet link the server reports, or one built from the hash, name and
+/// current tracker for servers that don't.
+pub fn magnet_link(torrent: &Torrent) -> String {
+    if let Some(uri) = torrent.magnet_uri.as_ref().filter(|uri| !uri.is_empty()) {
+        return uri.clone();
+    }
+    let mut link = format!(
+        "magnet:?xt=urn:btih:{}&dn={}",
+        torrent.hash,
+        utf8_percent_encode(&torrent.name, NON_ALPHANUMERIC)
+    );
+    if let Some(tracker) = torrent.tracker.as_ref().filter(|t| !t.is_empty()) {
+        link.push_str("&tr=");
+        link.extend(utf8_percent_encode(tracker, NON_ALPHANUMERIC));
+    }
+    link
+}
+
+/// Puts `text` on the clipboard with the first of `tools` that works. Over
+/// SSH, or when none does, the OSC 52 sequence that asks the terminal to do
+/// it is returned instead, for the caller to write out.
+pub fn copy(text: &str, tools: &[CopyTool]) -> Option<String> {
+    let over_ssh = env::var_os("SSH_CONNECTION").is_some() || env::var_os("SSH_TTY").is_some();
+    if !over_ssh && tools.iter().any(|tool| run_tool(tool, text)) {
+        return None;
+    }
+    Some(osc52(text))
+}
+
+/// Escape sequence setting the terminal's clipboard to `text`.
+pub fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", STANDARD.encode(text))
+}
+
+/// The platform's copy tools, most likely to work first.
+pub fn copy_tools() -> Vec<CopyTool> {
+    if cfg!(target_os = "macos") {
+        vec![&["pbcopy"]]
+    } else if cfg!(windows) {
+        vec![&["clip"]]
+    } else {
+        let mut tools: Vec<CopyTool> = Vec::new();
+        if env::var_os("WAYLAND_DISPLAY").is_some() {
+            tools.push(&["wl-copy"]);
+        }
+        if env::var_os("DISPLAY").is_some() {
+            tools.push(&["xclip", "-selection", "clipboard"]);
+            tools.push(&["xsel", "--clipboard", "--input"]);
+        }
+        tools
+    }
+}
+
+fn run_tool(tool: &[&str], text: &str) -> bool {
+    let Ok(mut child) = Command::new(tool[0])
+        .args(&tool[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+    else {
+        return false;
+    };
+    // Dropping stdin closes it, which tells the tool the text is complete
+    let written = child
+        .stdin
+        .take()
+        .is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+    written && wait_for(&mut child, TOOL_TIMEOUT)
+}
+
+// Whether `child` exits successfully within `timeout`; one that doesn't is
+// killed rather than left to hold up the screen
+fn wait_for(child: &mut Child, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return status.success(),
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(5)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn magnet_links_are_built_when_the_server_has_none() {
+        let mut torrent = Torrent {
+            hash: "c9e15763f722f23e98a29decdfae341b98d53056".to_string(),
+            name: "Debian 12 & more".to_string(),
+            tracker: Some("udp://tracker.example:6969/announce".to_string()),
+            ..Torrent::default()
+        };
+        assert_eq!(
+            magnet_link(&torrent),
+            "magnet:?xt=urn:btih:c9e15763f722f23e98a29decdfae341b98d53056\
+             &dn=Debian%2012%20%26%20more\
+             &tr=udp%3A%2F%2Ftracker%2Eexample%3A6969%2Fannounce"
+        );
+
+        torrent.magnet_uri = Some("magnet:?xt=urn:btih:abc".to_string());
+        assert_eq!(magnet_link(&torrent), "magnet:?xt=urn:btih:abc");
+        assert_eq!(CopyField::SavePath.value(&torrent), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stuck_tools_are_given_up_on() {
+        assert!(run_tool(&["cat"], "hash"));
+        assert!(!run_tool(&["false"], "hash"));
+        // It would have succeeded given the time
+        assert!(!run_tool(&["sleep", "5"], "hash"));
+    }
+
+    #[test]
+    fn osc52_carries_the_text_in_base64() {
+        assert_eq!(osc52("hash"), "\x1b]52;c;aGFzaA==\x07");
+    }
+}
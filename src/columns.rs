@@ -0,0 +1,206 @@
+use crate::app::SortColumn;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+/// A column the torrent list can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListColumn {
+    Name,
+    Progress,
+    Size,
+    DlSpeed,
+    UpSpeed,
+    State,
+    Eta,
+    Ratio,
+    Seeds,
+    Peers,
+    Added,
+    Completed,
+    Category,
+}
+
+impl ListColumn {
+    /// Every column, in the order the chooser lists hidden ones.
+    pub const ALL: [ListColumn; 13] = [
+        ListColumn::Name,
+        ListColumn::Progress,
+        ListColumn::Size,
+        ListColumn::DlSpeed,
+        ListColumn::UpSpeed,
+        ListColumn::State,
+        ListColumn::Eta,
+        ListColumn::Ratio,
+        ListColumn::Seeds,
+        ListColumn::Peers,
+        ListColumn::Added,
+        ListColumn::Completed,
+        ListColumn::Category,
+    ];
+
+    /// Shown until the chooser saves something else.
+    pub const DEFAULT: [ListColumn; 12] = [
+        ListColumn::Name,
+        ListColumn::Progress,
+        ListColumn::Size,
+        ListColumn::DlSpeed,
+        ListColumn::UpSpeed,
+        ListColumn::State,
+        ListColumn::Eta,
+        ListColumn::Ratio,
+        ListColumn::Seeds,
+        ListColumn::Peers,
+        ListColumn::Added,
+        ListColumn::Completed,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            ListColumn::Name => "Name",
+            ListColumn::Progress => "Progress",
+            ListColumn::Size => "Size",
+            ListColumn::DlSpeed => "Down Speed",
+            ListColumn::UpSpeed => "Up Speed",
+            ListColumn::State => "State",
+            ListColumn::Eta => "ETA",
+            ListColumn::Ratio => "Ratio",
+            ListColumn::Seeds => "Seeds",
+            ListColumn::Peers => "Peers",
+            ListColumn::Added => "Added",
+            ListColumn::Completed => "Completed",
+            ListColumn::Category => "Category",
+        }
+    }
+
+    pub fn sort_column(self) -> Option<SortColumn> {
+        match self {
+            ListColumn::Name => Some(SortColumn::Name),
+            ListColumn::Progress => Some(SortColumn::Progress),
+            ListColumn::Size => Some(SortColumn::Size),
+            ListColumn::DlSpeed => Some(SortColumn::DlSpeed),
+            ListColumn::UpSpeed => Some(SortColumn::UpSpeed),
+            ListColumn::State => Some(SortColumn::State),
+            ListColumn::Eta => Some(SortColumn::Eta),
+            ListColumn::Ratio => Some(SortColumn::Ratio),
+            ListColumn::Added => Some(SortColumn::AddedOn),
+            ListColumn::Completed => Some(SortColumn::CompletedOn),
+            ListColumn::Seeds | ListColumn::Peers | ListColumn::Category => None,
+        }
+    }
+}
+
+/// Drops repeats and puts the name first when a hand-edited config left it
+/// out, since it's the column that takes up the spare width.
+pub fn normalize(columns: &[ListColumn]) -> Vec<ListColumn> {
+    let mut normalized = Vec::with_capacity(columns.len() + 1);
+    if !columns.contains(&ListColumn::Name) {
+        normalized.push(ListColumn::Name);
+    }
+    for &column in columns {
+        if !normalized.contains(&column) {
+            normalized.push(column);
+        }
+    }
+    normalized
+}
+
+/// The popup listing every column with whether it's shown, in list order
+/// with the hidden ones after.
+#[derive(Debug, Clone)]
+pub struct ColumnChooser {
+    pub entries: Vec<(ListColumn, bool)>,
+    pub selected: usize,
+}
+
+impl ColumnChooser {
+    pub fn new(shown: &[ListColumn]) -> Self {
+        let hidden = ListColumn::ALL
+            .iter()
+            .filter(|column| !shown.contains(column));
+        let entries = shown
+            .iter()
+            .map(|&column| (column, true))
+            .chain(hidden.map(|&column| (column, false)))
+            .collect();
+        Self {
+            entries,
+            selected: 0,
+        }
+    }
+
+    /// Moves the highlight, shows or hides the highlighted column (never the
+    /// name), or with Shift moves it up or down the list.
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        let last = self.entries.len().saturating_sub(1);
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        match key.code {
+            KeyCode::Up if shift && self.selected > 0 => {
+                self.entries.swap(self.selected, self.selected - 1);
+                self.selected -= 1;
+            }
+            KeyCode::Down if shift && self.selected < last => {
+                self.entries.swap(self.selected, self.selected + 1);
+                self.selected += 1;
+            }
+            KeyCode::Up if !shift => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if !shift => self.selected = (self.selected + 1).min(last),
+            KeyCode::Char(' ') => {
+                if let Some((column, shown)) = self.entries.get_mut(self.selected)
+                    && *column != ListColumn::Name
+                {
+                    *shown = !*shown;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The shown columns, in order.
+    pub fn columns(&self) -> Vec<ListColumn> {
+        self.entries
+            .iter()
+            .filter(|(_, shown)| *shown)
+            .map(|&(column, _)| column)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(chooser: &mut ColumnChooser, code: KeyCode, modifiers: KeyModifiers) {
+        chooser.handle_key(KeyEvent::new(code, modifiers));
+    }
+
+    #[test]
+    fn columns_are_shown_hidden_and_reordered() {
+        let mut chooser = ColumnChooser::new(&[ListColumn::Name, ListColumn::Size]);
+        assert_eq!(chooser.entries.len(), ListColumn::ALL.len());
+        assert_eq!(chooser.entries[2], (ListColumn::Progress, false));
+
+        // The name always stays
+        press(&mut chooser, KeyCode::Char(' '), KeyModifiers::NONE);
+        assert_eq!(chooser.columns(), [ListColumn::Name, ListColumn::Size]);
+
+        press(&mut chooser, KeyCode::Down, KeyModifiers::NONE);
+        press(&mut chooser, KeyCode::Up, KeyModifiers::SHIFT);
+        press(&mut chooser, KeyCode::Down, KeyModifiers::NONE);
+        press(&mut chooser, KeyCode::Down, KeyModifiers::NONE);
+        press(&mut chooser, KeyCode::Char(' '), KeyModifiers::NONE);
+        assert_eq!(
+            chooser.columns(),
+            [ListColumn::Size, ListColumn::Name, ListColumn::Progress]
+        );
+    }
+
+    #[test]
+    fn saved_columns_always_include_the_name_once() {
+        assert_eq!(
+            normalize(&[ListColumn::Ratio, ListColumn::Ratio]),
+            [ListColumn::Name, ListColumn::Ratio]
+        );
+        assert_eq!(normalize(&ListColumn::DEFAULT), ListColumn::DEFAULT);
+    }
+}
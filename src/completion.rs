@@ -0,0 +1,182 @@
+use std::path::PathBuf;
+
+/// Outcome of completing a partially typed path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathCompletion {
+    /// Input extended by the longest prefix shared by all candidates.
+    pub completed: String,
+    /// Every matching entry as a full input string; directories end in `/`.
+    pub candidates: Vec<String>,
+}
+
+/// Completes `input` against the local filesystem, offering directories and
+/// `.torrent` files. A leading `~` is expanded for the lookup but kept in the
+/// returned strings, and hidden entries only match when the typed name starts
+/// with a dot.
+pub fn complete_path(input: &str) -> PathCompletion {
+    // "~" alone completes as the home directory itself
+    let input = if input == "~" { "~/" } else { input };
+    let (dir_part, name_prefix) = match input.rfind('/') {
+        Some(index) => input.split_at(index + 1),
+        None => ("", input),
+    };
+
+    let dir = if dir_part.is_empty() {
+        PathBuf::from(".")
+    } else {
+        expand_tilde(dir_part)
+    };
+
+    let mut candidates: Vec<String> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(name_prefix)
+                || (name.starts_with('.') && !name_prefix.starts_with('.'))
+            {
+                return None;
+            }
+            // Follows symlinks so linked directories are offered too
+            if entry.path().is_dir() {
+                Some(format!("{dir_part}{name}/"))
+            } else if name.to_lowercase().ends_with(".torrent") {
+                Some(format!("{dir_part}{name}"))
+            } else {
+                None
+            }
+        })
+        .collect();
+    candidates.sort();
+
+    let completed = match candidates.as_slice() {
+        [] => input.to_string(),
+        [only] => only.clone(),
+        [first, rest @ ..] => rest.iter().fold(first.clone(), |prefix, candidate| {
+            common_prefix(&prefix, candidate).to_string()
+        }),
+    };
+
+    PathCompletion {
+        completed,
+        candidates,
+    }
+}
+
+/// Replaces a leading `~` with the home directory.
+pub fn expand_tilde(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        None if path == "~" => dirs::home_dir().unwrap_or_default(),
+        None => PathBuf::from(path),
+    }
+}
+
+fn common_prefix<'a>(a: &'a str, b: &str) -> &'a str {
+    let len = a
+        .char_indices()
+        .zip(b.chars())
+        .find(|((_, ca), cb)| ca != cb)
+        .map_or(a.len().min(b.len()), |((index, _), _)| index);
+    &a[..len]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn fixture() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("movies")).unwrap();
+        fs::create_dir(dir.path().join(".hidden")).unwrap();
+        fs::write(dir.path().join("movie.torrent"), b"").unwrap();
+        fs::write(dir.path().join("music.TORRENT"), b"").unwrap();
+        fs::write(dir.path().join("notes.txt"), b"").unwrap();
+        fs::write(dir.path().join(".secret.torrent"), b"").unwrap();
+        dir
+    }
+
+    fn root(dir: &tempfile::TempDir) -> String {
+        format!("{}/", dir.path().display())
+    }
+
+    #[test]
+    fn completes_common_prefix_of_matches() {
+        let dir = fixture();
+        let root = root(&dir);
+
+        let result = complete_path(&format!("{root}m"));
+
+        assert_eq!(result.completed, format!("{root}m"));
+        assert_eq!(
+            result.candidates,
+            [
+                format!("{root}movie.torrent"),
+                format!("{root}movies/"),
+                format!("{root}music.TORRENT"),
+            ]
+        );
+
+        let result = complete_path(&format!("{root}mov"));
+        assert_eq!(result.completed, format!("{root}movie"));
+    }
+
+    #[test]
+    fn single_match_completes_fully() {
+        let dir = fixture();
+        let root = root(&dir);
+
+        let result = complete_path(&format!("{root}mu"));
+        assert_eq!(result.completed, format!("{root}music.TORRENT"));
+
+        let result = complete_path(&format!("{root}movies"));
+        assert_eq!(result.completed, format!("{root}movies/"));
+    }
+
+    #[test]
+    fn skips_other_files_and_hidden_entries() {
+        let dir = fixture();
+        let root = root(&dir);
+
+        let result = complete_path(&root);
+        assert!(!result.candidates.iter().any(|c| c.contains("notes")));
+        assert!(
+            !result
+                .candidates
+                .iter()
+                .any(|c| c[root.len()..].starts_with('.'))
+        );
+
+        let result = complete_path(&format!("{root}."));
+        assert_eq!(
+            result.candidates,
+            [format!("{root}.hidden/"), format!("{root}.secret.torrent")]
+        );
+    }
+
+    #[test]
+    fn missing_directory_leaves_input_untouched() {
+        let dir = fixture();
+        let input = format!("{}/does-not-exist/mov", dir.path().display());
+
+        let result = complete_path(&input);
+
+        assert_eq!(result.completed, input);
+        assert!(result.candidates.is_empty());
+    }
+
+    #[test]
+    fn tilde_is_kept_in_completions() {
+        let result = complete_path("~");
+        assert!(result.completed.starts_with("~/"));
+        assert!(result.candidates.iter().all(|c| c.starts_with("~/")));
+    }
+
+    #[test]
+    fn common_prefix_respects_char_boundaries() {
+        assert_eq!(common_prefix("café", "cafè"), "caf");
+        assert_eq!(common_prefix("abc", "ab"), "ab");
+    }
+}
@@ -1,13 +1,292 @@
+use crate::app::{RowMode, SortColumn, StatusFilter};
+use crate::columns::{self, ListColumn};
+use crate::paths;
+use crate::watcher::AfterAdd;
 use anyhow::Result;
-use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
+/// Key overrides from the config file, action name to key description such
+/// as `"ctrl+q"`, `"space"` or `"g g"`. Actions left out keep their defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct KeyBindings(pub BTreeMap<String, String>);
+
+impl KeyBindings {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// The `theme` section: a built-in preset plus per-color overrides, each a
+/// color name such as `"light-blue"` or an `"#rrggbb"` value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThemeConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    #[serde(flatten)]
+    pub colors: BTreeMap<String, String>,
+}
+
+impl ThemeConfig {
+    pub fn is_empty(&self) -> bool {
+        self.preset.is_none() && self.colors.is_empty()
+    }
+}
+
+/// The `ui` section: how the torrent list was left, restored on the next
+/// start.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UiConfig {
+    pub sort_column: SortColumn,
+    pub sort_descending: bool,
+    pub status_filter: StatusFilter,
+    pub show_sidebar: bool,
+    pub row_mode: RowMode,
+    /// Columns of the torrent list, in order
+    pub columns: Vec<ListColumn>,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            sort_column: SortColumn::Name,
+            sort_descending: false,
+            status_filter: StatusFilter::All,
+            show_sidebar: false,
+            row_mode: RowMode::default(),
+            columns: ListColumn::DEFAULT.to_vec(),
+        }
+    }
+}
+
+// Written by hand so a value that doesn't parse, say a sort column from a
+// newer version, only resets itself
+impl<'de> Deserialize<'de> for UiConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let section = Value::deserialize(deserializer)?;
+        let mut ui = Self::default();
+        read_field(&section, "sort_column", &mut ui.sort_column);
+        read_field(&section, "sort_descending", &mut ui.sort_descending);
+        read_field(&section, "status_filter", &mut ui.status_filter);
+        read_field(&section, "show_sidebar", &mut ui.show_sidebar);
+        read_field(&section, "row_mode", &mut ui.row_mode);
+        read_field(&section, "columns", &mut ui.columns);
+        ui.columns = columns::normalize(&ui.columns);
+        Ok(ui)
+    }
+}
+
+fn read_field<T: DeserializeOwned>(section: &Value, key: &str, target: &mut T) {
+    if let Some(value) = section.get(key)
+        && let Ok(parsed) = T::deserialize(value)
+    {
+        *target = parsed;
+    }
+}
+
+/// A saved server to connect to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Selected first in the profile picker
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub default: bool,
+}
+
+impl Profile {
+    /// A profile for `url` named after its host and port, e.g.
+    /// `seedbox.lan:8080`.
+    pub fn for_url(url: &str, username: Option<String>) -> Self {
+        let name = url::Url::parse(url)
+            .ok()
+            .and_then(|parsed| {
+                let host = parsed.host_str()?.to_string();
+                Some(match parsed.port() {
+                    Some(port) => format!("{host}:{port}"),
+                    None => host,
+                })
+            })
+            .unwrap_or_else(|| url.to_string());
+        Self {
+            name,
+            url: url.to_string(),
+            username,
+            default: false,
+        }
+    }
+}
+
+/// What the add dialog was last submitted with on one server.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AddDefaults {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub save_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Last used URL and username
     pub url: Option<String>,
     pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<Profile>,
     pub timezone: Option<String>,
+    #[serde(default)]
+    pub last_browse_dir: Option<String>,
+    /// Last save path and category used to add a torrent, by server URL so
+    /// each profile keeps its own
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub add_defaults: BTreeMap<String, AddDefaults>,
+    /// Whether the server's paths are this machine's, instead of guessing
+    /// from the URL's host
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_is_local: Option<bool>,
+    /// Server path prefixes and where they are mounted here, e.g. over NFS
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub path_mappings: BTreeMap<String, String>,
+    /// Folder whose `*.torrent` files are added automatically
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watch_dir: Option<String>,
+    /// What to do with a watched file once it was added
+    #[serde(default)]
+    pub watch_after_add: AfterAdd,
+    #[serde(default, skip_serializing_if = "KeyBindings::is_empty")]
+    pub keybindings: KeyBindings,
+    #[serde(default, skip_serializing_if = "ThemeConfig::is_empty")]
+    pub theme: ThemeConfig,
+    /// Keep the password in the system keyring and log in with it on start
+    #[serde(default)]
+    pub save_password: bool,
+    /// Skip HTTPS certificate verification
+    #[serde(default)]
+    pub insecure: bool,
+    /// PEM or DER certificate to trust in addition to the system's
+    #[serde(default)]
+    pub ca_cert: Option<String>,
+    /// `user:pass` for HTTP basic auth at a reverse proxy
+    #[serde(default)]
+    pub basic_auth: Option<String>,
+    /// Extra headers sent with every request, name to value
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Seconds to wait for a connection to the server
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    /// Seconds to wait for a whole request
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+    /// Times a failed read is tried again; 0 reports the first failure
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+    /// Milliseconds before the first retry, doubled for each one after it
+    #[serde(default = "default_retry_delay_ms")]
+    pub retry_delay_ms: u64,
+    /// Seconds between automatic refreshes; 0 refreshes only on request
+    #[serde(default = "default_refresh_interval_secs")]
+    pub refresh_interval_secs: u64,
+    /// Draw download/upload graphs in the header when there is room
+    #[serde(default = "default_show_speed_graphs")]
+    pub show_speed_graphs: bool,
+    /// Lead list rows with plain ASCII state symbols instead of Unicode ones
+    #[serde(default)]
+    pub ascii_glyphs: bool,
+    /// Ring the terminal bell when a torrent finishes downloading
+    #[serde(default = "default_completion_bell")]
+    pub completion_bell: bool,
+    /// Also show a desktop notification when a torrent finishes
+    #[serde(default)]
+    pub desktop_notifications: bool,
+    /// Shell command run for each finished torrent, with its details in
+    /// `QBT_NAME`, `QBT_HASH`, `QBT_CATEGORY` and `QBT_SAVE_PATH`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_complete_command: Option<String>,
+    /// Only log the completion command instead of running it
+    #[serde(default)]
+    pub on_complete_dry_run: bool,
+    /// Page asked whether the listening port is reachable from the internet,
+    /// with `{port}` standing for it. Unset by default, as it tells a third
+    /// party this machine's address and port; asked only after confirming
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port_check_url: Option<String>,
+    /// The header's free space turns yellow below this many GiB
+    #[serde(default = "default_free_space_warning_gib")]
+    pub free_space_warning_gib: u64,
+    /// And red below this many
+    #[serde(default = "default_free_space_critical_gib")]
+    pub free_space_critical_gib: u64,
+    /// Minutes a downloading torrent can go without receiving anything
+    /// before it's dimmed and listed under Stalled
+    #[serde(default = "default_stalled_after_mins")]
+    pub stalled_after_mins: u64,
+    /// Number of refreshes kept for the speed graphs
+    #[serde(default = "default_speed_history_len")]
+    pub speed_history_len: usize,
+    /// Milliseconds between redraws when nothing else happens
+    #[serde(default = "default_tick_rate_ms")]
+    pub tick_rate_ms: u64,
+    #[serde(default)]
+    pub ui: UiConfig,
+    /// Where the debug log goes instead of the state directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_file: Option<String>,
+}
+
+fn default_refresh_interval_secs() -> u64 {
+    2
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    5
+}
+
+fn default_request_timeout_secs() -> u64 {
+    15
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+fn default_retry_delay_ms() -> u64 {
+    200
+}
+
+fn default_completion_bell() -> bool {
+    true
+}
+
+fn default_show_speed_graphs() -> bool {
+    true
+}
+
+fn default_free_space_warning_gib() -> u64 {
+    50
+}
+
+fn default_free_space_critical_gib() -> u64 {
+    10
+}
+
+fn default_stalled_after_mins() -> u64 {
+    10
+}
+
+fn default_speed_history_len() -> usize {
+    120
+}
+
+fn default_tick_rate_ms() -> u64 {
+    250
 }
 
 impl Default for Config {
@@ -15,19 +294,73 @@ impl Default for Config {
         Self {
             url: None,
             username: None,
+            profiles: Vec::new(),
             timezone: Some("UTC".to_string()), // Default to UTC
+            last_browse_dir: None,
+            add_defaults: BTreeMap::new(),
+            server_is_local: None,
+            path_mappings: BTreeMap::new(),
+            watch_dir: None,
+            watch_after_add: AfterAdd::default(),
+            keybindings: KeyBindings::default(),
+            theme: ThemeConfig::default(),
+            save_password: false,
+            insecure: false,
+            ca_cert: None,
+            basic_auth: None,
+            headers: BTreeMap::new(),
+            connect_timeout_secs: default_connect_timeout_secs(),
+            request_timeout_secs: default_request_timeout_secs(),
+            retries: default_retries(),
+            retry_delay_ms: default_retry_delay_ms(),
+            refresh_interval_secs: default_refresh_interval_secs(),
+            show_speed_graphs: default_show_speed_graphs(),
+            ascii_glyphs: false,
+            completion_bell: default_completion_bell(),
+            desktop_notifications: false,
+            on_complete_command: None,
+            on_complete_dry_run: false,
+            port_check_url: None,
+            free_space_warning_gib: default_free_space_warning_gib(),
+            free_space_critical_gib: default_free_space_critical_gib(),
+            stalled_after_mins: default_stalled_after_mins(),
+            speed_history_len: default_speed_history_len(),
+            tick_rate_ms: default_tick_rate_ms(),
+            ui: UiConfig::default(),
+            log_file: None,
         }
     }
 }
 
 impl Config {
-    const CONFIG_FILE: &'static str = "qbittui_config.json";
-
     pub fn load() -> Self {
-        if Path::new(Self::CONFIG_FILE).exists() {
-            match fs::read_to_string(Self::CONFIG_FILE) {
-                Ok(content) => match serde_json::from_str(&content) {
-                    Ok(config) => config,
+        let path = paths::config_file();
+        let legacy = Path::new(paths::LEGACY_CONFIG_FILE);
+        if paths::config_override().is_none() && legacy != path {
+            match migrate_legacy_file(legacy, &path) {
+                Ok(true) => eprintln!("Moved {} to {}", legacy.display(), path.display()),
+                Ok(false) => {}
+                Err(e) => {
+                    eprintln!(
+                        "Failed to move {} to {}: {e}",
+                        legacy.display(),
+                        path.display()
+                    );
+                    return Self::load_from(legacy);
+                }
+            }
+        }
+        Self::load_from(&path)
+    }
+
+    fn load_from(path: &Path) -> Self {
+        if path.exists() {
+            match fs::read_to_string(path) {
+                Ok(content) => match Self::parse(&content) {
+                    Ok(mut config) => {
+                        config.migrate_profiles();
+                        config
+                    }
                     Err(e) => {
                         eprintln!("Failed to parse config file: {e}");
                         Self::default()
@@ -43,18 +376,96 @@ impl Config {
         }
     }
 
+    /// Parses a config file. A value of the wrong type only falls back to
+    /// its default instead of discarding the whole file.
+    fn parse(content: &str) -> serde_json::Result<Self> {
+        let error = match serde_json::from_str(content) {
+            Ok(config) => return Ok(config),
+            Err(e) => e,
+        };
+        let Value::Object(fields) = serde_json::from_str::<Value>(content)? else {
+            return Err(error);
+        };
+        let Value::Object(mut merged) = serde_json::to_value(Self::default())? else {
+            unreachable!("the config serializes to an object");
+        };
+        for (key, value) in fields {
+            let mut attempt: Map<String, Value> = merged.clone();
+            attempt.insert(key.clone(), value);
+            match serde_json::from_value::<Self>(Value::Object(attempt.clone())) {
+                Ok(_) => merged = attempt,
+                Err(e) => eprintln!("Ignoring '{key}' in the config file: {e}"),
+            }
+        }
+        serde_json::from_value(Value::Object(merged))
+    }
+
     pub fn save(&self) -> Result<()> {
+        if paths::config_read_only() {
+            return Ok(());
+        }
+        let path = paths::config_file();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
         let content = serde_json::to_string_pretty(self)?;
-        fs::write(Self::CONFIG_FILE, content)?;
+        fs::write(path, content)?;
         Ok(())
     }
 
+    // Configs written before profiles existed only have the last connection,
+    // which becomes the first profile
+    fn migrate_profiles(&mut self) {
+        if self.profiles.is_empty()
+            && let Some(url) = &self.url
+        {
+            let mut profile = Profile::for_url(url, self.username.clone());
+            profile.default = true;
+            self.profiles.push(profile);
+        }
+    }
+
+    pub fn profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    /// Whether a profile points at `url`, compared after normalizing so
+    /// `http://host:8080` matches `http://host:8080/`.
+    pub fn has_profile_for_url(&self, url: &str) -> bool {
+        self.profiles
+            .iter()
+            .any(|profile| url::Url::parse(&profile.url).is_ok_and(|parsed| parsed.as_str() == url))
+    }
+
+    /// Adds `profile`, replacing the one called `replaces` (or one with the
+    /// same name), and saves.
+    pub fn save_profile(&mut self, profile: Profile, replaces: Option<&str>) -> Result<()> {
+        let replaces = replaces.unwrap_or(&profile.name).to_string();
+        match self.profiles.iter_mut().find(|p| p.name == replaces) {
+            Some(existing) => *existing = profile,
+            None => self.profiles.push(profile),
+        }
+        self.save()
+    }
+
+    pub fn remove_profile(&mut self, name: &str) -> Result<()> {
+        self.profiles.retain(|profile| profile.name != name);
+        self.save()
+    }
+
     pub fn update_connection_info(&mut self, url: &str, username: &str) -> Result<()> {
         self.url = Some(url.to_string());
         self.username = Some(username.to_string());
         self.save()
     }
 
+    /// Remembers `url` without touching the saved username, for servers
+    /// that need no login.
+    pub fn set_last_url(&mut self, url: &str) -> Result<()> {
+        self.url = Some(url.to_string());
+        self.save()
+    }
+
     pub fn get_last_url(&self) -> Option<String> {
         self.url.clone()
     }
@@ -67,8 +478,179 @@ impl Config {
         self.timezone.clone().unwrap_or_else(|| "UTC".to_string())
     }
 
+    pub fn set_last_browse_dir(&mut self, dir: &str) -> Result<()> {
+        self.last_browse_dir = Some(dir.to_string());
+        self.save()
+    }
+
+    /// Values to prefill the add dialog with when connected to `url`.
+    pub fn add_defaults(&self, url: &str) -> AddDefaults {
+        self.add_defaults
+            .get(&normalize_url(url))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn set_add_defaults(&mut self, url: &str, defaults: AddDefaults) -> Result<()> {
+        self.add_defaults.insert(normalize_url(url), defaults);
+        self.save()
+    }
+
     pub fn set_timezone(&mut self, timezone: &str) -> Result<()> {
         self.timezone = Some(timezone.to_string());
         self.save()
     }
 }
+
+// Profiles may be saved without the trailing slash the client adds
+fn normalize_url(url: &str) -> String {
+    url::Url::parse(url).map_or_else(|_| url.to_string(), String::from)
+}
+
+/// Moves a config left in the working directory by older versions to
+/// `target`, unless there is already a config there. Returns whether a file
+/// was moved.
+fn migrate_legacy_file(legacy: &Path, target: &Path) -> Result<bool> {
+    if target.exists() || !legacy.is_file() {
+        return Ok(false);
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Copy then remove, since a rename fails across file systems
+    fs::copy(legacy, target)?;
+    fs::remove_file(legacy)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn old_configs_turn_their_last_connection_into_a_profile() {
+        let mut config: Config = serde_json::from_str(
+            r#"{"url": "http://seedbox.lan:8080/", "username": "admin", "timezone": "UTC"}"#,
+        )
+        .unwrap();
+        config.migrate_profiles();
+
+        assert_eq!(
+            config.profiles,
+            [Profile {
+                name: "seedbox.lan:8080".to_string(),
+                url: "http://seedbox.lan:8080/".to_string(),
+                username: Some("admin".to_string()),
+                default: true,
+            }]
+        );
+
+        // Existing profiles are left alone
+        config.profiles[0].name = "home".to_string();
+        config.migrate_profiles();
+        assert_eq!(config.profiles.len(), 1);
+        assert_eq!(config.profiles[0].name, "home");
+    }
+
+    #[test]
+    fn legacy_configs_move_once_and_never_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let legacy = dir.path().join("qbittui_config.json");
+        let target = dir
+            .path()
+            .join("config")
+            .join("qbittui")
+            .join("config.json");
+
+        assert!(!migrate_legacy_file(&legacy, &target).unwrap());
+
+        fs::write(&legacy, r#"{"url": "http://nas:8080/"}"#).unwrap();
+        assert!(migrate_legacy_file(&legacy, &target).unwrap());
+        assert!(!legacy.exists());
+        assert_eq!(
+            Config::load_from(&target).url.as_deref(),
+            Some("http://nas:8080/")
+        );
+
+        // A stray old file doesn't replace the config already in place
+        fs::write(&legacy, "{}").unwrap();
+        assert!(!migrate_legacy_file(&legacy, &target).unwrap());
+        assert!(legacy.exists());
+    }
+
+    #[test]
+    fn ui_section_round_trips() {
+        let config = Config {
+            ui: UiConfig {
+                sort_column: SortColumn::DlSpeed,
+                sort_descending: true,
+                status_filter: StatusFilter::Seeding,
+                show_sidebar: true,
+                row_mode: RowMode::Detailed,
+                columns: vec![ListColumn::Name, ListColumn::Category],
+            },
+            ..Config::default()
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(json.contains(r#""sort_column":"dl_speed""#));
+        assert!(json.contains(r#""columns":["name","category"]"#));
+        assert_eq!(Config::parse(&json).unwrap().ui, config.ui);
+
+        // Configs from before the section existed get the defaults
+        let old = Config::parse(r#"{"url": "http://nas:8080/"}"#).unwrap();
+        assert_eq!(old.ui, UiConfig::default());
+    }
+
+    #[test]
+    fn bad_values_only_reset_themselves() {
+        let config = Config::parse(
+            r#"{
+                "url": "http://nas:8080/",
+                "refresh_interval_secs": "often",
+                "ui": {"sort_column": "popularity", "sort_descending": true,
+                       "status_filter": 3, "show_sidebar": true}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.url.as_deref(), Some("http://nas:8080/"));
+        assert_eq!(
+            config.refresh_interval_secs,
+            default_refresh_interval_secs()
+        );
+        assert_eq!(
+            config.ui,
+            UiConfig {
+                sort_descending: true,
+                show_sidebar: true,
+                ..UiConfig::default()
+            }
+        );
+
+        assert!(Config::parse("not json").is_err());
+        assert!(Config::parse("[1, 2]").is_err());
+    }
+
+    #[test]
+    fn add_defaults_are_kept_per_server() {
+        let config = Config::parse(
+            r#"{"add_defaults": {"http://nas:8080/": {"save_path": "/data", "category": "tv"}}}"#,
+        )
+        .unwrap();
+        let saved = config.add_defaults("http://nas:8080");
+        assert_eq!(saved.save_path.as_deref(), Some("/data"));
+        assert_eq!(saved.category.as_deref(), Some("tv"));
+        assert_eq!(
+            config.add_defaults("http://vps:8080/"),
+            AddDefaults::default()
+        );
+    }
+
+    #[test]
+    fn profile_names_fall_back_to_the_url() {
+        assert_eq!(
+            Profile::for_url("https://vps.example.com/", None).name,
+            "vps.example.com"
+        );
+        assert_eq!(Profile::for_url("not a url", None).name, "not a url");
+    }
+}
@@ -0,0 +1,45 @@
+use anyhow::Result;
+use keyring::Entry;
+
+/// Service name the passwords are filed under in the system keyring.
+const SERVICE: &str = "qbittui";
+
+// One entry per server and user, so switching servers keeps both passwords
+fn entry(url: &str, username: &str) -> keyring::Result<Entry> {
+    Entry::new(SERVICE, &format!("{username}@{url}"))
+}
+
+// Keyring backends block on D-Bus or the OS, so they run off the async
+// threads
+async fn blocking<T: Send + 'static>(
+    task: impl FnOnce() -> keyring::Result<T> + Send + 'static,
+) -> keyring::Result<T> {
+    tokio::task::spawn_blocking(task)
+        .await
+        .unwrap_or_else(|e| Err(keyring::Error::PlatformFailure(Box::new(e))))
+}
+
+/// Password saved for `username` on the server at `url`, if any. A missing
+/// or unavailable keyring counts as no password.
+pub async fn load(url: &str, username: &str) -> Option<String> {
+    let (url, username) = (url.to_string(), username.to_string());
+    blocking(move || entry(&url, &username)?.get_password())
+        .await
+        .ok()
+}
+
+pub async fn store(url: &str, username: &str, password: &str) -> Result<()> {
+    let (url, username, password) = (url.to_string(), username.to_string(), password.to_string());
+    blocking(move || entry(&url, &username)?.set_password(&password)).await?;
+    Ok(())
+}
+
+/// Removes the saved password, returning whether there was one.
+pub async fn forget(url: &str, username: &str) -> Result<bool> {
+    let (url, username) = (url.to_string(), username.to_string());
+    match blocking(move || entry(&url, &username)?.delete_credential()).await {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,936 @@
+use crate::api::{
+    AddTorrentOptions, ApiError, ApiResult, BuildInfo, ErrorContext, LogEntry, MainData, Torrent,
+};
+use crate::backend::TorrentApi;
+use crate::models::{
+    Peer, Preferences, RssFeed, RssRule, SearchPlugin, SearchResults, SearchStatus, TorrentFile,
+    Tracker,
+};
+use crate::torrent_state::TorrentState;
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+use url::Url;
+
+/// Number of torrents the demo starts with.
+pub const TORRENT_COUNT: usize = 50;
+
+const GIB: i64 = 1 << 30;
+const MIB: i64 = 1 << 20;
+const SAVE_PATH: &str = "/downloads";
+
+const CATEGORIES: [(&str, &str); 4] = [
+    ("linux", "/downloads/linux"),
+    ("films", "/downloads/films"),
+    ("datasets", "/downloads/datasets"),
+    ("music", "/downloads/music"),
+];
+
+// Names are public domain or freely licensed, so screenshots can be shared
+const TITLES: [(&str, &str, i64); 20] = [
+    ("linux", "debian-12.{}.0-amd64-netinst.iso", 650 * MIB),
+    ("linux", "ubuntu-24.04.{}-desktop-amd64.iso", 6 * GIB),
+    ("linux", "Fedora-Workstation-Live-x86_64-4{}.iso", 2 * GIB),
+    ("linux", "archlinux-2025.0{}.01-x86_64.iso", 1200 * MIB),
+    ("linux", "linuxmint-22.{}-cinnamon-64bit.iso", 3 * GIB),
+    ("linux", "FreeBSD-14.{}-RELEASE-amd64-dvd1.iso", 4 * GIB),
+    (
+        "films",
+        "Night of the Living Dead (1968) [restored {}]",
+        4 * GIB,
+    ),
+    ("films", "Nosferatu (1922) 1080p reel {}", 3 * GIB),
+    ("films", "Big Buck Bunny 4K part {}", 700 * MIB),
+    ("films", "Sintel (2010) 2160p cut {}", 1500 * MIB),
+    ("films", "Tears of Steel (2012) take {}", 900 * MIB),
+    ("films", "His Girl Friday (1940) print {}", 2 * GIB),
+    ("datasets", "wikipedia-en-dump-2025-0{}", 22 * GIB),
+    ("datasets", "openstreetmap-planet-week-{}", 70 * GIB),
+    ("datasets", "common-voice-corpus-{}.0", 9 * GIB),
+    ("datasets", "imagenet-validation-shard-{}", 6 * GIB),
+    ("music", "Musopen Collection vol. {} [FLAC]", 3 * GIB),
+    ("music", "Free Music Archive sampler {}", 800 * MIB),
+    ("", "Internet Archive scans batch {}", 12 * GIB),
+    ("", "Project Gutenberg top 1000 ({} edition)", 500 * MIB),
+];
+
+const TRACKERS: [&str; 3] = [
+    "udp://tracker.opentrackr.org:1337/announce",
+    "https://torrent.ubuntu.com/announce",
+    "udp://open.demonii.com:1337/announce",
+];
+
+/// A pretend server for `--demo`: about fifty torrents whose progress,
+/// speeds and states move on each refresh, so the interface can be tried,
+/// worked on and screenshotted without qBittorrent. Every login succeeds.
+pub struct DemoServer {
+    base_url: Url,
+    authenticated: AtomicBool,
+    simulation: Mutex<Simulation>,
+}
+
+impl DemoServer {
+    pub fn new() -> Self {
+        Self {
+            base_url: Url::parse("http://demo.invalid/").expect("demo URL is valid"),
+            authenticated: AtomicBool::new(false),
+            simulation: Mutex::new(Simulation::new(Utc::now().timestamp())),
+        }
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut Simulation) -> T) -> T {
+        f(&mut self.simulation.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+/// The demo torrents and the transfer totals they add up to.
+#[derive(Debug)]
+struct Simulation {
+    torrents: Vec<Torrent>,
+    rng: Rng,
+    rid: i64,
+    last_step: Instant,
+    downloaded: i64,
+    uploaded: i64,
+    free_space: i64,
+    alt_speed: bool,
+    dl_limit: i64,
+    up_limit: i64,
+    log: Vec<LogEntry>,
+}
+
+impl Simulation {
+    fn new(now: i64) -> Self {
+        // A fixed seed gives the same torrents every run
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        let torrents = (0..TORRENT_COUNT)
+            .map(|i| generate(i, &mut rng, now))
+            .collect();
+        let log = [
+            "qBittorrent v5.0.0 started",
+            "Running in demo mode: nothing is downloaded or uploaded",
+            "Successfully listening on IP. IP: 0.0.0.0. Port: TCP/6881",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(id, message)| LogEntry {
+            id: id as i64,
+            message: message.to_string(),
+            timestamp: now,
+            kind: 2,
+        })
+        .collect();
+        Self {
+            torrents,
+            rng,
+            rid: 0,
+            last_step: Instant::now(),
+            downloaded: 0,
+            uploaded: 0,
+            free_space: 812 * GIB,
+            alt_speed: false,
+            dl_limit: 0,
+            up_limit: 0,
+            log,
+        }
+    }
+
+    /// Moves every torrent on by `secs` seconds.
+    fn step(&mut self, secs: f64, now: i64) {
+        for torrent in &mut self.torrents {
+            let state = &torrent.state;
+            if state.is_downloading() && *state != TorrentState::QueuedDl {
+                // Stalled downloads sometimes find peers again, and the
+                // others sometimes lose theirs; forced ones always have some
+                let forced = state.is_forced();
+                let stalled = *state == TorrentState::StalledDl;
+                let flip = self.rng.chance(if stalled { 0.15 } else { 0.02 });
+                let moving = forced || stalled == flip;
+                torrent.dlspeed = if moving {
+                    self.rng.between(200, 9_000) * 1024
+                } else {
+                    0
+                };
+                if !forced {
+                    torrent.state = if moving {
+                        TorrentState::Downloading
+                    } else {
+                        TorrentState::StalledDl
+                    };
+                }
+                let received = (torrent.dlspeed as f64 * secs) as i64;
+                self.downloaded += received;
+                self.free_space = (self.free_space - received).max(0);
+                let done = torrent.downloaded.unwrap_or(0) + received;
+                torrent.downloaded = Some(done.min(torrent.size));
+                torrent.downloaded_session =
+                    Some(torrent.downloaded_session.unwrap_or(0) + received);
+                torrent.progress = (done as f64 / torrent.size as f64).min(1.0);
+                torrent.eta =
+                    (torrent.dlspeed > 0).then(|| (torrent.size - done).max(0) / torrent.dlspeed);
+                if torrent.progress >= 1.0 {
+                    torrent.state = TorrentState::Uploading;
+                    torrent.dlspeed = 0;
+                    torrent.eta = None;
+                    torrent.completion_on = Some(now);
+                }
+            }
+            if torrent.state.is_seeding() {
+                let seeding = self.rng.chance(0.6);
+                torrent.upspeed = if seeding {
+                    self.rng.between(10, 2_000) * 1024
+                } else {
+                    0
+                };
+                if !torrent.state.is_forced() {
+                    torrent.state = if seeding {
+                        TorrentState::Uploading
+                    } else {
+                        TorrentState::StalledUp
+                    };
+                }
+                torrent.seeding_time = Some(torrent.seeding_time.unwrap_or(0) + secs as i64);
+            } else if !torrent.state.is_downloading() {
+                torrent.upspeed = 0;
+            }
+            let sent = (torrent.upspeed as f64 * secs) as i64;
+            self.uploaded += sent;
+            let uploaded = torrent.uploaded.unwrap_or(0) + sent;
+            torrent.uploaded = Some(uploaded);
+            torrent.uploaded_session = Some(torrent.uploaded_session.unwrap_or(0) + sent);
+            torrent.ratio = Some(uploaded as f64 / torrent.size.max(1) as f64);
+            if !torrent.state.is_paused() {
+                torrent.time_active = Some(torrent.time_active.unwrap_or(0) + secs as i64);
+            }
+        }
+    }
+
+    fn main_data(&mut self) -> MainData {
+        self.rid += 1;
+        let torrents: serde_json::Map<String, serde_json::Value> = self
+            .torrents
+            .iter()
+            .map(|t| (t.hash.clone(), serde_json::to_value(t).unwrap_or_default()))
+            .collect();
+        let categories: BTreeMap<&str, serde_json::Value> = CATEGORIES
+            .iter()
+            .map(|(name, path)| (*name, json!({ "name": name, "savePath": path })))
+            .collect();
+        let dl_speed: i64 = self.torrents.iter().map(|t| t.dlspeed).sum();
+        let up_speed: i64 = self.torrents.iter().map(|t| t.upspeed).sum();
+        // Everything is sent each time, which the app handles like any
+        // server's full update
+        serde_json::from_value(json!({
+            "rid": self.rid,
+            "full_update": true,
+            "torrents": torrents,
+            "categories": categories,
+            "tags": ["demo", "favourite", "archive"],
+            "server_state": {
+                "connection_status": "connected",
+                "dht_nodes": 348,
+                "dl_info_data": self.downloaded,
+                "dl_info_speed": dl_speed,
+                "dl_rate_limit": self.dl_limit,
+                "up_info_data": self.uploaded,
+                "up_info_speed": up_speed,
+                "up_rate_limit": self.up_limit,
+                "queueing": true,
+                "use_alt_speed_limits": self.alt_speed,
+                "alltime_dl": 3_200 * GIB + self.downloaded,
+                "alltime_ul": 5_100 * GIB + self.uploaded,
+                "global_ratio": "1.59",
+                "total_peer_connections": 143,
+                "free_space_on_disk": self.free_space,
+            },
+        }))
+        .unwrap_or_default()
+    }
+
+    fn update(&mut self, hashes: &[String], f: impl FnMut(&mut Torrent)) {
+        let all = hashes.iter().any(|hash| hash == "all");
+        self.torrents
+            .iter_mut()
+            .filter(|t| all || hashes.contains(&t.hash))
+            .for_each(f);
+    }
+
+    fn torrent(&self, hash: &str) -> ApiResult<&Torrent> {
+        self.torrents
+            .iter()
+            .find(|t| t.hash == hash)
+            .ok_or_else(|| {
+                ApiError::NotFound(demo_context("/api/v2/torrents/info", "No such torrent"))
+            })
+    }
+}
+
+fn generate(index: usize, rng: &mut Rng, now: i64) -> Torrent {
+    let (category, pattern, base_size) = TITLES[index % TITLES.len()];
+    let name = pattern.replace("{}", &(index / TITLES.len() + 1).to_string());
+    let size = base_size + rng.between(0, base_size / 4);
+    let (state, progress) = match rng.between(0, 100) {
+        0..=29 => (TorrentState::Downloading, rng.fraction()),
+        30..=36 => (TorrentState::StalledDl, rng.fraction()),
+        37..=40 => (TorrentState::QueuedDl, 0.0),
+        41..=45 => (TorrentState::StoppedDl, rng.fraction()),
+        46 => (TorrentState::MetaDl, 0.0),
+        47 => (TorrentState::Error, rng.fraction()),
+        48 => (TorrentState::MissingFiles, 1.0),
+        49..=84 => (TorrentState::Uploading, 1.0),
+        _ => (TorrentState::StoppedUp, 1.0),
+    };
+    let added_on = now - rng.between(3_600, 90 * 86_400);
+    let downloaded = (size as f64 * progress) as i64;
+    let uploaded = (downloaded as f64 * rng.fraction() * 3.0) as i64;
+    let complete = progress >= 1.0;
+    let category_path = CATEGORIES
+        .iter()
+        .find(|(name, _)| *name == category)
+        .map_or(SAVE_PATH, |(_, path)| path);
+    let tags = match index % 7 {
+        0 => "demo",
+        3 => "favourite",
+        5 => "archive, demo",
+        _ => "",
+    };
+    Torrent {
+        hash: format!("{:040x}", rng.next() as u128 * 0x1_0000 + index as u128),
+        name: name.clone(),
+        size,
+        progress,
+        state,
+        priority: Some(if complete { 0 } else { index as i32 + 1 }),
+        num_seeds: Some(rng.between(0, 80) as i32),
+        num_leechs: Some(rng.between(0, 40) as i32),
+        ratio: Some(uploaded as f64 / size as f64),
+        category: Some(category.to_string()),
+        tags: Some(tags.to_string()),
+        added_on: Some(added_on),
+        completion_on: complete.then(|| added_on + rng.between(600, 86_400)),
+        downloaded: Some(downloaded),
+        uploaded: Some(uploaded),
+        ratio_limit: Some(-2.0),
+        seeding_time_limit: Some(-2),
+        inactive_seeding_time_limit: Some(-2),
+        time_active: Some(now - added_on),
+        seeding_time: complete.then(|| rng.between(3_600, 30 * 86_400)),
+        auto_tmm: Some(!category.is_empty()),
+        save_path: Some(category_path.to_string()),
+        content_path: Some(format!("{category_path}/{name}")),
+        tracker: Some(TRACKERS[index % TRACKERS.len()].to_string()),
+        ..Torrent::default()
+    }
+}
+
+fn demo_context(endpoint: &str, detail: &str) -> ErrorContext {
+    ErrorContext {
+        endpoint: endpoint.to_string(),
+        target: None,
+        status: None,
+        detail: detail.to_string(),
+    }
+}
+
+fn unsupported<T>(endpoint: &str) -> ApiResult<T> {
+    Err(ApiError::Unsupported(demo_context(
+        endpoint,
+        "not available in demo mode",
+    )))
+}
+
+/// xorshift64*, plenty for made-up speeds.
+#[derive(Debug)]
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// In `low..high`, or `low` when that's empty.
+    fn between(&mut self, low: i64, high: i64) -> i64 {
+        let span = (high - low).max(1) as u64;
+        low + (self.next() % span) as i64
+    }
+
+    fn fraction(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        self.fraction() < probability
+    }
+}
+
+#[async_trait]
+impl TorrentApi for DemoServer {
+    fn get_base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    fn is_authenticated(&self) -> bool {
+        self.authenticated.load(Ordering::SeqCst)
+    }
+
+    fn is_reconnecting(&self) -> bool {
+        false
+    }
+
+    fn is_auth_bypassed(&self) -> bool {
+        false
+    }
+
+    async fn login_or_bypass(&self, _credentials: Option<(&str, &str)>) -> ApiResult<()> {
+        self.authenticated.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    async fn sync_maindata(&self, _rid: i64) -> ApiResult<MainData> {
+        self.with(|sim| {
+            // A long gap, such as a manual refresh after a while, counts as
+            // a few seconds so nothing jumps to done
+            let secs = sim.last_step.elapsed().as_secs_f64().min(5.0);
+            sim.last_step = Instant::now();
+            sim.step(secs, Utc::now().timestamp());
+            Ok(sim.main_data())
+        })
+    }
+
+    async fn pause_torrents(&self, hashes: &[String]) -> ApiResult<()> {
+        self.with(|sim| {
+            sim.update(hashes, |t| {
+                t.dlspeed = 0;
+                t.upspeed = 0;
+                t.eta = None;
+                t.state = if t.progress >= 1.0 {
+                    TorrentState::StoppedUp
+                } else {
+                    TorrentState::StoppedDl
+                };
+            })
+        });
+        Ok(())
+    }
+
+    async fn resume_torrents(&self, hashes: &[String]) -> ApiResult<()> {
+        self.with(|sim| {
+            sim.update(hashes, |t| {
+                if t.state.is_paused() || t.state.is_errored() {
+                    t.state = if t.progress >= 1.0 {
+                        TorrentState::StalledUp
+                    } else {
+                        TorrentState::StalledDl
+                    };
+                }
+            })
+        });
+        Ok(())
+    }
+
+    async fn pause_all(&self) -> ApiResult<()> {
+        self.pause_torrents(&["all".to_string()]).await
+    }
+
+    async fn resume_all(&self) -> ApiResult<()> {
+        self.resume_torrents(&["all".to_string()]).await
+    }
+
+    async fn set_force_start(&self, hashes: &[String], value: bool) -> ApiResult<()> {
+        self.with(|sim| {
+            sim.update(hashes, |t| {
+                t.state = match (value, t.progress >= 1.0) {
+                    (true, true) => TorrentState::ForcedUp,
+                    (true, false) => TorrentState::ForcedDl,
+                    (false, true) => TorrentState::StalledUp,
+                    (false, false) => TorrentState::StalledDl,
+                };
+            })
+        });
+        Ok(())
+    }
+
+    async fn delete_torrents(&self, hashes: &[String], _delete_files: bool) -> ApiResult<()> {
+        self.with(|sim| sim.torrents.retain(|t| !hashes.contains(&t.hash)));
+        Ok(())
+    }
+
+    async fn recheck_torrents(&self, _hashes: &[String]) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn set_category(&self, hashes: &[String], category: &str) -> ApiResult<()> {
+        self.with(|sim| sim.update(hashes, |t| t.category = Some(category.to_string())));
+        Ok(())
+    }
+
+    async fn add_tags(&self, hashes: &[String], tags: &str) -> ApiResult<()> {
+        self.with(|sim| {
+            sim.update(hashes, |t| {
+                let mut all: Vec<String> = t
+                    .tags
+                    .iter()
+                    .chain(std::iter::once(&tags.to_string()))
+                    .flat_map(|list| list.split(','))
+                    .map(|tag| tag.trim().to_string())
+                    .filter(|tag| !tag.is_empty())
+                    .collect();
+                all.sort();
+                all.dedup();
+                t.tags = Some(all.join(", "));
+            })
+        });
+        Ok(())
+    }
+
+    async fn set_auto_management(&self, hashes: &[String], enable: bool) -> ApiResult<()> {
+        self.with(|sim| sim.update(hashes, |t| t.auto_tmm = Some(enable)));
+        Ok(())
+    }
+
+    async fn set_share_limits(
+        &self,
+        hashes: &[String],
+        ratio_limit: f64,
+        seeding_time_limit: i64,
+        inactive_seeding_time_limit: i64,
+    ) -> ApiResult<()> {
+        self.with(|sim| {
+            sim.update(hashes, |t| {
+                t.ratio_limit = Some(ratio_limit);
+                t.seeding_time_limit = Some(seeding_time_limit);
+                t.inactive_seeding_time_limit = Some(inactive_seeding_time_limit);
+            })
+        });
+        Ok(())
+    }
+
+    async fn top_priority(&self, _hashes: &[String]) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn bottom_priority(&self, _hashes: &[String]) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn increase_priority(&self, _hashes: &[String]) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn decrease_priority(&self, _hashes: &[String]) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn add_torrent(&self, torrent_data: &[u8], options: &AddTorrentOptions) -> ApiResult<()> {
+        let size = crate::metainfo::total_size(torrent_data).map_or(GIB, |size| size as i64);
+        self.with(|sim| add(sim, "Added torrent", size, options));
+        Ok(())
+    }
+
+    async fn add_magnet(&self, urls: &str, options: &AddTorrentOptions) -> ApiResult<()> {
+        self.with(|sim| {
+            for url in urls.lines().filter(|line| !line.trim().is_empty()) {
+                let name = Url::parse(url.trim())
+                    .ok()
+                    .and_then(|url| {
+                        url.query_pairs()
+                            .find(|(key, _)| key == "dn")
+                            .map(|(_, name)| name.into_owned())
+                    })
+                    .unwrap_or_else(|| "Added magnet".to_string());
+                add(sim, &name, 2 * GIB, options);
+            }
+        });
+        Ok(())
+    }
+
+    async fn export_torrent(&self, _hash: &str) -> ApiResult<Vec<u8>> {
+        unsupported("/api/v2/torrents/export")
+    }
+
+    async fn get_trackers(&self, hash: &str) -> ApiResult<Vec<Tracker>> {
+        self.with(|sim| {
+            let torrent = sim.torrent(hash)?;
+            let mut trackers: Vec<Tracker> = ["** [DHT] **", "** [PeX] **", "** [LSD] **"]
+                .into_iter()
+                .map(|url| Tracker {
+                    url: url.to_string(),
+                    status: 2,
+                    ..Tracker::default()
+                })
+                .collect();
+            trackers.extend(TRACKERS.iter().map(|url| {
+                let current = torrent.tracker.as_deref() == Some(*url);
+                Tracker {
+                    url: url.to_string(),
+                    status: if current { 2 } else { 4 },
+                    num_seeds: if current {
+                        torrent.num_seeds.unwrap_or(0) as i64
+                    } else {
+                        0
+                    },
+                    num_leeches: if current {
+                        torrent.num_leechs.unwrap_or(0) as i64
+                    } else {
+                        0
+                    },
+                    msg: if current { "" } else { "Connection timed out" }.to_string(),
+                    ..Tracker::default()
+                }
+            }));
+            Ok(trackers)
+        })
+    }
+
+    async fn add_trackers(&self, _hash: &str, _urls: &[String]) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn remove_trackers(&self, _hash: &str, _urls: &[String]) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn edit_tracker(&self, _hash: &str, _orig_url: &str, _new_url: &str) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn get_peers(&self, hash: &str) -> ApiResult<Vec<Peer>> {
+        self.with(|sim| {
+            let torrent = sim.torrent(hash)?.clone();
+            let count = if torrent.dlspeed > 0 || torrent.upspeed > 0 {
+                8
+            } else {
+                0
+            };
+            let clients = ["qBittorrent/5.0.0", "Transmission 4.0.6", "Deluge 2.1.1"];
+            let mut peers: Vec<Peer> = (0..count)
+                .map(|i| Peer {
+                    address: format!("198.51.100.{}:{}", 10 + i, 6881 + i),
+                    client: clients[i % clients.len()].to_string(),
+                    connection: if i % 3 == 0 { "μTP" } else { "BT" }.to_string(),
+                    progress: sim.rng.fraction(),
+                    dl_speed: torrent.dlspeed / count as i64,
+                    up_speed: torrent.upspeed / count as i64,
+                    ..Peer::default()
+                })
+                .collect();
+            peers.sort_by_key(|p| std::cmp::Reverse(p.dl_speed + p.up_speed));
+            Ok(peers)
+        })
+    }
+
+    async fn ban_peers(&self, _peers: &[String]) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn add_peers(&self, _hash: &str, _peers: &[String]) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn get_piece_states(&self, hash: &str) -> ApiResult<Vec<u8>> {
+        self.with(|sim| {
+            let torrent = sim.torrent(hash)?;
+            let pieces = 400;
+            let done = (torrent.progress * pieces as f64) as usize;
+            Ok((0..pieces)
+                .map(|i| match i.cmp(&done) {
+                    std::cmp::Ordering::Less => 2,
+                    std::cmp::Ordering::Equal => 1,
+                    std::cmp::Ordering::Greater => 0,
+                })
+                .collect())
+        })
+    }
+
+    async fn get_files(&self, hash: &str) -> ApiResult<Vec<TorrentFile>> {
+        self.with(|sim| {
+            let torrent = sim.torrent(hash)?;
+            Ok(vec![
+                TorrentFile {
+                    name: format!("{}/{}", torrent.name, torrent.name),
+                    size: torrent.size - MIB,
+                    progress: torrent.progress,
+                },
+                TorrentFile {
+                    name: format!("{}/README.txt", torrent.name),
+                    size: MIB,
+                    progress: 1.0,
+                },
+            ])
+        })
+    }
+
+    async fn rename_file(&self, _hash: &str, _old_path: &str, _new_path: &str) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn rename_folder(&self, _hash: &str, _old_path: &str, _new_path: &str) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn get_main_log(
+        &self,
+        _normal: bool,
+        _info: bool,
+        _warning: bool,
+        _critical: bool,
+        last_known_id: i64,
+    ) -> ApiResult<Vec<LogEntry>> {
+        self.with(|sim| {
+            Ok(sim
+                .log
+                .iter()
+                .filter(|entry| entry.id > last_known_id)
+                .cloned()
+                .collect())
+        })
+    }
+
+    async fn get_search_plugins(&self) -> ApiResult<Vec<SearchPlugin>> {
+        Ok(Vec::new())
+    }
+
+    async fn start_search(&self, _pattern: &str, _plugins: &str) -> ApiResult<i64> {
+        unsupported("/api/v2/search/start")
+    }
+
+    async fn get_search_status(&self, _id: i64) -> ApiResult<Option<SearchStatus>> {
+        Ok(None)
+    }
+
+    async fn get_search_results(&self, _id: i64, _offset: usize) -> ApiResult<SearchResults> {
+        unsupported("/api/v2/search/results")
+    }
+
+    async fn stop_search(&self, _id: i64) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn delete_search(&self, _id: i64) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn get_rss_items(&self) -> ApiResult<Vec<RssFeed>> {
+        Ok(Vec::new())
+    }
+
+    async fn add_rss_feed(&self, _url: &str, _path: &str) -> ApiResult<()> {
+        unsupported("/api/v2/rss/addFeed")
+    }
+
+    async fn remove_rss_item(&self, _path: &str) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn refresh_rss_item(&self, _path: &str) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn mark_rss_article_read(&self, _path: &str, _article_id: &str) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn get_rss_rules(&self) -> ApiResult<Vec<(String, RssRule)>> {
+        Ok(Vec::new())
+    }
+
+    async fn set_rss_rule(&self, _name: &str, _rule: &RssRule) -> ApiResult<()> {
+        unsupported("/api/v2/rss/setRule")
+    }
+
+    async fn remove_rss_rule(&self, _name: &str) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn get_default_save_path(&self) -> ApiResult<String> {
+        Ok(SAVE_PATH.to_string())
+    }
+
+    async fn get_app_version(&self) -> ApiResult<String> {
+        Ok("v5.0.0 (demo)".to_string())
+    }
+
+    async fn get_build_info(&self) -> ApiResult<BuildInfo> {
+        Ok(BuildInfo {
+            qt: "6.7.2".to_string(),
+            libtorrent: "2.0.10.0".to_string(),
+            boost: "1.86.0".to_string(),
+            openssl: "3.3.2".to_string(),
+            bitness: 64,
+        })
+    }
+
+    async fn shutdown_application(&self) -> ApiResult<()> {
+        unsupported("/api/v2/app/shutdown")
+    }
+
+    async fn get_preferences(&self) -> ApiResult<Preferences> {
+        let (dl_limit, up_limit) = self.with(|sim| (sim.dl_limit, sim.up_limit));
+        Ok(Preferences {
+            save_path: SAVE_PATH.to_string(),
+            queueing_enabled: true,
+            max_active_downloads: 5,
+            max_active_uploads: 10,
+            max_active_torrents: 15,
+            dl_limit,
+            up_limit,
+            ..Preferences::default()
+        })
+    }
+
+    async fn set_preferences(&self, _patch: serde_json::Value) -> ApiResult<()> {
+        Ok(())
+    }
+
+    async fn get_global_download_limit(&self) -> ApiResult<i64> {
+        Ok(self.with(|sim| sim.dl_limit))
+    }
+
+    async fn get_global_upload_limit(&self) -> ApiResult<i64> {
+        Ok(self.with(|sim| sim.up_limit))
+    }
+
+    async fn set_global_download_limit(&self, limit: i64) -> ApiResult<()> {
+        self.with(|sim| sim.dl_limit = limit);
+        Ok(())
+    }
+
+    async fn set_global_upload_limit(&self, limit: i64) -> ApiResult<()> {
+        self.with(|sim| sim.up_limit = limit);
+        Ok(())
+    }
+
+    async fn toggle_alternative_speed_limits(&self) -> ApiResult<()> {
+        self.with(|sim| sim.alt_speed = !sim.alt_speed);
+        Ok(())
+    }
+}
+
+fn add(sim: &mut Simulation, name: &str, size: i64, options: &AddTorrentOptions) {
+    let now = Utc::now().timestamp();
+    let mut torrent = generate(sim.torrents.len(), &mut sim.rng, now);
+    torrent = Torrent {
+        name: name.to_string(),
+        size,
+        progress: 0.0,
+        state: if options.paused {
+            TorrentState::StoppedDl
+        } else {
+            TorrentState::MetaDl
+        },
+        category: Some(options.category.clone().unwrap_or_default()),
+        added_on: Some(now),
+        completion_on: None,
+        downloaded: Some(0),
+        uploaded: Some(0),
+        ratio: Some(0.0),
+        seeding_time: None,
+        time_active: Some(0),
+        save_path: options.save_path.clone().or(torrent.save_path),
+        ..torrent
+    };
+    sim.torrents.push(torrent);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hashes(sim: &Simulation, state: TorrentState) -> Vec<String> {
+        sim.torrents
+            .iter()
+            .filter(|t| t.state == state)
+            .map(|t| t.hash.clone())
+            .collect()
+    }
+
+    #[test]
+    fn the_demo_starts_the_same_every_time() {
+        let first = Simulation::new(1_700_000_000);
+        let second = Simulation::new(1_700_000_000);
+        assert_eq!(first.torrents.len(), TORRENT_COUNT);
+        let names = |sim: &Simulation| -> Vec<String> {
+            sim.torrents.iter().map(|t| t.name.clone()).collect()
+        };
+        assert_eq!(names(&first), names(&second));
+
+        let mut unique: Vec<&String> = first.torrents.iter().map(|t| &t.hash).collect();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), TORRENT_COUNT, "hashes are unique");
+        assert!(first.torrents.iter().any(|t| t.state.is_downloading()));
+        assert!(first.torrents.iter().any(|t| t.state.is_seeding()));
+        assert!(first.torrents.iter().any(|t| t.state.is_paused()));
+    }
+
+    #[test]
+    fn downloads_move_on_and_finish() {
+        let mut sim = Simulation::new(1_700_000_000);
+        let downloading = hashes(&sim, TorrentState::Downloading);
+        let before: f64 = sim.torrents.iter().map(|t| t.progress).sum();
+        sim.step(2.0, 1_700_000_002);
+        let after: f64 = sim.torrents.iter().map(|t| t.progress).sum();
+        assert!(after > before);
+        assert!(sim.downloaded > 0);
+
+        // Long enough for everything still downloading to finish
+        for _ in 0..2_000 {
+            sim.step(60.0, 1_700_000_100);
+        }
+        for hash in downloading {
+            let torrent = sim.torrent(&hash).unwrap();
+            assert_eq!(torrent.progress, 1.0, "{}", torrent.name);
+            assert!(torrent.state.is_seeding());
+            assert_eq!(torrent.completion_on, Some(1_700_000_100));
+        }
+        // Queued and stopped ones stay put
+        assert!(!hashes(&sim, TorrentState::QueuedDl).is_empty());
+        assert!(!hashes(&sim, TorrentState::StoppedDl).is_empty());
+    }
+
+    #[tokio::test]
+    async fn any_login_works_and_changes_show_up_in_the_next_refresh() {
+        let demo = DemoServer::new();
+        assert!(!demo.is_authenticated());
+        demo.login_or_bypass(Some(("anyone", "anything")))
+            .await
+            .unwrap();
+        assert!(demo.is_authenticated());
+
+        let data = demo.sync_maindata(0).await.unwrap();
+        assert!(data.full_update);
+        assert_eq!(data.torrents.len(), TORRENT_COUNT);
+        let hash = data.torrents.keys().next().unwrap().clone();
+
+        demo.pause_torrents(std::slice::from_ref(&hash))
+            .await
+            .unwrap();
+        let data = demo.sync_maindata(data.rid).await.unwrap();
+        assert!(data.torrents[&hash].state.as_ref().unwrap().is_paused());
+
+        demo.delete_torrents(std::slice::from_ref(&hash), true)
+            .await
+            .unwrap();
+        demo.add_magnet(
+            "magnet:?xt=urn:btih:abc&dn=Sintel",
+            &AddTorrentOptions::default(),
+        )
+        .await
+        .unwrap();
+        let data = demo.sync_maindata(data.rid).await.unwrap();
+        assert!(!data.torrents.contains_key(&hash));
+        assert!(
+            data.torrents
+                .values()
+                .any(|t| t.name.as_deref() == Some("Sintel"))
+        );
+        assert!(demo.export_torrent(&hash).await.is_err());
+    }
+}
@@ -1,38 +1,185 @@
-use crossterm::event::Event;
+use crate::api::{ApiResult, LogEntry, MainData};
+use crate::models::{Peer, RssFeed, SearchResults, TorrentFile, Tracker};
+use crossterm::event::{Event, EventStream};
+use futures::StreamExt;
 use std::time::Duration;
 use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tokio_util::sync::CancellationToken;
+
+/// Everything the main loop reacts to: terminal input and results from the
+/// background poller.
+#[derive(Debug)]
+pub enum AppEvent {
+    Input(Event),
+    MainDataUpdated(Box<MainData>),
+    /// The server could not be reached; polling retries after `retry_in`.
+    ConnectionLost {
+        retry_in: Duration,
+    },
+    ApiError(String),
+    /// A background login completed.
+    LoginFinished(ApiResult<()>),
+    /// New messages from the server log, for the log viewer.
+    LogFetched(ApiResult<Vec<LogEntry>>),
+    /// The RSS feeds were reloaded, for the RSS screen.
+    RssLoaded(ApiResult<Vec<RssFeed>>),
+    /// The trackers of the torrent `hash`, for the detail view.
+    TrackersLoaded {
+        hash: String,
+        result: ApiResult<Vec<Tracker>>,
+    },
+    /// The peers of the torrent `hash`, for the detail view.
+    PeersLoaded {
+        hash: String,
+        result: ApiResult<Vec<Peer>>,
+    },
+    /// The trackers of the torrent `hash`, for its message in the list.
+    TrackerHintLoaded {
+        hash: String,
+        result: ApiResult<Vec<Tracker>>,
+    },
+    /// The piece states of the torrent `hash`, for the detail view.
+    PiecesLoaded {
+        hash: String,
+        result: ApiResult<Vec<u8>>,
+    },
+    /// The files of the torrent `hash`, for the detail view.
+    FilesLoaded {
+        hash: String,
+        result: ApiResult<Vec<TorrentFile>>,
+    },
+    /// The next page of results of plugin search job `job`.
+    SearchResults {
+        job: i64,
+        result: ApiResult<SearchResults>,
+    },
+    /// A torrent file from the watch folder was added.
+    TorrentWatched(String),
+    /// Redraw for whatever changes with time, such as the connecting
+    /// spinner or notifications running out.
+    Tick,
+}
 
 pub struct EventHandler {
-    receiver: mpsc::UnboundedReceiver<Event>,
-    _sender: mpsc::UnboundedSender<Event>,
+    sender: mpsc::UnboundedSender<AppEvent>,
+    receiver: mpsc::UnboundedReceiver<AppEvent>,
+    tick_rate: Duration,
+    cancel: CancellationToken,
+    task: Option<JoinHandle<()>>,
 }
 
 impl EventHandler {
-    pub fn new() -> Self {
+    /// Forwards terminal input, plus a `Tick` every `tick_rate`.
+    pub fn new(tick_rate: Duration) -> Self {
         let (sender, receiver) = mpsc::unbounded_channel();
-        let event_sender = sender.clone();
+        let mut handler = Self {
+            sender,
+            receiver,
+            tick_rate,
+            cancel: CancellationToken::new(),
+            task: None,
+        };
+        handler.resume();
+        handler
+    }
+
+    /// Starts reading input again after `pause`.
+    pub fn resume(&mut self) {
+        if self.task.is_some() {
+            return;
+        }
+        self.cancel = CancellationToken::new();
+        let token = self.cancel.clone();
+        let input_sender = self.sender.clone();
+        let tick_rate = self.tick_rate;
 
-        // Spawn a task to handle crossterm events
-        tokio::spawn(async move {
+        // Spawn a task to forward crossterm events until cancelled
+        let task = tokio::spawn(async move {
+            let mut reader = EventStream::new();
+            let mut ticks = tokio::time::interval(tick_rate);
+            // A stalled loop gets one tick when it's back, not a burst
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
             loop {
-                if crossterm::event::poll(Duration::from_millis(100)).unwrap_or(false) {
-                    if let Ok(event) = crossterm::event::read() {
-                        if event_sender.send(event).is_err() {
+                tokio::select! {
+                    _ = token.cancelled() => break,
+                    _ = ticks.tick() => {
+                        if input_sender.send(AppEvent::Tick).is_err() {
                             break;
                         }
                     }
+                    maybe_event = reader.next() => match maybe_event {
+                        Some(Ok(event)) => {
+                            if input_sender.send(AppEvent::Input(event)).is_err() {
+                                break;
+                            }
+                        }
+                        // Stream ended or the terminal became unreadable
+                        Some(Err(_)) | None => break,
+                    },
                 }
-                tokio::time::sleep(Duration::from_millis(50)).await;
             }
         });
+        self.task = Some(task);
+    }
 
-        Self {
-            receiver,
-            _sender: sender,
+    /// Stops reading input, so the shell or an editor gets the keys typed
+    /// while the terminal is handed over. Events from other tasks still
+    /// queue up.
+    pub async fn pause(&mut self) {
+        self.cancel.cancel();
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
         }
     }
 
-    pub async fn next(&mut self) -> Option<Event> {
+    pub async fn next(&mut self) -> Option<AppEvent> {
         self.receiver.recv().await
     }
+
+    /// An event that is already queued, without waiting for one.
+    pub fn try_next(&mut self) -> Option<AppEvent> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// A handle other tasks can use to feed events into the main loop.
+    pub fn sender(&self) -> mpsc::UnboundedSender<AppEvent> {
+        self.sender.clone()
+    }
+
+    /// Stops the input task and waits for it to finish. Must be called before
+    /// the terminal is restored so no further input is read from it.
+    pub async fn close(&mut self) {
+        self.pause().await;
+        // Drop queued events so `next` reports the end of input
+        self.receiver.close();
+        while self.receiver.try_recv().is_ok() {}
+    }
+}
+
+impl Drop for EventHandler {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn close_terminates_polling_task() {
+        let mut handler = EventHandler::new(Duration::from_millis(250));
+        let task = handler
+            .task
+            .as_ref()
+            .expect("task should be running")
+            .abort_handle();
+
+        handler.close().await;
+
+        assert!(task.is_finished());
+        assert!(handler.next().await.is_none());
+    }
 }
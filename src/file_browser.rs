@@ -0,0 +1,174 @@
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrowserEntry {
+    pub name: String,
+    pub is_dir: bool,
+}
+
+/// Directory listing used to pick a `.torrent` file. Only directories and
+/// torrent files are shown, hidden entries are skipped.
+#[derive(Debug, Clone)]
+pub struct FileBrowser {
+    pub dir: PathBuf,
+    pub entries: Vec<BrowserEntry>,
+    pub selected: usize,
+    /// Why the last directory change failed, shown until the next move
+    pub error: Option<String>,
+}
+
+impl FileBrowser {
+    /// Opens `dir`, falling back to its closest readable ancestor.
+    pub fn open(dir: &Path) -> Self {
+        let mut browser = Self {
+            dir: dir.to_path_buf(),
+            entries: Vec::new(),
+            selected: 0,
+            error: None,
+        };
+
+        let mut candidate = Some(dir);
+        while let Some(path) = candidate {
+            if browser.change_dir(path.to_path_buf()) {
+                break;
+            }
+            candidate = path.parent();
+        }
+        browser
+    }
+
+    pub fn selected_entry(&self) -> Option<&BrowserEntry> {
+        self.entries.get(self.selected)
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let last = self.entries.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    /// Descends into the selected directory, or returns the selected file.
+    pub fn enter(&mut self) -> Option<PathBuf> {
+        let entry = self.selected_entry()?.clone();
+        let path = self.dir.join(&entry.name);
+        if entry.is_dir {
+            self.change_dir(path);
+            None
+        } else {
+            Some(path)
+        }
+    }
+
+    pub fn go_up(&mut self) {
+        let Some(parent) = self.dir.parent().map(Path::to_path_buf) else {
+            return;
+        };
+        let previous = self
+            .dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned());
+        if self.change_dir(parent) {
+            // Keep the directory we came from highlighted
+            if let Some(index) =
+                previous.and_then(|name| self.entries.iter().position(|e| e.name == name))
+            {
+                self.selected = index;
+            }
+        }
+    }
+
+    // Lists `dir` and switches to it; on failure the current listing is kept
+    fn change_dir(&mut self, dir: PathBuf) -> bool {
+        match list_dir(&dir) {
+            Ok(entries) => {
+                self.dir = dir;
+                self.entries = entries;
+                self.selected = 0;
+                self.error = None;
+                true
+            }
+            Err(e) => {
+                self.error = Some(format!("Cannot open {}: {e}", dir.display()));
+                false
+            }
+        }
+    }
+}
+
+fn list_dir(dir: &Path) -> std::io::Result<Vec<BrowserEntry>> {
+    let mut entries: Vec<BrowserEntry> = std::fs::read_dir(dir)?
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            if name.starts_with('.') {
+                return None;
+            }
+            let is_dir = entry.path().is_dir();
+            (is_dir || name.to_lowercase().ends_with(".torrent"))
+                .then_some(BrowserEntry { name, is_dir })
+        })
+        .collect();
+
+    // Directories first, then files, each alphabetically
+    entries.sort_by(|a, b| {
+        b.is_dir
+            .cmp(&a.is_dir)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn names(browser: &FileBrowser) -> Vec<&str> {
+        browser.entries.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    #[test]
+    fn lists_directories_then_torrents_and_navigates() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("zeta")).unwrap();
+        fs::create_dir(root.path().join(".cache")).unwrap();
+        fs::write(root.path().join("Alpha.torrent"), b"").unwrap();
+        fs::write(root.path().join("readme.md"), b"").unwrap();
+        fs::write(root.path().join("zeta/inner.torrent"), b"").unwrap();
+
+        let mut browser = FileBrowser::open(root.path());
+        assert_eq!(names(&browser), ["zeta", "Alpha.torrent"]);
+
+        assert_eq!(browser.enter(), None);
+        assert_eq!(names(&browser), ["inner.torrent"]);
+        assert_eq!(
+            browser.enter(),
+            Some(root.path().join("zeta/inner.torrent"))
+        );
+
+        browser.go_up();
+        assert_eq!(browser.dir, root.path());
+        assert_eq!(browser.selected_entry().unwrap().name, "zeta");
+    }
+
+    #[test]
+    fn unreadable_directory_keeps_current_listing() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir(root.path().join("gone")).unwrap();
+        let mut browser = FileBrowser::open(root.path());
+        fs::remove_dir(root.path().join("gone")).unwrap();
+
+        assert_eq!(browser.enter(), None);
+
+        assert_eq!(browser.dir, root.path());
+        assert_eq!(names(&browser), ["gone"]);
+        assert!(browser.error.is_some());
+    }
+
+    #[test]
+    fn missing_start_directory_falls_back_to_parent() {
+        let root = tempfile::tempdir().unwrap();
+        let browser = FileBrowser::open(&root.path().join("missing/deeper"));
+        assert_eq!(browser.dir, root.path());
+        assert!(browser.error.is_none());
+    }
+}
@@ -0,0 +1,169 @@
+use crate::models::TorrentFile;
+use crate::text_input::TextInput;
+
+/// A row of the Files tab: a file, or a folder holding the files below it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileEntry {
+    /// Path relative to the save path, as the rename endpoints expect it
+    pub path: String,
+    pub depth: usize,
+    pub is_folder: bool,
+    pub size: i64,
+    /// Bytes downloaded, so folder progress weighs files by size
+    pub done: f64,
+}
+
+impl FileEntry {
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+
+    pub fn progress(&self) -> f64 {
+        if self.size > 0 {
+            self.done / self.size as f64
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Lays the files out as a tree: each folder followed by its contents,
+/// sorted by path.
+pub fn build_tree(files: &[TorrentFile]) -> Vec<FileEntry> {
+    let mut sorted: Vec<&TorrentFile> = files.iter().collect();
+    sorted.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut entries: Vec<FileEntry> = Vec::new();
+    // Indexes into `entries` of the folders the previous file was in
+    let mut open: Vec<usize> = Vec::new();
+    for file in sorted {
+        let parts: Vec<&str> = file.name.split('/').collect();
+        let folders = &parts[..parts.len() - 1];
+        // Keep the folders shared with the previous file
+        let shared = open
+            .iter()
+            .zip(folders)
+            .take_while(|(index, name)| entries[**index].name() == **name)
+            .count();
+        open.truncate(shared);
+        for depth in shared..folders.len() {
+            open.push(entries.len());
+            entries.push(FileEntry {
+                path: folders[..=depth].join("/"),
+                depth,
+                is_folder: true,
+                size: 0,
+                done: 0.0,
+            });
+        }
+        let done = file.size as f64 * file.progress;
+        for index in &open {
+            entries[*index].size += file.size;
+            entries[*index].done += done;
+        }
+        entries.push(FileEntry {
+            path: file.name.clone(),
+            depth: folders.len(),
+            is_folder: false,
+            size: file.size,
+            done,
+        });
+    }
+    entries
+}
+
+/// The Files tab of the detail view.
+#[derive(Debug, Default)]
+pub struct FilePanel {
+    /// Torrent the files belong to
+    pub hash: String,
+    pub entries: Vec<FileEntry>,
+    pub selected: usize,
+    /// New path of the highlighted entry, while it's being renamed
+    pub rename: Option<TextInput>,
+    /// A reload is in flight, so polls don't pile up behind a slow server
+    pub loading: bool,
+}
+
+impl FilePanel {
+    /// Replaces the files, keeping the same entry highlighted when it's
+    /// still there.
+    pub fn set_files(&mut self, files: &[TorrentFile]) {
+        let selected = self.selected_entry().map(|e| e.path.clone());
+        self.entries = build_tree(files);
+        self.selected = selected
+            .and_then(|path| self.entries.iter().position(|e| e.path == path))
+            .unwrap_or(self.selected)
+            .min(self.entries.len().saturating_sub(1));
+    }
+
+    pub fn selected_entry(&self) -> Option<&FileEntry> {
+        self.entries.get(self.selected)
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let last = self.entries.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, size: i64, progress: f64) -> TorrentFile {
+        TorrentFile {
+            name: name.to_string(),
+            size,
+            progress,
+        }
+    }
+
+    #[test]
+    fn files_nest_under_their_folders() {
+        let files = [
+            file("Show/S01/e02.mkv", 300, 0.0),
+            file("Show/info.nfo", 100, 1.0),
+            file("Show/S01/e01.mkv", 300, 1.0),
+            file("Show/S02/e01.mkv", 300, 0.5),
+        ];
+        let tree = build_tree(&files);
+        let rows: Vec<(&str, usize, bool)> = tree
+            .iter()
+            .map(|e| (e.path.as_str(), e.depth, e.is_folder))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("Show", 0, true),
+                ("Show/S01", 1, true),
+                ("Show/S01/e01.mkv", 2, false),
+                ("Show/S01/e02.mkv", 2, false),
+                ("Show/S02", 1, true),
+                ("Show/S02/e01.mkv", 2, false),
+                ("Show/info.nfo", 1, false),
+            ]
+        );
+        assert_eq!(tree[0].size, 1000);
+        assert_eq!(tree[1].progress(), 0.5);
+        assert_eq!(tree[4].name(), "S02");
+
+        // A single-file torrent has no folders
+        let tree = build_tree(&[file("ubuntu.iso", 10, 0.2)]);
+        assert_eq!(tree.len(), 1);
+        assert_eq!(tree[0].depth, 0);
+    }
+
+    #[test]
+    fn reloads_keep_the_highlighted_entry() {
+        let mut panel = FilePanel::default();
+        panel.set_files(&[file("a/x", 1, 0.0), file("b/y", 1, 0.0)]);
+        panel.move_selection(2);
+        assert_eq!(panel.selected_entry().unwrap().path, "b");
+
+        // Renamed "a" sorts after "b" now
+        panel.set_files(&[file("c/x", 1, 0.0), file("b/y", 1, 0.0)]);
+        assert_eq!(panel.selected, 0);
+        assert_eq!(panel.selected_entry().unwrap().path, "b");
+    }
+}
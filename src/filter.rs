@@ -0,0 +1,324 @@
+use crate::api::Torrent;
+use crate::app::StatusFilter;
+use crate::utils::find_ignore_case;
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+
+/// A parsed search query. Terms are separated by spaces and must all match:
+///
+/// - `state:paused` - a status bucket (`downloading`, `seeding`, `paused`,
+///   ...) or part of the raw qBittorrent state
+/// - `cat:linux` - the category, `cat:` alone for uncategorized torrents
+/// - `tag:keep` - one of the tags
+/// - `size>10G`, `ratio<1` - comparisons with `<`, `<=`, `>`, `>=` or `=`
+/// - anything else - part of the name, state, category or tags
+///
+/// Double quotes keep spaces inside a term. A query starting with `/` is a
+/// case-insensitive regex matched against the name instead.
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    terms: Vec<Term>,
+}
+
+#[derive(Debug, Clone)]
+enum Term {
+    Text(String),
+    State(String),
+    Category(String),
+    Tag(String),
+    Size(Comparison, i64),
+    Ratio(Comparison, f64),
+    Regex(Regex),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+}
+
+impl Comparison {
+    fn holds<T: PartialOrd>(self, value: T, limit: T) -> bool {
+        match self {
+            Comparison::Less => value < limit,
+            Comparison::LessOrEqual => value <= limit,
+            Comparison::Greater => value > limit,
+            Comparison::GreaterOrEqual => value >= limit,
+            Comparison::Equal => value == limit,
+        }
+    }
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        if let Some(pattern) = input.strip_prefix('/') {
+            let regex = RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| match e {
+                    regex::Error::Syntax(_) => "invalid regex".to_string(),
+                    e => e.to_string(),
+                })?;
+            return Ok(Self {
+                terms: vec![Term::Regex(regex)],
+            });
+        }
+
+        let terms = tokenize(input)
+            .into_iter()
+            .map(|token| parse_term(&token))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { terms })
+    }
+
+    pub fn matches(&self, torrent: &Torrent) -> bool {
+        self.terms.iter().all(|term| term.matches(torrent))
+    }
+
+    /// Where the name matches the query, for highlighting: the regex match,
+    /// or the first plain term found in the name.
+    pub fn highlight(&self, name: &str) -> Option<Range<usize>> {
+        self.terms.iter().find_map(|term| match term {
+            Term::Text(text) => find_ignore_case(name, text),
+            Term::Regex(regex) => regex
+                .find(name)
+                .map(|m| m.range())
+                .filter(|r| !r.is_empty()),
+            _ => None,
+        })
+    }
+}
+
+impl Term {
+    fn matches(&self, torrent: &Torrent) -> bool {
+        let category = torrent.category.as_deref().unwrap_or_default();
+        let tags = torrent.tags.as_deref().unwrap_or_default();
+        match self {
+            Term::Text(text) => [
+                torrent.name.as_str(),
+                torrent.state.as_str(),
+                torrent.state.display_name(),
+                category,
+                tags,
+            ]
+            .iter()
+            .any(|field| field.to_lowercase().contains(text)),
+            Term::State(state) => match status_filter(state) {
+                Some(filter) => filter.matches(torrent),
+                None => torrent.state.as_str().to_lowercase().contains(state),
+            },
+            Term::Category(name) => category.to_lowercase() == *name,
+            Term::Tag(tag) => tags
+                .split(',')
+                .any(|t| !t.trim().is_empty() && t.trim().to_lowercase() == *tag),
+            Term::Size(comparison, limit) => comparison.holds(torrent.size, *limit),
+            Term::Ratio(comparison, limit) => {
+                comparison.holds(torrent.ratio.unwrap_or(0.0), *limit)
+            }
+            Term::Regex(regex) => regex.is_match(&torrent.name),
+        }
+    }
+}
+
+// Splits on whitespace outside double quotes, dropping the quotes
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in input.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+fn parse_term(token: &str) -> Result<Term, String> {
+    let lower = token.to_lowercase();
+    if let Some(state) = lower.strip_prefix("state:") {
+        return Ok(Term::State(state.to_string()));
+    }
+    if let Some(category) = lower.strip_prefix("cat:") {
+        return Ok(Term::Category(category.to_string()));
+    }
+    if let Some(tag) = lower.strip_prefix("tag:") {
+        return Ok(Term::Tag(tag.to_string()));
+    }
+    if let Some((comparison, value)) = comparison(&lower, "size") {
+        let size = parse_size(value).ok_or_else(|| format!("\"{value}\" is not a size"))?;
+        return Ok(Term::Size(comparison, size));
+    }
+    if let Some((comparison, value)) = comparison(&lower, "ratio") {
+        let ratio = value
+            .parse::<f64>()
+            .map_err(|_| format!("\"{value}\" is not a ratio"))?;
+        return Ok(Term::Ratio(comparison, ratio));
+    }
+    Ok(Term::Text(lower))
+}
+
+// `size>=10g` is `field` followed by an operator and the value
+fn comparison<'a>(token: &'a str, field: &str) -> Option<(Comparison, &'a str)> {
+    let rest = token.strip_prefix(field)?;
+    [
+        ("<=", Comparison::LessOrEqual),
+        (">=", Comparison::GreaterOrEqual),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+        ("=", Comparison::Equal),
+    ]
+    .into_iter()
+    .find_map(|(op, comparison)| Some((comparison, rest.strip_prefix(op)?)))
+}
+
+/// Parses sizes such as `700M`, `1.5gb` or `10GiB` into bytes. Units are
+/// binary, like the sizes shown in the list, and a bare number is bytes.
+fn parse_size(value: &str) -> Option<i64> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let unit = unit.trim_end_matches('b').trim_end_matches('i');
+    let power = match unit {
+        "" => 0,
+        "k" => 1,
+        "m" => 2,
+        "g" => 3,
+        "t" => 4,
+        _ => return None,
+    };
+    Some((number * 1024f64.powi(power)) as i64)
+}
+
+fn status_filter(name: &str) -> Option<StatusFilter> {
+    StatusFilter::ALL
+        .into_iter()
+        // `state:stalled` matches qBittorrent's stalledDL and stalledUP
+        .filter(|filter| !matches!(filter, StatusFilter::All | StatusFilter::Stalled))
+        .find(|filter| filter.label().eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn torrent(name: &str, state: &str) -> Torrent {
+        Torrent {
+            hash: name.to_string(),
+            name: name.to_string(),
+            state: state.into(),
+            ..Torrent::default()
+        }
+    }
+
+    fn names(query: &str, torrents: &[Torrent]) -> Vec<String> {
+        let query = Query::parse(query).unwrap();
+        torrents
+            .iter()
+            .filter(|t| query.matches(t))
+            .map(|t| t.name.clone())
+            .collect()
+    }
+
+    fn library() -> Vec<Torrent> {
+        let mut ubuntu = torrent("Ubuntu 24.04 Desktop", "stalledUP");
+        ubuntu.category = Some("Linux".to_string());
+        ubuntu.tags = Some("iso, keep".to_string());
+        ubuntu.size = 6 * 1024 * 1024 * 1024;
+        ubuntu.ratio = Some(2.5);
+
+        let mut debian = torrent("Debian 12 netinst", "pausedDL");
+        debian.category = Some("linux".to_string());
+        debian.tags = Some("iso".to_string());
+        debian.size = 650 * 1024 * 1024;
+        debian.ratio = Some(0.0);
+
+        let mut movie = torrent("Big Buck Bunny", "downloading");
+        movie.category = Some(String::new());
+        movie.tags = Some("keeper".to_string());
+        movie.size = 12 * 1024 * 1024 * 1024;
+        movie.ratio = Some(0.4);
+
+        vec![ubuntu, debian, movie]
+    }
+
+    #[test]
+    fn fields_combine_as_and() {
+        let library = library();
+        assert_eq!(names("", &library).len(), 3);
+        assert_eq!(names("cat:linux", &library).len(), 2);
+        assert_eq!(
+            names("cat:linux tag:keep", &library),
+            ["Ubuntu 24.04 Desktop"]
+        );
+        assert_eq!(names("cat:", &library), ["Big Buck Bunny"]);
+        // Tags match whole, so "keep" isn't found in "keeper"
+        assert_eq!(names("TAG:Keep", &library), ["Ubuntu 24.04 Desktop"]);
+        assert_eq!(names("ubuntu debian", &library), Vec::<String>::new());
+        assert_eq!(names("iso netinst", &library), ["Debian 12 netinst"]);
+    }
+
+    #[test]
+    fn states_match_buckets_or_raw_states() {
+        let library = library();
+        assert_eq!(names("state:seeding", &library), ["Ubuntu 24.04 Desktop"]);
+        assert_eq!(names("state:paused", &library), ["Debian 12 netinst"]);
+        assert_eq!(names("state:stalled", &library), ["Ubuntu 24.04 Desktop"]);
+    }
+
+    #[test]
+    fn sizes_and_ratios_compare() {
+        let library = library();
+        assert_eq!(names("size>10G", &library), ["Big Buck Bunny"]);
+        assert_eq!(names("size<=1gib", &library), ["Debian 12 netinst"]);
+        assert_eq!(names("size>=650M size<7g", &library).len(), 2);
+        assert_eq!(names("ratio<1", &library).len(), 2);
+        assert_eq!(names("ratio>=2.5", &library), ["Ubuntu 24.04 Desktop"]);
+        assert_eq!(names("ratio=0", &library), ["Debian 12 netinst"]);
+
+        assert_eq!(parse_size("1.5k"), Some(1536));
+        assert_eq!(parse_size("100"), Some(100));
+        assert!(Query::parse("size>lots").is_err());
+        assert!(Query::parse("size>10X").is_err());
+        assert!(Query::parse("ratio<one").is_err());
+    }
+
+    #[test]
+    fn quotes_keep_spaces_and_unknown_fields_are_text() {
+        let mut library = library();
+        library.push(torrent("Re:Zero S01", "pausedUP"));
+        assert_eq!(names("\"buck bunny\"", &library), ["Big Buck Bunny"]);
+        assert_eq!(names("re:zero", &library), ["Re:Zero S01"]);
+    }
+
+    #[test]
+    fn slash_switches_to_a_regex_on_the_name() {
+        let library = library();
+        assert_eq!(names("/^(ubuntu|debian) \\d+", &library).len(), 2);
+        assert_eq!(names("/bunny$", &library), ["Big Buck Bunny"]);
+        assert_eq!(Query::parse("/(unclosed").unwrap_err(), "invalid regex");
+    }
+
+    #[test]
+    fn highlights_the_first_name_match() {
+        let query = Query::parse("cat:linux DESK").unwrap();
+        assert_eq!(query.highlight("Ubuntu Desktop"), Some(7..11));
+        let query = Query::parse("/\\d+\\.\\d+").unwrap();
+        assert_eq!(query.highlight("Ubuntu 24.04"), Some(7..12));
+        assert_eq!(Query::parse("tag:iso").unwrap().highlight("iso"), None);
+    }
+}
@@ -0,0 +1,214 @@
+use crate::api::Torrent;
+use crate::event::AppEvent;
+use std::process::Stdio;
+use tokio::process::Command;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+/// The details of a finished torrent handed to the command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    pub name: String,
+    pub hash: String,
+    pub category: String,
+    pub save_path: String,
+}
+
+impl Completion {
+    pub fn new(torrent: &Torrent) -> Self {
+        Self {
+            name: torrent.name.clone(),
+            hash: torrent.hash.clone(),
+            category: torrent.category.clone().unwrap_or_default(),
+            save_path: torrent.save_path.clone().unwrap_or_default(),
+        }
+    }
+
+    fn env(&self) -> [(&'static str, &str); 4] {
+        [
+            ("QBT_NAME", &self.name),
+            ("QBT_HASH", &self.hash),
+            ("QBT_CATEGORY", &self.category),
+            ("QBT_SAVE_PATH", &self.save_path),
+        ]
+    }
+}
+
+/// Runs `Config::on_complete_command` through the shell for each finished
+/// torrent, one at a time, so a burst of completions waits in line instead
+/// of starting a process each.
+pub struct CompletionHook {
+    queue: mpsc::UnboundedSender<Completion>,
+    cancel: CancellationToken,
+    task: Option<JoinHandle<()>>,
+}
+
+impl CompletionHook {
+    /// With `dry_run` the command is only written to the log.
+    pub fn spawn(command: String, dry_run: bool, sender: mpsc::UnboundedSender<AppEvent>) -> Self {
+        let (queue, mut completions) = mpsc::unbounded_channel::<Completion>();
+        let cancel = CancellationToken::new();
+        let token = cancel.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                let completion = tokio::select! {
+                    _ = token.cancelled() => break,
+                    completion = completions.recv() => match completion {
+                        Some(completion) => completion,
+                        None => break,
+                    },
+                };
+                if dry_run {
+                    info!(
+                        "Would run on completion of {}: {command} with {:?}",
+                        completion.name,
+                        completion.env()
+                    );
+                    continue;
+                }
+                let failure = tokio::select! {
+                    _ = token.cancelled() => break,
+                    result = run(&command, &completion) => result.err(),
+                };
+                if let Some(message) = failure
+                    && sender.send(AppEvent::ApiError(message)).is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            queue,
+            cancel,
+            task: Some(task),
+        }
+    }
+
+    pub fn push(&self, completion: Completion) {
+        let _ = self.queue.send(completion);
+    }
+
+    /// Drops the commands still waiting. One that is running carries on by
+    /// itself.
+    pub async fn close(&mut self) {
+        self.cancel.cancel();
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+    }
+}
+
+impl Drop for CompletionHook {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+// Logs what the command printed; the error is the toast to show
+async fn run(command: &str, completion: &Completion) -> Result<(), String> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let output = shell
+        .arg(command)
+        .envs(completion.env())
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run the completion command: {e}"))?;
+
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        info!("{}: {line}", completion.name);
+    }
+    for line in String::from_utf8_lossy(&output.stderr).lines() {
+        info!("{} (stderr): {line}", completion.name);
+    }
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Completion command for {} failed: {}",
+            completion.name, output.status
+        ))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn completion(name: &str) -> Completion {
+        Completion {
+            name: name.to_string(),
+            hash: "abc123".to_string(),
+            category: "linux".to_string(),
+            save_path: "/data/linux".to_string(),
+        }
+    }
+
+    async fn next_event(receiver: &mut mpsc::UnboundedReceiver<AppEvent>) -> AppEvent {
+        tokio::time::timeout(Duration::from_secs(5), receiver.recv())
+            .await
+            .expect("hook should report within the timeout")
+            .expect("channel should stay open")
+    }
+
+    #[tokio::test]
+    async fn completions_run_in_order_with_their_details() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out");
+        let command = format!(
+            "echo \"$QBT_NAME $QBT_HASH $QBT_CATEGORY $QBT_SAVE_PATH\" >> {}; [ \"$QBT_NAME\" != bad ]",
+            out.display()
+        );
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let mut hook = CompletionHook::spawn(command, false, sender);
+        hook.push(completion("first"));
+        hook.push(completion("bad"));
+        hook.push(completion("last"));
+
+        // Only the failure is reported; the queue carries on after it
+        match next_event(&mut receiver).await {
+            AppEvent::ApiError(message) => assert!(message.contains("bad"), "{message}"),
+            other => panic!("unexpected event: {other:?}"),
+        }
+        hook.push(completion("bad"));
+        assert!(matches!(
+            next_event(&mut receiver).await,
+            AppEvent::ApiError(_)
+        ));
+        hook.close().await;
+
+        let lines = std::fs::read_to_string(&out).unwrap();
+        let names: Vec<&str> = lines
+            .lines()
+            .map(|l| l.split(' ').next().unwrap())
+            .collect();
+        assert_eq!(names, ["first", "bad", "last", "bad"]);
+        assert!(lines.starts_with("first abc123 linux /data/linux\n"));
+    }
+
+    #[tokio::test]
+    async fn dry_runs_start_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out");
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let mut hook = CompletionHook::spawn(format!("touch {}", out.display()), true, sender);
+        hook.push(completion("first"));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        hook.close().await;
+
+        assert!(!out.exists());
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,567 @@
+use crate::config::KeyBindings;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Something the main list can do in response to a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Refresh,
+    RefreshSlower,
+    RefreshFaster,
+    PauseResume,
+    ForceStart,
+    PauseAll,
+    ResumeAll,
+    Delete,
+    Add,
+    Search,
+    OnlineSearch,
+    MoveUp,
+    MoveDown,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    Details,
+    ToggleMark,
+    ClearMarks,
+    Recheck,
+    SetCategory,
+    AddTags,
+    QueueUp,
+    QueueDown,
+    QueueTop,
+    QueueBottom,
+    SortNext,
+    SortReverse,
+    ToggleSidebar,
+    FilterPane,
+    FocusFilterPane,
+    AltSpeed,
+    SpeedLimits,
+    Statistics,
+    Copy,
+    Columns,
+    RowMode,
+    Cleanup,
+    TestPort,
+    OpenPath,
+    OpenContent,
+    Log,
+    Rss,
+    Settings,
+    ShutdownServer,
+    CommandPalette,
+    Help,
+    /// Index into `StatusFilter::ALL`
+    StatusFilter(usize),
+}
+
+/// Config name and default keys of every action. Alternatives are separated
+/// by commas, a sequence of keys by spaces.
+const DEFAULTS: &[(Action, &str, &str)] = &[
+    (Action::Quit, "quit", "ctrl+q"),
+    (Action::Refresh, "refresh", "r"),
+    (Action::RefreshSlower, "refresh_slower", "+"),
+    (Action::RefreshFaster, "refresh_faster", "-"),
+    (Action::PauseResume, "pause_resume", "space"),
+    (Action::ForceStart, "force_start", "f, shift+space"),
+    (Action::PauseAll, "pause_all", "ctrl+p"),
+    (Action::ResumeAll, "resume_all", "ctrl+r"),
+    (Action::Delete, "delete", "delete"),
+    (Action::Add, "add", "ctrl+a"),
+    (Action::Search, "search", "ctrl+f"),
+    (Action::OnlineSearch, "online_search", "ctrl+s"),
+    (Action::MoveUp, "move_up", "up"),
+    (Action::MoveDown, "move_down", "down"),
+    (Action::PageUp, "page_up", "pageup"),
+    (Action::PageDown, "page_down", "pagedown"),
+    (Action::Top, "top", "home"),
+    (Action::Bottom, "bottom", "end"),
+    (Action::Details, "details", "enter"),
+    (Action::ToggleMark, "toggle_mark", "v"),
+    (Action::ClearMarks, "clear_marks", "esc"),
+    (Action::Recheck, "recheck", "R"),
+    (Action::SetCategory, "set_category", "c"),
+    (Action::AddTags, "add_tags", "t"),
+    (Action::QueueUp, "queue_up", "ctrl+up"),
+    (Action::QueueDown, "queue_down", "ctrl+down"),
+    (Action::QueueTop, "queue_top", "ctrl+home"),
+    (Action::QueueBottom, "queue_bottom", "ctrl+end"),
+    (Action::SortNext, "sort_next", "s"),
+    (Action::SortReverse, "sort_reverse", "S"),
+    (Action::ToggleSidebar, "toggle_sidebar", "b"),
+    (Action::FilterPane, "filter_pane", "F"),
+    (Action::FocusFilterPane, "focus_filter_pane", "tab"),
+    (Action::AltSpeed, "alt_speed", "A"),
+    (Action::SpeedLimits, "speed_limits", "l"),
+    (Action::Statistics, "statistics", "i"),
+    (Action::Copy, "copy", "y"),
+    (Action::Columns, "columns", "C"),
+    (Action::RowMode, "row_mode", "m"),
+    (Action::OpenPath, "open_path", "o"),
+    (Action::OpenContent, "open_content", "O"),
+    (Action::Log, "log", "L"),
+    (Action::Rss, "rss", "N"),
+    (Action::Settings, "settings", "P"),
+    // Deliberately awkward to reach
+    (Action::ShutdownServer, "shutdown_server", "ctrl+x Q"),
+    (Action::Cleanup, "cleanup", "ctrl+x c"),
+    (Action::TestPort, "test_port", "ctrl+x p"),
+    (Action::CommandPalette, "command_palette", "ctrl+k, :"),
+    (Action::Help, "help", "?, f1"),
+    (Action::StatusFilter(0), "filter_all", "1"),
+    (Action::StatusFilter(1), "filter_downloading", "2"),
+    (Action::StatusFilter(2), "filter_seeding", "3"),
+    (Action::StatusFilter(3), "filter_completed", "4"),
+    (Action::StatusFilter(4), "filter_paused", "5"),
+    (Action::StatusFilter(5), "filter_active", "6"),
+    (Action::StatusFilter(6), "filter_errored", "7"),
+    (Action::StatusFilter(7), "filter_stalled", "8"),
+];
+
+/// A single key press with its modifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        // Terminals disagree on whether an uppercase letter carries SHIFT, so
+        // printable characters are matched by the character alone
+        let modifiers = match code {
+            KeyCode::Char(c) if c != ' ' => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        Self { code, modifiers }
+    }
+
+    fn label(&self) -> String {
+        let mut label = String::new();
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl+"),
+            (KeyModifiers::ALT, "Alt+"),
+            (KeyModifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                label.push_str(name);
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => label.push_str("Space"),
+            KeyCode::Char(c) if self.modifiers.is_empty() => label.push(c),
+            KeyCode::Char(c) => label.extend(c.to_uppercase()),
+            KeyCode::Enter => label.push_str("Enter"),
+            KeyCode::Esc => label.push_str("Esc"),
+            KeyCode::Tab => label.push_str("Tab"),
+            KeyCode::BackTab => label.push_str("Shift+Tab"),
+            KeyCode::Backspace => label.push_str("Backspace"),
+            KeyCode::Delete => label.push_str("Del"),
+            KeyCode::Insert => label.push_str("Ins"),
+            KeyCode::Up => label.push('↑'),
+            KeyCode::Down => label.push('↓'),
+            KeyCode::Left => label.push('←'),
+            KeyCode::Right => label.push('→'),
+            KeyCode::PageUp => label.push_str("PgUp"),
+            KeyCode::PageDown => label.push_str("PgDn"),
+            KeyCode::Home => label.push_str("Home"),
+            KeyCode::End => label.push_str("End"),
+            KeyCode::F(n) => label.push_str(&format!("F{n}")),
+            other => label.push_str(&format!("{other:?}")),
+        }
+        label
+    }
+}
+
+/// Parses a key description such as `ctrl+q`, `space`, `G` or `g g`.
+fn parse_sequence(spec: &str) -> Result<Vec<KeyChord>, String> {
+    let sequence = spec
+        .split_whitespace()
+        .map(parse_chord)
+        .collect::<Result<Vec<_>, _>>()?;
+    if sequence.is_empty() {
+        return Err("empty key".to_string());
+    }
+    Ok(sequence)
+}
+
+fn parse_chord(spec: &str) -> Result<KeyChord, String> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut parts: Vec<&str> = spec.split('+').collect();
+    // A trailing "+" is the plus key itself, as in "ctrl++"
+    if spec.ends_with("++") || spec == "+" {
+        parts.truncate(parts.len().saturating_sub(2));
+        parts.push("+");
+    }
+    let key = parts.pop().unwrap_or_default();
+    for modifier in parts {
+        modifiers |= match modifier.to_lowercase().as_str() {
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "alt" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => return Err(format!("unknown modifier '{modifier}'")),
+        };
+    }
+
+    let code = match key.to_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "enter" | "return" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "backspace" => KeyCode::Backspace,
+        "del" | "delete" => KeyCode::Delete,
+        "ins" | "insert" => KeyCode::Insert,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "pgup" | "pageup" => KeyCode::PageUp,
+        "pgdn" | "pagedown" => KeyCode::PageDown,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "comma" => KeyCode::Char(','),
+        lower => match (lower.strip_prefix('f'), key.chars().count()) {
+            (Some(n), _) if !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()) => {
+                match n.parse() {
+                    Ok(n @ 1..=24) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key '{key}'")),
+                }
+            }
+            (_, 1) => {
+                let c = key.chars().next().unwrap_or_default();
+                // "ctrl+Q" means the same as "ctrl+q"; "shift+g" is "G"
+                if !modifiers.is_empty() && c.is_ascii_alphabetic() {
+                    if modifiers.contains(KeyModifiers::SHIFT) {
+                        KeyCode::Char(c.to_ascii_uppercase())
+                    } else {
+                        KeyCode::Char(c.to_ascii_lowercase())
+                    }
+                } else {
+                    KeyCode::Char(c)
+                }
+            }
+            _ => return Err(format!("unknown key '{key}'")),
+        },
+    };
+    Ok(KeyChord::new(code, modifiers))
+}
+
+/// Key sequences resolved to actions, built from the defaults with the
+/// user's overrides from the config file applied on top.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(Action, Vec<Vec<KeyChord>>)>,
+    /// Keys typed so far of a sequence that isn't complete yet
+    pending: Vec<KeyChord>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::from_config(&KeyBindings::default()).0
+    }
+}
+
+impl Keymap {
+    /// Builds the keymap, returning a warning for every entry that was
+    /// ignored. Invalid entries keep the action's default keys.
+    pub fn from_config(overrides: &KeyBindings) -> (Self, Vec<String>) {
+        let mut warnings = Vec::new();
+        for name in overrides.0.keys() {
+            if !DEFAULTS.iter().any(|(_, known, _)| known == name) {
+                warnings.push(format!("Unknown action '{name}' in keybindings"));
+            }
+        }
+
+        let mut overridden = Vec::new();
+        let mut defaults = Vec::new();
+        for &(action, name, default) in DEFAULTS {
+            let custom = overrides.0.get(name).and_then(|spec| {
+                parse_alternatives(spec)
+                    .map_err(|e| {
+                        warnings.push(format!(
+                            "Invalid key binding '{name}' = \"{spec}\": {e}; using \"{default}\""
+                        ));
+                    })
+                    .ok()
+            });
+            match custom {
+                Some(keys) => overridden.push((action, keys)),
+                None => {
+                    let keys = parse_alternatives(default).expect("default key bindings parse");
+                    defaults.push((action, keys));
+                }
+            }
+        }
+
+        // User bindings win when they reuse a key that has a default meaning
+        overridden.extend(defaults);
+        let keymap = Self {
+            bindings: overridden,
+            pending: Vec::new(),
+        };
+        for (hidden, keys, winner) in keymap.shadowed() {
+            warnings.push(format!(
+                "'{keys}' does nothing for '{}' because '{}' uses it",
+                config_name(hidden),
+                config_name(winner)
+            ));
+        }
+        (keymap, warnings)
+    }
+
+    /// Sequences that can never be typed, with the action that takes their
+    /// keys: an earlier binding has the same keys, or any binding has a
+    /// prefix of them, which matches first.
+    fn shadowed(&self) -> Vec<(Action, String, Action)> {
+        let sequences: Vec<(Action, &Vec<KeyChord>)> = self
+            .bindings
+            .iter()
+            .flat_map(|(action, keys)| keys.iter().map(move |seq| (*action, seq)))
+            .collect();
+        let mut shadowed = Vec::new();
+        for (i, &(action, sequence)) in sequences.iter().enumerate() {
+            let winner = sequences.iter().enumerate().find(|&(j, &(other, keys))| {
+                other != action
+                    && sequence.starts_with(keys)
+                    && (keys.len() < sequence.len() || j < i)
+            });
+            if let Some((_, &(other, _))) = winner {
+                shadowed.push((action, sequence_label(sequence), other));
+            }
+        }
+        shadowed
+    }
+
+    /// Feeds a key press, returning the action once a full sequence matches.
+    pub fn resolve(&mut self, key: KeyEvent) -> Option<Action> {
+        let chord = KeyChord::new(key.code, key.modifiers);
+        self.pending.push(chord);
+        if let Some(action) = self.lookup() {
+            return action;
+        }
+        // The sequence went nowhere; start over from this key
+        self.pending = vec![chord];
+        self.lookup().flatten().or_else(|| {
+            self.pending.clear();
+            None
+        })
+    }
+
+    /// `Some(Some(_))` on a full match, `Some(None)` while a longer sequence
+    /// still matches, `None` when nothing can match the pending keys.
+    fn lookup(&mut self) -> Option<Option<Action>> {
+        let sequences = self
+            .bindings
+            .iter()
+            .flat_map(|(action, keys)| keys.iter().map(move |seq| (*action, seq)));
+        let mut prefix = false;
+        for (action, sequence) in sequences {
+            if *sequence == self.pending {
+                self.pending.clear();
+                return Some(Some(action));
+            }
+            prefix |= sequence.starts_with(&self.pending);
+        }
+        prefix.then_some(None)
+    }
+
+    /// Keys bound to `action` as shown in help, e.g. `?/F1` or `g g`.
+    pub fn label(&self, action: Action) -> String {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map(|(_, keys)| {
+                keys.iter()
+                    .map(|sequence| sequence_label(sequence))
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn sequence_label(sequence: &[KeyChord]) -> String {
+    sequence
+        .iter()
+        .map(KeyChord::label)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn config_name(action: Action) -> &'static str {
+    DEFAULTS
+        .iter()
+        .find(|(known, _, _)| *known == action)
+        .map_or("?", |(_, name, _)| name)
+}
+
+fn parse_alternatives(spec: &str) -> Result<Vec<Vec<KeyChord>>, String> {
+    spec.split(',').map(parse_sequence).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(keymap: &mut Keymap, code: KeyCode, modifiers: KeyModifiers) -> Option<Action> {
+        keymap.resolve(KeyEvent::new(code, modifiers))
+    }
+
+    fn overrides(entries: &[(&str, &str)]) -> KeyBindings {
+        KeyBindings(
+            entries
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn parses_modifiers_and_named_keys() {
+        assert_eq!(
+            parse_chord("ctrl+q"),
+            Ok(KeyChord::new(KeyCode::Char('q'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(parse_chord("Ctrl+Q"), parse_chord("ctrl+q"));
+        assert_eq!(parse_chord("shift+g"), parse_chord("G"));
+        assert_eq!(
+            parse_chord("shift+space"),
+            Ok(KeyChord::new(KeyCode::Char(' '), KeyModifiers::SHIFT))
+        );
+        assert_eq!(
+            parse_chord("ctrl+pageup").map(|c| c.code),
+            Ok(KeyCode::PageUp)
+        );
+        assert_eq!(parse_chord("f5").map(|c| c.code), Ok(KeyCode::F(5)));
+        assert_eq!(parse_chord("f").map(|c| c.code), Ok(KeyCode::Char('f')));
+        assert_eq!(
+            parse_chord("ctrl++").map(|c| c.code),
+            Ok(KeyCode::Char('+'))
+        );
+        assert!(parse_chord("hyper+x").is_err());
+        assert!(parse_chord("banana").is_err());
+        assert!(parse_chord("f99").is_err());
+    }
+
+    #[test]
+    fn defaults_dispatch_actions() {
+        let mut keymap = Keymap::default();
+        assert_eq!(
+            press(&mut keymap, KeyCode::Char('q'), KeyModifiers::CONTROL),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            press(&mut keymap, KeyCode::Char('R'), KeyModifiers::SHIFT),
+            Some(Action::Recheck)
+        );
+        assert_eq!(
+            press(&mut keymap, KeyCode::Char(' '), KeyModifiers::SHIFT),
+            Some(Action::ForceStart)
+        );
+        assert_eq!(
+            press(&mut keymap, KeyCode::Char(' '), KeyModifiers::NONE),
+            Some(Action::PauseResume)
+        );
+        assert_eq!(
+            press(&mut keymap, KeyCode::Char('3'), KeyModifiers::NONE),
+            Some(Action::StatusFilter(2))
+        );
+        assert_eq!(
+            press(&mut keymap, KeyCode::Char('z'), KeyModifiers::NONE),
+            None
+        );
+        assert_eq!(keymap.label(Action::Help), "?/F1");
+        assert_eq!(keymap.label(Action::QueueUp), "Ctrl+↑");
+    }
+
+    #[test]
+    fn overrides_replace_defaults_and_support_sequences() {
+        let (mut keymap, warnings) = Keymap::from_config(&overrides(&[
+            ("move_down", "j, down"),
+            ("top", "g g"),
+            ("bottom", "G"),
+            ("delete", "d"),
+        ]));
+        assert!(warnings.is_empty());
+
+        assert_eq!(
+            press(&mut keymap, KeyCode::Char('j'), KeyModifiers::NONE),
+            Some(Action::MoveDown)
+        );
+        assert_eq!(
+            press(&mut keymap, KeyCode::Delete, KeyModifiers::NONE),
+            None
+        );
+        assert_eq!(
+            press(&mut keymap, KeyCode::Char('d'), KeyModifiers::NONE),
+            Some(Action::Delete)
+        );
+        assert_eq!(
+            press(&mut keymap, KeyCode::Char('g'), KeyModifiers::NONE),
+            None
+        );
+        assert_eq!(
+            press(&mut keymap, KeyCode::Char('g'), KeyModifiers::NONE),
+            Some(Action::Top)
+        );
+        // An interrupted sequence falls through to the key that broke it
+        assert_eq!(
+            press(&mut keymap, KeyCode::Char('g'), KeyModifiers::NONE),
+            None
+        );
+        assert_eq!(
+            press(&mut keymap, KeyCode::Char('G'), KeyModifiers::SHIFT),
+            Some(Action::Bottom)
+        );
+        assert_eq!(keymap.label(Action::Top), "g g");
+    }
+
+    #[test]
+    fn no_two_default_bindings_share_keys() {
+        assert_eq!(Keymap::default().shadowed(), []);
+        for (i, (action, name, _)) in DEFAULTS.iter().enumerate() {
+            assert!(
+                DEFAULTS[i + 1..]
+                    .iter()
+                    .all(|(other, other_name, _)| other != action && other_name != name),
+                "{name} is listed twice"
+            );
+        }
+    }
+
+    #[test]
+    fn overrides_that_hide_other_actions_warn() {
+        let (mut keymap, warnings) =
+            Keymap::from_config(&overrides(&[("refresh", "s"), ("sort_reverse", "ctrl+x")]));
+        assert_eq!(
+            warnings,
+            [
+                "'s' does nothing for 'sort_next' because 'refresh' uses it",
+                "'Ctrl+X Q' does nothing for 'shutdown_server' because 'sort_reverse' uses it",
+                "'Ctrl+X c' does nothing for 'cleanup' because 'sort_reverse' uses it",
+                "'Ctrl+X p' does nothing for 'test_port' because 'sort_reverse' uses it",
+            ]
+        );
+        assert_eq!(
+            press(&mut keymap, KeyCode::Char('s'), KeyModifiers::NONE),
+            Some(Action::Refresh)
+        );
+    }
+
+    #[test]
+    fn invalid_entries_warn_and_keep_defaults() {
+        let (mut keymap, warnings) =
+            Keymap::from_config(&overrides(&[("quit", "ctrl+banana"), ("teleport", "x")]));
+
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings.iter().any(|w| w.contains("'quit'")));
+        assert!(warnings.iter().any(|w| w.contains("'teleport'")));
+        assert_eq!(
+            press(&mut keymap, KeyCode::Char('q'), KeyModifiers::CONTROL),
+            Some(Action::Quit)
+        );
+    }
+}
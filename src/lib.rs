@@ -0,0 +1,30 @@
+//! A client for the qBittorrent Web API, the one the `qbittui` terminal
+//! interface is built on.
+//!
+//! ```no_run
+//! use qbittui::{ConnectionOptions, QBittorrentClient};
+//! use url::Url;
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let url = Url::parse("http://localhost:8080")?;
+//! let client = QBittorrentClient::new(url, &ConnectionOptions::default());
+//! client.login("admin", "adminadmin").await?;
+//! for torrent in client.get_torrents().await? {
+//!     println!("{} {:.0}%", torrent.name, torrent.progress * 100.0);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The client logs its requests through [`tracing`], so they show up only
+//! when the program using it installs a subscriber.
+
+pub mod api;
+pub mod models;
+pub mod torrent_state;
+
+pub use api::{
+    ApiError, ApiResult, ApiVersion, Category, ConnectionOptions, QBittorrentClient, ServerState,
+    Torrent,
+};
+pub use torrent_state::TorrentState;
@@ -0,0 +1,136 @@
+use crate::api::{LogEntry, LogLevel};
+
+/// Messages kept in memory; older ones are dropped as new ones arrive.
+const MAX_ENTRIES: usize = 5000;
+
+/// State of the server log viewer. Messages are fetched incrementally from
+/// `last_id`, and severities are filtered locally so toggling one doesn't
+/// need another request.
+#[derive(Debug, Clone)]
+pub struct LogView {
+    pub entries: Vec<LogEntry>,
+    /// Newest message id received, -1 before the first fetch
+    pub last_id: i64,
+    /// Shown severities, in `LogLevel::ALL` order
+    pub levels: [bool; 4],
+    /// First visible line; the UI clamps it to the lines available
+    pub scroll: usize,
+    /// Keeps the newest message in view as the log grows
+    pub follow: bool,
+    /// A request is in flight, so polls don't pile up behind a slow server
+    pub fetching: bool,
+}
+
+impl Default for LogView {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            last_id: -1,
+            levels: [true; 4],
+            scroll: 0,
+            follow: true,
+            fetching: false,
+        }
+    }
+}
+
+impl LogView {
+    /// Adds newly fetched messages, skipping any already seen.
+    pub fn append(&mut self, entries: Vec<LogEntry>) {
+        for entry in entries {
+            if entry.id > self.last_id {
+                self.last_id = entry.id;
+                self.entries.push(entry);
+            }
+        }
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..excess);
+            self.scroll = self.scroll.saturating_sub(excess);
+        }
+    }
+
+    pub fn shows(&self, level: LogLevel) -> bool {
+        LogLevel::ALL
+            .iter()
+            .position(|l| *l == level)
+            .is_some_and(|i| self.levels[i])
+    }
+
+    /// Shows or hides the severity at `index` in `LogLevel::ALL`.
+    pub fn toggle(&mut self, index: usize) {
+        if let Some(shown) = self.levels.get_mut(index) {
+            *shown = !*shown;
+        }
+    }
+
+    pub fn visible(&self) -> Vec<&LogEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| self.shows(entry.level()))
+            .collect()
+    }
+
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.follow = false;
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    // Scrolling back to the bottom turns following on again, in the UI
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.scroll = self.scroll.saturating_add(lines);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: i64, kind: i64) -> LogEntry {
+        LogEntry {
+            id,
+            message: format!("message {id}"),
+            timestamp: 1_700_000_000 + id,
+            kind,
+        }
+    }
+
+    #[test]
+    fn appends_only_new_messages() {
+        let mut log = LogView::default();
+        log.append(vec![entry(0, 1), entry(1, 2)]);
+        // The server may resend the last message it was asked about
+        log.append(vec![entry(1, 2), entry(2, 4)]);
+        assert_eq!(log.last_id, 2);
+        assert_eq!(
+            log.entries.iter().map(|e| e.id).collect::<Vec<_>>(),
+            [0, 1, 2]
+        );
+
+        log.append(
+            (3..MAX_ENTRIES as i64 + 10)
+                .map(|id| entry(id, 1))
+                .collect(),
+        );
+        assert_eq!(log.entries.len(), MAX_ENTRIES);
+        assert_eq!(log.entries[0].id, 10);
+    }
+
+    #[test]
+    fn severities_filter_locally() {
+        let mut log = LogView::default();
+        log.append(vec![entry(0, 1), entry(1, 2), entry(2, 4), entry(3, 8)]);
+        assert_eq!(log.visible().len(), 4);
+
+        // Hide normal and info messages
+        log.toggle(0);
+        log.toggle(1);
+        let levels: Vec<LogLevel> = log.visible().iter().map(|e| e.level()).collect();
+        assert_eq!(levels, [LogLevel::Warning, LogLevel::Critical]);
+
+        log.toggle(1);
+        assert_eq!(log.visible().len(), 3);
+        log.toggle(9);
+        assert_eq!(log.visible().len(), 3);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::utils::format_timestamp_with_timezone;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields};
+use tracing_subscriber::registry::LookupSpan;
+
+pub use tracing::level_filters::LevelFilter;
+
+/// Level used when neither `--log-level` nor the config picks one: only
+/// things that went wrong.
+pub const DEFAULT_LEVEL: LevelFilter = LevelFilter::WARN;
+
+/// Sends `tracing` events at `level` or above to `path`, appending. Nothing
+/// is created when logging is off.
+pub fn init(level: LevelFilter, path: &Path, timezone: String) -> io::Result<()> {
+    if level == LevelFilter::OFF {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    // Only fails when a subscriber is already set, which leaves that one
+    let _ = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(Mutex::new(file))
+        .event_format(LineFormat { timezone })
+        .try_init();
+    Ok(())
+}
+
+/// `[2024-05-01 12:00:00 CEST] message key=value`, with the fields of the
+/// spans the event happened in (such as an API request's method and URL)
+/// before the message.
+struct LineFormat {
+    timezone: String,
+}
+
+impl<S, N> FormatEvent<S, N> for LineFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        write!(
+            writer,
+            "[{}] ",
+            format_timestamp_with_timezone(&self.timezone)
+        )?;
+        let level = *event.metadata().level();
+        if level <= tracing::Level::WARN {
+            write!(writer, "{level}: ")?;
+        }
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                let extensions = span.extensions();
+                if let Some(fields) = extensions.get::<FormattedFields<N>>()
+                    && !fields.is_empty()
+                {
+                    write!(writer, "{}{{{fields}}} ", span.name())?;
+                }
+            }
+        }
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::Arc;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(bytes)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn lines_carry_the_time_and_span_fields() {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(LevelFilter::DEBUG)
+            .with_writer(move || writer.clone())
+            .event_format(LineFormat {
+                timezone: "UTC".to_string(),
+            })
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            let span =
+                tracing::debug_span!("request", method = "GET", status = tracing::field::Empty);
+            span.record("status", 200);
+            span.in_scope(|| tracing::debug!("finished"));
+            tracing::warn!("Failed to save config");
+            tracing::trace!("too detailed");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with('['));
+        assert!(lines[0].ends_with(" UTC] request{method=\"GET\" status=200} finished"));
+        assert!(lines[1].ends_with(" UTC] WARN: Failed to save config"));
+    }
+}
@@ -1,40 +1,104 @@
-mod api;
 mod app;
+mod backend;
+mod bindings;
+mod cleanup;
+mod cli;
+mod clipboard;
+mod columns;
+mod completion;
 mod config;
+mod credentials;
+mod demo;
 mod event;
+mod file_browser;
+mod files;
+mod filter;
+mod hooks;
+mod keymap;
+mod log_view;
+mod logging;
+mod metainfo;
+mod notification;
+mod opener;
+mod palette;
+mod paths;
+mod peers;
+mod pieces;
+mod poller;
+mod port_check;
+mod rss;
+mod search;
+mod settings;
+mod share_limits;
+mod speed_history;
+mod stalled;
+mod sync;
+mod terminal;
+mod text_input;
+mod theme;
+mod trackers;
 mod ui;
 mod utils;
+mod watcher;
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 use clap::Parser;
-use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
-    execute,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
-};
+use qbittui::{api, models, torrent_state};
 use ratatui::{Terminal, backend::CrosstermBackend};
-use std::io;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use url::Url;
 
-use app::App;
-use event::EventHandler;
+use app::{App, Handoff};
+use event::{AppEvent, EventHandler};
 use ui::draw;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// qBittorrent WebUI URL
-    #[arg(short, long, default_value = "http://localhost:8080")]
-    url: String,
+    /// Run a single command instead of starting the TUI
+    #[command(subcommand)]
+    command: Option<cli::Command>,
+
+    /// qBittorrent WebUI URL [default: http://localhost:8080, or the saved
+    /// URL for commands]
+    #[arg(short, long, global = true)]
+    url: Option<String>,
 
     /// Username for authentication
-    #[arg(long)]
+    #[arg(long, global = true)]
     username: Option<String>,
 
-    /// Password for authentication
-    #[arg(short, long)]
+    /// Password for authentication (commands also read QBITTUI_PASSWORD)
+    #[arg(short, long, global = true)]
     password: Option<String>,
 
+    /// Accept invalid HTTPS certificates, such as self-signed ones
+    #[arg(long, global = true)]
+    insecure: bool,
+
+    /// Trust this PEM or DER certificate when connecting over HTTPS
+    #[arg(long, value_name = "PATH", global = true)]
+    ca_cert: Option<PathBuf>,
+
+    /// HTTP basic auth for a reverse proxy in front of the WebUI
+    #[arg(long, value_name = "USER:PASS", global = true)]
+    basic_auth: Option<String>,
+
+    /// Extra header to send with every request, e.g. "X-Api-Key: secret";
+    /// can be repeated
+    #[arg(long, value_name = "NAME: VALUE", global = true)]
+    header: Vec<String>,
+
+    /// Connect to a saved server profile instead of picking one
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Read and save settings in this file instead of the default location
+    #[arg(long, value_name = "PATH", global = true)]
+    config: Option<PathBuf>,
+
     /// Set timezone for logs (e.g., UTC, US/Eastern, Europe/London)
     #[arg(long)]
     timezone: Option<String>,
@@ -42,6 +106,38 @@ struct Args {
     /// List available timezones
     #[arg(long)]
     list_timezones: bool,
+
+    /// Remove the password saved in the system keyring and exit
+    #[arg(long)]
+    forget_password: bool,
+
+    /// Seconds between automatic refreshes for this session, 0 for manual
+    /// refresh only
+    #[arg(long, value_name = "SECS")]
+    refresh_interval: Option<u64>,
+
+    /// Color theme for this session: dark, light or terminal-default
+    #[arg(long)]
+    theme: Option<String>,
+
+    /// How much to write to the debug log: off, error, warn, info, debug or
+    /// trace [default: warn]
+    #[arg(long, value_name = "LEVEL", global = true)]
+    log_level: Option<logging::LevelFilter>,
+
+    /// Write the debug log to this file instead of the default location
+    #[arg(long, value_name = "PATH", global = true)]
+    log_file: Option<PathBuf>,
+
+    /// Try the interface on made-up torrents instead of a server; any login
+    /// works and nothing is saved
+    #[arg(long)]
+    demo: bool,
+
+    /// Panic right after the terminal is set up, to check it gets restored
+    #[cfg(debug_assertions)]
+    #[arg(long, hide = true)]
+    panic_after_start: bool,
 }
 
 #[tokio::main]
@@ -60,7 +156,18 @@ async fn main() -> Result<()> {
     }
 
     // Load config
+    if let Some(path) = args.config.clone() {
+        paths::set_config_file(path);
+    }
     let mut config = config::Config::load();
+    if args.demo {
+        // Keeps the demo from running the completion command, taking files
+        // from the watch folder or touching the keyring
+        paths::set_config_read_only();
+        config.save_password = false;
+        config.watch_dir = None;
+        config.on_complete_command = None;
+    }
 
     // Set timezone if provided
     if let Some(timezone) = &args.timezone {
@@ -75,35 +182,218 @@ async fn main() -> Result<()> {
         }
     }
 
+    let log_file = args
+        .log_file
+        .clone()
+        .or_else(|| config.log_file.as_ref().map(PathBuf::from))
+        .unwrap_or_else(paths::default_log_file);
+    let log_level = args.log_level.unwrap_or(logging::DEFAULT_LEVEL);
+    if let Err(e) = logging::init(log_level, &log_file, config.get_timezone()) {
+        eprintln!(
+            "Warning: can't write the log to {}: {e}",
+            log_file.display()
+        );
+    }
+
+    // Report bad key bindings while the warnings can still be read
+    let (_, warnings) = keymap::Keymap::from_config(&config.keybindings);
+    for warning in &warnings {
+        eprintln!("Warning: {warning}");
+    }
+    let (theme, warnings) = theme::Theme::from_config(&config.theme, args.theme.as_deref());
+    for warning in &warnings {
+        eprintln!("Warning: {warning}");
+    }
+
+    let profile = match &args.profile {
+        Some(name) => match config.profile(name) {
+            Some(profile) => Some(profile.clone()),
+            None => {
+                let names: Vec<&str> = config.profiles.iter().map(|p| p.name.as_str()).collect();
+                eprintln!(
+                    "No profile called \"{name}\" (saved profiles: {})",
+                    if names.is_empty() {
+                        "none".to_string()
+                    } else {
+                        names.join(", ")
+                    }
+                );
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+    // An explicit --url or --username still wins over the profile
+    let profile_url = profile.as_ref().map(|p| p.url.clone());
+    let profile_username = profile.as_ref().and_then(|p| p.username.clone());
+
+    if args.forget_password {
+        let url = args
+            .url
+            .or(profile_url)
+            .or_else(|| config.get_last_url())
+            .unwrap_or_else(|| DEFAULT_URL.to_string());
+        // Passwords are filed under the URL as the client normalizes it:
+        // with a trailing slash and without any proxy credentials
+        let mut url = Url::parse(&url)?;
+        let _ = url.set_username("");
+        let _ = url.set_password(None);
+        let url = url.to_string();
+        let Some(username) = args
+            .username
+            .or(profile_username)
+            .or_else(|| config.get_last_username())
+        else {
+            eprintln!("No saved username; pass --username to pick the password to forget");
+            std::process::exit(1);
+        };
+        match credentials::forget(&url, &username).await {
+            Ok(true) => println!("Forgot the saved password for {username} on {url}"),
+            Ok(false) => println!("No password is saved for {username} on {url}"),
+            Err(e) => {
+                eprintln!("Failed to forget the saved password: {e}");
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
+    let connection = match connection_options(&args, &config) {
+        Ok(connection) => connection,
+        Err(e) => {
+            eprintln!("Error: {e:#}");
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(command) = args.command {
+        let url = args.url.or(profile_url);
+        let username = args.username.or(profile_username);
+        if let Err(e) =
+            run_command(command, url, username, args.password, config, &connection).await
+        {
+            eprintln!("Error: {e:#}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     // Validate URL
-    let base_url = Url::parse(&args.url)?;
+    let base_url = Url::parse(args.url.as_deref().unwrap_or(DEFAULT_URL))?;
 
-    // Initialize terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    // Initialize terminal; the guard restores it however this returns
+    terminal::install_panic_hook();
+    let _guard = terminal::TerminalGuard::new()?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    #[cfg(debug_assertions)]
+    if args.panic_after_start {
+        panic!("--panic-after-start was given");
+    }
 
     // Create app and event handler
-    let mut app = App::new_with_config(base_url, args.username, args.password, config).await?;
-    let mut event_handler = EventHandler::new();
+    // Anything faster would only redraw an unchanged screen
+    let tick_rate = Duration::from_millis(config.tick_rate_ms.max(MIN_TICK_RATE_MS));
+    let mut app =
+        App::new_with_config(base_url, args.username, args.password, config, connection).await?;
+    app.theme = theme;
+    if let Some(secs) = args.refresh_interval {
+        app.refresh_interval_secs = secs;
+    }
+    let mut event_handler = EventHandler::new(tick_rate);
+    app.connect_events(event_handler.sender());
+    if args.demo {
+        app.use_demo(demo::DemoServer::new());
+    }
+    match &profile {
+        Some(profile) if !args.demo => app.open_profile(profile).await?,
+        _ => app.login_on_start().await?,
+    }
 
     // Main loop
     let result = run_app(&mut terminal, &mut app, &mut event_handler).await;
 
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    // Stop polling and reading input before handing the terminal back
+    app.stop_background_tasks().await;
+    app.save_ui_state();
+    event_handler.close().await;
 
     result
 }
 
+const DEFAULT_URL: &str = "http://localhost:8080";
+const MIN_TICK_RATE_MS: u64 = 16;
+
+// Command line flags add to the config, so --insecure or an extra header
+// can be tried for one run
+fn connection_options(args: &Args, config: &config::Config) -> Result<api::ConnectionOptions> {
+    let ca_cert = match args
+        .ca_cert
+        .as_deref()
+        .or(config.ca_cert.as_deref().map(Path::new))
+    {
+        Some(path) => Some(api::load_certificate(path)?),
+        None => None,
+    };
+    let basic_auth = match args.basic_auth.as_deref().or(config.basic_auth.as_deref()) {
+        Some(value) => Some(api::parse_basic_auth(value)?),
+        None => None,
+    };
+    let configured = config
+        .headers
+        .iter()
+        .map(|(name, value)| format!("{name}: {value}"));
+    let headers = configured
+        .chain(args.header.iter().cloned())
+        .map(|header| api::parse_header(&header))
+        .collect::<Result<_>>()?;
+
+    Ok(api::ConnectionOptions {
+        insecure: args.insecure || config.insecure,
+        ca_cert,
+        basic_auth,
+        headers,
+        // Zero would fail every request
+        connect_timeout: Duration::from_secs(config.connect_timeout_secs.max(1)),
+        timeout: Duration::from_secs(config.request_timeout_secs.max(1)),
+        retry: api::RetryPolicy {
+            retries: config.retries,
+            base_delay: Duration::from_millis(config.retry_delay_ms),
+        },
+    })
+}
+
+// Commands fall back to the connection saved by the TUI, since they can't
+// ask for it
+async fn run_command(
+    command: cli::Command,
+    url: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    config: config::Config,
+    connection: &api::ConnectionOptions,
+) -> Result<()> {
+    let url = url
+        .or_else(|| config.get_last_url())
+        .unwrap_or_else(|| DEFAULT_URL.to_string());
+    let base_url = Url::parse(&url)?;
+    let Some(username) = username.or_else(|| config.get_last_username()) else {
+        bail!("no username given: pass --username or log in with the TUI once");
+    };
+    let password = match password.or_else(|| std::env::var(cli::PASSWORD_ENV).ok()) {
+        Some(password) => password,
+        None => match credentials::load(base_url.as_str(), &username).await {
+            Some(password) => password,
+            None => bail!(
+                "no password given: pass --password or set {}",
+                cli::PASSWORD_ENV
+            ),
+        },
+    };
+
+    let client = api::QBittorrentClient::new(base_url, connection);
+    cli::run(command, &client, &username, &password).await
+}
+
 async fn run_app(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut App,
@@ -121,22 +411,71 @@ async fn run_app(
             app.handle_resize(current_size.width, current_size.height);
         }
 
-        // Draw UI
+        // Only drawn once something happened; ticks cover what changes with time
         terminal.draw(|f| draw(f, app))?;
+        let output = std::mem::take(&mut app.terminal_output);
+        if !output.is_empty() {
+            let backend = terminal.backend_mut();
+            backend.write_all(output.as_bytes())?;
+            backend.flush()?;
+        }
 
-        // Handle events
-        if let Some(event) = event_handler.next().await {
-            // Handle resize events specifically
-            if let crossterm::event::Event::Resize(width, height) = event {
-                app.handle_resize(width, height);
-                terminal.clear()?;
-                continue;
+        // Everything already queued is handled before the next draw, so a
+        // burst of keys or updates costs one frame
+        let Some(mut event) = event_handler.next().await else {
+            return Ok(());
+        };
+        loop {
+            if handle_event(terminal, app, event).await? {
+                return Ok(());
             }
-
-            if app.handle_event(event).await? {
-                break;
+            match event_handler.try_next() {
+                Some(queued) => event = queued,
+                None => break,
             }
         }
+        if let Some(handoff) = app.handoff.take() {
+            hand_off(terminal, app, event_handler, handoff).await?;
+        }
     }
+}
+
+// Gives the terminal to the shell or an editor and takes it back, drawing
+// everything again afterwards
+async fn hand_off(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut App,
+    event_handler: &mut EventHandler,
+    handoff: Handoff,
+) -> Result<()> {
+    event_handler.pause().await;
+    terminal::restore();
+    match handoff {
+        Handoff::Suspend => terminal::suspend(),
+        Handoff::Edit { target, text } => {
+            let result = terminal::edit(&terminal::editor(), &text).await;
+            app.finish_edit(target, result);
+        }
+    }
+    terminal::reenter()?;
+    terminal.clear()?;
+    event_handler.resume();
     Ok(())
 }
+
+/// Returns whether the app should quit.
+async fn handle_event(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut App,
+    event: AppEvent,
+) -> Result<bool> {
+    match event {
+        AppEvent::Input(crossterm::event::Event::Resize(width, height)) => {
+            app.handle_resize(width, height);
+            terminal.clear()?;
+        }
+        AppEvent::Input(event) => return app.handle_event(event).await,
+        update => app.handle_update(update).await?,
+    }
+    Ok(false)
+}
@@ -0,0 +1,124 @@
+//! Just enough of the bencoded `.torrent` format to tell how much a torrent
+//! will download before it's added.
+
+/// Torrent files are rarely more than a few MiB; anything bigger isn't
+/// read to find out.
+pub const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+// Real torrents nest a few levels deep; this keeps a crafted file of
+// nested lists from overflowing the stack
+const MAX_DEPTH: usize = 32;
+
+/// Bytes the torrent's files add up to: `info.length` for a single file,
+/// the sum of `info.files[].length` for several. `None` when `data` isn't
+/// a torrent file.
+pub fn total_size(data: &[u8]) -> Option<u64> {
+    let (root, _) = parse(data, 0)?;
+    let info = root.get(b"info")?;
+    if let Some(length) = info.get(b"length").and_then(Value::integer) {
+        return u64::try_from(length).ok();
+    }
+    let Value::List(files) = info.get(b"files")? else {
+        return None;
+    };
+    files.iter().try_fold(0u64, |total, file| {
+        let length = u64::try_from(file.get(b"length")?.integer()?).ok()?;
+        total.checked_add(length)
+    })
+}
+
+#[derive(Debug)]
+enum Value<'a> {
+    Integer(i64),
+    Bytes(&'a [u8]),
+    List(Vec<Value<'a>>),
+    Dict(Vec<(&'a [u8], Value<'a>)>),
+}
+
+impl<'a> Value<'a> {
+    fn get(&self, key: &[u8]) -> Option<&Value<'a>> {
+        match self {
+            Value::Dict(entries) => entries.iter().find(|(k, _)| *k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn integer(&self) -> Option<i64> {
+        match self {
+            Value::Integer(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+// The value at the start of `data` and what follows it, `depth` containers
+// in
+fn parse(data: &[u8], depth: usize) -> Option<(Value<'_>, &[u8])> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    match data.first()? {
+        b'i' => {
+            let end = data.iter().position(|&b| b == b'e')?;
+            let number = std::str::from_utf8(&data[1..end]).ok()?.parse().ok()?;
+            Some((Value::Integer(number), &data[end + 1..]))
+        }
+        b'l' => {
+            let mut rest = &data[1..];
+            let mut items = Vec::new();
+            while *rest.first()? != b'e' {
+                let (item, after) = parse(rest, depth + 1)?;
+                items.push(item);
+                rest = after;
+            }
+            Some((Value::List(items), &rest[1..]))
+        }
+        b'd' => {
+            let mut rest = &data[1..];
+            let mut entries = Vec::new();
+            while *rest.first()? != b'e' {
+                let (Value::Bytes(key), after) = parse(rest, depth + 1)? else {
+                    return None;
+                };
+                let (value, after) = parse(after, depth + 1)?;
+                entries.push((key, value));
+                rest = after;
+            }
+            Some((Value::Dict(entries), &rest[1..]))
+        }
+        b'0'..=b'9' => {
+            let colon = data.iter().position(|&b| b == b':')?;
+            let length: usize = std::str::from_utf8(&data[..colon]).ok()?.parse().ok()?;
+            let start = colon + 1;
+            let bytes = data.get(start..start.checked_add(length)?)?;
+            Some((Value::Bytes(bytes), &data[start + length..]))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_add_up_the_files() {
+        let single = b"d8:announce3:url4:infod6:lengthi1024e4:name5:a.iso12:piece lengthi16384eee";
+        assert_eq!(total_size(single), Some(1024));
+
+        let several =
+            b"d4:infod5:filesld6:lengthi10e4:pathl1:aeed6:lengthi32e4:pathl1:beee4:name1:xee";
+        assert_eq!(total_size(several), Some(42));
+
+        assert_eq!(total_size(b"not a torrent"), None);
+        assert_eq!(total_size(b"d4:infod4:name1:xe"), None);
+        // Cut short by a failed download
+        assert_eq!(total_size(&single[..30]), None);
+    }
+
+    #[test]
+    fn deep_nesting_is_refused_instead_of_overflowing() {
+        let nested = [vec![b'l'; 1_000_000], vec![b'e'; 1_000_000]].concat();
+        assert_eq!(total_size(&nested), None);
+    }
+}
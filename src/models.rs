@@ -0,0 +1,287 @@
+//! The types the qBittorrent Web API returns besides torrents, server state
+//! and categories, which live with the client in [`crate::api`].
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// A file of a torrent, from `/torrents/files`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TorrentFile {
+    /// Path relative to the torrent's save path, `/`-separated
+    pub name: String,
+    pub size: i64,
+    pub progress: f64,
+}
+
+/// Separator qBittorrent uses between folder and feed names in item paths.
+pub const PATH_SEPARATOR: char = '\\';
+
+/// A feed from `/rss/items`, with the folders above it folded into `path`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RssFeed {
+    /// `folder\feed`, as the other RSS endpoints expect it
+    #[serde(skip)]
+    pub path: String,
+    pub url: String,
+    pub title: String,
+    pub is_loading: bool,
+    pub has_error: bool,
+    pub articles: Vec<RssArticle>,
+}
+
+impl RssFeed {
+    /// The feed's own name, without its folders.
+    pub fn name(&self) -> &str {
+        self.path
+            .rsplit(PATH_SEPARATOR)
+            .next()
+            .unwrap_or(&self.path)
+    }
+
+    pub fn unread(&self) -> usize {
+        self.articles.iter().filter(|a| !a.is_read).count()
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RssArticle {
+    pub id: String,
+    pub title: String,
+    pub date: String,
+    #[serde(rename = "torrentURL")]
+    pub torrent_url: Option<String>,
+    pub link: Option<String>,
+    pub is_read: bool,
+}
+
+/// An auto-downloading rule from `/rss/rules`. Only the fields shown in the
+/// rule viewer are named; the rest are kept so rules survive a round trip
+/// through `/rss/setRule` unchanged.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RssRule {
+    pub enabled: bool,
+    pub must_contain: String,
+    pub must_not_contain: String,
+    pub use_regex: bool,
+    pub episode_filter: String,
+    pub affected_feeds: Vec<String>,
+    pub assigned_category: String,
+    pub save_path: String,
+    pub last_match: String,
+    #[serde(flatten)]
+    pub other: BTreeMap<String, Value>,
+}
+
+/// Flattens the folder tree returned by `/rss/items` into its feeds, sorted
+/// by path. Folders are objects without a `url`.
+pub fn parse_items(items: &Value) -> Vec<RssFeed> {
+    let mut feeds = Vec::new();
+    collect_feeds(items, "", &mut feeds);
+    feeds
+}
+
+fn collect_feeds(folder: &Value, prefix: &str, feeds: &mut Vec<RssFeed>) {
+    let Some(items) = folder.as_object() else {
+        return;
+    };
+    for (name, item) in items {
+        let path = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{prefix}{PATH_SEPARATOR}{name}")
+        };
+        if item.get("url").is_some_and(Value::is_string) {
+            let mut feed: RssFeed = serde_json::from_value(item.clone()).unwrap_or_default();
+            feed.path = path;
+            feeds.push(feed);
+        } else {
+            collect_feeds(item, &path, feeds);
+        }
+    }
+}
+
+/// A search engine plugin installed in qBittorrent, from `/search/plugins`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchPlugin {
+    pub name: String,
+    pub enabled: bool,
+}
+
+/// One torrent found by a search job.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchResult {
+    pub file_name: String,
+    /// Link to the .torrent file or a magnet link
+    pub file_url: String,
+    /// -1 when the site doesn't say
+    pub file_size: i64,
+    pub nb_seeders: i64,
+    pub nb_leechers: i64,
+    pub site_url: String,
+    pub descr_link: String,
+}
+
+/// A page of results from `/search/results`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SearchResults {
+    pub results: Vec<SearchResult>,
+    /// "Running" or "Stopped"
+    pub status: String,
+    pub total: i64,
+}
+
+impl SearchResults {
+    pub fn is_running(&self) -> bool {
+        self.status == "Running"
+    }
+}
+
+/// Progress of a search job, from `/search/status`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SearchStatus {
+    pub id: i64,
+    pub status: String,
+    pub total: i64,
+}
+
+/// The application preferences the settings screen edits, from
+/// `/app/preferences`. The server has many more; they are never sent back,
+/// since saving only posts the keys that changed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    pub save_path: String,
+    pub temp_path_enabled: bool,
+    pub temp_path: String,
+    pub queueing_enabled: bool,
+    pub max_active_downloads: i64,
+    pub max_active_uploads: i64,
+    pub max_active_torrents: i64,
+    /// Bytes per second, 0 for unlimited
+    pub dl_limit: i64,
+    pub up_limit: i64,
+    pub alt_dl_limit: i64,
+    pub alt_up_limit: i64,
+    pub scheduler_enabled: bool,
+    pub schedule_from_hour: i64,
+    pub schedule_from_min: i64,
+    pub schedule_to_hour: i64,
+    pub schedule_to_min: i64,
+    /// Index into `SCHEDULER_DAYS`
+    pub scheduler_days: i64,
+    pub listen_port: i64,
+    /// Index into `ENCRYPTION_MODES`
+    pub encryption: i64,
+}
+
+/// A tracker of a torrent, from `/torrents/trackers`. DHT, PeX and LSD are
+/// listed too, as pseudo-trackers named like `** [DHT] **`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Tracker {
+    pub url: String,
+    pub status: i64,
+    pub num_peers: i64,
+    pub num_seeds: i64,
+    pub num_leeches: i64,
+    /// The tracker's last message, e.g. why it isn't working
+    pub msg: String,
+}
+
+impl Tracker {
+    pub fn status_label(&self) -> &'static str {
+        match self.status {
+            0 => "Disabled",
+            1 => "Not contacted",
+            2 => "Working",
+            3 => "Updating",
+            4 => "Not working",
+            _ => "Unknown",
+        }
+    }
+
+    /// DHT, PeX and LSD, which can't be edited or removed.
+    pub fn is_special(&self) -> bool {
+        self.url.starts_with("** [")
+    }
+}
+
+/// A peer of a torrent, from `/sync/torrentPeers`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Peer {
+    /// `ip:port`, the key the server lists it under and the form
+    /// `/transfer/banPeers` takes
+    #[serde(skip)]
+    pub address: String,
+    pub client: String,
+    /// "BT", "μTP" or "Web"
+    pub connection: String,
+    pub country: String,
+    pub flags: String,
+    pub progress: f64,
+    pub dl_speed: i64,
+    pub up_speed: i64,
+}
+
+/// The peers of a torrent keyed by address, as `/sync/torrentPeers` sends
+/// them.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PeerData {
+    pub peers: BTreeMap<String, Peer>,
+}
+
+impl PeerData {
+    /// The peers with their addresses filled in, fastest first.
+    pub fn into_peers(self) -> Vec<Peer> {
+        let mut peers: Vec<Peer> = self
+            .peers
+            .into_iter()
+            .map(|(address, peer)| Peer { address, ..peer })
+            .collect();
+        peers.sort_by_key(|peer| std::cmp::Reverse(peer.dl_speed + peer.up_speed));
+        peers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_keep_fields_they_do_not_show() {
+        let rule: RssRule = serde_json::from_str(
+            r#"{"enabled":true,"mustContain":"1080p","affectedFeeds":["https://a"],
+                "ignoreDays":3,"smartFilter":false}"#,
+        )
+        .unwrap();
+        assert_eq!(rule.must_contain, "1080p");
+        let json = serde_json::to_value(&rule).unwrap();
+        assert_eq!(json["ignoreDays"], 3);
+        assert_eq!(json["mustContain"], "1080p");
+    }
+
+    #[test]
+    fn peers_take_their_address_from_the_key() {
+        let data: PeerData = serde_json::from_str(
+            r#"{"full_update":true,"rid":1,"peers":{
+                "10.0.0.2:6881":{"client":"qBittorrent 4.6.2","dl_speed":10,"up_speed":0},
+                "[2001:db8::1]:51413":{"client":"Transmission 4.0","dl_speed":500}}}"#,
+        )
+        .unwrap();
+        let peers = data.into_peers();
+        assert_eq!(peers[0].address, "[2001:db8::1]:51413");
+        assert_eq!(peers[0].client, "Transmission 4.0");
+        assert_eq!(peers[1].address, "10.0.0.2:6881");
+    }
+}
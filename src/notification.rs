@@ -0,0 +1,103 @@
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+/// How long a toast stays on screen.
+pub const NOTIFICATION_DURATION: Duration = Duration::from_secs(5);
+
+/// At most this many toasts are stacked; older ones are dropped first.
+const MAX_NOTIFICATIONS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Success,
+    Warning,
+    Error,
+}
+
+/// Non-modal message shown as a toast until it expires.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub message: String,
+    pub severity: Severity,
+    pub created_at: Instant,
+}
+
+impl Notification {
+    pub fn is_expired(&self) -> bool {
+        self.created_at.elapsed() >= NOTIFICATION_DURATION
+    }
+}
+
+/// Adds a toast to `notifications`, dropping expired ones. Repeating the
+/// newest message restarts its timer instead of stacking a copy, so a
+/// refresh that keeps failing shows a single toast.
+pub fn push(notifications: &mut Vec<Notification>, severity: Severity, message: String) {
+    notifications.retain(|notification| !notification.is_expired());
+
+    if let Some(last) = notifications.last_mut()
+        && last.message == message
+        && last.severity == severity
+    {
+        last.created_at = Instant::now();
+        return;
+    }
+
+    notifications.push(Notification {
+        message,
+        severity,
+        created_at: Instant::now(),
+    });
+    if notifications.len() > MAX_NOTIFICATIONS {
+        notifications.remove(0);
+    }
+}
+
+/// Shows `body` as a desktop notification with `notify-send` or, on macOS,
+/// `osascript`. Without either nothing happens besides a debug log line.
+pub fn send_desktop(summary: &str, body: &str) {
+    let mut command = if cfg!(target_os = "macos") {
+        // Debug formatting quotes and escapes the way AppleScript expects
+        let script = format!("display notification {body:?} with title {summary:?}");
+        let mut command = tokio::process::Command::new("osascript");
+        command.arg("-e").arg(script);
+        command
+    } else {
+        let mut command = tokio::process::Command::new("notify-send");
+        command.arg(summary).arg(body);
+        command
+    };
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Tokio reaps the child once it exits, so it isn't waited for here
+    if let Err(e) = command.spawn() {
+        tracing::debug!("Failed to show a desktop notification: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_messages_collapse_and_old_ones_drop_off() {
+        let mut notifications = Vec::new();
+        push(&mut notifications, Severity::Error, "offline".to_string());
+        push(&mut notifications, Severity::Error, "offline".to_string());
+        assert_eq!(notifications.len(), 1);
+
+        for i in 0..MAX_NOTIFICATIONS {
+            push(&mut notifications, Severity::Success, format!("done {i}"));
+        }
+        assert_eq!(notifications.len(), MAX_NOTIFICATIONS);
+        assert_eq!(notifications[0].message, "done 0");
+
+        // Expired toasts are cleared on the next push
+        for notification in &mut notifications {
+            notification.created_at = Instant::now() - NOTIFICATION_DURATION;
+        }
+        push(&mut notifications, Severity::Warning, "new".to_string());
+        assert_eq!(notifications.len(), 1);
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use url::{Host, Url};
+
+/// Whether the server at `url` runs on this machine, judged by its host.
+pub fn is_local_host(url: &Url) -> bool {
+    match url.host() {
+        Some(Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+/// Where the server's `remote` path is found on this machine. The longest
+/// prefix in `mappings` (server path to local path) wins; without one the
+/// path is only usable as is when the server is `local`.
+pub fn local_path(
+    remote: &str,
+    mappings: &BTreeMap<String, String>,
+    local: bool,
+) -> Option<PathBuf> {
+    let mapped = mappings
+        .iter()
+        .filter_map(|(prefix, target)| {
+            let rest = strip_dir_prefix(remote, prefix)?;
+            Some((prefix.len(), target, rest))
+        })
+        .max_by_key(|(len, _, _)| *len);
+    match mapped {
+        Some((_, target, "")) => Some(PathBuf::from(target)),
+        Some((_, target, rest)) => Some(Path::new(target).join(rest)),
+        None => local.then(|| PathBuf::from(remote)),
+    }
+}
+
+// What follows `prefix` in `path`, if `prefix` is a whole number of its
+// folders
+fn strip_dir_prefix<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    let prefix = prefix.trim_end_matches(['/', '\\']);
+    let rest = path.strip_prefix(prefix)?;
+    if rest.is_empty() || rest.starts_with(['/', '\\']) {
+        Some(rest.trim_start_matches(['/', '\\']))
+    } else {
+        None
+    }
+}
+
+/// Opens `path` with the desktop's default application, a file manager for
+/// folders.
+pub fn open(path: &Path) -> io::Result<()> {
+    let program = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(windows) {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+    // Tokio reaps the child once it exits, so it isn't waited for here
+    tokio::process::Command::new(program)
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(drop)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loopback_hosts_are_local() {
+        for url in [
+            "http://localhost:8080",
+            "http://127.0.0.1:8080",
+            "http://[::1]/",
+        ] {
+            assert!(is_local_host(&Url::parse(url).unwrap()), "{url}");
+        }
+        assert!(!is_local_host(
+            &Url::parse("https://seedbox.example").unwrap()
+        ));
+        assert!(!is_local_host(
+            &Url::parse("http://192.168.1.5:8080").unwrap()
+        ));
+    }
+
+    #[test]
+    fn the_longest_mapping_wins_on_whole_folders() {
+        let mappings = BTreeMap::from([
+            ("/data".to_string(), "/mnt/seedbox".to_string()),
+            ("/data/movies/".to_string(), "/media/movies".to_string()),
+        ]);
+        assert_eq!(
+            local_path("/data/tv/show", &mappings, false),
+            Some(PathBuf::from("/mnt/seedbox/tv/show"))
+        );
+        assert_eq!(
+            local_path("/data/movies", &mappings, false),
+            Some(PathBuf::from("/media/movies"))
+        );
+        assert_eq!(
+            local_path("/data/movies/film.mkv", &mappings, false),
+            Some(PathBuf::from("/media/movies/film.mkv"))
+        );
+        // `/database` isn't inside `/data`
+        assert_eq!(local_path("/database", &mappings, false), None);
+        assert_eq!(
+            local_path("/database", &mappings, true),
+            Some(PathBuf::from("/database"))
+        );
+    }
+}
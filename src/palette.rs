@@ -0,0 +1,150 @@
+use crate::text_input::TextInput;
+use crossterm::event::{KeyCode, KeyEvent};
+
+/// One choice of a palette, shown as its label with its keys beside it.
+#[derive(Debug, Clone)]
+pub struct PaletteEntry<T> {
+    pub item: T,
+    pub label: String,
+    /// Keys that do the same thing without the palette, e.g. `Ctrl+A`
+    pub keys: String,
+}
+
+/// A list of choices narrowed down by typing, e.g. the command palette.
+#[derive(Debug, Clone)]
+pub struct Palette<T> {
+    entries: Vec<PaletteEntry<T>>,
+    pub input: TextInput,
+    /// Indexes into `entries` of the matches, best first
+    matches: Vec<usize>,
+    pub selected: usize,
+}
+
+impl<T: Copy> Palette<T> {
+    pub fn new(entries: Vec<PaletteEntry<T>>) -> Self {
+        let matches = (0..entries.len()).collect();
+        Self {
+            entries,
+            input: TextInput::default(),
+            matches,
+            selected: 0,
+        }
+    }
+
+    /// Edits the query or moves the highlight. Enter and Esc are left to
+    /// the caller.
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => {
+                self.selected = (self.selected + 1).min(self.matches.len().saturating_sub(1));
+            }
+            _ => {
+                if self.input.handle_key(key) {
+                    self.refilter();
+                }
+            }
+        }
+    }
+
+    pub fn matches(&self) -> impl Iterator<Item = &PaletteEntry<T>> {
+        self.matches.iter().map(|&index| &self.entries[index])
+    }
+
+    pub fn selected_item(&self) -> Option<T> {
+        self.matches
+            .get(self.selected)
+            .map(|&index| self.entries[index].item)
+    }
+
+    fn refilter(&mut self) {
+        let query = self.input.value().trim();
+        let mut scored: Vec<(i64, usize)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| Some((fuzzy_score(query, &entry.label)?, index)))
+            .collect();
+        // Stable, so equally good matches keep the listed order
+        scored.sort_by_key(|(score, _)| -score);
+        self.matches = scored.into_iter().map(|(_, index)| index).collect();
+        self.selected = 0;
+    }
+}
+
+/// How well `query` matches `text` when its chars appear in order, ignoring
+/// case; `None` when they don't. Runs of consecutive chars and chars at the
+/// start of a word count for more.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for wanted in query.chars().flat_map(char::to_lowercase) {
+        if wanted.is_whitespace() {
+            continue;
+        }
+        let found = position + text[position..].iter().position(|&c| c == wanted)?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn palette(labels: &[&'static str]) -> Palette<&'static str> {
+        Palette::new(
+            labels
+                .iter()
+                .map(|&label| PaletteEntry {
+                    item: label,
+                    label: label.to_string(),
+                    keys: String::new(),
+                })
+                .collect(),
+        )
+    }
+
+    fn type_text(palette: &mut Palette<&'static str>, text: &str) {
+        for c in text.chars() {
+            palette.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+    }
+
+    #[test]
+    fn chars_must_appear_in_order() {
+        assert!(fuzzy_score("rchk", "Recheck").is_some());
+        assert!(fuzzy_score("RECH", "recheck").is_some());
+        assert!(fuzzy_score("khcer", "Recheck").is_none());
+        assert_eq!(fuzzy_score("", "Anything"), Some(0));
+        // Word starts and runs beat scattered letters
+        assert!(fuzzy_score("tag", "Add tags") > fuzzy_score("tag", "Start again"));
+    }
+
+    #[test]
+    fn typing_narrows_and_ranks_the_list() {
+        let mut palette = palette(&["Toggle alt speed", "Add tags", "Delete", "Show log"]);
+        assert_eq!(palette.matches().count(), 4);
+        palette.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        assert_eq!(palette.selected_item(), Some("Add tags"));
+
+        type_text(&mut palette, "s");
+        let labels: Vec<&str> = palette.matches().map(|e| e.label.as_str()).collect();
+        assert_eq!(labels, ["Toggle alt speed", "Show log", "Add tags"]);
+        assert_eq!(palette.selected_item(), Some("Toggle alt speed"));
+
+        type_text(&mut palette, "zz");
+        assert_eq!(palette.selected_item(), None);
+    }
+}
@@ -0,0 +1,68 @@
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Where configs lived before they moved to the platform config directory,
+/// relative to wherever the app was started.
+pub const LEGACY_CONFIG_FILE: &str = "qbittui_config.json";
+
+const APP_DIR: &str = "qbittui";
+
+static CONFIG_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+static CONFIG_READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Uses `path` as the config file for the rest of the run (`--config`).
+pub fn set_config_file(path: PathBuf) {
+    let _ = CONFIG_OVERRIDE.set(path);
+}
+
+pub fn config_override() -> Option<&'static PathBuf> {
+    CONFIG_OVERRIDE.get()
+}
+
+/// Leaves the config file alone for the rest of the run (`--demo`).
+pub fn set_config_read_only() {
+    CONFIG_READ_ONLY.store(true, Ordering::Relaxed);
+}
+
+pub fn config_read_only() -> bool {
+    CONFIG_READ_ONLY.load(Ordering::Relaxed)
+}
+
+#[cfg(test)]
+thread_local! {
+    static TEST_CONFIG_FILE: std::cell::RefCell<Option<PathBuf>> = const { std::cell::RefCell::new(None) };
+}
+
+/// Uses `path` as the config file for the rest of the calling test. Unlike
+/// `set_config_file` it only holds on this thread, so tests running
+/// alongside keep their own.
+#[cfg(test)]
+pub fn set_test_config_file(path: PathBuf) {
+    TEST_CONFIG_FILE.set(Some(path));
+}
+
+/// `~/.config/qbittui/config.json` on Linux, the Application Support or
+/// AppData equivalent elsewhere, unless `--config` says otherwise.
+pub fn config_file() -> PathBuf {
+    #[cfg(test)]
+    if let Some(path) = TEST_CONFIG_FILE.with_borrow(Clone::clone) {
+        return path;
+    }
+    if let Some(path) = config_override() {
+        return path.clone();
+    }
+    match dirs::config_dir() {
+        Some(dir) => dir.join(APP_DIR).join("config.json"),
+        None => PathBuf::from(LEGACY_CONFIG_FILE),
+    }
+}
+
+/// The debug log, in the XDG state directory on Linux and the cache directory
+/// on other platforms, unless the config names another file.
+pub fn default_log_file() -> PathBuf {
+    match dirs::state_dir().or_else(dirs::cache_dir) {
+        Some(dir) => dir.join(APP_DIR).join("debug.log"),
+        None => PathBuf::from("qbittui_debug.log"),
+    }
+}
@@ -0,0 +1,96 @@
+use crate::models::Peer;
+use crate::text_input::TextInput;
+use std::net::SocketAddr;
+
+/// The `ip:port` addresses in `text`, separated by spaces or commas, or the
+/// first part that isn't one.
+pub fn parse_addresses(text: &str) -> Result<Vec<String>, String> {
+    text.split([' ', ','])
+        .filter(|part| !part.is_empty())
+        .map(|part| match part.parse::<SocketAddr>() {
+            Ok(_) => Ok(part.to_string()),
+            Err(_) => Err(format!(
+                "\"{part}\" is not an ip:port address ([…] around IPv6 ones)"
+            )),
+        })
+        .collect()
+}
+
+/// The Peers tab of the detail view.
+#[derive(Debug, Default)]
+pub struct PeerPanel {
+    /// Torrent the peers belong to
+    pub hash: String,
+    pub peers: Vec<Peer>,
+    pub selected: usize,
+    /// Addresses of peers to connect to, while the input is open
+    pub new_peers: Option<TextInput>,
+    /// Waiting for y/n before banning the highlighted peer
+    pub confirm_ban: bool,
+    /// A reload is in flight, so polls don't pile up behind a slow server
+    pub loading: bool,
+}
+
+impl PeerPanel {
+    /// Replaces the peers, keeping the same one highlighted when it's still
+    /// connected.
+    pub fn set_peers(&mut self, peers: Vec<Peer>) {
+        let selected = self.selected_peer().map(|p| p.address.clone());
+        self.peers = peers;
+        self.selected = selected
+            .and_then(|address| self.peers.iter().position(|p| p.address == address))
+            .unwrap_or(self.selected)
+            .min(self.peers.len().saturating_sub(1));
+    }
+
+    pub fn selected_peer(&self) -> Option<&Peer> {
+        self.peers.get(self.selected)
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let last = self.peers.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer(address: &str) -> Peer {
+        Peer {
+            address: address.to_string(),
+            ..Peer::default()
+        }
+    }
+
+    #[test]
+    fn addresses_need_a_port() {
+        assert_eq!(
+            parse_addresses("10.0.0.2:6881, [2001:db8::1]:51413"),
+            Ok(vec![
+                "10.0.0.2:6881".to_string(),
+                "[2001:db8::1]:51413".to_string()
+            ])
+        );
+        assert!(parse_addresses("10.0.0.2").is_err());
+        assert!(parse_addresses("tracker.example:6881").is_err());
+        assert_eq!(parse_addresses("  "), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn a_banned_peer_leaves_the_highlight_nearby() {
+        let mut panel = PeerPanel::default();
+        panel.set_peers(vec![peer("a:1"), peer("b:1"), peer("c:1")]);
+        panel.move_selection(2);
+        // Reordered, the same peer stays highlighted
+        panel.set_peers(vec![peer("c:1"), peer("a:1"), peer("b:1")]);
+        assert_eq!(panel.selected_peer().unwrap().address, "c:1");
+        // Gone, the highlight stays put or moves up to the last row
+        panel.set_peers(vec![peer("a:1"), peer("b:1")]);
+        assert_eq!(panel.selected, 0);
+        panel.move_selection(1);
+        panel.set_peers(vec![peer("a:1")]);
+        assert_eq!(panel.selected_peer().unwrap().address, "a:1");
+    }
+}
@@ -0,0 +1,127 @@
+/// State of one cell of the piece map, summing up the pieces it covers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PieceCell {
+    Missing,
+    /// Some of the pieces are done, none are being downloaded
+    Partial,
+    Downloading,
+    Done,
+}
+
+/// The piece states of the torrent in the detail view, from
+/// `/torrents/pieceStates` (0 missing, 1 downloading, 2 done). Kept as
+/// running counts so any cell's summary costs two lookups, however many
+/// pieces it covers.
+#[derive(Debug, Default)]
+pub struct PieceMap {
+    /// Torrent the pieces belong to
+    pub hash: String,
+    /// `done[i]` is how many of the first `i` pieces are done
+    done: Vec<u32>,
+    downloading: Vec<u32>,
+    /// A reload is in flight, so polls don't pile up behind a slow server
+    pub loading: bool,
+}
+
+impl PieceMap {
+    pub fn new(hash: String) -> Self {
+        Self {
+            hash,
+            ..Self::default()
+        }
+    }
+
+    pub fn set_states(&mut self, states: &[u8]) {
+        self.done.clear();
+        self.downloading.clear();
+        self.done.reserve(states.len() + 1);
+        self.downloading.reserve(states.len() + 1);
+        let (mut done, mut downloading) = (0, 0);
+        self.done.push(0);
+        self.downloading.push(0);
+        for state in states {
+            match state {
+                1 => downloading += 1,
+                2 => done += 1,
+                _ => {}
+            }
+            self.done.push(done);
+            self.downloading.push(downloading);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.done.len().saturating_sub(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Summary of cell `index` when the pieces are spread over `cells`
+    /// cells. With fewer pieces than cells, each piece spans several.
+    pub fn cell(&self, index: usize, cells: usize) -> PieceCell {
+        let len = self.len();
+        if len == 0 || cells == 0 {
+            return PieceCell::Missing;
+        }
+        let start = index * len / cells;
+        let end = ((index + 1) * len / cells).clamp(start + 1, len);
+        let done = self.done[end] - self.done[start];
+        let downloading = self.downloading[end] - self.downloading[start];
+        if done as usize == end - start {
+            PieceCell::Done
+        } else if downloading > 0 {
+            PieceCell::Downloading
+        } else if done > 0 {
+            PieceCell::Partial
+        } else {
+            PieceCell::Missing
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One char per cell: # done, + partial, > downloading, . missing
+    fn bar(map: &PieceMap, cells: usize) -> String {
+        (0..cells)
+            .map(|i| match map.cell(i, cells) {
+                PieceCell::Done => '#',
+                PieceCell::Partial => '+',
+                PieceCell::Downloading => '>',
+                PieceCell::Missing => '.',
+            })
+            .collect()
+    }
+
+    #[test]
+    fn cells_sum_up_their_pieces() {
+        let mut map = PieceMap::default();
+        map.set_states(&[2, 2, 2, 0, 2, 1, 0, 0]);
+        assert_eq!(map.len(), 8);
+        assert_eq!(bar(&map, 4), "#+>.");
+        assert_eq!(bar(&map, 1), ">");
+        // Fewer pieces than cells stretches them
+        assert_eq!(bar(&map, 16), "######..##>>....");
+
+        map.set_states(&[]);
+        assert!(map.is_empty());
+        assert_eq!(map.cell(0, 10), PieceCell::Missing);
+    }
+
+    #[test]
+    fn large_torrents_bucket_evenly() {
+        let mut map = PieceMap::default();
+        let states: Vec<u8> = (0..300_000)
+            .map(|i| if i < 150_000 { 2 } else { 0 })
+            .collect();
+        map.set_states(&states);
+        assert_eq!(
+            bar(&map, 80),
+            format!("{}{}", "#".repeat(40), ".".repeat(40))
+        );
+    }
+}
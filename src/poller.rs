@@ -0,0 +1,260 @@
+use crate::api::ApiError;
+use crate::backend::TorrentApi;
+use crate::event::AppEvent;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Notify, mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+enum PollOutcome {
+    Done,
+    ConnectionLost,
+    // The main loop stopped listening
+    Closed,
+}
+
+/// When the poller runs on its own; a manual refresh always goes through.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Schedule {
+    /// `None` polls only on request
+    interval: Option<Duration>,
+    paused: bool,
+}
+
+/// Fetches `/sync/maindata` deltas in the background so slow requests never
+/// hold up input handling.
+pub struct Poller {
+    refresh: Arc<Notify>,
+    schedule: watch::Sender<Schedule>,
+    cancel: CancellationToken,
+    task: Option<JoinHandle<()>>,
+}
+
+impl Poller {
+    /// Starts polling every `interval`, or only on [`Poller::refresh_now`]
+    /// when it is `None`.
+    pub fn spawn(
+        client: Arc<dyn TorrentApi>,
+        interval: Option<Duration>,
+        sender: mpsc::UnboundedSender<AppEvent>,
+    ) -> Self {
+        let refresh = Arc::new(Notify::new());
+        let cancel = CancellationToken::new();
+        let (schedule, mut schedule_changes) = watch::channel(Schedule {
+            interval,
+            paused: false,
+        });
+
+        let task = tokio::spawn({
+            let refresh = refresh.clone();
+            let token = cancel.clone();
+            async move {
+                let mut rid = 0;
+                let mut backoff = None;
+
+                'polling: loop {
+                    let outcome = tokio::select! {
+                        _ = token.cancelled() => break,
+                        outcome = poll_once(client.as_ref(), &sender, &mut rid) => outcome,
+                    };
+                    let polled_at = Instant::now();
+
+                    match outcome {
+                        PollOutcome::Closed => break,
+                        PollOutcome::Done => backoff = None,
+                        PollOutcome::ConnectionLost => {
+                            let retry_in = next_backoff(backoff);
+                            backoff = Some(retry_in);
+                            if sender.send(AppEvent::ConnectionLost { retry_in }).is_err() {
+                                break;
+                            }
+                        }
+                    }
+
+                    // A manual refresh skips both the interval and the backoff.
+                    // Schedule changes recompute the deadline from the last
+                    // poll, so resuming after a long pause polls right away.
+                    loop {
+                        let schedule = *schedule_changes.borrow_and_update();
+                        let delay = match schedule {
+                            Schedule { paused: true, .. } => None,
+                            Schedule { interval, .. } => backoff.or(interval),
+                        };
+                        let wait = async {
+                            match delay {
+                                Some(delay) => tokio::time::sleep_until(polled_at + delay).await,
+                                None => std::future::pending().await,
+                            }
+                        };
+
+                        tokio::select! {
+                            _ = token.cancelled() => break 'polling,
+                            _ = wait => break,
+                            _ = refresh.notified() => break,
+                            changed = schedule_changes.changed() => {
+                                if changed.is_err() {
+                                    break 'polling;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        Self {
+            refresh,
+            schedule,
+            cancel,
+            task: Some(task),
+        }
+    }
+
+    /// Asks for an immediate poll, e.g. after an action changed torrent state.
+    pub fn refresh_now(&self) {
+        self.refresh.notify_one();
+    }
+
+    pub fn set_interval(&self, interval: Option<Duration>) {
+        self.schedule.send_if_modified(|schedule| {
+            let changed = schedule.interval != interval;
+            schedule.interval = interval;
+            changed
+        });
+    }
+
+    /// Holds off automatic polls, e.g. while a dialog is open.
+    pub fn set_paused(&self, paused: bool) {
+        self.schedule.send_if_modified(|schedule| {
+            let changed = schedule.paused != paused;
+            schedule.paused = paused;
+            changed
+        });
+    }
+
+    /// Stops polling and waits for an in-flight request to be abandoned.
+    pub async fn close(&mut self) {
+        self.cancel.cancel();
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+    }
+}
+
+impl Drop for Poller {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+// Doubles the retry delay after each consecutive connection failure
+fn next_backoff(current: Option<Duration>) -> Duration {
+    current.map_or(INITIAL_BACKOFF, |delay| (delay * 2).min(MAX_BACKOFF))
+}
+
+// Deltas are applied by the app in the order they are sent, so tracking `rid`
+// here is enough.
+async fn poll_once(
+    client: &dyn TorrentApi,
+    sender: &mpsc::UnboundedSender<AppEvent>,
+    rid: &mut i64,
+) -> PollOutcome {
+    let event = match client.sync_maindata(*rid).await {
+        Ok(data) => {
+            *rid = data.rid;
+            AppEvent::MainDataUpdated(Box::new(data))
+        }
+        Err(ApiError::Network(..)) => {
+            return PollOutcome::ConnectionLost;
+        }
+        Err(e) => AppEvent::ApiError(format!("Failed to fetch torrents: {e}")),
+    };
+
+    if sender.send(event).is_ok() {
+        PollOutcome::Done
+    } else {
+        PollOutcome::Closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{ConnectionOptions, QBittorrentClient};
+    use url::Url;
+
+    async fn next_error(receiver: &mut mpsc::UnboundedReceiver<AppEvent>) -> String {
+        let event = tokio::time::timeout(Duration::from_secs(5), receiver.recv())
+            .await
+            .expect("poller should report within the timeout")
+            .expect("channel should stay open");
+        match event {
+            AppEvent::ApiError(message) => message,
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_a_minute() {
+        let mut delay = None;
+        let delays: Vec<u64> = (0..7)
+            .map(|_| {
+                delay = Some(next_backoff(delay));
+                delay.unwrap().as_secs()
+            })
+            .collect();
+        assert_eq!(delays, [2, 4, 8, 16, 32, 60, 60]);
+    }
+
+    #[tokio::test]
+    async fn refresh_now_polls_before_the_interval() {
+        let client = QBittorrentClient::new(
+            Url::parse("http://127.0.0.1:9").unwrap(),
+            &ConnectionOptions::default(),
+        );
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let mut poller = Poller::spawn(Arc::new(client), Some(Duration::from_secs(3600)), sender);
+
+        // The first poll runs immediately, the second only when asked for
+        assert!(next_error(&mut receiver).await.contains("torrents"));
+        poller.refresh_now();
+        assert!(next_error(&mut receiver).await.contains("torrents"));
+
+        poller.close().await;
+        assert!(receiver.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn pausing_holds_off_polls_until_resumed() {
+        let client = QBittorrentClient::new(
+            Url::parse("http://127.0.0.1:9").unwrap(),
+            &ConnectionOptions::default(),
+        );
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let mut poller = Poller::spawn(Arc::new(client), Some(Duration::from_millis(100)), sender);
+        assert!(next_error(&mut receiver).await.contains("torrents"));
+
+        poller.set_paused(true);
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        while receiver.try_recv().is_ok() {}
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert!(receiver.try_recv().is_err());
+
+        // The interval has long passed, so resuming polls straight away
+        poller.set_paused(false);
+        assert!(next_error(&mut receiver).await.contains("torrents"));
+
+        poller.set_interval(None);
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        while receiver.try_recv().is_ok() {}
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert!(receiver.try_recv().is_err());
+
+        poller.close().await;
+    }
+}
@@ -0,0 +1,70 @@
+use std::time::Duration;
+use url::Url;
+
+/// Checkers answer with a page or a line of text; this much of it is shown.
+const MAX_ANSWER_CHARS: usize = 200;
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The URL asking the checker at `template` about `port`, which stands in
+/// for `{port}`.
+pub fn check_url(template: &str, port: i64) -> Result<Url, String> {
+    Url::parse(&template.replace("{port}", &port.to_string()))
+        .map_err(|e| format!("port_check_url \"{template}\" isn't a URL: {e}"))
+}
+
+/// Asks the checker, returning the first line of its answer.
+pub async fn check(url: Url) -> Result<String, String> {
+    let client = reqwest::Client::builder()
+        .timeout(CHECK_TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let body = client
+        .get(url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| format!("Port check failed: {e}"))?
+        .text()
+        .await
+        .map_err(|e| format!("Port check failed: {e}"))?;
+    let answer = body
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("(empty answer)");
+    Ok(answer.chars().take(MAX_ANSWER_CHARS).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_port_goes_where_the_template_says() {
+        assert_eq!(
+            check_url("https://check.example/?port={port}", 51413)
+                .unwrap()
+                .as_str(),
+            "https://check.example/?port=51413"
+        );
+        assert!(check_url("not a url {port}", 1).is_err());
+    }
+
+    #[tokio::test]
+    async fn the_first_line_of_the_answer_is_kept() {
+        use wiremock::matchers::{method, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("port", "6881"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("\n  Port 6881 is open\nbye"))
+            .mount(&server)
+            .await;
+        let url = check_url(&format!("{}/?port={{port}}", server.uri()), 6881).unwrap();
+        assert_eq!(check(url).await.unwrap(), "Port 6881 is open");
+
+        let missing = check_url(&format!("{}/nothing", server.uri()), 6881).unwrap();
+        assert!(check(missing).await.is_err());
+    }
+}
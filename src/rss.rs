@@ -0,0 +1,146 @@
+use crate::models::{RssArticle, RssFeed, RssRule};
+use crate::text_input::TextInput;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum RssFocus {
+    #[default]
+    Feeds,
+    Articles,
+    Rules,
+}
+
+/// State of the RSS screen: feeds on the left, the selected feed's articles
+/// or the auto-downloading rules on the right.
+#[derive(Debug, Default)]
+pub struct RssView {
+    pub feeds: Vec<RssFeed>,
+    pub rules: Vec<(String, RssRule)>,
+    pub focus: RssFocus,
+    pub feed_index: usize,
+    pub article_index: usize,
+    pub rule_index: usize,
+    /// URL of a feed being added, while its input is open
+    pub new_feed: Option<TextInput>,
+    /// Waiting for y/n before removing the selected feed
+    pub confirm_remove: bool,
+    /// A reload is in flight, so polls don't pile up behind a slow server
+    pub loading: bool,
+}
+
+impl RssView {
+    /// Replaces the feeds, keeping the same feed selected when it still exists.
+    pub fn set_feeds(&mut self, feeds: Vec<RssFeed>) {
+        let selected = self.selected_feed().map(|f| f.path.clone());
+        self.feeds = feeds;
+        self.feed_index = selected
+            .and_then(|path| self.feeds.iter().position(|f| f.path == path))
+            .unwrap_or(0)
+            .min(self.feeds.len().saturating_sub(1));
+        let articles = self.selected_feed().map_or(0, |f| f.articles.len());
+        self.article_index = self.article_index.min(articles.saturating_sub(1));
+    }
+
+    pub fn selected_feed(&self) -> Option<&RssFeed> {
+        self.feeds.get(self.feed_index)
+    }
+
+    pub fn selected_article(&self) -> Option<&RssArticle> {
+        self.selected_feed()?.articles.get(self.article_index)
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let (index, len) = match self.focus {
+            RssFocus::Feeds => (&mut self.feed_index, self.feeds.len()),
+            RssFocus::Articles => (
+                &mut self.article_index,
+                self.feeds
+                    .get(self.feed_index)
+                    .map_or(0, |f| f.articles.len()),
+            ),
+            RssFocus::Rules => (&mut self.rule_index, self.rules.len()),
+        };
+        let before = *index;
+        *index = index
+            .saturating_add_signed(delta)
+            .min(len.saturating_sub(1));
+        // Another feed has other articles
+        if self.focus == RssFocus::Feeds && self.feed_index != before {
+            self.article_index = 0;
+        }
+    }
+
+    /// Marks an article read locally, ahead of the next reload.
+    pub fn mark_read(&mut self, feed: usize, article: usize) {
+        if let Some(article) = self
+            .feeds
+            .get_mut(feed)
+            .and_then(|f| f.articles.get_mut(article))
+        {
+            article.is_read = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::parse_items;
+    use serde_json::Value;
+
+    fn items() -> Value {
+        serde_json::json!({
+            "Linux": {
+                "Ubuntu": {
+                    "uid": "{1}",
+                    "url": "https://ubuntu.example/rss",
+                    "title": "Ubuntu releases",
+                    "isLoading": false,
+                    "hasError": false,
+                    "articles": [
+                        {"id": "a", "title": "24.04", "date": "Thu, 25 Apr 2024",
+                         "torrentURL": "https://ubuntu.example/24.04.torrent", "isRead": true},
+                        {"id": "b", "title": "24.10", "date": "Thu, 10 Oct 2024",
+                         "torrentURL": "magnet:?xt=urn:btih:b"}
+                    ]
+                },
+                "Arch": {"uid": "{2}", "url": "https://arch.example/rss"}
+            },
+            "Podcasts": {"uid": "{3}", "url": "https://pod.example/rss", "hasError": true}
+        })
+    }
+
+    #[test]
+    fn folders_fold_into_feed_paths() {
+        let feeds = parse_items(&items());
+        let paths: Vec<&str> = feeds.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["Linux\\Arch", "Linux\\Ubuntu", "Podcasts"]);
+        assert_eq!(feeds[1].name(), "Ubuntu");
+        assert_eq!(feeds[1].unread(), 1);
+        assert_eq!(
+            feeds[1].articles[1].torrent_url.as_deref(),
+            Some("magnet:?xt=urn:btih:b")
+        );
+        assert!(feeds[2].has_error);
+        assert!(feeds[0].articles.is_empty());
+    }
+
+    #[test]
+    fn reloads_keep_the_selected_feed() {
+        let mut view = RssView::default();
+        view.set_feeds(parse_items(&items()));
+        view.move_selection(1);
+        view.focus = RssFocus::Articles;
+        view.move_selection(5);
+        assert_eq!(view.selected_article().unwrap().id, "b");
+
+        // A new feed sorted in front of the selected one
+        let mut items = items();
+        items["Linux"]["Alpine"] = serde_json::json!({"uid": "{4}", "url": "https://alpine"});
+        view.set_feeds(parse_items(&items));
+        assert_eq!(view.selected_feed().unwrap().name(), "Ubuntu");
+        assert_eq!(view.selected_article().unwrap().id, "b");
+
+        view.mark_read(view.feed_index, 1);
+        assert_eq!(view.selected_feed().unwrap().unread(), 0);
+    }
+}
@@ -0,0 +1,147 @@
+use crate::models::{SearchPlugin, SearchResult, SearchResults};
+use crate::text_input::TextInput;
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SearchFocus {
+    #[default]
+    Query,
+    Results,
+}
+
+/// State of the plugin search screen. Results stream in from a background
+/// task while the job runs on the server.
+#[derive(Debug, Default)]
+pub struct SearchView {
+    pub query: TextInput,
+    pub results: Vec<SearchResult>,
+    pub selected: usize,
+    pub focus: SearchFocus,
+    /// Server-side id of the current job
+    pub job: Option<i64>,
+    pub running: bool,
+    /// Why searching isn't possible, e.g. no plugins are installed
+    pub message: Option<String>,
+    /// Stops the task polling the current job
+    pub cancel: Option<CancellationToken>,
+}
+
+impl SearchView {
+    /// Adds a page of results for `job`, ignoring pages from a job that has
+    /// since been replaced.
+    pub fn append(&mut self, job: i64, page: SearchResults) {
+        if self.job != Some(job) {
+            return;
+        }
+        self.running = page.is_running();
+        self.results.extend(page.results);
+    }
+
+    /// Forgets the current job, returning its id so it can be removed from
+    /// the server.
+    pub fn stop(&mut self) -> Option<i64> {
+        if let Some(cancel) = self.cancel.take() {
+            cancel.cancel();
+        }
+        self.running = false;
+        self.job.take()
+    }
+
+    pub fn selected_result(&self) -> Option<&SearchResult> {
+        self.results.get(self.selected)
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let last = self.results.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+}
+
+/// Names of the enabled plugins, or a message explaining why there are none.
+pub fn enabled_plugins(plugins: &[SearchPlugin]) -> Result<Vec<&str>, String> {
+    if plugins.is_empty() {
+        return Err(
+            "No search plugins are installed. Add some in qBittorrent under \
+                    Search → Search plugins, then try again."
+                .to_string(),
+        );
+    }
+    let enabled: Vec<&str> = plugins
+        .iter()
+        .filter(|p| p.enabled)
+        .map(|p| p.name.as_str())
+        .collect();
+    if enabled.is_empty() {
+        return Err(
+            "All search plugins are disabled. Enable one in qBittorrent under \
+                    Search → Search plugins, then try again."
+                .to_string(),
+        );
+    }
+    Ok(enabled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(names: &[&str], status: &str) -> SearchResults {
+        SearchResults {
+            results: names
+                .iter()
+                .map(|name| SearchResult {
+                    file_name: name.to_string(),
+                    ..SearchResult::default()
+                })
+                .collect(),
+            status: status.to_string(),
+            total: names.len() as i64,
+        }
+    }
+
+    #[test]
+    fn results_of_replaced_jobs_are_dropped() {
+        let mut view = SearchView {
+            job: Some(2),
+            running: true,
+            ..SearchView::default()
+        };
+        view.append(1, page(&["old"], "Running"));
+        view.append(2, page(&["a", "b"], "Running"));
+        view.append(2, page(&["c"], "Stopped"));
+        let names: Vec<&str> = view.results.iter().map(|r| r.file_name.as_str()).collect();
+        assert_eq!(names, ["a", "b", "c"]);
+        assert!(!view.running);
+
+        assert_eq!(view.stop(), Some(2));
+        view.append(2, page(&["late"], "Running"));
+        assert_eq!(view.results.len(), 3);
+    }
+
+    #[test]
+    fn explains_missing_or_disabled_plugins() {
+        let plugin = |name: &str, enabled| SearchPlugin {
+            name: name.to_string(),
+            enabled,
+        };
+        assert!(enabled_plugins(&[]).unwrap_err().contains("installed"));
+        assert!(
+            enabled_plugins(&[plugin("a", false)])
+                .unwrap_err()
+                .contains("disabled")
+        );
+        assert_eq!(
+            enabled_plugins(&[plugin("a", false), plugin("b", true)]).unwrap(),
+            ["b"]
+        );
+
+        let results: SearchResults = serde_json::from_str(
+            r#"{"results":[{"fileName":"Ubuntu","fileUrl":"magnet:?xt=1","fileSize":1024,
+                "nbSeeders":12,"nbLeechers":3,"siteUrl":"https://example.org",
+                "descrLink":"https://example.org/1"}],"status":"Running","total":1}"#,
+        )
+        .unwrap();
+        assert!(results.is_running());
+        assert_eq!(results.results[0].nb_seeders, 12);
+    }
+}
@@ -0,0 +1,407 @@
+use crate::models::Preferences;
+use crate::text_input::TextInput;
+use serde_json::{Map, Value};
+
+const SCHEDULER_DAYS: &[&str] = &[
+    "Every day",
+    "Weekdays",
+    "Weekends",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+const ENCRYPTION_MODES: &[&str] = &[
+    "Prefer encryption",
+    "Require encryption",
+    "Disable encryption",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldKind {
+    Text,
+    /// A whole number between the bounds
+    Number(i64, i64),
+    Bool,
+    /// A speed in KiB/s, stored in bytes per second
+    Rate,
+    /// One of the labels, stored as its index
+    Choice(&'static [&'static str]),
+}
+
+/// One editable preference, found by its key in the preferences JSON.
+#[derive(Debug, Clone, Copy)]
+pub struct Field {
+    pub group: &'static str,
+    pub label: &'static str,
+    pub key: &'static str,
+    pub kind: FieldKind,
+}
+
+const fn field(
+    group: &'static str,
+    label: &'static str,
+    key: &'static str,
+    kind: FieldKind,
+) -> Field {
+    Field {
+        group,
+        label,
+        key,
+        kind,
+    }
+}
+
+pub const FIELDS: &[Field] = &[
+    field(
+        "Downloads",
+        "Default save path",
+        "save_path",
+        FieldKind::Text,
+    ),
+    field(
+        "Downloads",
+        "Keep incomplete torrents elsewhere",
+        "temp_path_enabled",
+        FieldKind::Bool,
+    ),
+    field(
+        "Downloads",
+        "Incomplete torrents path",
+        "temp_path",
+        FieldKind::Text,
+    ),
+    field(
+        "Queueing",
+        "Queueing enabled",
+        "queueing_enabled",
+        FieldKind::Bool,
+    ),
+    field(
+        "Queueing",
+        "Max active downloads",
+        "max_active_downloads",
+        FieldKind::Number(-1, 9999),
+    ),
+    field(
+        "Queueing",
+        "Max active uploads",
+        "max_active_uploads",
+        FieldKind::Number(-1, 9999),
+    ),
+    field(
+        "Queueing",
+        "Max active torrents",
+        "max_active_torrents",
+        FieldKind::Number(-1, 9999),
+    ),
+    field("Speed", "Download limit", "dl_limit", FieldKind::Rate),
+    field("Speed", "Upload limit", "up_limit", FieldKind::Rate),
+    field(
+        "Speed",
+        "Alternative download limit",
+        "alt_dl_limit",
+        FieldKind::Rate,
+    ),
+    field(
+        "Speed",
+        "Alternative upload limit",
+        "alt_up_limit",
+        FieldKind::Rate,
+    ),
+    field(
+        "Scheduler",
+        "Use alternative limits on a schedule",
+        "scheduler_enabled",
+        FieldKind::Bool,
+    ),
+    field(
+        "Scheduler",
+        "From hour",
+        "schedule_from_hour",
+        FieldKind::Number(0, 23),
+    ),
+    field(
+        "Scheduler",
+        "From minute",
+        "schedule_from_min",
+        FieldKind::Number(0, 59),
+    ),
+    field(
+        "Scheduler",
+        "To hour",
+        "schedule_to_hour",
+        FieldKind::Number(0, 23),
+    ),
+    field(
+        "Scheduler",
+        "To minute",
+        "schedule_to_min",
+        FieldKind::Number(0, 59),
+    ),
+    field(
+        "Scheduler",
+        "Days",
+        "scheduler_days",
+        FieldKind::Choice(SCHEDULER_DAYS),
+    ),
+    field(
+        "Connection",
+        "Listening port",
+        "listen_port",
+        FieldKind::Number(0, 65535),
+    ),
+    field(
+        "Connection",
+        "Encryption",
+        "encryption",
+        FieldKind::Choice(ENCRYPTION_MODES),
+    ),
+];
+
+/// State of the settings screen: the preferences as loaded and as edited,
+/// both as JSON objects so every field is handled the same way.
+#[derive(Debug, Default)]
+pub struct SettingsView {
+    original: Map<String, Value>,
+    values: Map<String, Value>,
+    pub selected: usize,
+    /// The text of the field being edited, while its input is open
+    pub editing: Option<TextInput>,
+    /// Why the last edit was rejected
+    pub error: Option<String>,
+}
+
+impl SettingsView {
+    pub fn new(preferences: &Preferences) -> Self {
+        let values = match serde_json::to_value(preferences) {
+            Ok(Value::Object(values)) => values,
+            _ => Map::new(),
+        };
+        Self {
+            original: values.clone(),
+            values,
+            ..Self::default()
+        }
+    }
+
+    pub fn selected_field(&self) -> Field {
+        FIELDS[self.selected.min(FIELDS.len() - 1)]
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(FIELDS.len() - 1);
+    }
+
+    pub fn is_changed(&self, field: &Field) -> bool {
+        self.values.get(field.key) != self.original.get(field.key)
+    }
+
+    /// The field's value as shown on screen and pre-filled when editing.
+    pub fn display(&self, field: &Field) -> String {
+        let value = self.values.get(field.key);
+        let number = value.and_then(Value::as_i64).unwrap_or(0);
+        match field.kind {
+            FieldKind::Text => value
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            FieldKind::Number(..) => number.to_string(),
+            FieldKind::Bool => {
+                let on = value.and_then(Value::as_bool).unwrap_or(false);
+                if on { "Yes" } else { "No" }.to_string()
+            }
+            FieldKind::Rate if number <= 0 => "Unlimited".to_string(),
+            FieldKind::Rate => format!("{} KiB/s", number / 1024),
+            FieldKind::Choice(labels) => labels
+                .get(number as usize)
+                .copied()
+                .unwrap_or("?")
+                .to_string(),
+        }
+    }
+
+    /// Flips a yes/no field or moves a choice to its next option. Returns
+    /// false for fields that are typed in instead.
+    pub fn toggle(&mut self) -> bool {
+        let field = self.selected_field();
+        let value = match field.kind {
+            FieldKind::Bool => {
+                let on = self.values.get(field.key).and_then(Value::as_bool);
+                Value::Bool(!on.unwrap_or(false))
+            }
+            FieldKind::Choice(labels) => {
+                let index = self.values.get(field.key).and_then(Value::as_i64);
+                Value::from((index.unwrap_or(0) + 1) % labels.len() as i64)
+            }
+            _ => return false,
+        };
+        self.values.insert(field.key.to_string(), value);
+        true
+    }
+
+    /// Flips or cycles the selected field, or opens its input when it's
+    /// typed in.
+    pub fn activate(&mut self) {
+        if !self.toggle() {
+            self.start_editing();
+        }
+    }
+
+    /// Opens the input for the selected text or number field.
+    pub fn start_editing(&mut self) {
+        let field = self.selected_field();
+        let text = match field.kind {
+            FieldKind::Rate => {
+                let bytes = self.values.get(field.key).and_then(Value::as_i64);
+                (bytes.unwrap_or(0).max(0) / 1024).to_string()
+            }
+            _ => self.display(&field),
+        };
+        self.editing = Some(TextInput::new(text));
+        self.error = None;
+    }
+
+    /// Stores the typed text in the selected field, keeping the input open
+    /// with an error when it isn't valid.
+    pub fn finish_editing(&mut self) {
+        let Some(input) = &self.editing else {
+            return;
+        };
+        let field = self.selected_field();
+        match parse_value(&field, input.value()) {
+            Ok(value) => {
+                self.values.insert(field.key.to_string(), value);
+                self.editing = None;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    /// The changed keys with their new values, to send as the patch.
+    pub fn changes(&self) -> Map<String, Value> {
+        self.values
+            .iter()
+            .filter(|(key, value)| self.original.get(*key) != Some(*value))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    /// Makes the edited values the new baseline once the server has them.
+    pub fn mark_saved(&mut self) {
+        self.original = self.values.clone();
+    }
+}
+
+fn parse_value(field: &Field, text: &str) -> Result<Value, String> {
+    let text = text.trim();
+    let number = || {
+        text.parse::<i64>()
+            .map_err(|_| format!("{} must be a whole number", field.label))
+    };
+    match field.kind {
+        FieldKind::Text => Ok(Value::from(text)),
+        FieldKind::Number(min, max) => {
+            let value = number()?;
+            if !(min..=max).contains(&value) {
+                return Err(format!("{} must be between {min} and {max}", field.label));
+            }
+            Ok(Value::from(value))
+        }
+        FieldKind::Rate => {
+            if text.is_empty() {
+                return Ok(Value::from(0));
+            }
+            let value = number()?;
+            if value < 0 {
+                return Err(format!("{} can't be negative", field.label));
+            }
+            value
+                .checked_mul(1024)
+                .map(Value::from)
+                .ok_or_else(|| format!("{} is too large", field.label))
+        }
+        FieldKind::Bool | FieldKind::Choice(_) => {
+            Err(format!("{} is changed with Space", field.label))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn select(view: &mut SettingsView, key: &str) {
+        view.selected = FIELDS.iter().position(|f| f.key == key).unwrap();
+    }
+
+    fn edit(view: &mut SettingsView, key: &str, text: &str) {
+        select(view, key);
+        view.start_editing();
+        view.editing.as_mut().unwrap().set(text);
+        view.finish_editing();
+    }
+
+    #[test]
+    fn only_changed_keys_are_saved() {
+        let preferences: Preferences = serde_json::from_str(
+            r#"{"save_path":"/downloads","dl_limit":0,"listen_port":6881,
+                "encryption":0,"scheduler_enabled":false,"web_ui_port":8080}"#,
+        )
+        .unwrap();
+        let mut view = SettingsView::new(&preferences);
+        assert!(view.changes().is_empty());
+
+        edit(&mut view, "dl_limit", "512");
+        edit(&mut view, "listen_port", "51413");
+        select(&mut view, "scheduler_enabled");
+        assert!(view.toggle());
+        select(&mut view, "encryption");
+        view.toggle();
+        // Changing a value back drops it from the patch
+        edit(&mut view, "save_path", "/elsewhere");
+        edit(&mut view, "save_path", "/downloads");
+
+        let changes = Value::Object(view.changes());
+        assert_eq!(
+            changes,
+            serde_json::json!({
+                "dl_limit": 524288,
+                "listen_port": 51413,
+                "scheduler_enabled": true,
+                "encryption": 1,
+            })
+        );
+        // Preferences the screen doesn't know never round-trip
+        assert!(changes.get("web_ui_port").is_none());
+
+        view.mark_saved();
+        assert!(view.changes().is_empty());
+    }
+
+    #[test]
+    fn invalid_numbers_keep_the_input_open() {
+        let mut view = SettingsView::new(&Preferences::default());
+        edit(&mut view, "schedule_from_hour", "24");
+        assert!(view.editing.is_some());
+        assert!(view.error.as_deref().unwrap().contains("between 0 and 23"));
+
+        edit(&mut view, "up_limit", "fast");
+        assert!(view.error.as_deref().unwrap().contains("whole number"));
+        edit(&mut view, "up_limit", &i64::MAX.to_string());
+        assert!(view.editing.is_some());
+        assert!(view.error.as_deref().unwrap().contains("too large"));
+
+        edit(&mut view, "up_limit", "");
+        assert!(view.editing.is_none());
+        let field = view.selected_field();
+        assert_eq!(view.display(&field), "Unlimited");
+    }
+}
@@ -0,0 +1,203 @@
+use crate::api::Torrent;
+use crate::text_input::TextInput;
+
+/// Limit value meaning "use the global share limits".
+pub const GLOBAL: i64 = -2;
+/// Limit value meaning "no limit".
+pub const UNLIMITED: i64 = -1;
+
+/// A torrent's share limits, in the units `/torrents/setShareLimits` takes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShareLimits {
+    pub ratio: f64,
+    /// Minutes of seeding
+    pub seeding_time: i64,
+    /// Minutes of seeding without any upload
+    pub inactive_seeding_time: i64,
+}
+
+impl ShareLimits {
+    /// The torrent's current limits; servers that don't report one use the
+    /// global limit.
+    pub fn of(torrent: &Torrent) -> Self {
+        Self {
+            ratio: torrent.ratio_limit.unwrap_or(GLOBAL as f64),
+            seeding_time: torrent.seeding_time_limit.unwrap_or(GLOBAL),
+            inactive_seeding_time: torrent.inactive_seeding_time_limit.unwrap_or(GLOBAL),
+        }
+    }
+
+    /// "ratio 2.00, seeding 1440 min, inactive global", for the details view.
+    pub fn summary(&self) -> String {
+        format!(
+            "ratio {}, seeding {}, inactive {}",
+            format_ratio_limit(self.ratio),
+            format_time_limit(self.seeding_time),
+            format_time_limit(self.inactive_seeding_time),
+        )
+    }
+}
+
+pub fn format_ratio_limit(limit: f64) -> String {
+    match sentinel(limit.round() as i64) {
+        Some(name) if limit < 0.0 => name.to_string(),
+        _ => format!("{limit:.2}"),
+    }
+}
+
+pub fn format_time_limit(minutes: i64) -> String {
+    match sentinel(minutes) {
+        Some(name) => name.to_string(),
+        None => format!("{minutes} min"),
+    }
+}
+
+fn sentinel(value: i64) -> Option<&'static str> {
+    match value {
+        GLOBAL => Some("global"),
+        UNLIMITED => Some("unlimited"),
+        _ => None,
+    }
+}
+
+// "global" and "unlimited" (or their numbers) are accepted for every limit
+fn parse_sentinel(text: &str) -> Option<i64> {
+    match text.to_lowercase().as_str() {
+        "global" | "-2" => Some(GLOBAL),
+        "unlimited" | "none" | "-1" => Some(UNLIMITED),
+        _ => None,
+    }
+}
+
+pub fn parse_ratio_limit(text: &str) -> Result<f64, String> {
+    let text = text.trim();
+    if let Some(value) = parse_sentinel(text) {
+        return Ok(value as f64);
+    }
+    match text.parse::<f64>() {
+        Ok(ratio) if ratio.is_finite() && ratio >= 0.0 => Ok(ratio),
+        _ => Err(format!(
+            "Ratio must be a number of 0 or more, \"global\" or \"unlimited\", not \"{text}\""
+        )),
+    }
+}
+
+pub fn parse_time_limit(text: &str) -> Result<i64, String> {
+    let text = text.trim();
+    if let Some(value) = parse_sentinel(text) {
+        return Ok(value);
+    }
+    match text.parse::<i64>() {
+        Ok(minutes) if minutes >= 0 => Ok(minutes),
+        _ => Err(format!(
+            "Times are whole minutes, \"global\" or \"unlimited\", not \"{text}\""
+        )),
+    }
+}
+
+/// The share limits popup, opened from the details view.
+#[derive(Debug, Clone)]
+pub struct ShareLimitsEditor {
+    pub hash: String,
+    /// Ratio, seeding time and inactive seeding time, as typed
+    pub inputs: [TextInput; 3],
+    pub focus: usize,
+    /// Why the limits couldn't be applied
+    pub error: Option<String>,
+}
+
+impl ShareLimitsEditor {
+    pub const LABELS: [&'static str; 3] = [
+        "Ratio",
+        "Seeding time (minutes)",
+        "Inactive seeding time (minutes)",
+    ];
+
+    pub fn new(torrent: &Torrent) -> Self {
+        let limits = ShareLimits::of(torrent);
+        let time = |minutes: i64| sentinel(minutes).map_or(minutes.to_string(), str::to_string);
+        let ratio = if limits.ratio < 0.0 {
+            format_ratio_limit(limits.ratio)
+        } else {
+            limits.ratio.to_string()
+        };
+        Self {
+            hash: torrent.hash.clone(),
+            inputs: [
+                TextInput::new(ratio),
+                TextInput::new(time(limits.seeding_time)),
+                TextInput::new(time(limits.inactive_seeding_time)),
+            ],
+            focus: 0,
+            error: None,
+        }
+    }
+
+    pub fn focused_input(&mut self) -> &mut TextInput {
+        &mut self.inputs[self.focus]
+    }
+
+    pub fn cycle_focus(&mut self, delta: isize) {
+        self.focus = (self.focus as isize + delta).rem_euclid(self.inputs.len() as isize) as usize;
+    }
+
+    pub fn limits(&self) -> Result<ShareLimits, String> {
+        Ok(ShareLimits {
+            ratio: parse_ratio_limit(self.inputs[0].value())?,
+            seeding_time: parse_time_limit(self.inputs[1].value())?,
+            inactive_seeding_time: parse_time_limit(self.inputs[2].value())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_accept_numbers_and_sentinels() {
+        assert_eq!(parse_ratio_limit("1.5"), Ok(1.5));
+        assert_eq!(parse_ratio_limit(" Global "), Ok(-2.0));
+        assert_eq!(parse_ratio_limit("unlimited"), Ok(-1.0));
+        assert_eq!(parse_ratio_limit("-1"), Ok(-1.0));
+        assert!(parse_ratio_limit("-0.5").is_err());
+        assert!(parse_ratio_limit("lots").is_err());
+        assert!(parse_ratio_limit("NaN").is_err());
+
+        assert_eq!(parse_time_limit("1440"), Ok(1440));
+        assert_eq!(parse_time_limit("-2"), Ok(GLOBAL));
+        assert!(parse_time_limit("1.5").is_err());
+        assert!(parse_time_limit("-3").is_err());
+    }
+
+    #[test]
+    fn editor_starts_from_the_current_limits() {
+        let torrent = Torrent {
+            hash: "abc".to_string(),
+            ratio_limit: Some(2.0),
+            seeding_time_limit: Some(UNLIMITED),
+            ..Torrent::default()
+        };
+        let mut editor = ShareLimitsEditor::new(&torrent);
+        let values: Vec<&str> = editor.inputs.iter().map(TextInput::value).collect();
+        assert_eq!(values, ["2", "unlimited", "global"]);
+        assert_eq!(
+            ShareLimits::of(&torrent).summary(),
+            "ratio 2.00, seeding unlimited, inactive global"
+        );
+
+        editor.cycle_focus(-1);
+        assert_eq!(editor.focus, 2);
+        editor.focused_input().set("30");
+        assert_eq!(
+            editor.limits(),
+            Ok(ShareLimits {
+                ratio: 2.0,
+                seeding_time: UNLIMITED,
+                inactive_seeding_time: 30,
+            })
+        );
+        editor.inputs[0].set("fast");
+        assert!(editor.limits().unwrap_err().starts_with("Ratio"));
+    }
+}
@@ -0,0 +1,122 @@
+use crate::api::Torrent;
+use std::collections::VecDeque;
+
+/// Rolling download/upload speed samples, oldest first, for the header
+/// graphs. Once full, each new sample pushes out the oldest one.
+#[derive(Debug, Clone)]
+pub struct SpeedHistory {
+    capacity: usize,
+    download: VecDeque<u64>,
+    upload: VecDeque<u64>,
+}
+
+impl SpeedHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            download: VecDeque::with_capacity(capacity),
+            upload: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn push(&mut self, download: u64, upload: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.download.len() == self.capacity {
+            self.download.pop_front();
+            self.upload.pop_front();
+        }
+        self.download.push_back(download);
+        self.upload.push_back(upload);
+    }
+
+    /// The newest `count` download samples.
+    pub fn download(&self, count: usize) -> Vec<u64> {
+        latest(&self.download, count)
+    }
+
+    /// The newest `count` upload samples.
+    pub fn upload(&self, count: usize) -> Vec<u64> {
+        latest(&self.upload, count)
+    }
+}
+
+/// The highlighted torrent's own speeds, for the detail view. Only one
+/// torrent is followed at a time; moving to another starts over.
+#[derive(Debug, Clone)]
+pub struct TorrentSpeedHistory {
+    hash: Option<String>,
+    history: SpeedHistory,
+}
+
+impl TorrentSpeedHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            hash: None,
+            history: SpeedHistory::new(capacity),
+        }
+    }
+
+    pub fn push(&mut self, torrent: &Torrent) {
+        if self.hash.as_deref() != Some(&torrent.hash) {
+            self.hash = Some(torrent.hash.clone());
+            self.history = SpeedHistory::new(self.history.capacity);
+        }
+        self.history
+            .push(torrent.dlspeed.max(0) as u64, torrent.upspeed.max(0) as u64);
+    }
+
+    /// The samples for `hash`, if it's the torrent being followed.
+    pub fn get(&self, hash: &str) -> Option<&SpeedHistory> {
+        (self.hash.as_deref() == Some(hash)).then_some(&self.history)
+    }
+}
+
+fn latest(samples: &VecDeque<u64>, count: usize) -> Vec<u64> {
+    let skip = samples.len().saturating_sub(count);
+    samples.iter().skip(skip).copied().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_only_the_newest_samples() {
+        let mut history = SpeedHistory::new(3);
+        for i in 1..=5 {
+            history.push(i, i * 10);
+        }
+        assert_eq!(history.download(10), [3, 4, 5]);
+        assert_eq!(history.upload(10), [30, 40, 50]);
+        assert_eq!(history.download(2), [4, 5]);
+    }
+
+    #[test]
+    fn a_torrent_history_starts_over_for_another_torrent() {
+        let torrent = |hash: &str, dlspeed| Torrent {
+            hash: hash.to_string(),
+            dlspeed,
+            upspeed: 1,
+            ..Torrent::default()
+        };
+        let mut history = TorrentSpeedHistory::new(3);
+        history.push(&torrent("a", 10));
+        history.push(&torrent("a", 20));
+        assert_eq!(history.get("a").unwrap().download(10), [10, 20]);
+        assert!(history.get("b").is_none());
+
+        history.push(&torrent("b", 5));
+        assert!(history.get("a").is_none());
+        assert_eq!(history.get("b").unwrap().download(10), [5]);
+        assert_eq!(history.get("b").unwrap().upload(10), [1]);
+    }
+
+    #[test]
+    fn zero_capacity_records_nothing() {
+        let mut history = SpeedHistory::new(0);
+        history.push(1, 1);
+        assert!(history.download(5).is_empty());
+    }
+}
@@ -0,0 +1,117 @@
+use crate::api::Torrent;
+use crate::torrent_state::TorrentState;
+use std::collections::{HashMap, HashSet};
+
+/// When each downloading torrent last moved data, kept across refreshes to
+/// tell a torrent that's been stuck a while from one between two pieces.
+#[derive(Debug, Clone)]
+pub struct StallTracker {
+    /// Hash to the last time, in Unix seconds, it downloaded anything.
+    /// Torrents first seen count from then, so nothing shows as stalled
+    /// right after startup
+    last_active: HashMap<String, i64>,
+    threshold_secs: i64,
+}
+
+impl StallTracker {
+    pub fn new(threshold_secs: i64) -> Self {
+        Self {
+            last_active: HashMap::new(),
+            threshold_secs,
+        }
+    }
+
+    /// Notes which torrents are moving as of `now`. Torrents that stopped
+    /// downloading, and ones that are gone, are forgotten.
+    pub fn observe(&mut self, torrents: &[Torrent], now: i64) {
+        let stallable: HashSet<&str> = torrents
+            .iter()
+            .filter(|t| can_stall(t))
+            .map(|t| t.hash.as_str())
+            .collect();
+        self.last_active
+            .retain(|hash, _| stallable.contains(hash.as_str()));
+        for torrent in torrents.iter().filter(|t| can_stall(t)) {
+            let last = self.last_active.entry(torrent.hash.clone()).or_insert(now);
+            if torrent.dlspeed > 0 {
+                *last = now;
+            }
+        }
+    }
+
+    /// Seconds `torrent` has gone without downloading, once that's past the
+    /// threshold.
+    pub fn stalled_for(&self, torrent: &Torrent, now: i64) -> Option<i64> {
+        let idle = now - self.last_active.get(&torrent.hash)?;
+        (torrent.dlspeed == 0 && idle >= self.threshold_secs).then_some(idle)
+    }
+}
+
+// Queued torrents wait for their turn rather than for peers
+fn can_stall(torrent: &Torrent) -> bool {
+    torrent.state.is_downloading() && torrent.state != TorrentState::QueuedDl
+}
+
+/// "stalled 12m", in the largest unit only so it fits the ETA column.
+pub fn stalled_label(secs: i64) -> String {
+    let (size, unit) = [(86_400, "d"), (3_600, "h"), (60, "m")]
+        .into_iter()
+        .find(|(size, _)| secs >= *size)
+        .unwrap_or((1, "s"));
+    format!("stalled {}{unit}", secs / size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn downloading(hash: &str, dlspeed: i64) -> Torrent {
+        Torrent {
+            hash: hash.to_string(),
+            state: TorrentState::StalledDl,
+            dlspeed,
+            ..Torrent::default()
+        }
+    }
+
+    #[test]
+    fn only_torrents_idle_past_the_threshold_are_stalled() {
+        let mut tracker = StallTracker::new(600);
+        // Already idle at startup, but there's no telling for how long
+        tracker.observe(&[downloading("a", 0), downloading("b", 0)], 1_000);
+        assert_eq!(tracker.stalled_for(&downloading("a", 0), 1_000), None);
+
+        let later = [downloading("a", 0), downloading("b", 10)];
+        tracker.observe(&later, 1_300);
+        assert_eq!(tracker.stalled_for(&later[0], 1_300), None);
+        let queued = Torrent {
+            state: TorrentState::QueuedDl,
+            ..downloading("q", 0)
+        };
+        let later = [downloading("a", 0), downloading("b", 0), queued];
+        tracker.observe(&later, 1_600);
+        assert_eq!(tracker.stalled_for(&later[0], 1_600), Some(600));
+        assert_eq!(tracker.stalled_for(&later[1], 1_600), None);
+        assert_eq!(tracker.stalled_for(&later[2], 1_600), None);
+
+        // Moving again, or finishing, resets it
+        let moving = downloading("a", 5);
+        tracker.observe(std::slice::from_ref(&moving), 1_700);
+        assert_eq!(tracker.stalled_for(&downloading("a", 0), 1_700), None);
+        assert!(!tracker.last_active.contains_key("b"));
+        let done = Torrent {
+            state: TorrentState::Uploading,
+            ..downloading("a", 0)
+        };
+        tracker.observe(&[done], 3_000);
+        assert!(tracker.last_active.is_empty());
+    }
+
+    #[test]
+    fn labels_fit_the_eta_column() {
+        assert_eq!(stalled_label(45), "stalled 45s");
+        assert_eq!(stalled_label(12 * 60 + 5), "stalled 12m");
+        assert_eq!(stalled_label(23 * 3_600 + 59 * 60), "stalled 23h");
+        assert_eq!(stalled_label(9 * 86_400), "stalled 9d");
+    }
+}
@@ -0,0 +1,236 @@
+use crate::api::{MainData, PartialServerState, PartialTorrent, ServerState, Torrent};
+use std::collections::{HashMap, HashSet};
+
+/// Server state rebuilt from `/sync/maindata` responses applied in order.
+#[derive(Debug, Default)]
+pub struct SyncState {
+    torrents: HashMap<String, Torrent>,
+    pub server_state: Option<ServerState>,
+    /// Category name to save path
+    pub categories: HashMap<String, String>,
+    pub tags: HashSet<String>,
+}
+
+impl SyncState {
+    pub fn apply(&mut self, data: MainData) {
+        if data.full_update {
+            self.torrents.clear();
+            self.categories.clear();
+            self.tags.clear();
+        }
+
+        for hash in &data.torrents_removed {
+            self.torrents.remove(hash);
+        }
+        for (hash, partial) in data.torrents {
+            let torrent = self
+                .torrents
+                .entry(hash.clone())
+                .or_insert_with(|| Torrent {
+                    hash,
+                    ..Torrent::default()
+                });
+            merge_torrent(torrent, partial);
+        }
+
+        if let Some(partial) = data.server_state {
+            merge_server_state(
+                self.server_state.get_or_insert_with(ServerState::default),
+                partial,
+            );
+        }
+
+        for name in &data.categories_removed {
+            self.categories.remove(name);
+        }
+        for (name, category) in data.categories {
+            let save_path = self.categories.entry(name).or_default();
+            if let Some(path) = category.save_path {
+                *save_path = path;
+            }
+        }
+
+        for tag in &data.tags_removed {
+            self.tags.remove(tag);
+        }
+        self.tags.extend(data.tags);
+    }
+
+    /// Current torrents in no particular order.
+    pub fn torrents(&self) -> Vec<Torrent> {
+        self.torrents.values().cloned().collect()
+    }
+}
+
+fn merge_torrent(torrent: &mut Torrent, partial: PartialTorrent) {
+    if let Some(name) = partial.name {
+        torrent.name = name;
+    }
+    if let Some(size) = partial.size {
+        torrent.size = size;
+    }
+    if let Some(progress) = partial.progress {
+        torrent.progress = progress;
+    }
+    if let Some(dlspeed) = partial.dlspeed {
+        torrent.dlspeed = dlspeed;
+    }
+    if let Some(upspeed) = partial.upspeed {
+        torrent.upspeed = upspeed;
+    }
+    if let Some(state) = partial.state {
+        torrent.state = state;
+    }
+    torrent.eta = partial.eta.or(torrent.eta);
+    torrent.priority = partial.priority.or(torrent.priority);
+    torrent.num_seeds = partial.num_seeds.or(torrent.num_seeds);
+    torrent.num_leechs = partial.num_leechs.or(torrent.num_leechs);
+    torrent.ratio = partial.ratio.or(torrent.ratio);
+    torrent.category = partial.category.or(torrent.category.take());
+    torrent.tags = partial.tags.or(torrent.tags.take());
+    torrent.added_on = partial.added_on.or(torrent.added_on);
+    torrent.completion_on = partial.completion_on.or(torrent.completion_on);
+    torrent.downloaded = partial.downloaded.or(torrent.downloaded);
+    torrent.uploaded = partial.uploaded.or(torrent.uploaded);
+    torrent.downloaded_session = partial.downloaded_session.or(torrent.downloaded_session);
+    torrent.uploaded_session = partial.uploaded_session.or(torrent.uploaded_session);
+    torrent.ratio_limit = partial.ratio_limit.or(torrent.ratio_limit);
+    torrent.seeding_time_limit = partial.seeding_time_limit.or(torrent.seeding_time_limit);
+    torrent.inactive_seeding_time_limit = partial
+        .inactive_seeding_time_limit
+        .or(torrent.inactive_seeding_time_limit);
+    torrent.time_active = partial.time_active.or(torrent.time_active);
+    torrent.seeding_time = partial.seeding_time.or(torrent.seeding_time);
+    torrent.auto_tmm = partial.auto_tmm.or(torrent.auto_tmm);
+    torrent.save_path = partial.save_path.or(torrent.save_path.take());
+    torrent.content_path = partial.content_path.or(torrent.content_path.take());
+    torrent.tracker = partial.tracker.or(torrent.tracker.take());
+    torrent.magnet_uri = partial.magnet_uri.or(torrent.magnet_uri.take());
+}
+
+fn merge_server_state(state: &mut ServerState, partial: PartialServerState) {
+    if let Some(status) = partial.connection_status {
+        state.connection_status = status;
+    }
+    if let Some(data) = partial.dl_info_data {
+        state.dl_info_data = data;
+    }
+    if let Some(speed) = partial.dl_info_speed {
+        state.dl_info_speed = speed;
+    }
+    if let Some(data) = partial.up_info_data {
+        state.up_info_data = data;
+    }
+    if let Some(speed) = partial.up_info_speed {
+        state.up_info_speed = speed;
+    }
+    state.dht_nodes = partial.dht_nodes.or(state.dht_nodes);
+    state.dl_rate_limit = partial.dl_rate_limit.or(state.dl_rate_limit);
+    state.up_rate_limit = partial.up_rate_limit.or(state.up_rate_limit);
+    state.queueing = partial.queueing.or(state.queueing);
+    state.use_alt_speed_limits = partial.use_alt_speed_limits.or(state.use_alt_speed_limits);
+    state.refresh_interval = partial.refresh_interval.or(state.refresh_interval);
+    state.alltime_dl = partial.alltime_dl.or(state.alltime_dl);
+    state.alltime_ul = partial.alltime_ul.or(state.alltime_ul);
+    state.global_ratio = partial.global_ratio.or(state.global_ratio.take());
+    state.total_peer_connections = partial
+        .total_peer_connections
+        .or(state.total_peer_connections);
+    state.free_space_on_disk = partial.free_space_on_disk.or(state.free_space_on_disk);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn delta(value: serde_json::Value) -> MainData {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn torrent<'a>(sync: &'a SyncState, hash: &str) -> &'a Torrent {
+        sync.torrents.get(hash).unwrap()
+    }
+
+    #[test]
+    fn applies_a_sequence_of_deltas() {
+        let mut sync = SyncState::default();
+
+        sync.apply(delta(json!({
+            "rid": 1,
+            "full_update": true,
+            "torrents": {
+                "aaa": {"name": "alpha", "size": 100, "progress": 0.1, "dlspeed": 10,
+                        "upspeed": 0, "state": "downloading", "category": "movies", "tags": "hd"},
+                "bbb": {"name": "beta", "size": 200, "progress": 1.0, "dlspeed": 0,
+                        "upspeed": 5, "state": "uploading", "num_seeds": 3}
+            },
+            "server_state": {"connection_status": "connected", "dl_info_speed": 10,
+                             "dl_info_data": 1, "up_info_speed": 5, "up_info_data": 2,
+                             "use_alt_speed_limits": false},
+            "categories": {"movies": {"name": "movies", "savePath": "/data/movies"}},
+            "tags": ["hd"]
+        })));
+        assert_eq!(sync.torrents().len(), 2);
+        assert_eq!(torrent(&sync, "bbb").num_seeds, Some(3));
+
+        // Only changed fields are sent; everything else must be kept
+        sync.apply(delta(json!({
+            "rid": 2,
+            "torrents": {"aaa": {"progress": 0.5, "dlspeed": 20, "downloaded_session": 30}},
+            "server_state": {"dl_info_speed": 20, "use_alt_speed_limits": true}
+        })));
+        let alpha = torrent(&sync, "aaa");
+        assert_eq!(alpha.progress, 0.5);
+        assert_eq!(alpha.dlspeed, 20);
+        assert_eq!(alpha.downloaded_session, Some(30));
+        assert_eq!(alpha.name, "alpha");
+        assert_eq!(alpha.category.as_deref(), Some("movies"));
+        let state = sync.server_state.as_ref().unwrap();
+        assert_eq!(state.dl_info_speed, 20);
+        assert_eq!(state.connection_status, "connected");
+        assert_eq!(state.use_alt_speed_limits, Some(true));
+
+        sync.apply(delta(json!({
+            "rid": 3,
+            "torrents": {"ccc": {"name": "gamma", "state": "metaDL"}},
+            "torrents_removed": ["bbb"],
+            "categories": {"movies": {"savePath": "/mnt/movies"}, "tv": {"name": "tv"}},
+            "tags_removed": ["hd"]
+        })));
+        let mut hashes: Vec<_> = sync.torrents().into_iter().map(|t| t.hash).collect();
+        hashes.sort();
+        assert_eq!(hashes, ["aaa", "ccc"]);
+        assert_eq!(torrent(&sync, "ccc").name, "gamma");
+        assert_eq!(sync.categories["movies"], "/mnt/movies");
+        assert_eq!(sync.categories["tv"], "");
+        assert!(sync.tags.is_empty());
+
+        sync.apply(delta(json!({"rid": 4, "categories_removed": ["tv"]})));
+        assert!(!sync.categories.contains_key("tv"));
+    }
+
+    #[test]
+    fn full_update_replaces_previous_state() {
+        let mut sync = SyncState::default();
+        sync.apply(delta(json!({
+            "rid": 1,
+            "full_update": true,
+            "torrents": {"aaa": {"name": "alpha"}},
+            "categories": {"movies": {"savePath": "/data"}},
+            "tags": ["hd"]
+        })));
+
+        sync.apply(delta(json!({
+            "rid": 1,
+            "full_update": true,
+            "torrents": {"bbb": {"name": "beta"}}
+        })));
+
+        let torrents = sync.torrents();
+        assert_eq!(torrents.len(), 1);
+        assert_eq!(torrents[0].hash, "bbb");
+        assert!(sync.categories.is_empty());
+        assert!(sync.tags.is_empty());
+    }
+}
@@ -0,0 +1,177 @@
+use crossterm::{
+    cursor::Show,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::Stdio;
+
+/// Raw mode, the alternate screen and mouse capture for as long as it lives.
+/// Dropping it hands the terminal back, so an error returned anywhere after
+/// setup doesn't leave the shell unusable.
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    pub fn new() -> io::Result<Self> {
+        enable_raw_mode()?;
+        // Built before the screen switch so a failure there still restores
+        let guard = Self;
+        execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+        Ok(guard)
+    }
+}
+
+/// Takes the terminal back after `restore`, for a return from the shell or
+/// an editor. The screen is blank until the next full redraw.
+pub fn reenter() -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)
+}
+
+/// Stops the process the way Ctrl+Z does outside raw mode, returning once
+/// the shell continues it (`fg`). The terminal must be restored first.
+#[cfg(unix)]
+pub fn suspend() {
+    // SAFETY: kill has no memory-safety preconditions
+    unsafe {
+        libc::kill(libc::getpid(), libc::SIGSTOP);
+    }
+}
+
+/// Job control is a Unix thing; elsewhere Ctrl+Z does nothing.
+#[cfg(not(unix))]
+pub fn suspend() {}
+
+/// The user's editor: `$VISUAL`, then `$EDITOR`, then the platform's
+/// usual one.
+pub fn editor() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string())
+}
+
+/// Opens `text` in `editor` and returns what was saved. The editor is run
+/// through the shell, so it may carry arguments (`code --wait`); the
+/// terminal must be restored first.
+pub async fn edit(editor: &str, text: &str) -> io::Result<String> {
+    // A fresh file only this user can read, never one someone else left
+    // in the shared temp directory. It's closed before the editor opens it
+    // and removed when `file` drops
+    let mut file = tempfile::Builder::new()
+        .prefix("qbittui-")
+        .suffix(".txt")
+        .tempfile()?;
+    file.write_all(text.as_bytes())?;
+    let file = file.into_temp_path();
+    run_editor(editor, &file).await?;
+    std::fs::read_to_string(&file)
+}
+
+async fn run_editor(editor: &str, file: &Path) -> io::Result<()> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = tokio::process::Command::new("cmd");
+        shell
+            .arg("/C")
+            .arg(format!("{editor} \"{}\"", file.display()));
+        shell
+    } else {
+        // The path goes in as $1 so the shell never parses it
+        let mut shell = tokio::process::Command::new("sh");
+        shell
+            .arg("-c")
+            .arg(format!("{editor} \"$1\""))
+            .arg("sh")
+            .arg(file);
+        shell
+    };
+    let status = shell
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()
+        .await?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("{editor} exited with {status}")))
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+/// Undoes everything `TerminalGuard::new` set up. Errors are ignored, since
+/// this runs while already failing and there's nothing better to do.
+pub fn restore() {
+    let _ = disable_raw_mode();
+    let _ = execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        Show
+    );
+}
+
+/// Restores the terminal before a panic message is printed, so the message
+/// lands on the normal screen instead of vanishing with the alternate one.
+pub fn install_panic_hook() {
+    chain_panic_hook(restore);
+}
+
+fn chain_panic_hook(restore: fn()) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore();
+        previous(info);
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PANIC_CHILD: &str = "QBITTUI_PANIC_HOOK_TEST";
+
+    #[test]
+    fn panics_restore_the_terminal_first() {
+        // The hook is process-wide, so it goes into a copy of the test binary
+        // running only this test rather than under the tests running here
+        if std::env::var_os(PANIC_CHILD).is_some() {
+            chain_panic_hook(|| eprintln!("terminal restored"));
+            panic!("drawing went wrong");
+        }
+        let module = module_path!().split_once("::").unwrap().1;
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args([
+                "--exact",
+                &format!("{module}::panics_restore_the_terminal_first"),
+                "--nocapture",
+            ])
+            .env(PANIC_CHILD, "1")
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let restored = stderr.find("terminal restored").unwrap();
+        let message = stderr.find("drawing went wrong").unwrap();
+        assert!(restored < message, "{stderr}");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn edits_come_back_from_the_editor() {
+        let edited = edit("sed -i -e s/udp/https/", "udp://tracker.example\n")
+            .await
+            .unwrap();
+        assert_eq!(edited, "https://tracker.example\n");
+        // Quitting without success keeps the input as it was
+        assert!(edit("false", "kept").await.is_err());
+    }
+}
@@ -0,0 +1,324 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Single-line text field with a cursor. The cursor is kept as a char index
+/// so edits never land inside a multi-byte character.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextInput {
+    value: String,
+    cursor: usize,
+}
+
+impl TextInput {
+    /// Creates an input holding `value` with the cursor at the end.
+    pub fn new(value: impl Into<String>) -> Self {
+        let value = value.into();
+        let cursor = value.chars().count();
+        Self { value, cursor }
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    /// Replaces the text and moves the cursor to the end.
+    pub fn set(&mut self, value: impl Into<String>) {
+        *self = Self::new(value);
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Terminal columns taken by the text before the cursor.
+    pub fn cursor_width(&self) -> u16 {
+        self.value[..self.byte_index(self.cursor)].width() as u16
+    }
+
+    /// Same input with every char replaced by a bullet, for passwords.
+    pub fn masked(&self) -> Self {
+        Self {
+            value: "●".repeat(self.value.chars().count()),
+            cursor: self.cursor,
+        }
+    }
+
+    /// Part of the text that fits in a box `width` columns wide and the
+    /// cursor column within it. Text scrolls left once the cursor would
+    /// pass the right edge.
+    pub fn view(&self, width: u16) -> (String, u16) {
+        let width = width as usize;
+        let chars: Vec<char> = self.value.chars().collect();
+        let col = |c: &char| c.width().unwrap_or(0);
+
+        // Drop chars from the left until the cursor (plus its cell) fits
+        let mut start = 0;
+        let mut before = self.cursor_width() as usize;
+        while before >= width && start < self.cursor {
+            before -= col(&chars[start]);
+            start += 1;
+        }
+
+        let mut used = 0;
+        let visible: String = chars[start..]
+            .iter()
+            .take_while(|c| {
+                used += col(c);
+                used <= width
+            })
+            .collect();
+        (visible, before as u16)
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let index = self.byte_index(self.cursor);
+        self.value.insert(index, c);
+        self.cursor += 1;
+    }
+
+    /// Deletes the char before the cursor.
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let index = self.byte_index(self.cursor);
+            self.value.remove(index);
+        }
+    }
+
+    /// Deletes the char under the cursor.
+    pub fn delete(&mut self) {
+        if self.cursor < self.value.chars().count() {
+            let index = self.byte_index(self.cursor);
+            self.value.remove(index);
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.value.chars().count());
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.value.chars().count();
+    }
+
+    /// Moves to the start of the current or previous word.
+    pub fn move_word_left(&mut self) {
+        self.cursor = self.word_start();
+    }
+
+    /// Moves past the end of the current or next word.
+    pub fn move_word_right(&mut self) {
+        let chars: Vec<char> = self.value.chars().collect();
+        let mut cursor = self.cursor;
+        while cursor < chars.len() && !is_word_char(chars[cursor]) {
+            cursor += 1;
+        }
+        while cursor < chars.len() && is_word_char(chars[cursor]) {
+            cursor += 1;
+        }
+        self.cursor = cursor;
+    }
+
+    /// Deletes from the start of the previous word up to the cursor.
+    pub fn delete_word(&mut self) {
+        let start = self.word_start();
+        let range = self.byte_index(start)..self.byte_index(self.cursor);
+        self.value.replace_range(range, "");
+        self.cursor = start;
+    }
+
+    fn word_start(&self) -> usize {
+        let chars: Vec<char> = self.value.chars().collect();
+        let mut cursor = self.cursor;
+        while cursor > 0 && !is_word_char(chars[cursor - 1]) {
+            cursor -= 1;
+        }
+        while cursor > 0 && is_word_char(chars[cursor - 1]) {
+            cursor -= 1;
+        }
+        cursor
+    }
+
+    /// Applies an editing key, returning whether it was handled. Ctrl
+    /// shortcuts other than the editing ones, and Alt keys, are left to the
+    /// caller.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::ALT) {
+            return false;
+        }
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            match key.code {
+                KeyCode::Left => self.move_word_left(),
+                KeyCode::Right => self.move_word_right(),
+                KeyCode::Char('w') | KeyCode::Backspace => self.delete_word(),
+                KeyCode::Char('u') => self.clear(),
+                _ => return false,
+            }
+            return true;
+        }
+        match key.code {
+            KeyCode::Char(c) => self.insert(c),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left => self.move_left(),
+            KeyCode::Right => self.move_right(),
+            KeyCode::Home => self.move_home(),
+            KeyCode::End => self.move_end(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn byte_index(&self, chars: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(chars)
+            .map_or(self.value.len(), |(index, _)| index)
+    }
+}
+
+// Paths and URLs are split at punctuation, so a word is a run of
+// alphanumerics
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(input: &mut TextInput, code: KeyCode) -> bool {
+        input.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn ctrl(input: &mut TextInput, code: KeyCode) -> bool {
+        input.handle_key(KeyEvent::new(code, KeyModifiers::CONTROL))
+    }
+
+    #[test]
+    fn edits_in_the_middle_of_the_text() {
+        let mut input = TextInput::new("magnt");
+        press(&mut input, KeyCode::Left);
+        press(&mut input, KeyCode::Char('e'));
+        assert_eq!(input.value(), "magnet");
+        assert_eq!(input.cursor, 5);
+
+        press(&mut input, KeyCode::Home);
+        press(&mut input, KeyCode::Delete);
+        press(&mut input, KeyCode::End);
+        press(&mut input, KeyCode::Backspace);
+        assert_eq!(input.value(), "agne");
+        assert_eq!(input.cursor, 4);
+    }
+
+    #[test]
+    fn cursor_counts_chars_and_columns_not_bytes() {
+        let mut input = TextInput::new("/home/josé/種子");
+        assert_eq!(input.cursor, 13);
+        assert_eq!(input.value.width(), 15);
+        assert_eq!(input.cursor_width(), 15);
+
+        press(&mut input, KeyCode::Left);
+        assert_eq!(input.cursor_width(), 13);
+        press(&mut input, KeyCode::Backspace);
+        assert_eq!(input.value(), "/home/josé/子");
+
+        input.move_home();
+        for _ in 0..10 {
+            press(&mut input, KeyCode::Right);
+        }
+        assert_eq!(input.cursor_width(), 10);
+        press(&mut input, KeyCode::Backspace);
+        assert_eq!(input.value(), "/home/jos/子");
+    }
+
+    #[test]
+    fn ignores_modified_and_unknown_keys() {
+        let mut input = TextInput::new("abc");
+        assert!(!ctrl(&mut input, KeyCode::Char('h')));
+        assert!(!input.handle_key(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT)));
+        assert!(!press(&mut input, KeyCode::Tab));
+        assert_eq!(input.value(), "abc");
+
+        // Editing at the edges is a no-op rather than a panic
+        input.move_home();
+        press(&mut input, KeyCode::Backspace);
+        press(&mut input, KeyCode::Left);
+        input.move_end();
+        press(&mut input, KeyCode::Delete);
+        press(&mut input, KeyCode::Right);
+        assert_eq!(input.value(), "abc");
+        assert_eq!(input.cursor, 3);
+    }
+
+    #[test]
+    fn word_jumps_and_deletes() {
+        let mut input = TextInput::new("~/Downloads/some file.torrent");
+        ctrl(&mut input, KeyCode::Left);
+        assert_eq!(input.cursor, 22);
+        ctrl(&mut input, KeyCode::Left);
+        ctrl(&mut input, KeyCode::Left);
+        assert_eq!(input.cursor, 12);
+        ctrl(&mut input, KeyCode::Right);
+        assert_eq!(input.cursor, 16);
+
+        ctrl(&mut input, KeyCode::Char('w'));
+        assert_eq!(input.value(), "~/Downloads/ file.torrent");
+        assert_eq!(input.cursor, 12);
+        ctrl(&mut input, KeyCode::Char('w'));
+        assert_eq!(input.value(), "~/ file.torrent");
+
+        input.move_end();
+        ctrl(&mut input, KeyCode::Right);
+        assert_eq!(input.cursor, 15);
+
+        ctrl(&mut input, KeyCode::Char('u'));
+        assert!(input.is_empty());
+        assert_eq!(input.cursor, 0);
+    }
+
+    #[test]
+    fn view_scrolls_to_keep_the_cursor_visible() {
+        let mut input = TextInput::new("magnet:?xt=urn:btih:abcdef");
+        assert_eq!(input.view(40), (input.value().to_string(), 26));
+
+        // The cursor at the end needs a free cell after the text
+        let (text, cursor) = input.view(10);
+        assert_eq!(text, "ih:abcdef");
+        assert_eq!(cursor, 9);
+
+        input.move_home();
+        let (text, cursor) = input.view(10);
+        assert_eq!(text, "magnet:?xt");
+        assert_eq!(cursor, 0);
+
+        // Wide glyphs are never cut in half at the right edge
+        let input = TextInput::new("種子種子種子");
+        let (text, cursor) = input.view(5);
+        assert_eq!(text, "種子");
+        assert_eq!(cursor, 4);
+    }
+
+    #[test]
+    fn masked_keeps_the_cursor() {
+        let mut input = TextInput::new("pässwörd");
+        input.move_left();
+        let masked = input.masked();
+        assert_eq!(masked.value(), "●●●●●●●●");
+        assert_eq!(masked.cursor, 7);
+        // One bullet per char, scrolled like the text itself
+        assert_eq!(masked.view(4), ("●●●●".to_string(), 3));
+    }
+}
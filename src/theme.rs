@@ -0,0 +1,299 @@
+use crate::config::ThemeConfig;
+use crate::torrent_state::TorrentState;
+use ratatui::style::Color;
+
+/// Colors used by the UI, resolved from a preset plus the overrides in the
+/// config's `theme` section.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    /// Regular text
+    pub text: Color,
+    /// Hints, footers and other secondary text
+    pub muted: Color,
+    /// Popup background, and text drawn on a colored background
+    pub background: Color,
+    /// Borders of the main panes
+    pub border: Color,
+    /// Titles, field labels and the list header
+    pub header: Color,
+    /// Focused fields and section headings
+    pub accent: Color,
+    /// Background of the highlighted row
+    pub selection: Color,
+    /// Background of rows marked for batch actions
+    pub marked: Color,
+    /// Background of the search bar
+    pub search: Color,
+    pub error: Color,
+    pub success: Color,
+    pub warning: Color,
+    /// ETA and the alternative speed badge
+    pub info: Color,
+    pub download: Color,
+    pub upload: Color,
+    pub downloading: Color,
+    pub forced_downloading: Color,
+    pub seeding: Color,
+    pub forced_seeding: Color,
+    pub paused: Color,
+    pub queued: Color,
+    pub errored: Color,
+    /// Torrent states without a color of their own
+    pub other: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub const PRESETS: [&'static str; 3] = ["dark", "light", "terminal-default"];
+
+    pub fn dark() -> Self {
+        Self {
+            text: Color::White,
+            muted: Color::Gray,
+            background: Color::Black,
+            border: Color::Reset,
+            header: Color::Cyan,
+            accent: Color::Yellow,
+            selection: Color::DarkGray,
+            marked: Color::Indexed(24),
+            search: Color::Blue,
+            error: Color::Red,
+            success: Color::Green,
+            warning: Color::Yellow,
+            info: Color::Magenta,
+            download: Color::Green,
+            upload: Color::Red,
+            downloading: Color::Green,
+            forced_downloading: Color::LightGreen,
+            seeding: Color::Blue,
+            forced_seeding: Color::LightBlue,
+            paused: Color::Yellow,
+            queued: Color::Cyan,
+            errored: Color::Red,
+            other: Color::White,
+        }
+    }
+
+    // Yellow and the light ANSI colors wash out on a white background, so
+    // they are swapped for darker shades
+    pub fn light() -> Self {
+        Self {
+            text: Color::Black,
+            muted: Color::DarkGray,
+            background: Color::White,
+            border: Color::DarkGray,
+            header: Color::Blue,
+            accent: Color::Magenta,
+            selection: Color::Rgb(0xd0, 0xd0, 0xd0),
+            marked: Color::Rgb(0xc6, 0xdb, 0xf0),
+            search: Color::Rgb(0xc6, 0xdb, 0xf0),
+            error: Color::Red,
+            success: Color::Rgb(0x00, 0x80, 0x00),
+            warning: Color::Rgb(0xb0, 0x60, 0x00),
+            info: Color::Magenta,
+            download: Color::Rgb(0x00, 0x80, 0x00),
+            upload: Color::Red,
+            downloading: Color::Rgb(0x00, 0x80, 0x00),
+            forced_downloading: Color::Rgb(0x00, 0x5f, 0x00),
+            seeding: Color::Blue,
+            forced_seeding: Color::Rgb(0x00, 0x00, 0x8b),
+            paused: Color::Rgb(0xb0, 0x60, 0x00),
+            queued: Color::Rgb(0x00, 0x80, 0x80),
+            errored: Color::Red,
+            other: Color::Black,
+        }
+    }
+
+    /// Leaves text and backgrounds to the terminal and only uses the basic
+    /// ANSI colors, which the terminal's own palette maps to something
+    /// readable.
+    pub fn terminal_default() -> Self {
+        Self {
+            text: Color::Reset,
+            muted: Color::Reset,
+            background: Color::Reset,
+            border: Color::Reset,
+            selection: Color::Reset,
+            marked: Color::Reset,
+            search: Color::Reset,
+            forced_downloading: Color::Green,
+            forced_seeding: Color::Blue,
+            other: Color::Reset,
+            ..Self::dark()
+        }
+    }
+
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "terminal-default" => Some(Self::terminal_default()),
+            _ => None,
+        }
+    }
+
+    /// Builds the theme from the config, with `preset` (from the command
+    /// line) taking the place of the configured preset. Unknown presets,
+    /// colors and color names are reported as warnings and ignored.
+    pub fn from_config(config: &ThemeConfig, preset: Option<&str>) -> (Self, Vec<String>) {
+        let mut warnings = Vec::new();
+        let mut theme = match preset.or(config.preset.as_deref()) {
+            Some(name) => Self::preset(name).unwrap_or_else(|| {
+                warnings.push(format!(
+                    "unknown theme \"{name}\", expected one of {}",
+                    Self::PRESETS.join(", ")
+                ));
+                Self::default()
+            }),
+            None => Self::default(),
+        };
+
+        for (name, value) in &config.colors {
+            let Some(slot) = theme.slot_mut(name) else {
+                warnings.push(format!("unknown theme color \"{name}\""));
+                continue;
+            };
+            match parse_color(value) {
+                Ok(color) => *slot = color,
+                Err(e) => warnings.push(format!("theme color \"{name}\": {e}")),
+            }
+        }
+
+        (theme, warnings)
+    }
+
+    /// The color a torrent in `state` is drawn in.
+    pub fn state_color(&self, state: &TorrentState) -> Color {
+        match state {
+            TorrentState::Downloading => self.downloading,
+            TorrentState::ForcedDl => self.forced_downloading,
+            TorrentState::Uploading | TorrentState::StalledUp => self.seeding,
+            TorrentState::ForcedUp => self.forced_seeding,
+            state if state.is_paused() => self.paused,
+            state if state.is_errored() => self.errored,
+            TorrentState::QueuedDl | TorrentState::QueuedUp => self.queued,
+            _ => self.other,
+        }
+    }
+
+    fn slot_mut(&mut self, name: &str) -> Option<&mut Color> {
+        let slot = match name {
+            "text" => &mut self.text,
+            "muted" => &mut self.muted,
+            "background" => &mut self.background,
+            "border" => &mut self.border,
+            "header" => &mut self.header,
+            "accent" => &mut self.accent,
+            "selection" => &mut self.selection,
+            "marked" => &mut self.marked,
+            "search" => &mut self.search,
+            "error" => &mut self.error,
+            "success" => &mut self.success,
+            "warning" => &mut self.warning,
+            "info" => &mut self.info,
+            "download" => &mut self.download,
+            "upload" => &mut self.upload,
+            "downloading" => &mut self.downloading,
+            "forced_downloading" => &mut self.forced_downloading,
+            "seeding" => &mut self.seeding,
+            "forced_seeding" => &mut self.forced_seeding,
+            "paused" => &mut self.paused,
+            "queued" => &mut self.queued,
+            "errored" => &mut self.errored,
+            "other" => &mut self.other,
+            _ => return None,
+        };
+        Some(slot)
+    }
+}
+
+/// Parses a color name such as `"light-blue"`, `"reset"` for the terminal
+/// default, an `#rrggbb` hex value or a 0-255 palette index.
+pub fn parse_color(value: &str) -> Result<Color, String> {
+    let value = value.trim();
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("\"{value}\" is not a #rrggbb color"));
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or(0);
+        return Ok(Color::Rgb(channel(0), channel(2), channel(4)));
+    }
+    value
+        .parse::<Color>()
+        .map_err(|_| format!("unknown color \"{value}\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn config(preset: Option<&str>, colors: &[(&str, &str)]) -> ThemeConfig {
+        ThemeConfig {
+            preset: preset.map(str::to_string),
+            colors: colors
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect::<BTreeMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn parses_names_hex_and_indexes() {
+        assert_eq!(parse_color("light-blue"), Ok(Color::LightBlue));
+        assert_eq!(parse_color("Red"), Ok(Color::Red));
+        assert_eq!(parse_color("#ff8000"), Ok(Color::Rgb(0xff, 0x80, 0x00)));
+        assert_eq!(parse_color("24"), Ok(Color::Indexed(24)));
+        assert_eq!(parse_color("reset"), Ok(Color::Reset));
+        assert!(parse_color("#ff80").is_err());
+        assert!(parse_color("#gg0000").is_err());
+        assert!(parse_color("blurple").is_err());
+    }
+
+    #[test]
+    fn unknown_states_get_the_other_color() {
+        let theme = Theme::default();
+        let state = TorrentState::Unknown("rebalancing".to_string());
+        assert_eq!(theme.state_color(&state), theme.other);
+        assert_eq!(
+            theme.state_color(&TorrentState::ForcedUp),
+            theme.forced_seeding
+        );
+    }
+
+    #[test]
+    fn overrides_apply_on_top_of_the_preset() {
+        let (theme, warnings) = Theme::from_config(
+            &config(Some("light"), &[("accent", "#123456"), ("paused", "cyan")]),
+            None,
+        );
+        assert!(warnings.is_empty());
+        assert_eq!(theme.accent, Color::Rgb(0x12, 0x34, 0x56));
+        assert_eq!(theme.paused, Color::Cyan);
+        assert_eq!(theme.text, Theme::light().text);
+
+        // The command line preset replaces the configured one, overrides stay
+        let (theme, _) = Theme::from_config(
+            &config(Some("light"), &[("paused", "cyan")]),
+            Some("terminal-default"),
+        );
+        assert_eq!(theme.text, Color::Reset);
+        assert_eq!(theme.paused, Color::Cyan);
+    }
+
+    #[test]
+    fn bad_entries_are_reported_and_skipped() {
+        let (theme, warnings) = Theme::from_config(
+            &config(Some("neon"), &[("titles", "red"), ("error", "nope")]),
+            None,
+        );
+        assert_eq!(theme, Theme::dark());
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[0].contains("neon"));
+    }
+}
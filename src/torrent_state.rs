@@ -0,0 +1,295 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A torrent's state as qBittorrent reports it in `/torrents/info`. States
+/// this version doesn't know are kept as `Unknown` with the raw string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TorrentState {
+    Error,
+    MissingFiles,
+    Uploading,
+    /// Renamed `stoppedUP` in qBittorrent 5.0
+    PausedUp,
+    StoppedUp,
+    QueuedUp,
+    StalledUp,
+    CheckingUp,
+    ForcedUp,
+    Allocating,
+    Downloading,
+    MetaDl,
+    ForcedMetaDl,
+    /// Renamed `stoppedDL` in qBittorrent 5.0
+    PausedDl,
+    StoppedDl,
+    QueuedDl,
+    StalledDl,
+    CheckingDl,
+    ForcedDl,
+    CheckingResumeData,
+    Moving,
+    Unknown(String),
+}
+
+impl TorrentState {
+    /// Every state qBittorrent documents, with its API string.
+    pub const KNOWN: [(TorrentState, &'static str); 21] = [
+        (TorrentState::Error, "error"),
+        (TorrentState::MissingFiles, "missingFiles"),
+        (TorrentState::Uploading, "uploading"),
+        (TorrentState::PausedUp, "pausedUP"),
+        (TorrentState::StoppedUp, "stoppedUP"),
+        (TorrentState::QueuedUp, "queuedUP"),
+        (TorrentState::StalledUp, "stalledUP"),
+        (TorrentState::CheckingUp, "checkingUP"),
+        (TorrentState::ForcedUp, "forcedUP"),
+        (TorrentState::Allocating, "allocating"),
+        (TorrentState::Downloading, "downloading"),
+        (TorrentState::MetaDl, "metaDL"),
+        (TorrentState::ForcedMetaDl, "forcedMetaDL"),
+        (TorrentState::PausedDl, "pausedDL"),
+        (TorrentState::StoppedDl, "stoppedDL"),
+        (TorrentState::QueuedDl, "queuedDL"),
+        (TorrentState::StalledDl, "stalledDL"),
+        (TorrentState::CheckingDl, "checkingDL"),
+        (TorrentState::ForcedDl, "forcedDL"),
+        (TorrentState::CheckingResumeData, "checkingResumeData"),
+        (TorrentState::Moving, "moving"),
+    ];
+
+    /// The string the API uses for this state.
+    pub fn as_str(&self) -> &str {
+        match self {
+            TorrentState::Unknown(raw) => raw,
+            known => TorrentState::KNOWN
+                .iter()
+                .find(|(state, _)| state == known)
+                .map_or("unknown", |(_, raw)| raw),
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        matches!(
+            self,
+            TorrentState::PausedDl
+                | TorrentState::PausedUp
+                | TorrentState::StoppedDl
+                | TorrentState::StoppedUp
+        )
+    }
+
+    /// Still fetching data, whether or not any is arriving right now.
+    pub fn is_downloading(&self) -> bool {
+        matches!(
+            self,
+            TorrentState::Downloading
+                | TorrentState::MetaDl
+                | TorrentState::ForcedMetaDl
+                | TorrentState::ForcedDl
+                | TorrentState::StalledDl
+                | TorrentState::QueuedDl
+                | TorrentState::Allocating
+        )
+    }
+
+    pub fn is_seeding(&self) -> bool {
+        matches!(
+            self,
+            TorrentState::Uploading
+                | TorrentState::ForcedUp
+                | TorrentState::StalledUp
+                | TorrentState::QueuedUp
+        )
+    }
+
+    /// Has all its data, whether it's being shared or not.
+    pub fn is_complete(&self) -> bool {
+        matches!(
+            self,
+            TorrentState::Uploading
+                | TorrentState::PausedUp
+                | TorrentState::StoppedUp
+                | TorrentState::QueuedUp
+                | TorrentState::StalledUp
+                | TorrentState::CheckingUp
+                | TorrentState::ForcedUp
+        )
+    }
+
+    pub fn is_errored(&self) -> bool {
+        matches!(self, TorrentState::Error | TorrentState::MissingFiles)
+    }
+
+    /// Started with Force Start, ignoring the queue.
+    pub fn is_forced(&self) -> bool {
+        matches!(
+            self,
+            TorrentState::ForcedDl | TorrentState::ForcedUp | TorrentState::ForcedMetaDl
+        )
+    }
+
+    /// How the state is shown in the list and the detail view.
+    pub fn display_name(&self) -> &str {
+        match self {
+            TorrentState::Error => "Errored",
+            TorrentState::MissingFiles => "Missing files",
+            TorrentState::Uploading => "Seeding",
+            TorrentState::PausedUp | TorrentState::PausedDl => "Paused",
+            TorrentState::StoppedUp | TorrentState::StoppedDl => "Stopped",
+            TorrentState::QueuedUp | TorrentState::QueuedDl => "Queued",
+            TorrentState::StalledUp | TorrentState::StalledDl => "Stalled",
+            TorrentState::CheckingUp | TorrentState::CheckingDl => "Checking",
+            TorrentState::ForcedUp => "[F] Seeding",
+            TorrentState::Allocating => "Allocating",
+            TorrentState::Downloading => "Downloading",
+            TorrentState::MetaDl => "Metadata",
+            TorrentState::ForcedMetaDl => "[F] Metadata",
+            TorrentState::ForcedDl => "[F] Download",
+            TorrentState::CheckingResumeData => "Checking data",
+            TorrentState::Moving => "Moving",
+            TorrentState::Unknown(raw) => raw,
+        }
+    }
+
+    /// `display_name` cut down for narrow lists.
+    pub fn short_name(&self) -> &str {
+        match self {
+            TorrentState::Error => "Error",
+            TorrentState::MissingFiles => "Missing",
+            TorrentState::Uploading => "Seed",
+            TorrentState::PausedUp | TorrentState::PausedDl => "Paused",
+            TorrentState::StoppedUp | TorrentState::StoppedDl => "Stopped",
+            TorrentState::QueuedUp | TorrentState::QueuedDl => "Queued",
+            TorrentState::StalledUp | TorrentState::StalledDl => "Stalled",
+            TorrentState::CheckingUp
+            | TorrentState::CheckingDl
+            | TorrentState::CheckingResumeData => "Check",
+            TorrentState::ForcedUp => "F Seed",
+            TorrentState::Allocating => "Alloc",
+            TorrentState::Downloading => "DL",
+            TorrentState::MetaDl => "Meta",
+            TorrentState::ForcedMetaDl => "F Meta",
+            TorrentState::ForcedDl => "F DL",
+            TorrentState::Moving => "Moving",
+            TorrentState::Unknown(raw) => raw,
+        }
+    }
+
+    /// The symbol leading the torrent's row, from plain ASCII with `ascii`
+    /// for terminals that draw the others badly.
+    pub fn glyph(&self, ascii: bool) -> &'static str {
+        let (unicode, plain) = match self {
+            state if state.is_errored() => ("!", "!"),
+            TorrentState::PausedUp | TorrentState::StoppedUp => ("✓", "+"),
+            state if state.is_paused() => ("⏸", "="),
+            TorrentState::QueuedUp | TorrentState::QueuedDl => ("⌛", "~"),
+            TorrentState::Uploading | TorrentState::StalledUp | TorrentState::ForcedUp => {
+                ("▲", "^")
+            }
+            TorrentState::Downloading
+            | TorrentState::StalledDl
+            | TorrentState::ForcedDl
+            | TorrentState::MetaDl
+            | TorrentState::ForcedMetaDl => ("▼", "v"),
+            TorrentState::Unknown(_) => ("?", "?"),
+            _ => ("↻", "*"),
+        };
+        if ascii { plain } else { unicode }
+    }
+}
+
+impl Default for TorrentState {
+    fn default() -> Self {
+        TorrentState::Unknown("unknown".to_string())
+    }
+}
+
+impl From<&str> for TorrentState {
+    fn from(raw: &str) -> Self {
+        TorrentState::KNOWN
+            .iter()
+            .find(|(_, known)| *known == raw)
+            .map_or_else(
+                || TorrentState::Unknown(raw.to_string()),
+                |(state, _)| state.clone(),
+            )
+    }
+}
+
+impl<'de> Deserialize<'de> for TorrentState {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        Ok(TorrentState::from(raw.as_str()))
+    }
+}
+
+impl Serialize for TorrentState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_glyphs_stay_ascii() {
+        for (state, _) in TorrentState::KNOWN {
+            assert!(state.glyph(true).is_ascii(), "{state:?}");
+            assert!(!state.glyph(false).is_empty());
+        }
+        assert_eq!(TorrentState::Downloading.glyph(false), "▼");
+        assert_eq!(TorrentState::StoppedUp.glyph(true), "+");
+    }
+
+    #[test]
+    fn documented_states_round_trip() {
+        for (state, raw) in TorrentState::KNOWN {
+            assert_eq!(TorrentState::from(raw), state, "{raw}");
+            assert_eq!(state.as_str(), raw);
+            assert!(!state.display_name().is_empty(), "{raw}");
+            let json = serde_json::to_string(&state).unwrap();
+            assert_eq!(json, format!("\"{raw}\""));
+            assert_eq!(serde_json::from_str::<TorrentState>(&json).unwrap(), state);
+        }
+    }
+
+    #[test]
+    fn every_state_falls_in_at_most_one_group() {
+        let groups = |state: &TorrentState| {
+            [
+                state.is_paused(),
+                state.is_downloading(),
+                state.is_seeding(),
+                state.is_errored(),
+            ]
+            .iter()
+            .filter(|&&yes| yes)
+            .count()
+        };
+        for (state, raw) in TorrentState::KNOWN {
+            assert!(groups(&state) <= 1, "{raw}");
+        }
+        assert!(TorrentState::StoppedUp.is_paused());
+        assert!(TorrentState::ForcedMetaDl.is_downloading());
+        assert!(TorrentState::StalledUp.is_seeding());
+        assert!(TorrentState::MissingFiles.is_errored());
+        assert!(TorrentState::ForcedUp.is_forced());
+        for (state, raw) in TorrentState::KNOWN {
+            assert!(!(state.is_complete() && state.is_downloading()), "{raw}");
+        }
+        assert!(TorrentState::StoppedUp.is_complete());
+        // Checking and moving are transitional
+        assert_eq!(groups(&TorrentState::CheckingResumeData), 0);
+        assert_eq!(groups(&TorrentState::Moving), 0);
+    }
+
+    #[test]
+    fn unknown_states_keep_their_string() {
+        let state: TorrentState = serde_json::from_str("\"rebalancing\"").unwrap();
+        assert_eq!(state, TorrentState::Unknown("rebalancing".to_string()));
+        assert_eq!(state.as_str(), "rebalancing");
+        assert_eq!(state.display_name(), "rebalancing");
+        assert_eq!(TorrentState::from("unknown").as_str(), "unknown");
+    }
+}
@@ -0,0 +1,290 @@
+use crate::api::Torrent;
+use crate::models::Tracker;
+use crate::text_input::TextInput;
+use crate::torrent_state::TorrentState;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// How long a torrent's tracker message is shown before it's fetched again
+const HINT_TTL: Duration = Duration::from_secs(60);
+/// Fetches of tracker messages in flight at once
+const MAX_HINT_FETCHES: usize = 2;
+
+/// Errored, or running with no tracker that works, which is when its
+/// trackers' messages are worth showing outside the detail view.
+pub fn in_trouble(torrent: &Torrent) -> bool {
+    let running = matches!(
+        torrent.state,
+        TorrentState::Downloading
+            | TorrentState::ForcedDl
+            | TorrentState::StalledDl
+            | TorrentState::MetaDl
+            | TorrentState::ForcedMetaDl
+            | TorrentState::Uploading
+            | TorrentState::ForcedUp
+            | TorrentState::StalledUp
+    );
+    // The sync data names the current tracker, empty when none works
+    torrent.state.is_errored() || (running && torrent.tracker.as_deref() == Some(""))
+}
+
+/// What the first tracker with something to say said, preferring ones that
+/// aren't working.
+pub fn failure_message(trackers: &[Tracker]) -> Option<String> {
+    let mut said = trackers
+        .iter()
+        .filter(|t| !t.is_special() && !t.msg.trim().is_empty());
+    let first = said
+        .clone()
+        .find(|t| t.status == 4)
+        .or_else(|| said.next())?;
+    Some(first.msg.trim().to_string())
+}
+
+/// Tracker messages of torrents in trouble, for the list to show without
+/// opening the detail view. Each is fetched once a minute at most, and only
+/// a couple at a time.
+#[derive(Debug, Default)]
+pub struct TrackerHints {
+    messages: HashMap<String, (Instant, Option<String>)>,
+    pending: HashSet<String>,
+}
+
+impl TrackerHints {
+    /// Whether the torrent's message should be fetched now, noting that it
+    /// is when so.
+    pub fn start(&mut self, hash: &str, now: Instant) -> bool {
+        let fresh = self
+            .messages
+            .get(hash)
+            .is_some_and(|(fetched, _)| now.duration_since(*fetched) < HINT_TTL);
+        if fresh || self.pending.contains(hash) || self.pending.len() >= MAX_HINT_FETCHES {
+            return false;
+        }
+        self.pending.insert(hash.to_string())
+    }
+
+    /// Records the trackers fetched for `hash`; `None` when that failed, so
+    /// it isn't asked for again right away.
+    pub fn finish(&mut self, hash: &str, trackers: Option<&[Tracker]>, now: Instant) {
+        self.pending.remove(hash);
+        self.messages
+            .insert(hash.to_string(), (now, trackers.and_then(failure_message)));
+        self.messages
+            .retain(|_, (fetched, _)| now.duration_since(*fetched) < HINT_TTL);
+    }
+
+    pub fn get(&self, hash: &str) -> Option<&str> {
+        self.messages.get(hash)?.1.as_deref()
+    }
+}
+
+/// Several URLs typed one per line. Enter starts a new line.
+#[derive(Debug, Clone, Default)]
+pub struct UrlList {
+    pub lines: Vec<TextInput>,
+    pub row: usize,
+}
+
+impl UrlList {
+    pub fn new() -> Self {
+        Self {
+            lines: vec![TextInput::default()],
+            row: 0,
+        }
+    }
+
+    /// The list holding `text`'s lines, as it came back from an editor.
+    pub fn from_text(text: &str) -> Self {
+        let mut lines: Vec<TextInput> = text.lines().map(TextInput::new).collect();
+        if lines.is_empty() {
+            lines.push(TextInput::default());
+        }
+        Self {
+            row: lines.len() - 1,
+            lines,
+        }
+    }
+
+    /// Every line, for an editor.
+    pub fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| format!("{}\n", line.value()))
+            .collect()
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.lines[self.row].handle_key(key);
+            return;
+        }
+        match key.code {
+            KeyCode::Enter => {
+                self.row += 1;
+                self.lines.insert(self.row, TextInput::default());
+            }
+            KeyCode::Up => self.row = self.row.saturating_sub(1),
+            KeyCode::Down => self.row = (self.row + 1).min(self.lines.len() - 1),
+            // Backspace on an empty line joins it with the one above
+            KeyCode::Backspace if self.lines[self.row].is_empty() && self.row > 0 => {
+                self.lines.remove(self.row);
+                self.row -= 1;
+            }
+            _ => {
+                self.lines[self.row].handle_key(key);
+            }
+        }
+    }
+
+    /// The non-empty lines, trimmed.
+    pub fn urls(&self) -> Vec<String> {
+        self.lines
+            .iter()
+            .map(|line| line.value().trim())
+            .filter(|url| !url.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TrackerInput {
+    Add(UrlList),
+    /// A new URL for the tracker that was highlighted
+    Edit {
+        original: String,
+        url: TextInput,
+    },
+}
+
+/// The Trackers tab of the detail view.
+#[derive(Debug, Default)]
+pub struct TrackerPanel {
+    /// Torrent the trackers belong to
+    pub hash: String,
+    pub trackers: Vec<Tracker>,
+    pub selected: usize,
+    pub input: Option<TrackerInput>,
+    /// Waiting for y/n before removing the highlighted tracker
+    pub confirm_remove: bool,
+    /// A reload is in flight, so polls don't pile up behind a slow server
+    pub loading: bool,
+}
+
+impl TrackerPanel {
+    /// Replaces the trackers, keeping the same one highlighted when it's
+    /// still there.
+    pub fn set_trackers(&mut self, trackers: Vec<Tracker>) {
+        let selected = self.selected_tracker().map(|t| t.url.clone());
+        self.trackers = trackers;
+        self.selected = selected
+            .and_then(|url| self.trackers.iter().position(|t| t.url == url))
+            .unwrap_or(self.selected)
+            .min(self.trackers.len().saturating_sub(1));
+    }
+
+    pub fn selected_tracker(&self) -> Option<&Tracker> {
+        self.trackers.get(self.selected)
+    }
+
+    /// The highlighted tracker, unless it's one of DHT, PeX or LSD.
+    pub fn editable_tracker(&self) -> Option<&Tracker> {
+        self.selected_tracker().filter(|t| !t.is_special())
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let last = self.trackers.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(url: &str) -> Tracker {
+        Tracker {
+            url: url.to_string(),
+            ..Tracker::default()
+        }
+    }
+
+    #[test]
+    fn hints_are_fetched_once_a_minute_a_few_at_a_time() {
+        let now = Instant::now();
+        let mut hints = TrackerHints::default();
+        assert!(hints.start("a", now));
+        assert!(!hints.start("a", now));
+        assert!(hints.start("b", now));
+        assert!(!hints.start("c", now));
+
+        let trackers = [
+            Tracker {
+                msg: "skipped".to_string(),
+                ..tracker("** [DHT] **")
+            },
+            Tracker {
+                status: 2,
+                msg: "fine".to_string(),
+                ..tracker("udp://a")
+            },
+            Tracker {
+                status: 4,
+                msg: " torrent not registered ".to_string(),
+                ..tracker("udp://b")
+            },
+        ];
+        hints.finish("a", Some(&trackers), now);
+        hints.finish("b", None, now);
+        assert_eq!(hints.get("a"), Some("torrent not registered"));
+        assert_eq!(hints.get("b"), None);
+        assert!(hints.start("c", now));
+        assert!(!hints.start("a", now + Duration::from_secs(30)));
+        assert!(hints.start("a", now + HINT_TTL));
+    }
+
+    #[test]
+    fn url_lists_split_on_enter() {
+        let mut list = UrlList::new();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        for c in "udp://a".chars() {
+            list.handle_key(key(KeyCode::Char(c)));
+        }
+        list.handle_key(key(KeyCode::Enter));
+        list.handle_key(key(KeyCode::Enter));
+        // The blank line is dropped again
+        list.handle_key(key(KeyCode::Backspace));
+        for c in " http://b ".chars() {
+            list.handle_key(key(KeyCode::Char(c)));
+        }
+        assert_eq!(list.lines.len(), 2);
+        assert_eq!(list.urls(), ["udp://a", "http://b"]);
+    }
+
+    #[test]
+    fn reloads_keep_the_highlighted_tracker() {
+        let mut panel = TrackerPanel::default();
+        panel.set_trackers(vec![tracker("** [DHT] **"), tracker("udp://a")]);
+        assert!(panel.editable_tracker().is_none());
+        panel.move_selection(1);
+        assert_eq!(panel.editable_tracker().unwrap().url, "udp://a");
+
+        panel.set_trackers(vec![
+            tracker("** [DHT] **"),
+            tracker("http://new"),
+            tracker("udp://a"),
+        ]);
+        assert_eq!(panel.selected, 2);
+        panel.set_trackers(vec![tracker("** [DHT] **")]);
+        assert_eq!(panel.selected, 0);
+
+        let parsed: Tracker = serde_json::from_str(
+            r#"{"url":"udp://a","status":4,"tier":0,"num_peers":-1,"msg":"Connection refused"}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.status_label(), "Not working");
+        assert_eq!(parsed.msg, "Connection refused");
+    }
+}
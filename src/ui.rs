@@ -1,60 +1,306 @@
-use crate::app::{App, AppState, InputMode};
+use crate::api::{LogLevel, Torrent};
+use crate::app::{
+    App, AppState, DetailsTab, InputMode, PromptAction, RowMode, SPINNER_INTERVAL, StatusFilter,
+    state_bucket,
+};
+use crate::bindings::{self, BINDINGS, HelpContext};
+use crate::cleanup::{CleanupFocus, seeding_secs};
+use crate::clipboard::CopyField;
+use crate::columns::ListColumn;
+use crate::config::Config;
+use crate::models;
+use crate::notification::Severity;
+use crate::pieces::{PieceCell, PieceMap};
+use crate::rss::RssFocus;
+use crate::search::SearchFocus;
+use crate::settings;
+use crate::share_limits::{ShareLimits, ShareLimitsEditor};
+use crate::stalled::stalled_label;
+use crate::theme::Theme;
+use crate::torrent_state::TorrentState;
+use crate::trackers::{TrackerInput, UrlList};
+use crate::utils::{
+    INFINITE_SECS, fit_to_width, format_age, format_clock_time, format_duration, format_elapsed,
+    format_speed_limit, format_unix_timestamp, progress_bar, truncate_to_width,
+};
 use humansize::{BINARY, format_size};
 use ratatui::{
     Frame,
     layout::{Alignment, Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, Paragraph, Scrollbar, ScrollbarOrientation,
+        ScrollbarState, Sparkline, Wrap,
+    },
 };
+use unicode_width::UnicodeWidthStr;
 
 pub fn draw(f: &mut Frame, app: &mut App) {
+    let theme = app.theme;
     let size = f.area();
 
     // Update app with current terminal size
     app.terminal_width = size.width;
     app.terminal_height = size.height;
 
-    // Check minimum terminal size
-    if size.width < 80 || size.height < 24 {
+    // Smaller terminals get fewer columns, see `fit_columns`; only ones
+    // with no room for a usable list are turned away
+    if size.width < MIN_TERMINAL_WIDTH || size.height < MIN_TERMINAL_HEIGHT {
         let warning = Paragraph::new(vec![
             Line::from("Terminal too small!"),
-            Line::from(""),
-            Line::from("Minimum size required:"),
-            Line::from("Width: 80 characters"),
-            Line::from("Height: 24 lines"),
-            Line::from(""),
-            Line::from(format!("Current: {}x{}", size.width, size.height)),
-            Line::from(""),
-            Line::from("Please resize your terminal and try again."),
-            Line::from("Press Ctrl+Q to quit."),
+            Line::from(format!(
+                "Needs {MIN_TERMINAL_WIDTH}x{MIN_TERMINAL_HEIGHT}, is {}x{}",
+                size.width, size.height
+            )),
+            Line::from("Ctrl+Q quits"),
         ])
-        .style(Style::default().fg(Color::Red))
+        .style(Style::default().fg(theme.error))
         .alignment(Alignment::Center)
-        .block(
-            Block::default()
-                .title("Terminal Size Warning")
-                .borders(Borders::ALL)
-                .style(Style::default().fg(Color::Red)),
-        );
+        .wrap(Wrap { trim: true });
 
-        let warning_area = centered_rect_percent(50, 12, size);
-        f.render_widget(Clear, warning_area);
-        f.render_widget(warning, warning_area);
+        f.render_widget(Clear, size);
+        f.render_widget(warning, size);
         return;
     }
     match app.state {
+        AppState::ProfilePicker => draw_profile_picker(f, app),
+        AppState::ProfileEdit => draw_profile_edit(f, app),
         AppState::UrlConfig => draw_url_config(f, app),
         AppState::Login => draw_login(f, app),
+        AppState::Connecting => {
+            draw_login(f, app);
+            draw_connecting(f, app);
+        }
         AppState::Main => draw_main(f, app),
         AppState::AddTorrent => draw_add_torrent(f, app),
-        AppState::Search => draw_search(f, app),
+        AppState::FileBrowser => draw_file_browser(f, app),
+        AppState::Search => draw_main(f, app),
         AppState::ConfirmDelete => draw_confirm_delete(f, app),
-        AppState::Error(ref message) => draw_error(f, message),
+        AppState::SpeedLimits => draw_speed_limits(f, app),
+        AppState::Details => draw_details(f, app),
+        AppState::ShareLimits => draw_share_limits(f, app),
+        AppState::Help => draw_help(f, app),
+        AppState::Log => draw_log(f, app),
+        AppState::Rss => draw_rss(f, app),
+        AppState::Settings => draw_settings(f, app),
+        AppState::CommandPalette => draw_command_palette(f, app),
+        AppState::Copy => {
+            draw_main(f, app);
+            draw_copy_menu(f, app);
+        }
+        AppState::Columns => {
+            draw_main(f, app);
+            draw_column_chooser(f, app);
+        }
+        AppState::Cleanup => {
+            draw_main(f, app);
+            draw_cleanup(f, app);
+        }
+        AppState::OnlineSearch => draw_online_search(f, app),
+        AppState::Statistics => draw_statistics(f, app),
+        AppState::Confirm => draw_confirm(f, app),
+        AppState::Prompt => draw_prompt(f, app),
+        AppState::Error(ref message) => draw_error(f, message, &app.theme),
+    }
+
+    draw_notifications(f, app);
+}
+
+// Toasts stack upwards from the bottom right corner, just above the footer,
+// newest at the bottom
+fn draw_notifications(f: &mut Frame, app: &App) {
+    let theme = app.theme;
+    let size = f.area();
+    let max_width = size.width / 2;
+    let mut bottom = size.height.saturating_sub(3);
+
+    for notification in app
+        .active_notifications()
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+    {
+        if bottom == 0 {
+            break;
+        }
+        let (icon, color) = match notification.severity {
+            Severity::Success => ("✓", theme.success),
+            Severity::Warning => ("!", theme.warning),
+            Severity::Error => ("✗", theme.error),
+        };
+        let text = truncate_to_width(
+            &format!(" {icon} {} ", notification.message),
+            max_width as usize,
+        );
+        let width = text.width() as u16;
+        bottom -= 1;
+        let area = Rect::new(size.width.saturating_sub(width + 1), bottom, width, 1);
+
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(text).style(
+                Style::default()
+                    .fg(theme.background)
+                    .bg(color)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            area,
+        );
+    }
+}
+
+fn draw_profile_picker(f: &mut Frame, app: &App) {
+    let theme = app.theme;
+    let size = f.area();
+
+    f.render_widget(Clear, size);
+
+    // One row per profile plus "New connection…", the borders and the hints
+    let rows = app.config.profiles.len() as u16 + 1;
+    let popup_width = (size.width * 80 / 100).clamp(50, 80);
+    let popup_height = (rows + 5).min(size.height);
+    let popup_area = centered_rect(popup_width, popup_height, size);
+
+    let block = Block::default()
+        .title(" Connect to qBittorrent ")
+        .title_style(
+            Style::default()
+                .fg(theme.header)
+                .add_modifier(Modifier::BOLD),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.header));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(2)])
+        .split(inner.inner(Margin {
+            vertical: 0,
+            horizontal: 1,
+        }));
+
+    let width = chunks[0].width as usize;
+    let name_width = (width / 3).max(10);
+    let mut items: Vec<ListItem> = app
+        .config
+        .profiles
+        .iter()
+        .map(|profile| {
+            let name = if profile.default {
+                format!("{} *", profile.name)
+            } else {
+                profile.name.clone()
+            };
+            let target = match &profile.username {
+                Some(username) => format!("{username} @ {}", profile.url),
+                None => profile.url.clone(),
+            };
+            let line = format!(
+                "{} {}",
+                fit_to_width(&name, name_width, false),
+                truncate_to_width(&target, width.saturating_sub(name_width + 1))
+            );
+            ListItem::new(line)
+        })
+        .collect();
+    items.push(ListItem::new("New connection…"));
+
+    let items: Vec<ListItem> = items
+        .into_iter()
+        .enumerate()
+        .map(|(i, item)| {
+            if i == app.profile_index {
+                item.style(
+                    Style::default()
+                        .fg(theme.background)
+                        .bg(theme.accent)
+                        .add_modifier(Modifier::BOLD),
+                )
+            } else {
+                item.style(Style::default().fg(theme.text))
+            }
+        })
+        .collect();
+    f.render_widget(List::new(items), chunks[0]);
+
+    let instructions =
+        Paragraph::new("Enter: Connect | e: Edit | d: Delete | ↑↓: Select | Esc: Quit | * default")
+            .style(Style::default().fg(theme.muted))
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true });
+    f.render_widget(instructions, chunks[1]);
+}
+
+fn draw_profile_edit(f: &mut Frame, app: &App) {
+    let theme = app.theme;
+    let size = f.area();
+
+    f.render_widget(Clear, size);
+
+    let popup_width = (size.width * 80 / 100).clamp(50, 80);
+    let popup_area = centered_rect(popup_width, 14.min(size.height), size);
+
+    let block = Block::default()
+        .title(" Edit Profile ")
+        .title_style(
+            Style::default()
+                .fg(theme.header)
+                .add_modifier(Modifier::BOLD),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.header));
+    f.render_widget(block, popup_area);
+
+    let inner = popup_area.inner(Margin {
+        vertical: 1,
+        horizontal: 2,
+    });
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Name
+            Constraint::Length(3), // URL
+            Constraint::Length(3), // Username
+            Constraint::Min(1),    // Instructions
+        ])
+        .split(inner);
+
+    let fields = [
+        ("Name", InputMode::ProfileName, &app.profile_name_input),
+        ("WebUI URL", InputMode::Url, &app.url_input),
+        ("Username", InputMode::Username, &app.username_input),
+    ];
+    for (i, (title, mode, input)) in fields.into_iter().enumerate() {
+        let active = app.input_mode == mode;
+        let color = if active { theme.accent } else { theme.text };
+        let (text, cursor) = input.view(chunks[i].width.saturating_sub(2));
+        let field = Paragraph::new(text)
+            .style(Style::default().fg(theme.text))
+            .block(
+                Block::default()
+                    .title(format!(" {title} "))
+                    .title_style(Style::default().fg(color).add_modifier(Modifier::BOLD))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(color)),
+            );
+        f.render_widget(field, chunks[i]);
+        if active {
+            f.set_cursor_position((chunks[i].x + cursor + 1, chunks[i].y + 1));
+        }
     }
+
+    let instructions = Paragraph::new("Tab/↑↓: Switch field | Enter: Save | Esc: Cancel")
+        .style(Style::default().fg(theme.muted))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(instructions, chunks[3]);
 }
 
 fn draw_url_config(f: &mut Frame, app: &App) {
+    let theme = app.theme;
     let size = f.area();
 
     // Clear the entire screen first
@@ -70,11 +316,11 @@ fn draw_url_config(f: &mut Frame, app: &App) {
         .title(" qBittorrent WebUI Configuration ")
         .title_style(
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme.header)
                 .add_modifier(Modifier::BOLD),
         )
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan));
+        .border_style(Style::default().fg(theme.header));
 
     f.render_widget(block, popup_area);
 
@@ -100,7 +346,7 @@ fn draw_url_config(f: &mut Frame, app: &App) {
     };
 
     let description = Paragraph::new(description_text)
-        .style(Style::default().fg(Color::White))
+        .style(Style::default().fg(theme.text))
         .alignment(Alignment::Left);
     f.render_widget(description, chunks[0]);
 
@@ -109,11 +355,13 @@ fn draw_url_config(f: &mut Frame, app: &App) {
         .title(" WebUI URL (Active) ")
         .title_style(
             Style::default()
-                .fg(Color::Yellow)
+                .fg(theme.accent)
                 .add_modifier(Modifier::BOLD),
         )
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Yellow));
+        .border_style(Style::default().fg(theme.accent));
+
+    let (url_text, url_cursor) = app.url_input.view(chunks[1].width.saturating_sub(2));
 
     // Make sure the text is visible with good contrast
     let url_display = if app.url_input.is_empty() {
@@ -121,31 +369,30 @@ fn draw_url_config(f: &mut Frame, app: &App) {
         Paragraph::new("http://localhost:8080").block(url_block.clone())
     } else {
         // Show actual input in white
-        Paragraph::new(app.url_input.clone()).block(url_block.clone())
+        Paragraph::new(url_text).block(url_block.clone())
     };
 
     f.render_widget(url_display, chunks[1]);
 
     // Instructions
-    let instructions = Paragraph::new(vec![Line::from(
-        "Enter: Continue to login | Esc: Quit | Ctrl+Q: Force quit",
-    )])
-    .style(Style::default().fg(Color::Gray))
+    let instructions = Paragraph::new(vec![Line::from(if app.config.profiles.is_empty() {
+        "Enter: Continue to login | Esc: Quit | Ctrl+Q: Force quit"
+    } else {
+        "Enter: Continue to login | Esc: Back to profiles | Ctrl+Q: Force quit"
+    })])
+    .style(Style::default().fg(theme.muted))
     .alignment(Alignment::Center)
     .wrap(Wrap { trim: true });
     f.render_widget(instructions, chunks[2]);
 
-    // Cursor positioning - use actual input length, not display length
-    let cursor_x = if app.url_input.is_empty() {
-        chunks[1].x + 1 // Start of field if empty
-    } else {
-        chunks[1].x + app.url_input.len() as u16 + 1 // End of actual input
-    };
+    // Cursor positioning - use the real input, not the placeholder
+    let cursor_x = chunks[1].x + url_cursor + 1;
 
     f.set_cursor_position((cursor_x, chunks[1].y + 1));
 }
 
 fn draw_login(f: &mut Frame, app: &App) {
+    let theme = app.theme;
     let size = f.area();
 
     // Clear the entire screen first
@@ -154,6 +401,9 @@ fn draw_login(f: &mut Frame, app: &App) {
     // Create a responsive centered login form
     let popup_width = (size.width * 70 / 100).clamp(40, 70); // 70% of width, but between 40-70 chars
     let popup_height = (size.height * 50 / 100).clamp(9, 12); // 50% of height, but between 9-12 lines
+    // A line under the form for why the last login failed
+    let error_height = u16::from(app.login_error.is_some());
+    let popup_height = popup_height + error_height;
 
     let popup_area = centered_rect(popup_width, popup_height, size);
 
@@ -161,12 +411,12 @@ fn draw_login(f: &mut Frame, app: &App) {
         .title(" qBittorrent Login ")
         .title_style(
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme.header)
                 .add_modifier(Modifier::BOLD),
         )
         // Outside border
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan));
+        .border_style(Style::default().fg(theme.header));
 
     f.render_widget(block, popup_area);
 
@@ -178,15 +428,16 @@ fn draw_login(f: &mut Frame, app: &App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3), // Username
-            Constraint::Length(3), // Password
-            Constraint::Min(2),    // Instructions (flexible)
+            Constraint::Length(3),            // Username
+            Constraint::Length(3),            // Password
+            Constraint::Length(error_height), // Login error
+            Constraint::Min(2),               // Instructions (flexible)
         ])
         .split(inner);
 
     // Username field
     let username_title = if app.config.get_last_username().is_some()
-        && app.config.get_last_username().as_ref() == Some(&app.username_input)
+        && app.config.get_last_username().as_deref() == Some(app.username_input.value())
     {
         "Username (Last used)"
     } else {
@@ -196,9 +447,10 @@ fn draw_login(f: &mut Frame, app: &App) {
     let username_block = Block::default()
         .title(username_title)
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Yellow));
+        .border_style(Style::default().fg(theme.accent));
 
-    let username_text = format!("{} ", app.username_input); // Add space for cursor
+    let (username_text, username_cursor) =
+        app.username_input.view(chunks[0].width.saturating_sub(2));
     let username_paragraph = Paragraph::new(username_text).block(username_block);
     f.render_widget(username_paragraph, chunks[0]);
 
@@ -206,39 +458,40 @@ fn draw_login(f: &mut Frame, app: &App) {
     let password_block = Block::default()
         .title("Password")
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Yellow));
+        .border_style(Style::default().fg(theme.accent));
 
-    let password_display = if app.show_password {
-        format!("{} ", app.password_input)
+    let password_width = chunks[1].width.saturating_sub(2);
+    let (password_display, password_cursor) = if app.show_password {
+        app.password_input.view(password_width)
     } else {
-        format!("{} ", "●".repeat(app.password_input.len()))
+        app.password_input.masked().view(password_width)
     };
 
     let password_paragraph = Paragraph::new(password_display).block(password_block);
     f.render_widget(password_paragraph, chunks[1]);
 
+    if let Some(error) = &app.login_error {
+        let error = Paragraph::new(error.as_str())
+            .style(Style::default().fg(theme.error))
+            .alignment(Alignment::Center);
+        f.render_widget(error, chunks[2]);
+    }
+
     // Instructions
     let instructions = Paragraph::new(
-        "Tab: Switch | Enter: Login | Esc: Quit | Ctrl+H: Show/Hide | Ctrl+Q: Force quit",
+        "Tab: Switch | Enter: Login | Esc: Quit | Ctrl+H: Show/Hide | Ctrl+D: Forget saved password | Ctrl+Q: Force quit",
     )
-    .style(Style::default().fg(Color::Gray))
+    .style(Style::default().fg(theme.muted))
     .alignment(Alignment::Center)
     .wrap(Wrap { trim: true });
-    f.render_widget(instructions, chunks[2]);
+    f.render_widget(instructions, chunks[3]);
 
-    // Cursor positioning - simpler approach
     match app.input_mode {
         InputMode::Username => {
-            f.set_cursor_position((
-                chunks[0].x + app.username_input.len() as u16 + 1,
-                chunks[0].y + 1,
-            ));
+            f.set_cursor_position((chunks[0].x + username_cursor + 1, chunks[0].y + 1));
         }
         InputMode::Password => {
-            f.set_cursor_position((
-                chunks[1].x + app.password_input.len() as u16 + 1,
-                chunks[1].y + 1,
-            ));
+            f.set_cursor_position((chunks[1].x + password_cursor + 1, chunks[1].y + 1));
         }
         _ => {}
     }
@@ -247,86 +500,488 @@ fn draw_login(f: &mut Frame, app: &App) {
 fn draw_main(f: &mut Frame, app: &mut App) {
     let size = f.area();
 
+    let header_height = header_height(size.height, app.config.show_speed_graphs);
+    let status_height = status_height(size.height);
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3),
+            Constraint::Length(header_height),
             Constraint::Min(0),
-            Constraint::Length(3),
+            Constraint::Length(status_height),
+            Constraint::Length(FOOTER_HEIGHT),
         ])
         .split(size);
 
     // Header with server info
     draw_header(f, chunks[0], app);
+    if status_height > 0 {
+        draw_status_bar(f, chunks[2], app);
+    }
+
+    // Torrent list, with the status and category/tag panes on its left when shown
+    if app.show_sidebar || app.show_filter_pane {
+        let body = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(24), Constraint::Min(0)])
+            .split(chunks[1]);
+
+        match (app.show_sidebar, app.show_filter_pane) {
+            (true, true) => {
+                let panes = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([
+                        Constraint::Length(StatusFilter::ALL.len() as u16 + 2),
+                        Constraint::Min(0),
+                    ])
+                    .split(body[0]);
+                draw_status_sidebar(f, panes[0], app);
+                draw_filter_pane(f, panes[1], app);
+            }
+            (true, false) => draw_status_sidebar(f, body[0], app),
+            _ => draw_filter_pane(f, body[0], app),
+        }
+        draw_torrent_list(f, body[1], app);
+    } else {
+        draw_torrent_list(f, chunks[1], app);
+    }
+
+    // Footer with controls, or the query while searching
+    if app.state == AppState::Search {
+        draw_search_bar(f, chunks[3], app);
+    } else {
+        draw_footer(f, chunks[3], app);
+    }
+}
+
+// One line about the highlighted torrent, for what the list has no room for
+fn draw_status_bar(f: &mut Frame, area: Rect, app: &App) {
+    let theme = app.theme;
+    let Some(torrent) = app.get_current_selected_torrent() else {
+        return;
+    };
+    let mut spans = Vec::new();
+    let fields = status_fields(torrent, app.tracker_hint(torrent));
+    for (label, value) in fit_status_fields(fields, area.width as usize) {
+        spans.push(Span::styled(
+            format!(" {label}: "),
+            Style::default().fg(theme.header),
+        ));
+        spans.push(Span::styled(value, Style::default().fg(theme.text)));
+    }
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
 
-    // Torrent list
-    draw_torrent_list(f, chunks[1], app);
+/// The highlighted torrent's details, led by its `tracker_hint` when its
+/// trackers are failing.
+fn status_fields(torrent: &Torrent, tracker_hint: Option<&str>) -> Vec<(&'static str, String)> {
+    let size = |bytes: Option<i64>| format_size(bytes.unwrap_or(0).max(0) as u64, BINARY);
+    let or_none = |value: Option<&String>| {
+        value
+            .filter(|value| !value.is_empty())
+            .map_or("—".to_string(), |value| value.clone())
+    };
+    let hint = tracker_hint.map(|message| ("Tracker", message.to_string()));
+    hint.into_iter()
+        .chain([
+            ("Downloaded", size(torrent.downloaded)),
+            ("Uploaded", size(torrent.uploaded)),
+            (
+                "Ratio",
+                torrent
+                    .ratio
+                    .map_or("-".to_string(), |ratio| format!("{ratio:.2}")),
+            ),
+            ("Category", or_none(torrent.category.as_ref())),
+            ("Tags", or_none(torrent.tags.as_ref())),
+            ("Save path", or_none(torrent.save_path.as_ref())),
+        ])
+        .collect()
+}
 
-    // Footer with controls
-    draw_footer(f, chunks[2]);
+/// Keeps the fields that fit in `width` columns, each drawn as
+/// " label: value". The first one that doesn't fit is cut short, and any
+/// after it are dropped.
+fn fit_status_fields(
+    fields: Vec<(&'static str, String)>,
+    width: usize,
+) -> Vec<(&'static str, String)> {
+    let mut used = 0;
+    let mut fitted = Vec::new();
+    for (label, value) in fields {
+        let label_width = label.width() + 3;
+        let room = width.saturating_sub(used + label_width);
+        if room < 4 {
+            break;
+        }
+        let cut = value.width() > room;
+        let value = truncate_to_width(&value, room);
+        used += label_width + value.width();
+        fitted.push((label, value));
+        if cut {
+            break;
+        }
+    }
+    fitted
 }
 
 fn draw_header(f: &mut Frame, area: Rect, app: &App) {
+    let theme = app.theme;
     let block = Block::default()
         .title("qBittorrent TUI")
-        .borders(Borders::ALL);
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border));
 
     let inner = block.inner(area);
     f.render_widget(block, area);
 
+    if let Some(retry_in) = app.offline_retry {
+        let last_update = app.last_update_at.map_or("never".to_string(), |time| {
+            format_clock_time(time, &app.config.get_timezone())
+        });
+        let status = if app.server_shut_down {
+            "SHUT DOWN"
+        } else {
+            "OFFLINE"
+        };
+        let banner = Paragraph::new(format!(
+            "{status} — last update {last_update}, retrying every {}s (r to retry now)",
+            retry_in.as_secs()
+        ))
+        .style(
+            Style::default()
+                .fg(theme.text)
+                .bg(theme.error)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(Alignment::Center);
+        f.render_widget(banner, inner);
+        return;
+    }
+
     if let Some(state) = &app.server_state {
-        let info_text = vec![Line::from(vec![
-            Span::styled("Status: ", Style::default().fg(Color::Cyan)),
-            Span::raw(&state.connection_status),
+        let (icon, color) =
+            connection_icon(&state.connection_status, app.config.ascii_glyphs, &theme);
+        let mut spans = vec![
+            Span::styled("Status: ", Style::default().fg(theme.header)),
+            Span::styled(
+                format!("{icon} {}", state.connection_status),
+                Style::default().fg(color),
+            ),
+            Span::styled(
+                if app.client.is_auth_bypassed() {
+                    " (no auth)"
+                } else {
+                    ""
+                },
+                Style::default().fg(theme.muted),
+            ),
+            Span::styled(
+                state
+                    .dht_nodes
+                    .map_or_else(String::new, |nodes| format!(" DHT: {nodes}")),
+                Style::default().fg(theme.muted),
+            ),
             Span::raw("  |  "),
-            Span::styled("Down: ", Style::default().fg(Color::Green)),
+            Span::styled("Down: ", Style::default().fg(theme.download)),
             Span::raw(format_size(state.dl_info_speed as u64, BINARY) + "/s"),
+            Span::styled(
+                format!(
+                    " [{}]",
+                    format_speed_limit(state.dl_rate_limit.unwrap_or(0))
+                ),
+                Style::default().fg(theme.muted),
+            ),
             Span::raw("  |  "),
-            Span::styled("Up: ", Style::default().fg(Color::Red)),
+            Span::styled("Up: ", Style::default().fg(theme.upload)),
             Span::raw(format_size(state.up_info_speed as u64, BINARY) + "/s"),
+            Span::styled(
+                format!(
+                    " [{}]",
+                    format_speed_limit(state.up_rate_limit.unwrap_or(0))
+                ),
+                Style::default().fg(theme.muted),
+            ),
             Span::raw("  |  "),
-            Span::styled("Torrents: ", Style::default().fg(Color::Yellow)),
+            Span::styled("Torrents: ", Style::default().fg(theme.accent)),
             Span::raw(app.torrents.len().to_string()),
-        ])];
+        ];
+        if let Some(free) = app.free_space() {
+            spans.push(Span::raw("  |  "));
+            spans.push(Span::styled("Free: ", Style::default().fg(theme.header)));
+            spans.push(Span::styled(
+                format_size(free, BINARY),
+                Style::default().fg(free_space_color(free, &app.config, &theme)),
+            ));
+        }
+        spans.extend([
+            Span::raw("  |  "),
+            Span::styled("Refresh: ", Style::default().fg(theme.header)),
+            Span::raw(match app.refresh_interval_secs {
+                0 => "manual".to_string(),
+                secs => format!("{secs}s"),
+            }),
+        ]);
+
+        if app.client.is_reconnecting() {
+            spans.push(Span::raw("  |  "));
+            spans.push(Span::styled(
+                "reconnecting…",
+                Style::default()
+                    .fg(theme.warning)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+
+        if app.connection.insecure {
+            spans.push(Span::raw("  |  "));
+            spans.push(Span::styled(
+                "⚠ INSECURE",
+                Style::default()
+                    .fg(theme.warning)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+
+        if state.use_alt_speed_limits == Some(true) {
+            spans.push(Span::raw("  |  "));
+            spans.push(Span::styled(
+                "ALT SPEED",
+                Style::default().fg(theme.info).add_modifier(Modifier::BOLD),
+            ));
+        }
+
+        let info_text = vec![Line::from(spans)];
 
         let paragraph = Paragraph::new(info_text).alignment(Alignment::Center);
         f.render_widget(paragraph, inner);
+
+        if inner.height >= 2 {
+            let graph_row = Rect::new(inner.x, inner.y + 1, inner.width, 1);
+            draw_speed_graphs(f, graph_row, app);
+        }
+    }
+}
+
+/// Yellow and then red as the free space drops below the configured GiB.
+fn free_space_color(free: u64, config: &Config, theme: &Theme) -> Color {
+    const GIB: u64 = 1 << 30;
+    if free < config.free_space_critical_gib.saturating_mul(GIB) {
+        theme.error
+    } else if free < config.free_space_warning_gib.saturating_mul(GIB) {
+        theme.warning
+    } else {
+        theme.text
+    }
+}
+
+/// Symbol and color for the server's `connection_status`.
+fn connection_icon(status: &str, ascii: bool, theme: &Theme) -> (&'static str, Color) {
+    let ((unicode, plain), color) = match status {
+        "connected" => (("●", "+"), theme.success),
+        "firewalled" => (("▲", "!"), theme.warning),
+        "disconnected" => (("✗", "x"), theme.error),
+        _ => (("?", "?"), theme.muted),
+    };
+    (if ascii { plain } else { unicode }, color)
+}
+
+fn draw_speed_graphs(f: &mut Frame, area: Rect, app: &App) {
+    let theme = app.theme;
+    let halves = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+
+    let graphs = [
+        (
+            "↓ ",
+            theme.download,
+            app.speed_history.download(area.width as usize),
+        ),
+        (
+            "↑ ",
+            theme.upload,
+            app.speed_history.upload(area.width as usize),
+        ),
+    ];
+    for ((label, color, samples), half) in graphs.into_iter().zip(halves.iter()) {
+        draw_speed_graph(
+            f,
+            *half,
+            Span::styled(label, Style::default().fg(color)),
+            &samples,
+            color,
+        );
     }
 }
 
+/// One line of `label` followed by a graph of `samples`, leaving a column
+/// free on the right.
+fn draw_speed_graph(f: &mut Frame, area: Rect, label: Span, samples: &[u64], color: Color) {
+    let parts = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Length(label.width() as u16),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .split(area);
+    f.render_widget(Paragraph::new(Line::from(label)), parts[0]);
+    // Newest samples on the right, as many as fit
+    let width = parts[1].width as usize;
+    let samples = &samples[samples.len().saturating_sub(width)..];
+    f.render_widget(
+        Sparkline::default()
+            .data(samples)
+            .style(Style::default().fg(color)),
+        parts[1],
+    );
+}
+
+fn draw_status_sidebar(f: &mut Frame, area: Rect, app: &App) {
+    let theme = app.theme;
+    let items: Vec<ListItem> = StatusFilter::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, &filter)| {
+            let style = if filter == app.status_filter {
+                Style::default()
+                    .fg(theme.background)
+                    .bg(theme.header)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            ListItem::new(format!(
+                "{} {} ({})",
+                i + 1,
+                filter.label(),
+                app.status_filter_count(filter)
+            ))
+            .style(style)
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .title("Status")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border)),
+    );
+    f.render_widget(list, area);
+}
+
+fn draw_filter_pane(f: &mut Frame, area: Rect, app: &App) {
+    let theme = app.theme;
+    let entries = app.filter_pane_entries();
+    let items: Vec<ListItem> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let (label, count) = match entry {
+                Some(filter) => (filter.label(), app.label_filter_count(filter)),
+                None => ("All".to_string(), app.torrents.len()),
+            };
+            let style = if i == app.filter_pane_index && app.filter_pane_focused {
+                Style::default()
+                    .fg(theme.background)
+                    .bg(theme.header)
+                    .add_modifier(Modifier::BOLD)
+            } else if *entry == app.label_filter {
+                Style::default()
+                    .fg(theme.header)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            ListItem::new(format!("{label} ({count})")).style(style)
+        })
+        .collect();
+
+    let border_style = if app.filter_pane_focused {
+        Style::default().fg(theme.accent)
+    } else {
+        Style::default().fg(theme.border)
+    };
+    let block = Block::default()
+        .title("Categories / Tags")
+        .borders(Borders::ALL)
+        .border_style(border_style);
+
+    // Keep the highlighted entry in view
+    let inner_height = area.height.saturating_sub(2) as usize;
+    let skip = (app.filter_pane_index + 1).saturating_sub(inner_height);
+    let list = List::new(items.into_iter().skip(skip).collect::<Vec<_>>()).block(block);
+    f.render_widget(list, area);
+}
+
 fn draw_torrent_list(f: &mut Frame, area: Rect, app: &mut App) {
-    let scroll_info = if app.torrents.len() > app.get_max_visible_rows() {
+    let theme = app.theme;
+    let list_len = app.get_current_torrent_list_len();
+    let scroll_info = if list_len > app.get_max_visible_rows() {
         format!(
             " [{}-{}/{}]",
             app.scroll_offset + 1,
-            (app.scroll_offset + app.get_max_visible_rows()).min(app.torrents.len()),
-            app.torrents.len()
+            (app.scroll_offset + app.get_max_visible_rows()).min(list_len),
+            list_len
         )
     } else {
         String::new()
     };
 
+    let mut filters = Vec::new();
+    if app.status_filter != StatusFilter::All {
+        filters.push(app.status_filter.label().to_string());
+    }
+    if let Some(filter) = &app.label_filter {
+        filters.push(filter.label());
+    }
+    if let Some(query) = app.active_search() {
+        filters.push(format!("filter: \"{query}\""));
+    }
+    let title = if filters.is_empty() {
+        format!("Torrents ({list_len})")
+    } else {
+        format!("Torrents — {} ({list_len})", filters.join(" · "))
+    };
+
+    let sort_arrow = if app.sort_descending { '▼' } else { '▲' };
     let block = Block::default()
-        .title(format!("Torrents ({}){}", app.torrents.len(), scroll_info))
-        .borders(Borders::ALL);
+        .title(format!(
+            "{}{} — sorted by {} {}",
+            title,
+            scroll_info,
+            app.sort_column.label(),
+            sort_arrow
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border));
 
     let inner = block.inner(area);
     f.render_widget(block, area);
 
     if app.torrents.is_empty() {
         let no_torrents = Paragraph::new("No torrents found\n\nPress 'a' to add a torrent")
-            .style(Style::default().fg(Color::Gray))
+            .style(Style::default().fg(theme.muted))
             .alignment(Alignment::Center);
         f.render_widget(no_torrents, inner);
         return;
     }
 
+    if list_len == 0 {
+        let no_matches = Paragraph::new("No torrents match this filter")
+            .style(Style::default().fg(theme.muted))
+            .alignment(Alignment::Center);
+        f.render_widget(no_matches, inner);
+        return;
+    }
+
     // Calculate available space for torrents
-    let header_height = 2;
+    let header_height = COLUMN_HEADER_HEIGHT;
     let available_height = inner.height.saturating_sub(header_height);
 
-    // Update app with the actual number of visible rows
-    app.set_max_visible_rows(available_height as usize);
+    // Matches `list_rows` for this terminal, so a resize already set it
+    app.set_max_visible_rows(available_height as usize / app.row_mode.height());
 
     let header_area = Rect {
         x: inner.x,
@@ -342,26 +997,27 @@ fn draw_torrent_list(f: &mut Frame, area: Rect, app: &mut App) {
         height: available_height,
     };
 
-    // Calculate the same widths as used in the data rows
-    let available_width = inner.width.saturating_sub(8 + 12 + 12 + 12 + 15 + 8 + 7) as usize; // Progress + Size + Down + Up + State + ETA + spacing
-    let name_width = available_width.max(20); // Minimum 20 chars for name, same as in data rows
+    let layout = ListLayout::new(inner.width as usize, &app.columns);
 
-    // Draw header
+    // Draw header, marking the sorted column
+    let header: Vec<String> = layout
+        .columns
+        .iter()
+        .map(|spec| {
+            let sorted = spec.column.sort_column() == Some(app.sort_column);
+            layout.header_cell(spec, sorted.then_some(sort_arrow))
+        })
+        .collect();
+    // Rows are shifted right by the list's highlight symbol and the glyph
     let header_text = vec![
         Line::from(vec![Span::styled(
             format!(
-                "{:<width$} {:>8} {:>12} {:>12} {:>12} {:<15} {:>8}",
-                "Name",
-                "Progress",
-                "Size",
-                "Down Speed",
-                "Up Speed",
-                "State",
-                "ETA",
-                width = name_width
+                "{}{}",
+                " ".repeat(HIGHLIGHT_WIDTH + GLYPH_WIDTH),
+                header.join(" ")
             ),
             Style::default()
-                .fg(Color::Cyan)
+                .fg(theme.header)
                 .add_modifier(Modifier::BOLD),
         )]),
         Line::from(vec![Span::raw("─".repeat(inner.width as usize))]),
@@ -370,94 +1026,89 @@ fn draw_torrent_list(f: &mut Frame, area: Rect, app: &mut App) {
     let header_paragraph = Paragraph::new(header_text);
     f.render_widget(header_paragraph, header_area);
 
+    // Remember where rows and sortable headers are for mouse clicks
+    app.list_area = list_area;
+    app.header_sort_areas.clear();
+    let mut x = header_area.x + (HIGHLIGHT_WIDTH + GLYPH_WIDTH) as u16;
+    for spec in &layout.columns {
+        let width = layout.width(spec) as u16;
+        if let Some(column) = spec.column.sort_column() {
+            let cell = Rect::new(x, header_area.y, width, 1).intersection(header_area);
+            app.header_sort_areas.push((cell, column));
+        }
+        x = x.saturating_add(width + 1);
+    }
+
     // Create torrent list items
+    let now = chrono::Utc::now().timestamp();
     let visible_torrents = app.get_visible_torrents();
     let items: Vec<ListItem> = visible_torrents
         .iter()
         .map(|torrent| {
-            let progress = (torrent.progress * 100.0) as u8;
-            let size_str = format_size(torrent.size as u64, BINARY);
-            let dl_speed_str = if torrent.dlspeed > 0 {
-                format_size(torrent.dlspeed as u64, BINARY) + "/s"
-            } else {
-                "".to_string()
-            };
-            let ul_speed_str = if torrent.upspeed > 0 {
-                format_size(torrent.upspeed as u64, BINARY) + "/s"
-            } else {
-                "".to_string()
-            };
-
-            // Calculate available width for name (total width - other columns - spacing)
-            let available_width =
-                inner.width.saturating_sub(8 + 12 + 12 + 12 + 15 + 8 + 7) as usize; // Progress + Size + Down + Up + State + ETA + spacing
-            let name_width = available_width.max(20); // Minimum 20 chars for name
-
-            let name = if torrent.name.len() > name_width {
-                format!("{}...", &torrent.name[..name_width.saturating_sub(3)])
-            } else {
-                torrent.name.clone()
-            };
-
-            let state_color = match torrent.state.as_str() {
-                "downloading" => Color::Green,
-                "uploading" | "stalledUP" => Color::Blue,
-                "pausedDL" | "pausedUP" => Color::Yellow,
-                "error" => Color::Red,
-                "queuedDL" | "queuedUP" => Color::Cyan,
-                _ => Color::White,
-            };
-
-            let line = Line::from(vec![
-                Span::raw(format!("{name:<name_width$}")),
-                Span::raw(" "),
-                Span::styled(format!("{progress:>7}%"), Style::default().fg(Color::Green)),
-                Span::raw(" "),
-                Span::raw(format!("{size_str:>11}")),
-                Span::raw(" "),
-                Span::raw(format!("{dl_speed_str:>11}")),
-                Span::raw(" "),
-                Span::raw(format!("{ul_speed_str:>11}")),
-                Span::raw(" "),
-                Span::styled(
-                    format!("{:<14}", torrent.state),
-                    Style::default().fg(state_color),
-                ),
-                Span::raw(" "),
-                Span::styled(
-                    format!(
-                        "{:>7}",
-                        match torrent.state.as_str() {
-                            "downloading" | "stalledDL" | "queuedDL" => {
-                                torrent.eta.map_or("∞".to_string(), |e| {
-                                    if e < 0 {
-                                        "∞".to_string()
-                                    } else if e == 0 {
-                                        "0s".to_string()
-                                    } else if e < 60 {
-                                        format!("{e}s")
-                                    } else if e < 3600 {
-                                        format!("{}m", e / 60)
-                                    } else if e < 86400 {
-                                        format!("{}h{}m", e / 3600, (e % 3600) / 60)
-                                    } else {
-                                        format!("{}d{}h", e / 86400, (e % 86400) / 3600)
-                                    }
-                                })
-                            }
-                            _ => "-".to_string(), // For uploading, stalled upload, completed, etc.
-                        }
-                    ),
-                    Style::default().fg(Color::Magenta),
-                ),
-            ]);
+            let marked = app.selected_hashes.contains(&torrent.hash);
+            let stalled = app.stalled_for(torrent);
+            let glyph = torrent.state.glyph(app.config.ascii_glyphs);
+            let mut spans = vec![Span::styled(
+                format!("{} ", fit_to_width(glyph, GLYPH_WIDTH - 1, false)),
+                Style::default().fg(theme.state_color(&torrent.state)),
+            )];
+            for (i, spec) in layout.columns.iter().enumerate() {
+                if i > 0 {
+                    spans.push(Span::raw(" "));
+                }
+                let (mut text, mut style) = layout.row_cell(spec, torrent, marked, &theme, now);
+                if let Some(secs) = stalled
+                    && spec.column == ListColumn::Eta
+                {
+                    text = spec.pad(&stalled_label(secs), layout.width(spec));
+                    style = Style::default().fg(theme.muted);
+                }
+                let found = (spec.column == ListColumn::Name && app.is_searching)
+                    .then(|| {
+                        // A name cell too narrow for the whole marker has
+                        // no name left to highlight
+                        let name = if marked {
+                            text.strip_prefix(MARKER)?
+                        } else {
+                            &text
+                        };
+                        let marker = text.len() - name.len();
+                        let found = app.search_query.highlight(name.trim_end())?;
+                        Some(found.start + marker..found.end + marker)
+                    })
+                    .flatten();
+                match found {
+                    Some(found) => {
+                        spans.push(Span::raw(text[..found.start].to_string()));
+                        spans.push(Span::styled(
+                            text[found.clone()].to_string(),
+                            Style::default()
+                                .fg(theme.accent)
+                                .add_modifier(Modifier::BOLD),
+                        ));
+                        spans.push(Span::raw(text[found.end..].to_string()));
+                    }
+                    None => spans.push(Span::styled(text, style)),
+                }
+            }
+            let mut lines = vec![Line::from(spans)];
+            if app.row_mode == RowMode::Detailed {
+                lines.push(detail_line(torrent, app.tracker_hint(torrent), &theme));
+            }
 
-            ListItem::new(line)
+            let mut style = Style::default();
+            if marked {
+                style = style.bg(theme.marked);
+            }
+            if stalled.is_some() {
+                style = style.add_modifier(Modifier::DIM | Modifier::ITALIC);
+            }
+            ListItem::new(lines).style(style)
         })
         .collect();
 
     let selected_style = Style::default()
-        .bg(Color::DarkGray)
+        .bg(theme.selection)
         .add_modifier(Modifier::BOLD);
 
     let list = List::new(items)
@@ -468,33 +1119,2143 @@ fn draw_torrent_list(f: &mut Frame, area: Rect, app: &mut App) {
     list_state.select(Some(app.get_relative_selected_index()));
 
     f.render_stateful_widget(list, list_area, &mut list_state);
+    draw_scrollbar(
+        f,
+        border_rows(area, list_area),
+        list_len,
+        app.max_visible_rows,
+        app.scroll_offset,
+    );
 }
 
-fn draw_footer(f: &mut Frame, area: Rect) {
-    let block = Block::default().title("Controls").borders(Borders::ALL);
+/// The stretch of `outer`'s right border next to `rows`, where scrollbars go.
+fn border_rows(outer: Rect, rows: Rect) -> Rect {
+    Rect::new(outer.right().saturating_sub(1), rows.y, 1, rows.height)
+}
 
-    let controls = Paragraph::new(
-        "Ctrl+Q: Quit | r: Refresh | ↑↓: Navigate | PgUp/PgDn: Page | Home/End: First/Last | Space: Pause/Resume | Del: Delete | Ctrl+A: Add | Ctrl+F: Search"
-    )
-    .block(block)
-    .style(Style::default().fg(Color::Gray))
-    .alignment(Alignment::Center);
+/// Draws a vertical scrollbar in `area` when `total` rows don't all fit in
+/// the `visible` ones; `offset` is the first row shown.
+fn draw_scrollbar(f: &mut Frame, area: Rect, total: usize, visible: usize, offset: usize) {
+    if total <= visible {
+        return;
+    }
+    // One position per possible offset, so the thumb reaches the end
+    let mut state = ScrollbarState::new(total - visible + 1)
+        .viewport_content_length(visible)
+        .position(offset);
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(None)
+        .end_symbol(None);
+    f.render_stateful_widget(scrollbar, area, &mut state);
+}
 
-    f.render_widget(controls, area);
+/// Terminals shorter than this keep the single-line header
+const SPEED_GRAPH_MIN_HEIGHT: u16 = 30;
+/// Terminals shorter than this give the status bar's line to the list
+const STATUS_BAR_MIN_HEIGHT: u16 = 20;
+const FOOTER_HEIGHT: u16 = 3;
+/// The column titles and the rule under them
+const COLUMN_HEADER_HEIGHT: u16 = 2;
+
+// Tall terminals get a second header line with speed graphs
+fn header_height(terminal_height: u16, speed_graphs: bool) -> u16 {
+    if speed_graphs && terminal_height >= SPEED_GRAPH_MIN_HEIGHT {
+        4
+    } else {
+        3
+    }
 }
 
-fn draw_add_torrent(f: &mut Frame, app: &App) {
-    let size = f.area();
-    let popup_area = centered_rect(60, 10, size);
+fn status_height(terminal_height: u16) -> u16 {
+    if terminal_height >= STATUS_BAR_MIN_HEIGHT {
+        1
+    } else {
+        0
+    }
+}
 
-    f.render_widget(Clear, popup_area);
+/// Torrents the main screen's list fits in a terminal `terminal_height`
+/// lines tall, as drawing will find, so a resize can scroll before the next
+/// frame.
+pub fn list_rows(terminal_height: u16, speed_graphs: bool, row_height: usize) -> usize {
+    let chrome = header_height(terminal_height, speed_graphs)
+        + status_height(terminal_height)
+        + FOOTER_HEIGHT
+        // The list's borders
+        + 2
+        + COLUMN_HEADER_HEIGHT;
+    terminal_height.saturating_sub(chrome) as usize / row_height
+}
 
-    let block = Block::default()
-        .title("Add Torrent")
-        .borders(Borders::ALL)
-        .style(Style::default().bg(Color::Black));
+/// Terminals smaller than this only get a note asking for more room
+const MIN_TERMINAL_WIDTH: u16 = 40;
+const MIN_TERMINAL_HEIGHT: u16 = 10;
+/// Lists narrower than this lose the upload speed and ETA
+const REDUCED_LIST_WIDTH: usize = 100;
+/// Lists narrower than this lose the size and shorten state names
+const SHORT_STATE_WIDTH: usize = 80;
+/// Lists narrower than this keep only the name and progress, leaving the
+/// state to the glyph leading each row
+const MINIMAL_LIST_WIDTH: usize = 60;
 
-    f.render_widget(block, popup_area);
+const HIGHLIGHT_WIDTH: usize = 2;
+/// The state glyph starting each row, in two cells as some are wide, and
+/// the space after it
+const GLYPH_WIDTH: usize = 3;
+const MIN_NAME_WIDTH: usize = 20;
+/// Lists narrower than this show progress as a plain percentage
+const PROGRESS_BAR_MIN_WIDTH: usize = 120;
+const PROGRESS_BAR_CELLS: usize = 10;
+/// Starts the name of a marked row
+const MARKER: &str = "✔ ";
+
+/// Layout of one torrent list column, shared by the header and the rows so
+/// the two always line up.
+struct ColumnSpec {
+    column: ListColumn,
+    /// Fixed width; the name column takes whatever is left instead, see
+    /// `ListLayout::width`
+    width: usize,
+    right_aligned: bool,
+    /// Columns with a lower rank are dropped first on narrow terminals;
+    /// `None` is always shown
+    drop_rank: Option<u8>,
+}
+
+impl ColumnSpec {
+    fn pad(&self, text: &str, width: usize) -> String {
+        fit_to_width(text, width, self.right_aligned)
+    }
+}
+
+const fn column(
+    column: ListColumn,
+    width: usize,
+    right_aligned: bool,
+    drop_rank: Option<u8>,
+) -> ColumnSpec {
+    ColumnSpec {
+        column,
+        width,
+        right_aligned,
+        drop_rank,
+    }
+}
+
+/// Every column's layout; the chooser picks which are shown and in what
+/// order.
+const COLUMNS: &[ColumnSpec] = &[
+    column(ListColumn::Name, 0, false, None),
+    column(ListColumn::Progress, 8, true, None),
+    column(ListColumn::Size, 11, true, None),
+    column(ListColumn::DlSpeed, 12, true, None),
+    column(ListColumn::UpSpeed, 12, true, Some(5)),
+    column(ListColumn::State, 14, false, None),
+    column(ListColumn::Eta, 11, true, Some(6)),
+    column(ListColumn::Ratio, 6, true, Some(4)),
+    column(ListColumn::Seeds, 5, true, Some(3)),
+    column(ListColumn::Peers, 5, true, Some(2)),
+    column(ListColumn::Added, 8, true, Some(1)),
+    column(ListColumn::Completed, 9, true, Some(0)),
+    column(ListColumn::Category, 12, false, Some(7)),
+];
+
+fn column_spec(column: ListColumn) -> &'static ColumnSpec {
+    COLUMNS
+        .iter()
+        .find(|spec| spec.column == column)
+        .expect("every column has a spec")
+}
+
+/// How the State column writes a torrent's state.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StateLabel {
+    Full,
+    Short,
+}
+
+impl StateLabel {
+    fn width(self, spec: &ColumnSpec) -> usize {
+        match self {
+            StateLabel::Full => spec.width,
+            StateLabel::Short => 7,
+        }
+    }
+
+    fn text(self, state: &TorrentState) -> &str {
+        match self {
+            StateLabel::Full => state.display_name(),
+            StateLabel::Short => state.short_name(),
+        }
+    }
+}
+
+/// Which of the `shown` columns a list `total` columns wide has room for,
+/// in order, and how it writes states. Any that still don't fit are left to
+/// `ListLayout` to drop by rank.
+fn fit_columns(total: usize, shown: &[ListColumn]) -> (Vec<ListColumn>, StateLabel) {
+    let (dropped, label): (&[ListColumn], StateLabel) = if total < SHORT_STATE_WIDTH {
+        (
+            &[ListColumn::UpSpeed, ListColumn::Eta, ListColumn::Size],
+            StateLabel::Short,
+        )
+    } else if total < REDUCED_LIST_WIDTH {
+        (&[ListColumn::UpSpeed, ListColumn::Eta], StateLabel::Full)
+    } else {
+        (&[], StateLabel::Full)
+    };
+    if total < MINIMAL_LIST_WIDTH {
+        let kept = [ListColumn::Name, ListColumn::Progress];
+        let columns = shown
+            .iter()
+            .copied()
+            .filter(|column| kept.contains(column))
+            .collect();
+        return (columns, label);
+    }
+    let columns = shown
+        .iter()
+        .copied()
+        .filter(|column| !dropped.contains(column))
+        .collect();
+    (columns, label)
+}
+
+/// Columns chosen for a list `total` columns wide and the width each gets.
+struct ListLayout {
+    columns: Vec<&'static ColumnSpec>,
+    name_width: usize,
+    /// Draw progress as a bar rather than a bare percentage
+    progress_bars: bool,
+    state_label: StateLabel,
+}
+
+impl ListLayout {
+    /// Lays out the `shown` columns in order, leaving out the ones
+    /// `fit_columns` has no room for and then the lowest ranked until the
+    /// name gets at least `MIN_NAME_WIDTH`.
+    fn new(total: usize, shown: &[ListColumn]) -> Self {
+        let (columns, state_label) = fit_columns(total, shown);
+        let mut layout = Self {
+            columns: columns.into_iter().map(column_spec).collect(),
+            name_width: 0,
+            progress_bars: total >= PROGRESS_BAR_MIN_WIDTH,
+            state_label,
+        };
+        loop {
+            // Space left once the fixed columns, their separators, the
+            // highlight symbol and the state glyph are taken out
+            let fixed: usize = layout
+                .columns
+                .iter()
+                .filter(|spec| spec.column != ListColumn::Name)
+                .map(|spec| layout.width(spec) + 1)
+                .sum();
+            layout.name_width = total.saturating_sub(fixed + HIGHLIGHT_WIDTH + GLYPH_WIDTH);
+            if layout.name_width >= MIN_NAME_WIDTH {
+                break;
+            }
+
+            let lowest = layout
+                .columns
+                .iter()
+                .enumerate()
+                .filter_map(|(i, spec)| spec.drop_rank.map(|rank| (rank, i)))
+                .min();
+            match lowest {
+                Some((_, i)) => {
+                    layout.columns.remove(i);
+                }
+                None => break,
+            }
+        }
+        layout
+    }
+
+    fn width(&self, spec: &ColumnSpec) -> usize {
+        match spec.column {
+            ListColumn::Name => self.name_width,
+            ListColumn::Progress if self.progress_bars => PROGRESS_BAR_CELLS + 5,
+            ListColumn::State => self.state_label.width(spec),
+            _ => spec.width,
+        }
+    }
+
+    /// The header's cell for `spec`, its title followed by `arrow` when the
+    /// list is sorted on it.
+    fn header_cell(&self, spec: &ColumnSpec, arrow: Option<char>) -> String {
+        let width = self.width(spec);
+        let title = spec.column.title();
+        let label = match arrow {
+            Some(arrow) => {
+                let title: String = title.chars().take(width.saturating_sub(2)).collect();
+                format!("{title} {arrow}")
+            }
+            None => title.to_string(),
+        };
+        spec.pad(&label, width)
+    }
+
+    /// A row's cell for `spec`, padded to the same width as its header.
+    fn row_cell(
+        &self,
+        spec: &ColumnSpec,
+        torrent: &Torrent,
+        marked: bool,
+        theme: &Theme,
+        now: i64,
+    ) -> (String, Style) {
+        let width = self.width(spec);
+        let (text, style) = match spec.column {
+            ListColumn::Name => {
+                // Marked rows give up two columns of the name for the marker
+                let marker = if marked { MARKER } else { "" };
+                let name = truncate_to_width(&torrent.name, width.saturating_sub(marker.width()));
+                (format!("{marker}{name}"), Style::default())
+            }
+            ListColumn::Progress if self.progress_bars => {
+                let percent = format!("{}%", (torrent.progress * 100.0) as u8);
+                (
+                    format!(
+                        "{} {percent:>4}",
+                        progress_bar(torrent.progress, PROGRESS_BAR_CELLS)
+                    ),
+                    Style::default().fg(progress_color(&torrent.state, theme)),
+                )
+            }
+            ListColumn::State => (
+                self.state_label.text(&torrent.state).to_string(),
+                Style::default().fg(theme.state_color(&torrent.state)),
+            ),
+            column => column_cell(column, torrent, theme, now),
+        };
+        (spec.pad(&text, width), style)
+    }
+}
+
+fn progress_color(state: &TorrentState, theme: &Theme) -> Color {
+    match state_bucket(state) {
+        Some(StatusFilter::Downloading) => theme.downloading,
+        Some(StatusFilter::Seeding) => theme.seeding,
+        Some(StatusFilter::Paused) => theme.paused,
+        Some(StatusFilter::Errored) => theme.errored,
+        _ => theme.muted,
+    }
+}
+
+/// The second line of a torrent in the detailed row mode: a progress bar,
+/// both speeds and the tracker it's working with, or why none is.
+fn detail_line(torrent: &Torrent, tracker_hint: Option<&str>, theme: &Theme) -> Line<'static> {
+    let muted = Style::default().fg(theme.muted);
+    let speed = |bytes: i64| format_size(bytes.max(0) as u64, BINARY) + "/s";
+    let mut spans = vec![
+        Span::raw(" ".repeat(GLYPH_WIDTH)),
+        Span::styled(
+            progress_bar(torrent.progress, PROGRESS_BAR_CELLS),
+            Style::default().fg(progress_color(&torrent.state, theme)),
+        ),
+        Span::styled(
+            format!(" {:>4}", format!("{}%", (torrent.progress * 100.0) as u8)),
+            muted,
+        ),
+        Span::styled(format!("  ↓ {}", speed(torrent.dlspeed)), muted),
+        Span::styled(format!("  ↑ {}", speed(torrent.upspeed)), muted),
+    ];
+    // The tracker's host says as much as its full announce URL
+    if let Some(message) = tracker_hint {
+        spans.push(Span::styled(
+            format!("  Tracker: {message}"),
+            Style::default().fg(theme.error),
+        ));
+    } else if let Some(tracker) = torrent.tracker.as_deref().filter(|t| !t.is_empty()) {
+        let host = url::Url::parse(tracker)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_else(|| tracker.to_string());
+        spans.push(Span::styled(format!("  {host}"), muted));
+    }
+    Line::from(spans)
+}
+
+fn column_cell(column: ListColumn, torrent: &Torrent, theme: &Theme, now: i64) -> (String, Style) {
+    let speed = |bytes: i64| {
+        if bytes > 0 {
+            format_size(bytes as u64, BINARY) + "/s"
+        } else {
+            String::new()
+        }
+    };
+    let count = |n: Option<i32>| n.map_or("-".to_string(), |n| n.to_string());
+
+    match column {
+        ListColumn::Name => (torrent.name.clone(), Style::default()),
+        ListColumn::Progress => (
+            format!("{}%", (torrent.progress * 100.0) as u8),
+            Style::default().fg(theme.download),
+        ),
+        ListColumn::Size => (format_size(torrent.size as u64, BINARY), Style::default()),
+        ListColumn::DlSpeed => (speed(torrent.dlspeed), Style::default()),
+        ListColumn::UpSpeed => (speed(torrent.upspeed), Style::default()),
+        ListColumn::State => {
+            let state = &torrent.state;
+            (
+                state.display_name().to_string(),
+                Style::default().fg(theme.state_color(state)),
+            )
+        }
+        ListColumn::Eta => {
+            // Seeding, paused and checking torrents have nothing left to fetch
+            let eta = if torrent.state.is_downloading() {
+                format_duration(torrent.eta.unwrap_or(INFINITE_SECS))
+            } else {
+                "-".to_string()
+            };
+            (eta, Style::default().fg(theme.info))
+        }
+        ListColumn::Ratio => match torrent.ratio {
+            Some(ratio) => {
+                let color = if ratio >= 1.0 {
+                    theme.success
+                } else {
+                    theme.muted
+                };
+                (format!("{ratio:.2}"), Style::default().fg(color))
+            }
+            None => ("-".to_string(), Style::default().fg(theme.muted)),
+        },
+        ListColumn::Seeds => (count(torrent.num_seeds), Style::default()),
+        ListColumn::Peers => (count(torrent.num_leechs), Style::default()),
+        ListColumn::Added => (
+            format_age(torrent.added_on.unwrap_or(0), now),
+            Style::default().fg(theme.muted),
+        ),
+        ListColumn::Completed => (
+            format_age(torrent.completion_on.unwrap_or(0), now),
+            Style::default().fg(theme.muted),
+        ),
+        ListColumn::Category => (
+            torrent.category.clone().unwrap_or_default(),
+            Style::default().fg(theme.muted),
+        ),
+    }
+}
+
+fn draw_footer(f: &mut Frame, area: Rect, app: &App) {
+    let theme = app.theme;
+    let mut block = Block::default()
+        .title("Controls")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border));
+
+    if !app.selected_hashes.is_empty() {
+        block = block.title(Span::styled(
+            format!(" {} selected ", app.selected_hashes.len()),
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    let controls = Paragraph::new(bindings::footer_text(&app.keymap))
+        .block(block)
+        .style(Style::default().fg(theme.muted))
+        .alignment(Alignment::Center);
+
+    f.render_widget(controls, area);
+}
+
+fn draw_add_torrent(f: &mut Frame, app: &App) {
+    let theme = app.theme;
+    let size = f.area();
+    let popup_area = centered_rect(70, 19, size);
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title("Add Torrent")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(theme.background));
+
+    f.render_widget(block, popup_area);
+
+    let inner = popup_area.inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Path or magnet
+            Constraint::Length(3), // Save path
+            Constraint::Length(3), // Category
+            Constraint::Length(1), // Where the category or default saves
+            Constraint::Length(3), // Start paused
+            Constraint::Min(1),    // Instructions
+        ])
+        .split(inner);
+
+    let field_style = |mode: InputMode| {
+        if app.input_mode == mode {
+            Style::default().fg(theme.accent)
+        } else {
+            Style::default().fg(theme.muted)
+        }
+    };
+
+    let (path_text, path_cursor) = app
+        .torrent_path_input
+        .view(chunks[0].width.saturating_sub(2));
+    let path_paragraph = Paragraph::new(path_text).block(
+        Block::default()
+            .title("Torrent File Path or Magnet Link")
+            .borders(Borders::ALL)
+            .style(field_style(InputMode::TorrentPath)),
+    );
+    f.render_widget(path_paragraph, chunks[0]);
+
+    let (save_path_text, save_path_cursor) = app
+        .add_save_path_input
+        .view(chunks[1].width.saturating_sub(2));
+    let save_path_paragraph = Paragraph::new(save_path_text).block(
+        Block::default()
+            .title("Save Path")
+            .borders(Borders::ALL)
+            .style(field_style(InputMode::SavePath)),
+    );
+    f.render_widget(save_path_paragraph, chunks[1]);
+
+    let category = app.add_category().unwrap_or("(none)");
+    let category_paragraph = Paragraph::new(format!("◀ {category} ▶")).block(
+        Block::default()
+            .title("Category")
+            .borders(Borders::ALL)
+            .style(field_style(InputMode::Category)),
+    );
+    f.render_widget(category_paragraph, chunks[2]);
+
+    let mut spans = Vec::new();
+    if let Some((hint, custom)) = app.add_save_path_hint() {
+        spans.push(Span::styled(hint, Style::default().fg(theme.muted)));
+        if custom {
+            spans.push(Span::styled(
+                " (custom)",
+                Style::default()
+                    .fg(theme.muted)
+                    .add_modifier(Modifier::ITALIC),
+            ));
+        }
+    }
+    if let Some((free, too_big)) = app.add_free_space() {
+        if !spans.is_empty() {
+            spans.push(Span::styled(" · ", Style::default().fg(theme.muted)));
+        }
+        let color = if too_big { theme.warning } else { theme.muted };
+        spans.push(Span::styled(free, Style::default().fg(color)));
+    }
+    if !spans.is_empty() {
+        f.render_widget(
+            Paragraph::new(Line::from(spans)),
+            chunks[3].inner(Margin {
+                vertical: 0,
+                horizontal: 1,
+            }),
+        );
+    }
+
+    let paused_mark = if app.add_start_paused { "[x]" } else { "[ ]" };
+    let paused_paragraph = Paragraph::new(format!("{paused_mark} Start paused")).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .style(field_style(InputMode::StartPaused)),
+    );
+    f.render_widget(paused_paragraph, chunks[4]);
+
+    let instructions = Paragraph::new(
+        "Tab: Complete path / Next field | Ctrl+O: Browse | Ctrl+P: Category preset | ↑↓: Switch field | ←→: Change category | Space: Toggle | Enter on last field: Add | Esc: Cancel",
+    )
+    .style(Style::default().fg(theme.muted))
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true });
+    f.render_widget(instructions, chunks[5]);
+
+    // Completion candidates drop down over the fields below the path
+    if !app.path_completions.is_empty() {
+        const MAX_SHOWN: usize = 6;
+        let selected = app
+            .path_completions
+            .iter()
+            .position(|c| c == app.torrent_path_input.value());
+        let skip = selected.map_or(0, |i| (i + 1).saturating_sub(MAX_SHOWN));
+        let items: Vec<ListItem> = app
+            .path_completions
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .take(MAX_SHOWN)
+            .map(|(i, candidate)| {
+                // Only the entry name, the directory is already in the input
+                let name = candidate
+                    .trim_end_matches('/')
+                    .rsplit('/')
+                    .next()
+                    .unwrap_or(candidate);
+                let name = if candidate.ends_with('/') {
+                    format!("{name}/")
+                } else {
+                    name.to_string()
+                };
+                let style = if Some(i) == selected {
+                    Style::default().fg(theme.background).bg(theme.accent)
+                } else {
+                    Style::default().fg(theme.text)
+                };
+                ListItem::new(name).style(style)
+            })
+            .collect();
+
+        let list_area = Rect {
+            x: chunks[0].x + 1,
+            y: chunks[0].y + chunks[0].height,
+            width: chunks[0].width.saturating_sub(2),
+            height: (items.len() as u16 + 2).min(inner.bottom() - chunks[1].y),
+        };
+        f.render_widget(Clear, list_area);
+        f.render_widget(
+            List::new(items).block(
+                Block::default()
+                    .title(format!("{} matches", app.path_completions.len()))
+                    .borders(Borders::ALL)
+                    .style(Style::default().bg(theme.background)),
+            ),
+            list_area,
+        );
+    }
+
+    match app.input_mode {
+        InputMode::TorrentPath => {
+            f.set_cursor_position((chunks[0].x + path_cursor + 1, chunks[0].y + 1))
+        }
+        InputMode::SavePath => {
+            f.set_cursor_position((chunks[1].x + save_path_cursor + 1, chunks[1].y + 1))
+        }
+        _ => {}
+    }
+}
+
+fn draw_file_browser(f: &mut Frame, app: &App) {
+    let theme = app.theme;
+    draw_add_torrent(f, app);
+
+    let Some(browser) = &app.file_browser else {
+        return;
+    };
+
+    let size = f.area();
+    let popup_area = centered_rect(
+        size.width.saturating_sub(10).min(80),
+        size.height.saturating_sub(4).min(24),
+        size,
+    );
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(format!("Select Torrent File — {}", browser.dir.display()))
+        .borders(Borders::ALL)
+        .style(Style::default().bg(theme.background));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let visible = chunks[0].height as usize;
+    let skip = (browser.selected + 1).saturating_sub(visible);
+    let items: Vec<ListItem> = browser
+        .entries
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .take(visible)
+        .map(|(i, entry)| {
+            let (label, color) = if entry.is_dir {
+                (format!("{}/", entry.name), theme.header)
+            } else {
+                (entry.name.clone(), theme.text)
+            };
+            let style = if i == browser.selected {
+                Style::default().fg(theme.background).bg(color)
+            } else {
+                Style::default().fg(color)
+            };
+            ListItem::new(label).style(style)
+        })
+        .collect();
+
+    if items.is_empty() {
+        f.render_widget(
+            Paragraph::new("No folders or .torrent files here")
+                .style(Style::default().fg(theme.muted))
+                .alignment(Alignment::Center),
+            chunks[0],
+        );
+    } else {
+        f.render_widget(List::new(items), chunks[0]);
+        draw_scrollbar(
+            f,
+            border_rows(popup_area, chunks[0]),
+            browser.entries.len(),
+            visible,
+            skip,
+        );
+    }
+
+    if let Some(error) = &browser.error {
+        f.render_widget(
+            Paragraph::new(error.as_str()).style(Style::default().fg(theme.error)),
+            chunks[1],
+        );
+    }
+
+    f.render_widget(
+        Paragraph::new(
+            "↑↓: Navigate | Enter: Open/Select | Backspace: Parent folder | Esc: Cancel",
+        )
+        .style(Style::default().fg(theme.muted))
+        .alignment(Alignment::Center),
+        chunks[2],
+    );
+}
+
+fn draw_help(f: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    draw_main(f, app);
+
+    let mut lines = Vec::new();
+    for context in HelpContext::ALL {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(Span::styled(
+            context.title(),
+            Style::default()
+                .fg(theme.header)
+                .add_modifier(Modifier::BOLD),
+        )));
+        for binding in BINDINGS.iter().filter(|b| b.context == context) {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("  {:<18}", binding.keys(&app.keymap)),
+                    Style::default().fg(theme.accent),
+                ),
+                Span::raw(binding.description),
+            ]));
+        }
+    }
+
+    let size = f.area();
+    let popup_area = centered_rect(
+        size.width.saturating_sub(10).min(70),
+        size.height.saturating_sub(4),
+        size,
+    );
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title("Help — ↑↓/PgUp/PgDn: Scroll | Esc: Close")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(theme.background));
+    let inner = block.inner(popup_area);
+    let visible = inner.height as usize;
+    let total = lines.len();
+
+    // Clamp here since only the UI knows how much fits
+    app.help_scroll = app.help_scroll.min(total.saturating_sub(visible));
+    let help = Paragraph::new(lines)
+        .block(block)
+        .scroll((app.help_scroll as u16, 0));
+    f.render_widget(help, popup_area);
+    draw_scrollbar(
+        f,
+        border_rows(popup_area, inner),
+        total,
+        visible,
+        app.help_scroll,
+    );
+}
+
+fn draw_online_search(f: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    draw_main(f, app);
+
+    let size = f.area();
+    let popup_area = centered_rect(
+        size.width.saturating_sub(4),
+        size.height.saturating_sub(2),
+        size,
+    );
+    f.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .title("Search Plugins — Enter: Search / Add | Tab: Switch | Esc: Close")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(theme.background));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Min(1),
+        ])
+        .split(inner);
+
+    let view = &app.search_view;
+    let focused = |focus| {
+        if view.focus == focus {
+            Style::default().fg(theme.accent)
+        } else {
+            Style::default().fg(theme.border)
+        }
+    };
+    let (query, cursor) = view.query.view(chunks[0].width.saturating_sub(2));
+    let input = Paragraph::new(query).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Query")
+            .border_style(focused(SearchFocus::Query)),
+    );
+    f.render_widget(input, chunks[0]);
+    if view.focus == SearchFocus::Query {
+        f.set_cursor_position((chunks[0].x + cursor + 1, chunks[0].y + 1));
+    }
+
+    let status = match (&view.message, view.running) {
+        (Some(message), _) => Span::styled(message.clone(), Style::default().fg(theme.warning)),
+        (None, true) => Span::styled(
+            format!("Searching… {} results so far", view.results.len()),
+            Style::default().fg(theme.info),
+        ),
+        (None, false) if view.job.is_some() || !view.results.is_empty() => Span::styled(
+            format!("{} results", view.results.len()),
+            Style::default().fg(theme.muted),
+        ),
+        (None, false) => Span::styled(
+            "Type what to look for and press Enter",
+            Style::default().fg(theme.muted),
+        ),
+    };
+    f.render_widget(Paragraph::new(Line::from(status)), chunks[1]);
+
+    let results_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(focused(SearchFocus::Results));
+    let list_area = results_block.inner(chunks[2]);
+    f.render_widget(results_block, chunks[2]);
+
+    const SIZE: usize = 10;
+    const PEERS: usize = 6;
+    const SITE: usize = 20;
+    let width = list_area.width as usize;
+    let name_width = width.saturating_sub(HIGHLIGHT_WIDTH + SIZE + 2 * PEERS + SITE + 4);
+    let header = format!(
+        "{}{} {} {} {} {}",
+        " ".repeat(HIGHLIGHT_WIDTH),
+        fit_to_width("Name", name_width, false),
+        fit_to_width("Size", SIZE, true),
+        fit_to_width("Seeds", PEERS, true),
+        fit_to_width("Peers", PEERS, true),
+        fit_to_width("Site", SITE, false),
+    );
+    f.render_widget(
+        Paragraph::new(header).style(
+            Style::default()
+                .fg(theme.header)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Rect::new(list_area.x, list_area.y, list_area.width, 1),
+    );
+    let rows_area = Rect::new(
+        list_area.x,
+        list_area.y + 1,
+        list_area.width,
+        list_area.height.saturating_sub(1),
+    );
+
+    let items: Vec<ListItem> = view
+        .results
+        .iter()
+        .map(|result| {
+            let size = if result.file_size < 0 {
+                "?".to_string()
+            } else {
+                format_size(result.file_size as u64, BINARY)
+            };
+            let site = url::Url::parse(&result.site_url)
+                .ok()
+                .and_then(|url| url.host_str().map(str::to_string))
+                .unwrap_or_else(|| result.site_url.clone());
+            Line::from(vec![
+                Span::raw(fit_to_width(&result.file_name, name_width, false)),
+                Span::raw(" "),
+                Span::raw(fit_to_width(&size, SIZE, true)),
+                Span::raw(" "),
+                Span::styled(
+                    fit_to_width(&result.nb_seeders.max(0).to_string(), PEERS, true),
+                    Style::default().fg(theme.success),
+                ),
+                Span::raw(" "),
+                Span::raw(fit_to_width(
+                    &result.nb_leechers.max(0).to_string(),
+                    PEERS,
+                    true,
+                )),
+                Span::raw(" "),
+                Span::styled(
+                    fit_to_width(&site, SITE, false),
+                    Style::default().fg(theme.muted),
+                ),
+            ])
+            .into()
+        })
+        .collect();
+
+    let list = List::new(items)
+        .highlight_style(
+            Style::default()
+                .bg(theme.selection)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("→ ")
+        // The header is laid out for the symbol's column
+        .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
+    let mut state = ratatui::widgets::ListState::default();
+    if view.focus == SearchFocus::Results {
+        state.select(Some(view.selected));
+    }
+    f.render_stateful_widget(list, rows_area, &mut state);
+}
+
+fn draw_rss(f: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    draw_main(f, app);
+
+    let size = f.area();
+    let popup_area = centered_rect(
+        size.width.saturating_sub(4),
+        size.height.saturating_sub(2),
+        size,
+    );
+    f.render_widget(Clear, popup_area);
+    let view = &app.rss_view;
+    let title = if view.loading && view.feeds.is_empty() {
+        "RSS — loading…"
+    } else {
+        "RSS"
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .style(Style::default().bg(theme.background));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(inner);
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+        .split(rows[0]);
+
+    let pane = |title: String, focused: bool| {
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(if focused { theme.accent } else { theme.border }))
+    };
+    let highlight = Style::default()
+        .bg(theme.selection)
+        .add_modifier(Modifier::BOLD);
+
+    // Feeds, with their unread count and whether the last fetch failed
+    let feeds: Vec<ListItem> = view
+        .feeds
+        .iter()
+        .map(|feed| {
+            let mut spans = vec![Span::raw(feed.path.replace(models::PATH_SEPARATOR, "/"))];
+            let unread = feed.unread();
+            if unread > 0 {
+                spans.push(Span::styled(
+                    format!(" ({unread})"),
+                    Style::default().fg(theme.accent),
+                ));
+            }
+            if feed.has_error {
+                spans.push(Span::styled(" ⚠", Style::default().fg(theme.error)));
+            } else if feed.is_loading {
+                spans.push(Span::styled(" …", Style::default().fg(theme.muted)));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    let mut feed_state = ratatui::widgets::ListState::default();
+    feed_state.select((!view.feeds.is_empty()).then_some(view.feed_index));
+    f.render_stateful_widget(
+        List::new(feeds)
+            .block(pane("Feeds".to_string(), view.focus == RssFocus::Feeds))
+            .highlight_style(highlight),
+        panes[0],
+        &mut feed_state,
+    );
+
+    let mut right_state = ratatui::widgets::ListState::default();
+    if view.focus == RssFocus::Rules {
+        let items: Vec<ListItem> = view
+            .rules
+            .iter()
+            .map(|(name, rule)| {
+                let (mark, style) = if rule.enabled {
+                    ("✔ ", Style::default().fg(theme.success))
+                } else {
+                    ("✘ ", Style::default().fg(theme.muted))
+                };
+                let mut details = Vec::new();
+                if !rule.must_contain.is_empty() {
+                    details.push(format!("contains \"{}\"", rule.must_contain));
+                }
+                if !rule.must_not_contain.is_empty() {
+                    details.push(format!("not \"{}\"", rule.must_not_contain));
+                }
+                if !rule.episode_filter.is_empty() {
+                    details.push(format!("episodes {}", rule.episode_filter));
+                }
+                if !rule.assigned_category.is_empty() {
+                    details.push(format!("→ {}", rule.assigned_category));
+                }
+                if !rule.last_match.is_empty() {
+                    details.push(format!("last match {}", rule.last_match));
+                }
+                ListItem::new(vec![
+                    Line::from(vec![Span::styled(mark, style), Span::raw(name.clone())]),
+                    Line::from(Span::styled(
+                        format!("  {}", details.join(" · ")),
+                        Style::default().fg(theme.muted),
+                    )),
+                ])
+            })
+            .collect();
+        right_state.select((!view.rules.is_empty()).then_some(view.rule_index));
+        f.render_stateful_widget(
+            List::new(items)
+                .block(pane("Auto-download rules".to_string(), true))
+                .highlight_style(highlight),
+            panes[1],
+            &mut right_state,
+        );
+    } else {
+        let feed = view.selected_feed();
+        let articles: Vec<ListItem> = feed
+            .map(|feed| feed.articles.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .map(|article| {
+                let (marker, style) = if article.is_read {
+                    ("  ", Style::default().fg(theme.muted))
+                } else {
+                    ("● ", Style::default().add_modifier(Modifier::BOLD))
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(marker, Style::default().fg(theme.accent)),
+                    Span::styled(article.title.clone(), style),
+                    Span::styled(
+                        format!("  {}", article.date),
+                        Style::default().fg(theme.muted),
+                    ),
+                ]))
+            })
+            .collect();
+        if view.focus == RssFocus::Articles && !articles.is_empty() {
+            right_state.select(Some(view.article_index));
+        }
+        let title = feed.map_or_else(|| "Articles".to_string(), |feed| feed.title.clone());
+        f.render_stateful_widget(
+            List::new(articles)
+                .block(pane(title, view.focus == RssFocus::Articles))
+                .highlight_style(highlight),
+            panes[1],
+            &mut right_state,
+        );
+    }
+
+    // The bottom line doubles as the feed URL input and the removal question
+    let footer = if let Some(input) = &view.new_feed {
+        let label = "Feed URL: ";
+        let (text, cursor) = input.view(rows[1].width.saturating_sub(label.len() as u16));
+        f.set_cursor_position((rows[1].x + label.len() as u16 + cursor, rows[1].y));
+        Line::from(vec![
+            Span::styled(label, Style::default().fg(theme.accent)),
+            Span::raw(text),
+        ])
+    } else if view.confirm_remove {
+        let name = match view.focus {
+            RssFocus::Rules => view
+                .rules
+                .get(view.rule_index)
+                .map(|(name, _)| name.as_str()),
+            _ => view.selected_feed().map(|feed| feed.name()),
+        };
+        Line::from(Span::styled(
+            format!(
+                "Remove {}? y: Yes | any other key: No",
+                name.unwrap_or_default()
+            ),
+            Style::default().fg(theme.warning),
+        ))
+    } else {
+        let hints = match view.focus {
+            RssFocus::Rules => "Space: Enable/disable | d: Remove | u: Back to feeds | Esc: Close",
+            _ => {
+                "Tab: Switch pane | Enter: Add torrent | a: Add feed | d: Remove | r: Refresh | u: Rules | Esc: Close"
+            }
+        };
+        Line::from(Span::styled(hints, Style::default().fg(theme.muted)))
+    };
+    f.render_widget(Paragraph::new(footer), rows[1]);
+}
+
+fn draw_settings(f: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    draw_main(f, app);
+
+    let size = f.area();
+    let popup_area = centered_rect(
+        size.width.saturating_sub(4),
+        size.height.saturating_sub(2),
+        size,
+    );
+    f.render_widget(Clear, popup_area);
+    let view = &app.settings_view;
+    let changed = view.changes().len();
+    let title = if changed > 0 {
+        format!("Preferences — {changed} unsaved")
+    } else {
+        "Preferences".to_string()
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .style(Style::default().bg(theme.background));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+    let label_width = settings::FIELDS
+        .iter()
+        .map(|field| field.label.width())
+        .max()
+        .unwrap_or(0)
+        + 4;
+
+    // Fields under a heading per group; the selected one is kept in view
+    let mut lines = Vec::new();
+    let mut selected_line = 0;
+    let mut group = "";
+    for (index, field) in settings::FIELDS.iter().enumerate() {
+        if field.group != group {
+            if !group.is_empty() {
+                lines.push(Line::default());
+            }
+            group = field.group;
+            lines.push(Line::from(Span::styled(
+                group,
+                Style::default()
+                    .fg(theme.accent)
+                    .add_modifier(Modifier::BOLD),
+            )));
+        }
+        let selected = index == view.selected;
+        let marker = if view.is_changed(field) { "* " } else { "  " };
+        let label = format!("{marker}{:<width$}", field.label, width = label_width - 2);
+        let value = match &view.editing {
+            Some(input) if selected => {
+                let available = rows[0].width.saturating_sub(label_width as u16);
+                let (text, cursor) = input.view(available);
+                let line = lines.len() - scroll_offset(lines.len(), rows[0].height);
+                f.set_cursor_position((
+                    rows[0].x + label_width as u16 + cursor,
+                    rows[0].y + line as u16,
+                ));
+                text
+            }
+            _ => view.display(field),
+        };
+        if selected {
+            selected_line = lines.len();
+        }
+        let style = if selected {
+            Style::default().bg(theme.selection)
+        } else {
+            Style::default()
+        };
+        lines.push(
+            Line::from(vec![
+                Span::styled(label, Style::default().fg(theme.muted)),
+                Span::styled(value, Style::default().fg(theme.text)),
+            ])
+            .style(style),
+        );
+    }
+    let scroll = scroll_offset(selected_line, rows[0].height);
+    f.render_widget(Paragraph::new(lines).scroll((scroll as u16, 0)), rows[0]);
+
+    let footer = match (&view.error, &view.editing) {
+        (Some(error), _) => Span::styled(error.as_str(), Style::default().fg(theme.error)),
+        (None, Some(_)) => Span::styled(
+            "Enter: Apply | Esc: Cancel",
+            Style::default().fg(theme.muted),
+        ),
+        (None, None) => Span::styled(
+            "Enter: Edit | Space: Toggle | s: Save | Esc: Close",
+            Style::default().fg(theme.muted),
+        ),
+    };
+    f.render_widget(Paragraph::new(Line::from(footer)), rows[1]);
+}
+
+// First line to show so that `line` is visible, keeping it near the middle
+fn scroll_offset(line: usize, height: u16) -> usize {
+    line.saturating_sub(height as usize / 2)
+}
+
+fn draw_log(f: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    draw_main(f, app);
+
+    let size = f.area();
+    let popup_area = centered_rect(
+        size.width.saturating_sub(4),
+        size.height.saturating_sub(2),
+        size,
+    );
+    f.render_widget(Clear, popup_area);
+
+    let level_color = |level: LogLevel| match level {
+        LogLevel::Normal => theme.text,
+        LogLevel::Info => theme.info,
+        LogLevel::Warning => theme.warning,
+        LogLevel::Critical => theme.error,
+    };
+    let mut title = vec![Span::raw("qBittorrent Log — ")];
+    for (i, level) in LogLevel::ALL.into_iter().enumerate() {
+        let style = if app.log_view.shows(level) {
+            Style::default()
+                .fg(level_color(level))
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+                .fg(theme.muted)
+                .add_modifier(Modifier::CROSSED_OUT)
+        };
+        title.push(Span::styled(format!("{}:{}", i + 1, level.label()), style));
+        title.push(Span::raw(" "));
+    }
+    title.push(Span::raw(
+        "| ↑↓/PgUp/PgDn: Scroll | End: Follow | Esc: Close",
+    ));
+
+    let block = Block::default()
+        .title(Line::from(title))
+        .borders(Borders::ALL)
+        .style(Style::default().bg(theme.background));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let timezone = app.config.get_timezone();
+    let entries = app.log_view.visible();
+    let visible = inner.height as usize;
+    let total = entries.len();
+    if total == 0 {
+        let message = if app.log_view.fetching {
+            "Loading…"
+        } else {
+            "No messages"
+        };
+        let empty = Paragraph::new(message)
+            .style(Style::default().fg(theme.muted))
+            .alignment(Alignment::Center);
+        f.render_widget(empty, inner);
+        return;
+    }
+
+    // Clamp here since only the UI knows how much fits; reaching the bottom
+    // follows new messages again
+    let last_page = total.saturating_sub(visible);
+    let follow = app.log_view.follow || app.log_view.scroll >= last_page;
+    let scroll = if follow {
+        last_page
+    } else {
+        app.log_view.scroll
+    };
+
+    let lines: Vec<Line> = entries[scroll..(scroll + visible).min(total)]
+        .iter()
+        .map(|entry| {
+            let time = format_unix_timestamp(entry.timestamp, &timezone);
+            let width = (inner.width as usize).saturating_sub(time.width() + 1);
+            Line::from(vec![
+                Span::styled(time, Style::default().fg(theme.muted)),
+                Span::raw(" "),
+                Span::styled(
+                    truncate_to_width(&entry.message, width),
+                    Style::default().fg(level_color(entry.level())),
+                ),
+            ])
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), inner);
+    draw_scrollbar(f, border_rows(popup_area, inner), total, visible, scroll);
+
+    app.log_view.follow = follow;
+    app.log_view.scroll = scroll;
+}
+
+fn draw_details(f: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    // Keep the torrent list visible behind the popup
+    draw_main(f, app);
+
+    let Some(torrent) = app.get_current_selected_torrent() else {
+        return;
+    };
+
+    let size = f.area();
+    let popup_area = centered_rect(size.width.saturating_sub(10).min(90), 24, size);
+
+    f.render_widget(Clear, popup_area);
+
+    // The tabs are listed in the title, the current one highlighted
+    let mut title = vec![Span::raw("Torrent Details ")];
+    for tab in DetailsTab::ALL {
+        let style = if tab == app.details_tab {
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.muted)
+        };
+        title.push(Span::raw("│ "));
+        title.push(Span::styled(tab.label(), style));
+        title.push(Span::raw(" "));
+    }
+    let block = Block::default()
+        .title(Line::from(title))
+        .borders(Borders::ALL)
+        .style(Style::default().bg(theme.background));
+
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    match app.details_tab {
+        DetailsTab::General => {}
+        DetailsTab::Trackers => return draw_trackers_tab(f, app, chunks[0], chunks[1]),
+        DetailsTab::Peers => return draw_peers_tab(f, app, chunks[0], chunks[1]),
+        DetailsTab::Files => return draw_files_tab(f, app, chunks[0], chunks[1]),
+    }
+
+    let label = Style::default().fg(theme.header);
+    let field = |name: &'static str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{name:<14}"), label),
+            Span::raw(value),
+        ])
+    };
+
+    let timezone = app.config.get_timezone();
+
+    // qBittorrent reports a queue position <= 0 when the torrent isn't queued
+    let queue_position = match torrent.priority {
+        Some(priority) if priority > 0 => priority.to_string(),
+        _ => "-".to_string(),
+    };
+
+    let mut lines = vec![
+        field("Name", torrent.name.clone()),
+        field("Hash", torrent.hash.clone()),
+        field("State", torrent.state.display_name().to_string()),
+        field("Size", format_size(torrent.size as u64, BINARY)),
+        field("Progress", format!("{:.1}%", torrent.progress * 100.0)),
+        field("Queue", queue_position),
+        field("Category", torrent.category.clone().unwrap_or_default()),
+        field("Tags", torrent.tags.clone().unwrap_or_default()),
+        field("Save path", torrent.save_path.clone().unwrap_or_default()),
+        field(
+            "Auto managed",
+            if torrent.auto_tmm.unwrap_or(false) {
+                "Yes"
+            } else {
+                "No"
+            }
+            .to_string(),
+        ),
+        field(
+            "Added",
+            format_unix_timestamp(torrent.added_on.unwrap_or(0), &timezone),
+        ),
+        field(
+            "Completed",
+            format_unix_timestamp(torrent.completion_on.unwrap_or(0), &timezone),
+        ),
+        field(
+            "Ratio",
+            torrent
+                .ratio
+                .map_or("-".to_string(), |ratio| format!("{ratio:.2}")),
+        ),
+        field(
+            "Seeds/Peers",
+            format!(
+                "{} / {}",
+                torrent.num_seeds.unwrap_or(0),
+                torrent.num_leechs.unwrap_or(0)
+            ),
+        ),
+        field(
+            "Time active",
+            torrent.time_active.map_or("-".to_string(), format_elapsed),
+        ),
+        field(
+            "Seeding time",
+            torrent.seeding_time.map_or("-".to_string(), format_elapsed),
+        ),
+        field("Share limits", ShareLimits::of(torrent).summary()),
+        field(
+            "Session",
+            format!(
+                "↓ {}  ↑ {}",
+                format_size(
+                    torrent.downloaded_session.unwrap_or(0).max(0) as u64,
+                    BINARY
+                ),
+                format_size(torrent.uploaded_session.unwrap_or(0).max(0) as u64, BINARY)
+            ),
+        ),
+    ];
+    if !app.piece_map.is_empty() {
+        let cells = chunks[0].width.saturating_sub(14) as usize;
+        let mut pieces = vec![Span::styled(format!("{:<14}", "Pieces"), label)];
+        pieces.extend(piece_bar(&app.piece_map, cells, &theme));
+        lines.push(Line::from(pieces));
+    }
+
+    // The torrent's recent speeds along the bottom, to tell one that's
+    // moving from one that only flickers
+    let general = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(2)])
+        .split(chunks[0]);
+    let details = Paragraph::new(lines).wrap(Wrap { trim: false });
+    f.render_widget(details, general[0]);
+
+    let history = app.torrent_speed_history.get(&torrent.hash);
+    let graphs = [
+        (
+            "Speed ↓",
+            theme.download,
+            torrent.dlspeed,
+            history.map(|h| h.download(usize::MAX)),
+        ),
+        (
+            "Speed ↑",
+            theme.upload,
+            torrent.upspeed,
+            history.map(|h| h.upload(usize::MAX)),
+        ),
+    ];
+    for (row, (name, color, speed, samples)) in graphs.into_iter().enumerate() {
+        let label = format!(
+            "{name:<14}{:>12} ",
+            format_size(speed.max(0) as u64, BINARY) + "/s"
+        );
+        draw_speed_graph(
+            f,
+            Rect::new(general[1].x, general[1].y + row as u16, general[1].width, 1)
+                .intersection(general[1]),
+            Span::styled(label, Style::default().fg(color)),
+            &samples.unwrap_or_default(),
+            color,
+        );
+    }
+
+    let instructions = Paragraph::new(
+        "Tab: Trackers | Ctrl+↑↓: Move in queue | a: Auto management | s: Share limits | Esc: Close",
+    )
+    .style(Style::default().fg(theme.muted))
+    .alignment(Alignment::Center);
+    f.render_widget(instructions, chunks[1]);
+}
+
+fn draw_trackers_tab(f: &mut Frame, app: &App, area: Rect, footer_area: Rect) {
+    let theme = app.theme;
+    let panel = &app.tracker_panel;
+
+    const STATUS: usize = 13;
+    const PEERS: usize = 6;
+    let width = area.width as usize;
+    let rest = width.saturating_sub(HIGHLIGHT_WIDTH + STATUS + 2 * PEERS + 4);
+    // The URL and the tracker's message share what's left
+    let url_width = rest / 2;
+    let message_width = rest - url_width;
+    let header = format!(
+        "{}{} {} {} {} {}",
+        " ".repeat(HIGHLIGHT_WIDTH),
+        fit_to_width("URL", url_width, false),
+        fit_to_width("Status", STATUS, false),
+        fit_to_width("Seeds", PEERS, true),
+        fit_to_width("Peers", PEERS, true),
+        fit_to_width("Message", message_width, false),
+    );
+    f.render_widget(
+        Paragraph::new(header).style(
+            Style::default()
+                .fg(theme.header)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Rect::new(area.x, area.y, area.width, 1),
+    );
+    let rows_area = Rect::new(
+        area.x,
+        area.y + 1,
+        area.width,
+        area.height.saturating_sub(1),
+    );
+
+    if panel.trackers.is_empty() {
+        let message = if panel.loading {
+            "Loading trackers…"
+        } else {
+            "No trackers"
+        };
+        f.render_widget(
+            Paragraph::new(message).style(Style::default().fg(theme.muted)),
+            rows_area,
+        );
+    }
+
+    // A count of -1 means the tracker hasn't said
+    let count = |value: i64| {
+        let text = if value < 0 {
+            "-".to_string()
+        } else {
+            value.to_string()
+        };
+        fit_to_width(&text, PEERS, true)
+    };
+    let items: Vec<ListItem> = panel
+        .trackers
+        .iter()
+        .map(|tracker| {
+            let status_color = match tracker.status {
+                2 => theme.success,
+                4 => theme.error,
+                3 => theme.warning,
+                _ => theme.muted,
+            };
+            Line::from(vec![
+                Span::raw(fit_to_width(&tracker.url, url_width, false)),
+                Span::raw(" "),
+                Span::styled(
+                    fit_to_width(tracker.status_label(), STATUS, false),
+                    Style::default().fg(status_color),
+                ),
+                Span::raw(" "),
+                Span::raw(count(tracker.num_seeds)),
+                Span::raw(" "),
+                Span::raw(count(tracker.num_peers)),
+                Span::raw(" "),
+                Span::styled(
+                    fit_to_width(&tracker.msg, message_width, false),
+                    Style::default().fg(theme.muted),
+                ),
+            ])
+            .into()
+        })
+        .collect();
+    let list = List::new(items)
+        .highlight_style(
+            Style::default()
+                .bg(theme.selection)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("→ ")
+        // The header is laid out for the symbol's column
+        .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
+    let mut state = ratatui::widgets::ListState::default();
+    state.select(Some(panel.selected));
+    f.render_stateful_widget(list, rows_area, &mut state);
+
+    let footer = match &panel.input {
+        Some(TrackerInput::Edit { url, .. }) => {
+            let label = "New URL: ";
+            let (text, cursor) = url.view(footer_area.width.saturating_sub(label.len() as u16));
+            f.set_cursor_position((footer_area.x + label.len() as u16 + cursor, footer_area.y));
+            Line::from(vec![
+                Span::styled(label, Style::default().fg(theme.accent)),
+                Span::raw(text),
+            ])
+        }
+        _ if panel.confirm_remove => Line::from(Span::styled(
+            format!(
+                "Remove {}? y: Yes | any other key: No",
+                panel
+                    .selected_tracker()
+                    .map(|t| t.url.as_str())
+                    .unwrap_or_default()
+            ),
+            Style::default().fg(theme.warning),
+        )),
+        _ => Line::from(Span::styled(
+            "Tab: Peers | a: Add | e: Edit URL | d: Remove | Esc: Close",
+            Style::default().fg(theme.muted),
+        ))
+        .alignment(Alignment::Center),
+    };
+    f.render_widget(Paragraph::new(footer), footer_area);
+
+    if let Some(TrackerInput::Add(urls)) = &panel.input {
+        draw_add_trackers(f, urls, &theme);
+    }
+}
+
+fn draw_peers_tab(f: &mut Frame, app: &App, area: Rect, footer_area: Rect) {
+    let theme = app.theme;
+    let panel = &app.peer_panel;
+
+    const ADDRESS: usize = 24;
+    const PROGRESS: usize = 8;
+    const SPEED: usize = 11;
+    const FLAGS: usize = 10;
+    let width = area.width as usize;
+    let client_width =
+        width.saturating_sub(HIGHLIGHT_WIDTH + ADDRESS + PROGRESS + 2 * SPEED + FLAGS + 5);
+    let header = format!(
+        "{}{} {} {} {} {} {}",
+        " ".repeat(HIGHLIGHT_WIDTH),
+        fit_to_width("Address", ADDRESS, false),
+        fit_to_width("Client", client_width, false),
+        fit_to_width("Progress", PROGRESS, true),
+        fit_to_width("Down", SPEED, true),
+        fit_to_width("Up", SPEED, true),
+        fit_to_width("Flags", FLAGS, false),
+    );
+    f.render_widget(
+        Paragraph::new(header).style(
+            Style::default()
+                .fg(theme.header)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Rect::new(area.x, area.y, area.width, 1),
+    );
+    let rows_area = Rect::new(
+        area.x,
+        area.y + 1,
+        area.width,
+        area.height.saturating_sub(1),
+    );
+
+    if panel.peers.is_empty() {
+        let message = if panel.loading {
+            "Loading peers…"
+        } else {
+            "No peers connected"
+        };
+        f.render_widget(
+            Paragraph::new(message).style(Style::default().fg(theme.muted)),
+            rows_area,
+        );
+    }
+
+    let speed = |bytes: i64| {
+        fit_to_width(
+            &(format_size(bytes.max(0) as u64, BINARY) + "/s"),
+            SPEED,
+            true,
+        )
+    };
+    let items: Vec<ListItem> = panel
+        .peers
+        .iter()
+        .map(|peer| {
+            Line::from(vec![
+                Span::raw(fit_to_width(&peer.address, ADDRESS, false)),
+                Span::raw(" "),
+                Span::raw(fit_to_width(&peer.client, client_width, false)),
+                Span::raw(" "),
+                Span::raw(fit_to_width(
+                    &format!("{:.1}%", peer.progress * 100.0),
+                    PROGRESS,
+                    true,
+                )),
+                Span::raw(" "),
+                Span::raw(speed(peer.dl_speed)),
+                Span::raw(" "),
+                Span::raw(speed(peer.up_speed)),
+                Span::raw(" "),
+                Span::styled(
+                    fit_to_width(&peer.flags, FLAGS, false),
+                    Style::default().fg(theme.muted),
+                ),
+            ])
+            .into()
+        })
+        .collect();
+    let list = List::new(items)
+        .highlight_style(
+            Style::default()
+                .bg(theme.selection)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("→ ")
+        // The header is laid out for the symbol's column
+        .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
+    let mut state = ratatui::widgets::ListState::default();
+    state.select(Some(panel.selected));
+    f.render_stateful_widget(list, rows_area, &mut state);
+
+    let footer = match &panel.new_peers {
+        Some(input) => {
+            let label = "Connect to (ip:port): ";
+            let (text, cursor) = input.view(footer_area.width.saturating_sub(label.len() as u16));
+            f.set_cursor_position((footer_area.x + label.len() as u16 + cursor, footer_area.y));
+            Line::from(vec![
+                Span::styled(label, Style::default().fg(theme.accent)),
+                Span::raw(text),
+            ])
+        }
+        None if panel.confirm_ban => Line::from(Span::styled(
+            format!(
+                "Ban {} from every torrent? y: Yes | any other key: No",
+                panel
+                    .selected_peer()
+                    .map(|p| p.address.as_str())
+                    .unwrap_or_default()
+            ),
+            Style::default().fg(theme.warning),
+        )),
+        None => Line::from(Span::styled(
+            "Tab: Files | a: Add peer | b: Ban | Esc: Close",
+            Style::default().fg(theme.muted),
+        ))
+        .alignment(Alignment::Center),
+    };
+    f.render_widget(Paragraph::new(footer), footer_area);
+}
+
+// One cell per bucket of pieces, with runs of the same state merged into
+// a single span
+fn piece_bar(map: &PieceMap, cells: usize, theme: &Theme) -> Vec<Span<'static>> {
+    let style = |cell: PieceCell| match cell {
+        PieceCell::Done => ('█', theme.success),
+        PieceCell::Partial => ('▓', theme.success),
+        PieceCell::Downloading => ('▓', theme.info),
+        PieceCell::Missing => ('░', theme.muted),
+    };
+    let mut spans = Vec::new();
+    let mut run: Option<(PieceCell, usize)> = None;
+    for index in 0..cells {
+        let cell = map.cell(index, cells);
+        match &mut run {
+            Some((state, length)) if *state == cell => *length += 1,
+            _ => {
+                if let Some((state, length)) = run.replace((cell, 1)) {
+                    let (c, color) = style(state);
+                    spans.push(Span::styled(
+                        c.to_string().repeat(length),
+                        Style::default().fg(color),
+                    ));
+                }
+            }
+        }
+    }
+    if let Some((state, length)) = run {
+        let (c, color) = style(state);
+        spans.push(Span::styled(
+            c.to_string().repeat(length),
+            Style::default().fg(color),
+        ));
+    }
+    spans
+}
+
+fn draw_files_tab(f: &mut Frame, app: &App, area: Rect, footer_area: Rect) {
+    let theme = app.theme;
+    let panel = &app.file_panel;
+
+    const SIZE: usize = 10;
+    const PROGRESS: usize = 6;
+    const BAR: usize = 10;
+    let width = area.width as usize;
+    let name_width = width.saturating_sub(HIGHLIGHT_WIDTH + SIZE + BAR + PROGRESS + 3);
+    let header = format!(
+        "{}{} {} {} {}",
+        " ".repeat(HIGHLIGHT_WIDTH),
+        fit_to_width("Name", name_width, false),
+        fit_to_width("Size", SIZE, true),
+        fit_to_width("", BAR, false),
+        fit_to_width("Done", PROGRESS, true),
+    );
+    f.render_widget(
+        Paragraph::new(header).style(
+            Style::default()
+                .fg(theme.header)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Rect::new(area.x, area.y, area.width, 1),
+    );
+    let rows_area = Rect::new(
+        area.x,
+        area.y + 1,
+        area.width,
+        area.height.saturating_sub(1),
+    );
+
+    if panel.entries.is_empty() {
+        let message = if panel.loading {
+            "Loading files…"
+        } else {
+            "No files yet (magnet links list theirs once the metadata arrives)"
+        };
+        f.render_widget(
+            Paragraph::new(message).style(Style::default().fg(theme.muted)),
+            rows_area,
+        );
+    }
+
+    let items: Vec<ListItem> = panel
+        .entries
+        .iter()
+        .map(|entry| {
+            let indent = "  ".repeat(entry.depth);
+            let (name, style) = if entry.is_folder {
+                (
+                    format!("{indent}{}/", entry.name()),
+                    Style::default().fg(theme.accent),
+                )
+            } else {
+                (format!("{indent}{}", entry.name()), Style::default())
+            };
+            let progress = entry.progress();
+            Line::from(vec![
+                Span::styled(fit_to_width(&name, name_width, false), style),
+                Span::raw(" "),
+                Span::raw(fit_to_width(
+                    &format_size(entry.size.max(0) as u64, BINARY),
+                    SIZE,
+                    true,
+                )),
+                Span::raw(" "),
+                Span::styled(
+                    progress_bar(progress, BAR),
+                    Style::default().fg(theme.success),
+                ),
+                Span::raw(" "),
+                Span::raw(fit_to_width(
+                    &format!("{:.0}%", progress * 100.0),
+                    PROGRESS,
+                    true,
+                )),
+            ])
+            .into()
+        })
+        .collect();
+    let list = List::new(items)
+        .highlight_style(
+            Style::default()
+                .bg(theme.selection)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("→ ")
+        // The header is laid out for the symbol's column
+        .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
+    let mut state = ratatui::widgets::ListState::default();
+    state.select(Some(panel.selected));
+    f.render_stateful_widget(list, rows_area, &mut state);
+
+    let footer = match &panel.rename {
+        Some(input) => {
+            let label = "Rename to: ";
+            let (text, cursor) = input.view(footer_area.width.saturating_sub(label.len() as u16));
+            f.set_cursor_position((footer_area.x + label.len() as u16 + cursor, footer_area.y));
+            Line::from(vec![
+                Span::styled(label, Style::default().fg(theme.accent)),
+                Span::raw(text),
+            ])
+        }
+        None => Line::from(Span::styled(
+            "Tab: General | F2: Rename | Esc: Close",
+            Style::default().fg(theme.muted),
+        ))
+        .alignment(Alignment::Center),
+    };
+    f.render_widget(Paragraph::new(footer), footer_area);
+}
+
+// Popup taking new tracker URLs, one per line
+fn draw_add_trackers(f: &mut Frame, urls: &UrlList, theme: &Theme) {
+    let size = f.area();
+    let height = (urls.lines.len() as u16 + 4).clamp(7, size.height.saturating_sub(4));
+    let popup_area = centered_rect(size.width.saturating_sub(10).min(80), height, size);
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title("Add Trackers (one URL per line)")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(theme.background));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+    let area = chunks[0];
+    let scroll = scroll_offset(urls.row, area.height);
+    let lines: Vec<Line> = urls
+        .lines
+        .iter()
+        .enumerate()
+        .skip(scroll)
+        .take(area.height as usize)
+        .map(|(row, line)| {
+            if row == urls.row {
+                let (text, cursor) = line.view(area.width);
+                f.set_cursor_position((area.x + cursor, area.y + (row - scroll) as u16));
+                Line::from(text)
+            } else {
+                Line::from(line.view(area.width).0)
+            }
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), area);
+    f.render_widget(
+        Paragraph::new("Enter: New line | Ctrl+E: Editor | Ctrl+S: Add | Esc: Cancel")
+            .style(Style::default().fg(theme.muted))
+            .alignment(Alignment::Center),
+        chunks[1],
+    );
+}
+
+fn draw_share_limits(f: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    draw_details(f, app);
+
+    let Some(editor) = &app.share_limits else {
+        return;
+    };
+
+    let size = f.area();
+    let popup_area = centered_rect(56, 13, size);
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title("Share Limits")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(theme.background));
+    f.render_widget(block, popup_area);
+
+    let inner = popup_area.inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(2),
+        ])
+        .split(inner);
+
+    for (i, (label, input)) in ShareLimitsEditor::LABELS
+        .iter()
+        .zip(&editor.inputs)
+        .enumerate()
+    {
+        let active = editor.focus == i;
+        let block = Block::default()
+            .title(*label)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(if active { theme.accent } else { theme.muted }));
+        let (text, cursor) = input.view(chunks[i].width.saturating_sub(2));
+        f.render_widget(Paragraph::new(text).block(block), chunks[i]);
+        if active {
+            f.set_cursor_position((chunks[i].x + 1 + cursor, chunks[i].y + 1));
+        }
+    }
+
+    let footer = match &editor.error {
+        Some(error) => Paragraph::new(error.as_str()).style(Style::default().fg(theme.error)),
+        None => Paragraph::new(
+            "Tab: Switch | Enter: Apply | Esc: Cancel\n\"global\" or \"unlimited\" for any limit",
+        )
+        .style(Style::default().fg(theme.muted)),
+    };
+    f.render_widget(
+        footer
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        chunks[3],
+    );
+}
+
+fn draw_statistics(f: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    // Drawn over the live list so the numbers update with every refresh
+    draw_main(f, app);
+
+    let size = f.area();
+    let popup_area = centered_rect(size.width.saturating_sub(10).min(60), 20, size);
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title("Statistics")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(theme.background));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    let label = Style::default().fg(theme.header);
+    let heading = |title: &'static str| {
+        Line::from(Span::styled(
+            title,
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD),
+        ))
+    };
+    let field = |name: &'static str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("  {name:<18}"), label),
+            Span::raw(value),
+        ])
+    };
+    let bytes = |value: i64| format_size(value.max(0) as u64, BINARY);
+    let unknown = || "-".to_string();
+
+    let mut lines = vec![heading("Server")];
+    lines.push(field(
+        "qBittorrent",
+        app.server_version.clone().unwrap_or_else(unknown),
+    ));
+    lines.push(field(
+        "libtorrent",
+        app.build_info
+            .as_ref()
+            .map_or_else(unknown, |info| info.libtorrent.clone()),
+    ));
+
+    if let Some(state) = &app.server_state {
+        let session_ratio = if state.dl_info_data > 0 {
+            format!(
+                "{:.2}",
+                state.up_info_data as f64 / state.dl_info_data as f64
+            )
+        } else {
+            "∞".to_string()
+        };
+
+        lines.push(field("Connection", state.connection_status.clone()));
+        lines.push(field(
+            "Listening port",
+            app.listen_port
+                .map_or_else(unknown, |port| port.to_string()),
+        ));
+        lines.push(field(
+            "DHT nodes",
+            state.dht_nodes.map_or_else(unknown, |n| n.to_string()),
+        ));
+        lines.push(field(
+            "Peers",
+            state
+                .total_peer_connections
+                .map_or_else(unknown, |n| n.to_string()),
+        ));
+        lines.push(Line::from(""));
+        lines.push(heading("This session"));
+        lines.push(field("Downloaded", bytes(state.dl_info_data)));
+        lines.push(field("Uploaded", bytes(state.up_info_data)));
+        lines.push(field("Ratio", session_ratio));
+        lines.push(field(
+            "Rate limits",
+            format!(
+                "↓ {}  ↑ {}",
+                format_speed_limit(state.dl_rate_limit.unwrap_or(0)),
+                format_speed_limit(state.up_rate_limit.unwrap_or(0))
+            ),
+        ));
+        lines.push(Line::from(""));
+        lines.push(heading("All time"));
+        lines.push(field(
+            "Downloaded",
+            state.alltime_dl.map_or_else(unknown, bytes),
+        ));
+        lines.push(field(
+            "Uploaded",
+            state.alltime_ul.map_or_else(unknown, bytes),
+        ));
+        lines.push(field(
+            "Ratio",
+            state.global_ratio.clone().unwrap_or_else(unknown),
+        ));
+    } else {
+        lines.push(Line::from(""));
+        lines.push(Line::from("Waiting for the first update from the server…"));
+    }
+
+    f.render_widget(Paragraph::new(lines), chunks[0]);
+    f.render_widget(
+        Paragraph::new("Esc: Close")
+            .style(Style::default().fg(theme.muted))
+            .alignment(Alignment::Center),
+        chunks[1],
+    );
+}
+
+fn draw_speed_limits(f: &mut Frame, app: &App) {
+    let theme = app.theme;
+    let size = f.area();
+    let popup_area = centered_rect(50, 12, size);
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title("Global Speed Limits")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(theme.background));
+
+    f.render_widget(block, popup_area);
 
     let inner = popup_area.inner(Margin {
         vertical: 1,
@@ -503,38 +3264,582 @@ fn draw_add_torrent(f: &mut Frame, app: &App) {
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Length(2)])
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(2),
+        ])
         .split(inner);
 
-    let input_block = Block::default()
-        .title("Torrent File Path")
-        .borders(Borders::ALL)
-        .style(Style::default().fg(Color::Yellow));
+    let fields = [
+        (
+            "Download limit (KiB/s)",
+            &app.download_limit_input,
+            InputMode::DownloadLimit,
+        ),
+        (
+            "Upload limit (KiB/s)",
+            &app.upload_limit_input,
+            InputMode::UploadLimit,
+        ),
+    ];
+
+    for (i, (title, value, mode)) in fields.iter().enumerate() {
+        let active = app.input_mode == *mode;
+        let current = value.parse::<i64>().unwrap_or(0).saturating_mul(1024);
+        let block = Block::default()
+            .title(format!("{title} - {}", format_speed_limit(current)))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(if active { theme.accent } else { theme.muted }));
 
-    let input_paragraph = Paragraph::new(app.torrent_path_input.as_str()).block(input_block);
-    f.render_widget(input_paragraph, chunks[0]);
+        let paragraph = Paragraph::new(value.as_str()).block(block);
+        f.render_widget(paragraph, chunks[i]);
 
-    let instructions = Paragraph::new("Enter: Add torrent | Esc: Cancel")
-        .style(Style::default().fg(Color::Gray))
+        if active {
+            f.set_cursor_position((chunks[i].x + value.len() as u16 + 1, chunks[i].y + 1));
+        }
+    }
+
+    let instructions = Paragraph::new("Tab: Switch | Enter: Apply | Esc: Cancel | 0 = unlimited")
+        .style(Style::default().fg(theme.muted))
         .alignment(Alignment::Center);
+    f.render_widget(instructions, chunks[2]);
+}
+
+/// Names listed in the delete confirmation before the rest are summed up.
+const MAX_DELETE_NAMES: usize = 8;
+
+fn draw_confirm_delete(f: &mut Frame, app: &mut App) {
+    draw_main(f, app);
+    let theme = app.theme;
+    let targets = &app.delete_confirmation;
+
+    let size = f.area();
+    let listed = targets.len().min(MAX_DELETE_NAMES);
+    let more = targets.len() - listed;
+    // Question, names, "…and N more", a blank line, the two choices, cancel
+    let height = (listed + usize::from(more > 0) + 7) as u16;
+    let popup_area = centered_rect(70.min(size.width), height.min(size.height), size);
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title("Confirm Delete")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(theme.background).fg(theme.error));
+    let inner = block.inner(popup_area).inner(Margin {
+        vertical: 0,
+        horizontal: 1,
+    });
+    f.render_widget(block, popup_area);
+
+    let total: i64 = targets.iter().map(|target| target.size.max(0)).sum();
+    let question = match targets.len() {
+        1 => "Delete this torrent?".to_string(),
+        count => format!(
+            "Delete these {count} torrents ({} in total)?",
+            format_size(total as u64, BINARY)
+        ),
+    };
+
+    let width = inner.width as usize;
+    let mut lines = vec![Line::from(Span::styled(
+        question,
+        Style::default().fg(theme.text),
+    ))];
+    for target in targets.iter().take(listed) {
+        let size = format!("  {}", format_size(target.size.max(0) as u64, BINARY));
+        lines.push(Line::from(vec![
+            Span::styled(
+                truncate_to_width(&target.name, width.saturating_sub(size.width())),
+                Style::default().fg(theme.text).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(size, Style::default().fg(theme.muted)),
+        ]));
+    }
+    if more > 0 {
+        lines.push(Line::from(Span::styled(
+            format!("…and {more} more"),
+            Style::default().fg(theme.muted),
+        )));
+    }
+    lines.push(Line::default());
+    lines.push(Line::from(vec![
+        Span::styled(
+            "y",
+            Style::default()
+                .fg(theme.accent)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            ": remove from qBittorrent, keep the downloaded files",
+            Style::default().fg(theme.text),
+        ),
+    ]));
+    lines.push(Line::from(vec![
+        Span::styled(
+            "Y",
+            Style::default()
+                .fg(theme.error)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            ": also delete the downloaded files from disk",
+            Style::default().fg(theme.error),
+        ),
+    ]));
+    lines.push(Line::from(Span::styled(
+        "n/Esc: Cancel",
+        Style::default().fg(theme.muted),
+    )));
+
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+fn draw_command_palette(f: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    draw_main(f, app);
+    let Some(palette) = &app.command_palette else {
+        return;
+    };
+
+    let size = f.area();
+    let popup_area = centered_rect(
+        size.width.saturating_sub(4).min(70),
+        size.height.saturating_sub(2).min(20),
+        size,
+    );
+    f.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .title("Actions — Enter: Run | Esc: Close")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(theme.background));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1)])
+        .split(inner);
+
+    let (query, cursor) = palette.input.view(chunks[0].width.saturating_sub(2));
+    let input = Paragraph::new(query).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.accent)),
+    );
+    f.render_widget(input, chunks[0]);
+    f.set_cursor_position((chunks[0].x + cursor + 1, chunks[0].y + 1));
+
+    const KEYS: usize = 16;
+    let label_width = (chunks[1].width as usize).saturating_sub(HIGHLIGHT_WIDTH + KEYS + 1);
+    let items: Vec<ListItem> = palette
+        .matches()
+        .map(|entry| {
+            Line::from(vec![
+                Span::raw(fit_to_width(&entry.label, label_width, false)),
+                Span::raw(" "),
+                Span::styled(
+                    fit_to_width(&entry.keys, KEYS, true),
+                    Style::default().fg(theme.muted),
+                ),
+            ])
+            .into()
+        })
+        .collect();
+    if items.is_empty() {
+        f.render_widget(
+            Paragraph::new("No matching actions")
+                .style(Style::default().fg(theme.muted))
+                .alignment(Alignment::Center),
+            chunks[1],
+        );
+        return;
+    }
+
+    let list = List::new(items)
+        .highlight_style(
+            Style::default()
+                .bg(theme.selection)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("→ ")
+        .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
+    let mut state = ratatui::widgets::ListState::default();
+    state.select(Some(palette.selected));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+fn draw_prompt(f: &mut Frame, app: &mut App) {
+    let theme = app.theme;
+    if app
+        .prompt
+        .as_ref()
+        .is_some_and(|prompt| prompt.return_to == AppState::Details)
+    {
+        draw_details(f, app);
+    } else {
+        draw_main(f, app);
+    }
+
+    let Some(prompt) = &app.prompt else {
+        return;
+    };
+
+    let size = f.area();
+    let popup_area = centered_rect(60, 7, size);
+
+    f.render_widget(Clear, popup_area);
+
+    // Exports only ever write the highlighted torrent
+    let targets = match prompt.action {
+        PromptAction::ExportTorrent | PromptAction::ShutdownServer => 1,
+        _ => app.action_targets().len(),
+    };
+    let shutdown = prompt.action == PromptAction::ShutdownServer;
+    let block = Block::default()
+        .title(if targets > 1 {
+            format!("{} ({targets} torrents)", prompt.title)
+        } else {
+            prompt.title.clone()
+        })
+        .borders(Borders::ALL)
+        .style(if shutdown {
+            Style::default().bg(theme.background).fg(theme.error)
+        } else {
+            Style::default().bg(theme.background)
+        });
+
+    f.render_widget(block, popup_area);
+
+    let inner = popup_area.inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(1)])
+        .split(inner);
+
+    let input = Paragraph::new(prompt.input.as_str()).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.accent)),
+    );
+    f.render_widget(input, chunks[0]);
+
+    let instructions = Paragraph::new(if shutdown {
+        "Type yes and press Enter to stop the server | Esc: Cancel"
+    } else {
+        "Enter: Apply | Esc: Cancel"
+    })
+    .style(Style::default().fg(theme.muted))
+    .alignment(Alignment::Center);
     f.render_widget(instructions, chunks[1]);
 
-    f.set_cursor_position((
-        chunks[0].x + app.torrent_path_input.len() as u16 + 1,
-        chunks[0].y + 1,
-    ));
+    f.set_cursor_position((chunks[0].x + prompt.input.len() as u16 + 1, chunks[0].y + 1));
+}
+
+fn draw_confirm(f: &mut Frame, app: &mut App) {
+    if app
+        .confirmation
+        .as_ref()
+        .is_some_and(|confirmation| confirmation.return_to == AppState::Details)
+    {
+        draw_details(f, app);
+    } else {
+        draw_main(f, app);
+    }
+
+    if let Some(confirmation) = &app.confirmation {
+        draw_confirm_popup(
+            f,
+            &confirmation.title,
+            &confirmation.message,
+            "Y/Enter: Confirm | N/Esc: Cancel",
+            &app.theme,
+        );
+    }
+}
+
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+fn draw_connecting(f: &mut Frame, app: &App) {
+    let theme = app.theme;
+    let elapsed = app.connecting_for().unwrap_or_default();
+    let frame = (elapsed.as_millis() / SPINNER_INTERVAL.as_millis()) as usize % SPINNER.len();
+
+    let popup_area = centered_rect(50, 5, f.area());
+    f.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .style(Style::default().bg(theme.background).fg(theme.accent));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let url = app.client.get_base_url();
+    let status = if app.is_logging_in() && !app.username_input.is_empty() {
+        format!("Logging in to {url} as {}…", app.username_input.value())
+    } else {
+        format!("Connecting to {url}…")
+    };
+    let text = vec![
+        Line::from(Span::styled(
+            format!("{} {status}", SPINNER[frame]),
+            Style::default().fg(theme.text),
+        )),
+        Line::from(Span::styled(
+            "Esc: Cancel",
+            Style::default().fg(theme.muted),
+        )),
+    ];
+    f.render_widget(
+        Paragraph::new(text)
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        inner,
+    );
+}
+
+fn draw_copy_menu(f: &mut Frame, app: &App) {
+    let theme = app.theme;
+    let popup_area = centered_rect(30, CopyField::MENU.len() as u16 + 4, f.area());
+    f.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .title("Copy")
+        .title_bottom(" Esc: Cancel ")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(theme.background).fg(theme.accent));
+    let inner = block.inner(popup_area).inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+    f.render_widget(block, popup_area);
+
+    let lines: Vec<Line> = CopyField::MENU
+        .iter()
+        .map(|(key, field)| {
+            Line::from(vec![
+                Span::styled(format!("{key}  "), Style::default().fg(theme.accent)),
+                Span::styled(field.label(), Style::default().fg(theme.text)),
+            ])
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+fn draw_column_chooser(f: &mut Frame, app: &App) {
+    let Some(chooser) = &app.column_chooser else {
+        return;
+    };
+    let theme = app.theme;
+    let popup_area = centered_rect(44, chooser.entries.len() as u16 + 4, f.area());
+    f.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .title("Columns")
+        .title_bottom(" Space: Show/hide  Shift+↑↓: Move  Enter: Save  Esc: Cancel ")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(theme.background).fg(theme.accent));
+    let inner = block.inner(popup_area).inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+    f.render_widget(block, popup_area);
+
+    let items: Vec<ListItem> = chooser
+        .entries
+        .iter()
+        .map(|(column, shown)| {
+            let check = if *shown { "[x]" } else { "[ ]" };
+            let color = if *shown { theme.text } else { theme.muted };
+            ListItem::new(format!("{check} {}", column.title())).style(Style::default().fg(color))
+        })
+        .collect();
+    let list = List::new(items).highlight_style(
+        Style::default()
+            .bg(theme.selection)
+            .add_modifier(Modifier::BOLD),
+    );
+    let mut state = ratatui::widgets::ListState::default().with_selected(Some(chooser.selected));
+    f.render_stateful_widget(list, inner, &mut state);
+}
+
+fn draw_cleanup(f: &mut Frame, app: &App) {
+    let Some(view) = &app.cleanup else {
+        return;
+    };
+    let theme = app.theme;
+    let size = f.area();
+    let popup_area = centered_rect(
+        size.width.saturating_sub(4).min(84),
+        size.height.saturating_sub(2).min(26),
+        size,
+    );
+    f.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .title("Clean Up Finished Torrents")
+        .title_bottom(" Tab: Next  Space: Pick  a: Pick all  Enter: Delete picked  Esc: Close ")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(theme.background).fg(theme.accent));
+    let inner = block.inner(popup_area).inner(Margin {
+        vertical: 0,
+        horizontal: 1,
+    });
+    f.render_widget(block, popup_area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+    let fields = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Length(14),
+            Constraint::Length(24),
+            Constraint::Min(10),
+        ])
+        .split(rows[0]);
+    let border = |focus: CleanupFocus| {
+        let color = if view.focus == focus {
+            theme.accent
+        } else {
+            theme.muted
+        };
+        Style::default().fg(color)
+    };
+    for (area, title, input, focus) in [
+        (fields[0], "Min ratio", &view.ratio, CleanupFocus::Ratio),
+        (
+            fields[1],
+            "Seeded (7d, 36h)",
+            &view.seeding_time,
+            CleanupFocus::SeedingTime,
+        ),
+    ] {
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(border(focus));
+        let (text, cursor) = input.view(area.width.saturating_sub(2));
+        f.render_widget(
+            Paragraph::new(text)
+                .style(Style::default().fg(theme.text))
+                .block(block),
+            area,
+        );
+        if view.focus == focus {
+            f.set_cursor_position((area.x + 1 + cursor, area.y + 1));
+        }
+    }
+    f.render_widget(
+        Paragraph::new(format!("◂ {} ▸", view.states.label()))
+            .style(Style::default().fg(theme.text))
+            .block(
+                Block::default()
+                    .title("State")
+                    .borders(Borders::ALL)
+                    .border_style(border(CleanupFocus::States)),
+            ),
+        fields[2],
+    );
+
+    let now = chrono::Utc::now().timestamp();
+    let matches = view.matches(&app.torrents, now);
+    let width = rows[1].width as usize;
+    let items: Vec<ListItem> = matches
+        .iter()
+        .map(|torrent| {
+            let picked = view.selected.contains(&torrent.hash);
+            let details = format!(
+                "  {:>6}  {:>8}  {:>10}",
+                torrent
+                    .ratio
+                    .map_or("-".to_string(), |ratio| format!("{ratio:.2}")),
+                format_elapsed(seeding_secs(torrent, now)),
+                format_size(torrent.size.max(0) as u64, BINARY)
+            );
+            let check = if picked { "[x] " } else { "[ ] " };
+            let name_width = width.saturating_sub(check.len() + details.width());
+            let color = if picked { theme.error } else { theme.text };
+            ListItem::new(Line::from(vec![
+                Span::styled(check, Style::default().fg(color)),
+                Span::styled(
+                    fit_to_width(&torrent.name, name_width, false),
+                    Style::default().fg(color),
+                ),
+                Span::styled(details, Style::default().fg(theme.muted)),
+            ]))
+        })
+        .collect();
+    if items.is_empty() {
+        f.render_widget(
+            Paragraph::new("No finished torrents meet these criteria")
+                .style(Style::default().fg(theme.muted))
+                .alignment(Alignment::Center),
+            rows[1],
+        );
+    } else {
+        let mut list = List::new(items);
+        if view.focus == CleanupFocus::Matches {
+            list = list.highlight_style(
+                Style::default()
+                    .bg(theme.selection)
+                    .add_modifier(Modifier::BOLD),
+            );
+        }
+        let mut state = ratatui::widgets::ListState::default().with_selected(Some(view.cursor));
+        f.render_stateful_widget(list, rows[1], &mut state);
+    }
+
+    let footer = match view
+        .error
+        .clone()
+        .map_or_else(|| view.criteria().map(drop), Err)
+    {
+        Err(error) => Line::from(Span::styled(error, Style::default().fg(theme.error))),
+        Ok(()) => {
+            let total = |list: &[&Torrent]| {
+                format_size(
+                    list.iter().map(|t| t.size.max(0)).sum::<i64>() as u64,
+                    BINARY,
+                )
+            };
+            let chosen = view.chosen(&app.torrents, now);
+            Line::from(Span::styled(
+                format!(
+                    "{} matching, {} in all; {} picked to delete, {}",
+                    matches.len(),
+                    total(&matches),
+                    chosen.len(),
+                    total(&chosen)
+                ),
+                Style::default().fg(theme.text),
+            ))
+        }
+    };
+    f.render_widget(Paragraph::new(footer), rows[2]);
 }
 
-fn draw_confirm_delete(f: &mut Frame, _app: &App) {
+fn draw_confirm_popup(
+    f: &mut Frame,
+    title: &str,
+    question: &str,
+    instructions: &str,
+    theme: &Theme,
+) {
     let size = f.area();
     let popup_area = centered_rect(50, 8, size);
 
     f.render_widget(Clear, popup_area);
 
     let block = Block::default()
-        .title("Confirm Delete")
+        .title(title.to_string())
         .borders(Borders::ALL)
-        .style(Style::default().bg(Color::Black).fg(Color::Red));
+        .style(Style::default().bg(theme.background).fg(theme.error));
 
     f.render_widget(block, popup_area);
 
@@ -548,19 +3853,19 @@ fn draw_confirm_delete(f: &mut Frame, _app: &App) {
         .constraints([Constraint::Length(2), Constraint::Length(2)])
         .split(inner);
 
-    let question = Paragraph::new("Are you sure you want to delete this torrent?")
-        .style(Style::default().fg(Color::White))
+    let question = Paragraph::new(question.to_string())
+        .style(Style::default().fg(theme.text))
         .alignment(Alignment::Center)
         .wrap(Wrap { trim: true });
     f.render_widget(question, chunks[0]);
 
-    let instructions = Paragraph::new("Y: Delete | Shift+Y: Delete with files | N/Esc: Cancel")
-        .style(Style::default().fg(Color::Gray))
+    let instructions = Paragraph::new(instructions.to_string())
+        .style(Style::default().fg(theme.muted))
         .alignment(Alignment::Center);
     f.render_widget(instructions, chunks[1]);
 }
 
-fn draw_error(f: &mut Frame, message: &str) {
+fn draw_error(f: &mut Frame, message: &str, theme: &Theme) {
     let size = f.area();
     let popup_area = centered_rect(60, 15, size);
 
@@ -569,7 +3874,7 @@ fn draw_error(f: &mut Frame, message: &str) {
     let block = Block::default()
         .title("Error")
         .borders(Borders::ALL)
-        .style(Style::default().bg(Color::Black).fg(Color::Red));
+        .style(Style::default().bg(theme.background).fg(theme.error));
 
     f.render_widget(block, popup_area);
 
@@ -584,13 +3889,13 @@ fn draw_error(f: &mut Frame, message: &str) {
         .split(inner);
 
     let error_text = Paragraph::new(message)
-        .style(Style::default().fg(Color::White))
+        .style(Style::default().fg(theme.text))
         .alignment(Alignment::Center)
         .wrap(Wrap { trim: true });
     f.render_widget(error_text, chunks[0]);
 
     let instructions = Paragraph::new("Press Enter or Esc to continue")
-        .style(Style::default().fg(Color::Gray))
+        .style(Style::default().fg(theme.muted))
         .alignment(Alignment::Center);
     f.render_widget(instructions, chunks[1]);
 }
@@ -615,67 +3920,294 @@ fn centered_rect(width: u16, height: u16, r: Rect) -> Rect {
         .split(popup_layout[1])[1]
 }
 
-// Helper function for percentage-based centering
-fn centered_rect_percent(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
-        ])
-        .split(r);
+// Takes the footer's place, so none of the narrowed list is covered
+fn draw_search_bar(f: &mut Frame, area: Rect, app: &App) {
+    let theme = app.theme;
+    let search_title = if let Some(error) = &app.search_error {
+        Span::styled(
+            format!("Search Torrents — {error}"),
+            Style::default().fg(theme.error),
+        )
+    } else if app.is_searching {
+        Span::raw(format!(
+            "Search Torrents ({})",
+            app.get_current_torrent_list_len()
+        ))
+    } else {
+        Span::raw("Search Torrents")
+    };
 
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
-        ])
-        .split(popup_layout[1])[1]
+    let (search_text, search_cursor) = app.search_input.view(area.width.saturating_sub(2));
+    let search_input = Paragraph::new(search_text)
+        .style(Style::default().fg(theme.text).bg(theme.search))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(search_title)
+                .title_bottom(" ↑↓: Move  Enter: Keep  Esc: Clear ")
+                .style(Style::default().fg(theme.accent)),
+        );
+
+    f.render_widget(search_input, area);
+    f.set_cursor_position((area.x + search_cursor + 1, area.y + 1));
 }
 
-fn draw_search(f: &mut Frame, app: &mut App) {
-    // First draw the main torrent list as background
-    draw_main(f, app);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let size = f.area();
+    fn shown(total: usize) -> Vec<ListColumn> {
+        ListLayout::new(total, &ListColumn::DEFAULT)
+            .columns
+            .iter()
+            .map(|spec| spec.column)
+            .collect()
+    }
 
-    // Calculate search popup dimensions
-    let popup_width = size.width.saturating_sub(4).min(60);
-    let popup_height = 3;
+    #[test]
+    fn status_bar_cuts_the_field_that_overflows() {
+        let torrent = Torrent {
+            downloaded: Some(2048),
+            ratio: Some(0.5),
+            tags: Some(String::new()),
+            save_path: Some("/srv/downloads/movies".to_string()),
+            ..Torrent::default()
+        };
+        let fields = status_fields(&torrent, None);
+        assert_eq!(fields[0].1, "2 KiB");
+        assert_eq!(fields[3].1, "—");
 
-    // Center the search popup
-    let popup_area = Rect {
-        x: (size.width.saturating_sub(popup_width)) / 2,
-        y: (size.height.saturating_sub(popup_height)) / 2,
-        width: popup_width,
-        height: popup_height,
-    };
+        let all = fit_status_fields(fields.clone(), 200);
+        assert_eq!(all.len(), 6);
+        assert_eq!(all[5].1, "/srv/downloads/movies");
+        // Everything up to the save path's value takes 76 columns
+        let fitted = fit_status_fields(fields.clone(), 85);
+        assert_eq!(fitted.len(), 6);
+        assert_eq!(fitted[5].1, "/srv/d...");
+        let labels: Vec<&str> = fit_status_fields(fields, 60)
+            .iter()
+            .map(|(label, _)| *label)
+            .collect();
+        assert_eq!(labels, ["Downloaded", "Uploaded", "Ratio", "Category"]);
+        assert!(fit_status_fields(status_fields(&torrent, None), 10).is_empty());
 
-    // Clear the area and draw the search input
-    f.render_widget(Clear, popup_area);
+        let fields = status_fields(&torrent, Some("torrent not registered"));
+        assert_eq!(fields[0], ("Tracker", "torrent not registered".to_string()));
+        assert_eq!(fields.len(), 7);
+    }
 
-    let search_title = if app.is_searching {
-        format!("Search Torrents ({})", app.filtered_torrents.len())
-    } else {
-        "Search Torrents".to_string()
-    };
+    #[test]
+    fn wide_terminals_show_every_column() {
+        assert_eq!(shown(160).len(), ListColumn::DEFAULT.len());
+        let layout = ListLayout::new(160, &ListColumn::DEFAULT);
+        assert!(layout.progress_bars);
+        assert_eq!(
+            layout
+                .columns
+                .iter()
+                .map(|spec| layout.width(spec) + 1)
+                .sum::<usize>()
+                - 1
+                + HIGHLIGHT_WIDTH
+                + GLYPH_WIDTH,
+            160
+        );
+    }
 
-    let search_input = Paragraph::new(app.search_input.as_str())
-        .style(Style::default().fg(Color::White).bg(Color::Blue))
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(search_title)
-                .style(Style::default().fg(Color::Yellow)),
+    #[test]
+    fn narrow_terminals_drop_peers_seeds_and_ratio_first() {
+        let columns = shown(100);
+        assert!(!columns.contains(&ListColumn::Peers));
+        assert!(!columns.contains(&ListColumn::Seeds));
+        assert!(!columns.contains(&ListColumn::Ratio));
+        assert!(columns.contains(&ListColumn::UpSpeed));
+
+        for total in [40, 59, 60, 79, 80, 90, 100, 120] {
+            assert!(ListLayout::new(total, &ListColumn::DEFAULT).name_width >= MIN_NAME_WIDTH);
+        }
+        assert!(!ListLayout::new(100, &ListColumn::DEFAULT).progress_bars);
+        assert!(shown(80).contains(&ListColumn::State));
+        assert!(!shown(59).contains(&ListColumn::State));
+    }
+
+    #[test]
+    fn each_breakpoint_narrows_the_columns() {
+        let (columns, label) = fit_columns(REDUCED_LIST_WIDTH, &ListColumn::DEFAULT);
+        assert_eq!(columns, ListColumn::DEFAULT);
+        assert_eq!(label, StateLabel::Full);
+
+        let (columns, label) = fit_columns(REDUCED_LIST_WIDTH - 1, &ListColumn::DEFAULT);
+        assert!(!columns.contains(&ListColumn::UpSpeed));
+        assert!(!columns.contains(&ListColumn::Eta));
+        assert!(columns.contains(&ListColumn::Size));
+        assert_eq!(columns.len(), ListColumn::DEFAULT.len() - 2);
+        assert_eq!(label, StateLabel::Full);
+
+        let (columns, label) = fit_columns(SHORT_STATE_WIDTH - 1, &ListColumn::DEFAULT);
+        assert!(!columns.contains(&ListColumn::Size));
+        assert!(columns.contains(&ListColumn::DlSpeed));
+        assert_eq!(label, StateLabel::Short);
+
+        let (columns, label) = fit_columns(MINIMAL_LIST_WIDTH - 1, &ListColumn::DEFAULT);
+        assert_eq!(columns, [ListColumn::Name, ListColumn::Progress]);
+        assert_eq!(label, StateLabel::Short);
+
+        // Hidden columns stay hidden at every width
+        let (columns, _) = fit_columns(40, &[ListColumn::Name, ListColumn::Ratio]);
+        assert_eq!(columns, [ListColumn::Name]);
+    }
+
+    #[test]
+    fn chosen_columns_keep_their_order_and_hidden_ones_take_no_space() {
+        let chosen = [ListColumn::Ratio, ListColumn::Name, ListColumn::Category];
+        let layout = ListLayout::new(80, &chosen);
+        let columns: Vec<ListColumn> = layout.columns.iter().map(|spec| spec.column).collect();
+        assert_eq!(columns, chosen);
+        // Everything but the name, its separators and the highlight symbol
+        assert_eq!(
+            layout.name_width,
+            80 - (6 + 1) - (12 + 1) - HIGHLIGHT_WIDTH - GLYPH_WIDTH
+        );
+    }
+
+    #[test]
+    fn free_space_turns_yellow_then_red() {
+        const GIB: u64 = 1 << 30;
+        let theme = Theme::default();
+        let config = Config::default();
+        assert_eq!(free_space_color(500 * GIB, &config, &theme), theme.text);
+        assert_eq!(free_space_color(49 * GIB, &config, &theme), theme.warning);
+        assert_eq!(free_space_color(9 * GIB, &config, &theme), theme.error);
+        let lenient = Config {
+            free_space_warning_gib: 0,
+            free_space_critical_gib: 0,
+            ..Config::default()
+        };
+        assert_eq!(free_space_color(1, &lenient, &theme), theme.text);
+    }
+
+    #[test]
+    fn header_and_row_cells_are_the_same_width() {
+        let torrent = Torrent {
+            name: "種子 with a long name that never fits in the column".to_string(),
+            progress: 0.42,
+            size: 123_456_789,
+            state: TorrentState::StalledUp,
+            eta: Some(8_640_000),
+            ratio: Some(12.5),
+            category: Some("linux-isos".to_string()),
+            ..Torrent::default()
+        };
+        let theme = Theme::default();
+        for total in (MIN_TERMINAL_WIDTH as usize..=200).step_by(3) {
+            for shown in [&ListColumn::DEFAULT[..], &ListColumn::ALL[..]] {
+                let layout = ListLayout::new(total, shown);
+                for spec in &layout.columns {
+                    let width = layout.width(spec);
+                    for arrow in [None, Some('▼')] {
+                        assert_eq!(layout.header_cell(spec, arrow).width(), width);
+                    }
+                    for marked in [false, true] {
+                        let (text, _) = layout.row_cell(spec, &torrent, marked, &theme, 0);
+                        assert_eq!(text.width(), width, "{:?} at {total}", spec.column);
+                    }
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn marked_matches_draw_however_narrow_the_name_column() {
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+        let mut app = App::new_with_config(
+            url::Url::parse("http://localhost:8080").unwrap(),
+            None,
+            None,
+            Config::default(),
+            crate::api::ConnectionOptions::default(),
+        )
+        .await
+        .unwrap();
+        app.state = AppState::Main;
+        let data = serde_json::from_value(serde_json::json!({
+            "rid": 1,
+            "full_update": true,
+            "torrents": {
+                "a": {"name": "alpha", "size": 1, "progress": 0.5, "dlspeed": 0,
+                      "upspeed": 0, "state": "downloading"}
+            }
+        }))
+        .unwrap();
+        app.handle_update(crate::event::AppEvent::MainDataUpdated(Box::new(data)))
+            .await
+            .unwrap();
+        app.selected_hashes.insert("a".to_string());
+        app.show_sidebar = true;
+        let ctrl_f = KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL);
+        app.handle_event(Event::Key(ctrl_f)).await.unwrap();
+        for c in "alp".chars() {
+            let key = KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+            app.handle_event(Event::Key(key)).await.unwrap();
+        }
+        assert!(app.is_searching);
+
+        // The torrent's row, one symbol per cell, and which cells are drawn
+        // as part of the match
+        let mut draw_row = |width: u16| {
+            let backend = ratatui::backend::TestBackend::new(width, 24);
+            let mut terminal = ratatui::Terminal::new(backend).unwrap();
+            terminal.draw(|f| draw(f, &mut app)).unwrap();
+            let buffer = terminal.backend().buffer();
+            let y = (0..24)
+                .find(|&y| (0..width).any(|x| buffer[(x, y)].symbol() == "→"))
+                .unwrap();
+            let cells: Vec<_> = (0..width).map(|x| &buffer[(x, y)]).collect();
+            let symbols: Vec<String> = cells.iter().map(|c| c.symbol().to_string()).collect();
+            let found: Vec<bool> = cells
+                .iter()
+                .map(|c| c.fg == app.theme.accent && c.modifier.contains(Modifier::BOLD))
+                .collect();
+            (symbols, found)
+        };
+
+        // At 40 columns beside the sidebar the name column has no room left,
+        // not even for the whole marker
+        let (symbols, found) = draw_row(40);
+        assert!(!symbols.contains(&"✔".to_string()));
+        assert!(!found.contains(&true));
+        assert!(symbols.concat().ends_with("50%│"));
+
+        // With room, the marker leads the name and only the match stands out
+        let (symbols, found) = draw_row(64);
+        let name = symbols.iter().position(|s| s == "✔").unwrap();
+        assert_eq!(symbols[name..name + 7].concat(), "✔ alpha");
+        assert_eq!(
+            found[name..name + 7],
+            [false, false, true, true, true, false, false]
         );
+        assert!(symbols[name + 7..].concat().trim_start().starts_with("50%"));
+    }
+
+    fn scrollbar_column(total: usize, visible: usize, offset: usize) -> String {
+        let backend = ratatui::backend::TestBackend::new(3, 10);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        terminal
+            .draw(|f| draw_scrollbar(f, Rect::new(2, 0, 1, 10), total, visible, offset))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..10).map(|y| buffer[(2, y)].symbol()).collect()
+    }
 
-    f.render_widget(search_input, popup_area);
+    #[test]
+    fn scrollbar_tracks_offset_and_hides_when_everything_fits() {
+        assert_eq!(scrollbar_column(10, 10, 0).trim(), "");
 
-    // Position cursor in the search input
-    let cursor_x = popup_area.x + app.search_input.len() as u16 + 1;
-    let cursor_y = popup_area.y + 1;
-    f.set_cursor_position((cursor_x, cursor_y));
+        let top = scrollbar_column(100, 10, 0);
+        let bottom = scrollbar_column(100, 10, 90);
+        assert!(top.starts_with('█'));
+        assert!(!top.ends_with('█'));
+        assert!(bottom.ends_with('█'));
+        assert!(!bottom.starts_with('█'));
+    }
 }
@@ -1,8 +1,10 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use chrono_tz::{Tz, UTC};
-use std::fs::OpenOptions;
-use std::io::Write;
+use humansize::{BINARY, format_size};
+use std::ops::Range;
 use std::str::FromStr;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 pub fn format_timestamp_with_timezone(timezone_str: &str) -> String {
     let now = Utc::now();
@@ -21,6 +23,86 @@ pub fn format_timestamp_with_timezone(timezone_str: &str) -> String {
     }
 }
 
+pub fn format_clock_time(time: DateTime<Utc>, timezone_str: &str) -> String {
+    match Tz::from_str(timezone_str) {
+        Ok(tz) => time.with_timezone(&tz).format("%H:%M:%S").to_string(),
+        Err(_) => time.format("%H:%M:%S UTC").to_string(),
+    }
+}
+
+/// Formats a Unix timestamp from the API as a date and time in
+/// `timezone_str`. qBittorrent reports "never" (e.g. not completed yet) as 0
+/// or a negative value, shown as "—".
+pub fn format_unix_timestamp(timestamp: i64, timezone_str: &str) -> String {
+    let Some(time) = DateTime::<Utc>::from_timestamp(timestamp, 0).filter(|_| timestamp > 0) else {
+        return "—".to_string();
+    };
+    match Tz::from_str(timezone_str) {
+        Ok(tz) => time
+            .with_timezone(&tz)
+            .format("%Y-%m-%d %H:%M:%S %Z")
+            .to_string(),
+        Err(_) => time.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+    }
+}
+
+/// How long before `now` the Unix timestamp `timestamp` was, in its largest
+/// whole unit: "45s ago", "2h ago", "3d ago". "—" for "never", as above.
+pub fn format_age(timestamp: i64, now: i64) -> String {
+    if timestamp <= 0 {
+        return "—".to_string();
+    }
+    // Clocks that disagree slightly shouldn't show a time in the future
+    let secs = (now - timestamp).max(0);
+    let age = match secs {
+        0..60 => format!("{secs}s"),
+        60..3_600 => format!("{}m", secs / 60),
+        3_600..86_400 => format!("{}h", secs / 3_600),
+        86_400..31_536_000 => format!("{}d", secs / 86_400),
+        _ => format!("{}y", secs / 31_536_000),
+    };
+    format!("{age} ago")
+}
+
+/// Seconds qBittorrent reports for a time it can't estimate, such as the
+/// ETA of a torrent with no peers (100 days).
+pub const INFINITE_SECS: i64 = 8_640_000;
+
+/// An estimate such as an ETA, as `format_elapsed` does it. Negative values
+/// and `INFINITE_SECS` or more are "∞".
+pub fn format_duration(secs: i64) -> String {
+    if !(0..INFINITE_SECS).contains(&secs) {
+        return "∞".to_string();
+    }
+    format_elapsed(secs)
+}
+
+/// A span of seconds in its two largest units: "45s", "12m5s", "3h20m",
+/// "2d4h", "3w1d". Negative values count as none.
+pub fn format_elapsed(secs: i64) -> String {
+    let secs = secs.max(0);
+    const UNITS: [(i64, &str); 5] = [
+        (604_800, "w"),
+        (86_400, "d"),
+        (3_600, "h"),
+        (60, "m"),
+        (1, "s"),
+    ];
+    for (i, (size, unit)) in UNITS.iter().enumerate() {
+        if secs >= *size || *size == 1 {
+            let mut text = format!("{}{unit}", secs / size);
+            if let Some((smaller, smaller_unit)) = UNITS.get(i + 1) {
+                let rest = secs % size / smaller;
+                if rest > 0 {
+                    text.push_str(&format!("{rest}{smaller_unit}"));
+                }
+            }
+            return text;
+        }
+    }
+    unreachable!("the last unit is one second")
+}
+
 pub fn is_valid_timezone(timezone_str: &str) -> bool {
     Tz::from_str(timezone_str).is_ok()
 }
@@ -52,13 +134,203 @@ pub fn get_common_timezones() -> Vec<&'static str> {
     ]
 }
 
-pub fn log_debug(message: &str, timezone: &str) {
-    if let Ok(mut file) = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open("qbittui_debug.log")
-    {
-        let timestamp = format_timestamp_with_timezone(timezone);
-        let _ = writeln!(file, "[{timestamp}] {message}");
+// qBittorrent reports "no limit" as 0 (or a negative value on some versions)
+pub fn format_speed_limit(limit: i64) -> String {
+    if limit <= 0 {
+        "∞".to_string()
+    } else {
+        format_size(limit as u64, BINARY) + "/s"
+    }
+}
+
+/// Byte range of the first occurrence of `needle` in `haystack`, ignoring
+/// case. Compares character by character so the range always lines up with
+/// `haystack` itself, not a lowercased copy of it.
+pub fn find_ignore_case(haystack: &str, needle: &str) -> Option<Range<usize>> {
+    if needle.is_empty() {
+        return None;
+    }
+    let same = |a: char, b: char| a == b || a.to_lowercase().eq(b.to_lowercase());
+    haystack.char_indices().find_map(|(start, _)| {
+        let mut rest = haystack[start..].char_indices();
+        let mut end = start;
+        for n in needle.chars() {
+            let (offset, c) = rest.next()?;
+            if !same(c, n) {
+                return None;
+            }
+            end = start + offset + c.len_utf8();
+        }
+        Some(start..end)
+    })
+}
+
+/// Shortens `text` to at most `cols` terminal columns, ending in "..." when
+/// anything was cut. Works on grapheme clusters so wide glyphs and combining
+/// marks are never split.
+pub fn truncate_to_width(text: &str, cols: usize) -> String {
+    if text.width() <= cols {
+        return text.to_string();
+    }
+
+    let (budget, ellipsis) = if cols >= 3 {
+        (cols - 3, "...")
+    } else {
+        (cols, "")
+    };
+    let mut used = 0;
+    let mut truncated = String::new();
+    for grapheme in text.graphemes(true) {
+        let width = grapheme.width();
+        if used + width > budget {
+            break;
+        }
+        used += width;
+        truncated.push_str(grapheme);
+    }
+    truncated.push_str(ellipsis);
+    truncated
+}
+
+/// Truncates or pads `text` to exactly `cols` terminal columns.
+pub fn fit_to_width(text: &str, cols: usize, right_aligned: bool) -> String {
+    let text = truncate_to_width(text, cols);
+    let padding = " ".repeat(cols.saturating_sub(text.width()));
+    if right_aligned {
+        format!("{padding}{text}")
+    } else {
+        format!("{text}{padding}")
+    }
+}
+
+/// Horizontal bar `cells` wide filled to `fraction`, using eighth blocks
+/// for the partial cell. Rounds down so only 100% draws a full bar.
+pub fn progress_bar(fraction: f64, cells: usize) -> String {
+    const PARTIAL: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+    let eighths = (fraction.clamp(0.0, 1.0) * (cells * 8) as f64).floor() as usize;
+    let full = eighths / 8;
+
+    let mut bar = "█".repeat(full);
+    if full < cells {
+        bar.push(PARTIAL[eighths % 8]);
+        bar.push_str(&" ".repeat(cells - full - 1));
+    }
+    bar
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamps_format_in_the_timezone_and_as_an_age() {
+        // 2024-03-01 12:00:00 UTC
+        let timestamp = 1_709_294_400;
+        assert_eq!(
+            format_unix_timestamp(timestamp, "Europe/Paris"),
+            "2024-03-01 13:00:00 CET"
+        );
+        assert_eq!(
+            format_unix_timestamp(timestamp, "Nowhere/Special"),
+            "2024-03-01 12:00:00 UTC"
+        );
+        assert_eq!(format_unix_timestamp(0, "UTC"), "—");
+        assert_eq!(format_unix_timestamp(-1, "UTC"), "—");
+
+        assert_eq!(format_age(timestamp, timestamp + 59), "59s ago");
+        assert_eq!(format_age(timestamp, timestamp + 2 * 3_600 + 5), "2h ago");
+        assert_eq!(format_age(timestamp, timestamp + 3 * 86_400), "3d ago");
+        assert_eq!(format_age(timestamp, timestamp - 10), "0s ago");
+        assert_eq!(format_age(0, timestamp), "—");
+    }
+
+    #[test]
+    fn durations_use_their_two_largest_units() {
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(60), "1m");
+        assert_eq!(format_duration(12 * 60 + 5), "12m5s");
+        assert_eq!(format_duration(3 * 3_600 + 20 * 60 + 59), "3h20m");
+        assert_eq!(format_duration(2 * 86_400 + 4 * 3_600), "2d4h");
+        assert_eq!(format_duration(86_400 + 30), "1d");
+        assert_eq!(format_duration(3 * 604_800 + 86_400), "3w1d");
+        assert_eq!(format_duration(INFINITE_SECS - 1), "14w1d");
+        assert_eq!(format_duration(INFINITE_SECS), "∞");
+        assert_eq!(format_duration(-1), "∞");
+        // Time that has actually passed has no sentinel
+        assert_eq!(format_elapsed(INFINITE_SECS), "14w2d");
+        assert_eq!(format_elapsed(-1), "0s");
+    }
+
+    #[test]
+    fn finds_matches_regardless_of_case() {
+        assert_eq!(find_ignore_case("Ubuntu 24.04", "UNTU"), Some(2..6));
+        assert_eq!(find_ignore_case("Ärzte Live", "ärz"), Some(0..4));
+        assert_eq!(find_ignore_case("abc", "abcd"), None);
+        assert_eq!(find_ignore_case("abc", ""), None);
+    }
+
+    #[test]
+    fn ascii_is_cut_with_an_ellipsis() {
+        assert_eq!(
+            truncate_to_width("ubuntu-24.04.iso", 20),
+            "ubuntu-24.04.iso"
+        );
+        assert_eq!(truncate_to_width("ubuntu-24.04.iso", 10), "ubuntu-...");
+        assert_eq!(truncate_to_width("ubuntu", 2), "ub");
+        assert_eq!(truncate_to_width("ubuntu", 0), "");
+    }
+
+    #[test]
+    fn wide_glyphs_count_as_two_columns() {
+        // Each CJK character is three bytes and two columns wide
+        let name = "進撃の巨人 Season 1";
+        let cut = truncate_to_width(name, 8);
+        assert_eq!(cut, "進撃...");
+        assert!(cut.width() <= 8);
+
+        // An odd budget must not split a wide glyph
+        let cut = truncate_to_width(name, 9);
+        assert_eq!(cut, "進撃の...");
+        assert_eq!(truncate_to_width("🎬🎬🎬🎬", 6), "🎬...");
+    }
+
+    #[test]
+    fn combining_marks_stay_with_their_base() {
+        // "e" followed by a combining acute accent
+        let name = "Cafe\u{301} Society (2016)";
+        let cut = truncate_to_width(name, 7);
+        assert_eq!(cut, "Cafe\u{301}...");
+        assert_eq!(
+            truncate_to_width("e\u{301}e\u{301}e\u{301}", 2),
+            "e\u{301}e\u{301}"
+        );
+    }
+
+    #[test]
+    fn every_width_is_safe_and_within_budget() {
+        let name = "Ünïcödé 日本語 🎉 mix\u{301}ed";
+        for cols in 0..=name.width() + 2 {
+            let cut = truncate_to_width(name, cols);
+            assert!(cut.width() <= cols, "{cut:?} exceeds {cols}");
+            assert_eq!(fit_to_width(name, cols, false).width(), cols);
+            assert_eq!(fit_to_width(name, cols, true).width(), cols);
+        }
+    }
+
+    #[test]
+    fn progress_bar_rounds_down_to_the_nearest_eighth() {
+        assert_eq!(progress_bar(0.0, 10), " ".repeat(10));
+        assert_eq!(progress_bar(1.0, 10), "█".repeat(10));
+        // 99.6% must not look finished
+        assert_eq!(progress_bar(0.996, 10), format!("{}▉", "█".repeat(9)));
+        assert_eq!(progress_bar(0.5, 4), "██  ");
+        assert_eq!(progress_bar(0.0625, 2), "▏ ");
+        // Out of range values from the API are clamped
+        assert_eq!(progress_bar(1.2, 3), "███");
+        assert_eq!(progress_bar(-0.1, 3), "   ");
+        for step in 0..=1000 {
+            assert_eq!(progress_bar(step as f64 / 1000.0, 10).chars().count(), 10);
+        }
     }
 }
@@ -0,0 +1,276 @@
+use crate::api::{AddTorrentOptions, ApiError};
+use crate::backend::TorrentApi;
+use crate::event::AppEvent;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tokio_util::sync::CancellationToken;
+
+const SCAN_INTERVAL: Duration = Duration::from_secs(5);
+/// Files changed more recently than this may still be being written
+const SETTLE_TIME: Duration = Duration::from_secs(2);
+/// Where added files go when they are kept
+const ADDED_DIR: &str = "added";
+
+/// What happens to a file in the watch folder once its torrent was added.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AfterAdd {
+    /// Moved into the folder's `added/` subfolder
+    #[default]
+    Move,
+    Delete,
+}
+
+/// Adds the `*.torrent` files that show up in a folder. Scans run one after
+/// the other in a single task, so a file whose upload is slow can't be
+/// picked up a second time.
+pub struct DirWatcher {
+    cancel: CancellationToken,
+    task: Option<JoinHandle<()>>,
+}
+
+impl DirWatcher {
+    pub fn spawn(
+        client: Arc<dyn TorrentApi>,
+        dir: PathBuf,
+        after_add: AfterAdd,
+        sender: mpsc::UnboundedSender<AppEvent>,
+    ) -> Self {
+        let cancel = CancellationToken::new();
+        let token = cancel.clone();
+        let task = tokio::spawn(async move {
+            let mut folder = WatchFolder::new(dir, after_add);
+            let mut ticks = tokio::time::interval(SCAN_INTERVAL);
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            'watching: loop {
+                tokio::select! {
+                    _ = token.cancelled() => break,
+                    _ = ticks.tick() => {}
+                }
+                let events = tokio::select! {
+                    _ = token.cancelled() => break,
+                    events = folder.scan(client.as_ref()) => events,
+                };
+                for event in events {
+                    if sender.send(event).is_err() {
+                        break 'watching;
+                    }
+                }
+            }
+        });
+
+        Self {
+            cancel,
+            task: Some(task),
+        }
+    }
+
+    /// Stops watching, abandoning an upload in progress. Its file stays in
+    /// the folder and is added on the next start.
+    pub async fn close(&mut self) {
+        self.cancel.cancel();
+        if let Some(task) = self.task.take() {
+            let _ = task.await;
+        }
+    }
+}
+
+impl Drop for DirWatcher {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+struct WatchFolder {
+    dir: PathBuf,
+    after_add: AfterAdd,
+    /// Files the server turned down, with the modification time they had
+    /// then. They stay where they are and are tried again once they change.
+    rejected: HashMap<PathBuf, SystemTime>,
+    /// The folder couldn't be listed last time, which was already reported
+    unreadable: bool,
+}
+
+impl WatchFolder {
+    fn new(dir: PathBuf, after_add: AfterAdd) -> Self {
+        Self {
+            dir,
+            after_add,
+            rejected: HashMap::new(),
+            unreadable: false,
+        }
+    }
+
+    /// Adds every settled torrent file in the folder, returning the toasts
+    /// to show for them.
+    async fn scan(&mut self, client: &dyn TorrentApi) -> Vec<AppEvent> {
+        let mut events = Vec::new();
+        let candidates = match self.candidates() {
+            Ok(candidates) => {
+                self.unreadable = false;
+                candidates
+            }
+            Err(e) => {
+                if !std::mem::replace(&mut self.unreadable, true) {
+                    events.push(AppEvent::ApiError(format!(
+                        "Failed to read watch folder {}: {e}",
+                        self.dir.display()
+                    )));
+                }
+                return events;
+            }
+        };
+
+        for (path, modified) in candidates {
+            let name = path
+                .file_name()
+                .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+            let result = match fs::read(&path) {
+                Ok(data) => {
+                    client
+                        .add_torrent(&data, &AddTorrentOptions::default())
+                        .await
+                }
+                Err(e) => {
+                    events.push(AppEvent::ApiError(format!("Failed to read {name}: {e}")));
+                    self.rejected.insert(path, modified);
+                    continue;
+                }
+            };
+            match result {
+                Ok(()) => {
+                    if let Err(e) = self.put_away(&path) {
+                        events.push(AppEvent::ApiError(format!(
+                            "Added {name} but failed to clear it from the watch folder: {e}"
+                        )));
+                        // Left in place, but not to be added again
+                        self.rejected.insert(path, modified);
+                    }
+                    events.push(AppEvent::TorrentWatched(name));
+                }
+                // The poller reports the lost connection; this is retried
+                // on the next scan
+                Err(ApiError::Network(..)) => break,
+                Err(e) => {
+                    events.push(AppEvent::ApiError(format!(
+                        "Failed to add {name} from the watch folder: {e}"
+                    )));
+                    self.rejected.insert(path, modified);
+                }
+            }
+        }
+        events
+    }
+
+    // Torrent files not written to in the last moments and not turned down
+    // in their current form, oldest first
+    fn candidates(&self) -> io::Result<Vec<(PathBuf, SystemTime)>> {
+        let now = SystemTime::now();
+        let mut candidates = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let metadata = entry.metadata()?;
+            let is_torrent = path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("torrent"));
+            if !metadata.is_file() || !is_torrent {
+                continue;
+            }
+            let modified = metadata.modified()?;
+            let settled = now
+                .duration_since(modified)
+                .is_ok_and(|age| age >= SETTLE_TIME);
+            if settled && self.rejected.get(&path) != Some(&modified) {
+                candidates.push((path, modified));
+            }
+        }
+        candidates.sort_by_key(|(_, modified)| *modified);
+        Ok(candidates)
+    }
+
+    fn put_away(&self, path: &Path) -> io::Result<()> {
+        match self.after_add {
+            AfterAdd::Delete => fs::remove_file(path),
+            AfterAdd::Move => {
+                let added = self.dir.join(ADDED_DIR);
+                fs::create_dir_all(&added)?;
+                let name = path.file_name().unwrap_or_default();
+                fs::rename(path, added.join(name))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::MockBackend;
+    use std::sync::atomic::Ordering;
+
+    // Written `age` seconds ago, so it counts as settled
+    fn write_old(path: &Path, contents: &[u8], age: u64) {
+        fs::write(path, contents).unwrap();
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(age))
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn added_files_move_away_and_are_not_added_twice() {
+        let client = MockBackend::new(Vec::new());
+        let dir = tempfile::tempdir().unwrap();
+        write_old(&dir.path().join("ubuntu.torrent"), b"d4:infoe", 60);
+        write_old(&dir.path().join("notes.txt"), b"not a torrent", 60);
+        // Still being downloaded
+        fs::write(dir.path().join("partial.torrent"), b"d4:in").unwrap();
+
+        let mut folder = WatchFolder::new(dir.path().to_path_buf(), AfterAdd::Move);
+        let events = folder.scan(&client).await;
+        assert!(
+            matches!(&events[..], [AppEvent::TorrentWatched(name)] if name == "ubuntu.torrent"),
+            "{events:?}"
+        );
+        assert!(dir.path().join("added/ubuntu.torrent").is_file());
+        assert!(!dir.path().join("ubuntu.torrent").exists());
+
+        assert!(folder.scan(&client).await.is_empty());
+        assert_eq!(client.calls().len(), 1);
+        assert!(dir.path().join("notes.txt").is_file());
+        assert!(dir.path().join("partial.torrent").is_file());
+    }
+
+    #[tokio::test]
+    async fn rejected_files_stay_until_they_change() {
+        let client = MockBackend::new(Vec::new());
+        client.reject_adds.store(true, Ordering::SeqCst);
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("broken.torrent");
+        write_old(&file, b"garbage", 60);
+
+        let mut folder = WatchFolder::new(dir.path().to_path_buf(), AfterAdd::Delete);
+        let events = folder.scan(&client).await;
+        assert!(
+            matches!(&events[..], [AppEvent::ApiError(message)] if message.contains("broken.torrent")),
+            "{events:?}"
+        );
+        assert!(file.is_file());
+        assert!(folder.scan(&client).await.is_empty());
+
+        // A new copy gets another try
+        write_old(&file, b"still garbage", 30);
+        assert_eq!(folder.scan(&client).await.len(), 1);
+        assert_eq!(client.calls().len(), 2);
+    }
+}